@@ -0,0 +1,14 @@
+//! Any input must either parse or return an error, and rendering the error
+//! must not panic either. Run with `cargo +nightly fuzz run parse_athena_file`.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        if let Err(error) = athena::athena::parse_athena_file(input) {
+            let _ = error.to_string();
+        }
+    }
+});
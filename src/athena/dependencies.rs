@@ -0,0 +1,196 @@
+//! DEPENDS-ON graph: cycle detection and the order services start in.
+//!
+//! Dependencies on services that don't exist are skipped here; they are
+//! reported separately with a did-you-mean suggestion.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedValidationError};
+use crate::athena::parser::ast::{NetworkMode, Service};
+
+/// First dependency cycle found, as a closed path such as `["a", "b", "a"]`.
+/// Services are visited in the given order, so the result is deterministic.
+pub fn find_cycle<'a>(graph: &[(&'a str, Vec<&'a str>)]) -> Option<Vec<String>> {
+    #[derive(PartialEq)]
+    enum Visit {
+        InProgress,
+        Done,
+    }
+
+    let edges: HashMap<&str, &[&str]> = graph.iter().map(|(name, deps)| (*name, deps.as_slice())).collect();
+    let mut state: HashMap<&str, Visit> = HashMap::new();
+
+    for &(start, _) in graph {
+        if state.contains_key(start) {
+            continue;
+        }
+
+        // Iterative DFS: each frame is a service and the index of its next dependency
+        let mut path: Vec<(&str, usize)> = vec![(start, 0)];
+        state.insert(start, Visit::InProgress);
+
+        while let Some((node, next)) = path.last_mut() {
+            let deps = edges.get(*node).copied().unwrap_or_default();
+            let Some(&dep) = deps.get(*next) else {
+                state.insert(*node, Visit::Done);
+                path.pop();
+                continue;
+            };
+            *next += 1;
+
+            match state.get(dep) {
+                Some(Visit::InProgress) => {
+                    let from = path.iter().position(|(name, _)| *name == dep).unwrap_or(0);
+                    let mut cycle: Vec<String> = path[from..].iter().map(|(name, _)| name.to_string()).collect();
+                    cycle.push(dep.to_string());
+                    return Some(cycle);
+                }
+                Some(Visit::Done) => {}
+                None if edges.contains_key(dep) => {
+                    state.insert(dep, Visit::InProgress);
+                    path.push((dep, 0));
+                }
+                None => {}
+            }
+        }
+    }
+
+    None
+}
+
+/// Fail with the full cycle path if DEPENDS-ON loops back on itself
+pub fn check_cycles(graph: &[(&str, Vec<&str>)]) -> AthenaResult<()> {
+    match find_cycle(graph) {
+        Some(cycle) => Err(AthenaError::validation_error_enhanced(
+            EnhancedValidationError::circular_dependency(&cycle),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn service_graph(services: &[Service]) -> Vec<(&str, Vec<&str>)> {
+    services
+        .iter()
+        .map(|s| (s.name.as_str(), s.depends_on.iter().map(|d| d.service.as_str()).collect()))
+        .collect()
+}
+
+/// `roots` and every service they need: DEPENDS-ON targets and services whose
+/// network namespace they share, transitively. Returned in declaration order.
+pub fn with_dependencies<'a>(services: &'a [Service], roots: &[&str]) -> Vec<&'a str> {
+    let mut edges: HashMap<&str, Vec<&str>> = service_graph(services).into_iter().collect();
+    for service in services {
+        if let Some(NetworkMode::Service(target)) = &service.network_mode {
+            edges.entry(service.name.as_str()).or_default().push(target);
+        }
+    }
+
+    let mut needed: HashSet<&str> = HashSet::new();
+    let mut pending: Vec<&str> = roots.to_vec();
+    while let Some(name) = pending.pop() {
+        if needed.insert(name) {
+            pending.extend(edges.get(name).into_iter().flatten().copied());
+        }
+    }
+
+    services
+        .iter()
+        .map(|s| s.name.as_str())
+        .filter(|name| needed.contains(name))
+        .collect()
+}
+
+/// Group services into layers that can start in parallel: the first layer
+/// has no dependencies, and each later one only depends on earlier layers.
+/// Names within a layer are sorted.
+pub fn start_order(services: &[Service]) -> AthenaResult<Vec<Vec<String>>> {
+    let graph = service_graph(services);
+    check_cycles(&graph)?;
+
+    let known: HashSet<&str> = graph.iter().map(|(name, _)| *name).collect();
+    let mut started: HashSet<&str> = HashSet::new();
+    let mut layers = Vec::new();
+
+    while started.len() < graph.len() {
+        let mut layer: Vec<&str> = graph
+            .iter()
+            .filter(|(name, deps)| {
+                !started.contains(name)
+                    && deps.iter().all(|dep| started.contains(dep) || !known.contains(dep))
+            })
+            .map(|(name, _)| *name)
+            .collect();
+        layer.sort_unstable();
+        layer.dedup();
+
+        started.extend(&layer);
+        layers.push(layer.into_iter().map(str::to_string).collect());
+    }
+
+    Ok(layers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::ast::Dependency;
+
+    fn service(name: &str, deps: &[&str]) -> Service {
+        let mut service = Service::new(name.to_string());
+        service.depends_on = deps.iter().map(|d| Dependency::new(*d)).collect();
+        service
+    }
+
+    #[test]
+    fn test_start_order_groups_parallel_services() {
+        let services = vec![
+            service("web", &["api"]),
+            service("api", &["db", "cache"]),
+            service("cache", &[]),
+            service("db", &[]),
+            service("worker", &["db", "missing"]),
+        ];
+
+        let layers = start_order(&services).unwrap();
+        assert_eq!(
+            layers,
+            vec![
+                vec!["cache".to_string(), "db".to_string()],
+                vec!["api".to_string(), "worker".to_string()],
+                vec!["web".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_with_dependencies_follows_a_diamond_once() {
+        let mut sidecar = service("sidecar", &[]);
+        sidecar.network_mode = Some(NetworkMode::Service("web".to_string()));
+        let services = vec![
+            service("db", &[]),
+            service("auth", &["db"]),
+            service("cache", &["db"]),
+            service("api", &["auth", "cache"]),
+            service("web", &["api"]),
+            service("worker", &["db"]),
+            sidecar,
+        ];
+
+        assert_eq!(with_dependencies(&services, &["api"]), ["db", "auth", "cache", "api"]);
+        assert_eq!(with_dependencies(&services, &["sidecar"]).len(), 6);
+        assert_eq!(with_dependencies(&services, &["worker", "db"]), ["db", "worker"]);
+    }
+
+    #[test]
+    fn test_cycle_reports_full_path() {
+        let services = vec![
+            service("standalone", &[]),
+            service("a", &["b"]),
+            service("b", &["c"]),
+            service("c", &["a"]),
+        ];
+
+        let err = start_order(&services).unwrap_err().to_string();
+        assert!(err.contains("a -> b -> c -> a"), "{err}");
+    }
+}
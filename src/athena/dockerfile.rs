@@ -288,17 +288,18 @@ RUN echo "Building app"
 
     #[test]
     fn test_validate_build_args_success() {
-        let mut dockerfile_args = Vec::new();
-        dockerfile_args.push(DockerfileArg {
-            name: "NODE_VERSION".to_string(),
-            default_value: Some("18".to_string()),
-            line_number: 1,
-        });
-        dockerfile_args.push(DockerfileArg {
-            name: "BUILD_ENV".to_string(),
-            default_value: None,
-            line_number: 2,
-        });
+        let dockerfile_args = vec![
+            DockerfileArg {
+                name: "NODE_VERSION".to_string(),
+                default_value: Some("18".to_string()),
+                line_number: 1,
+            },
+            DockerfileArg {
+                name: "BUILD_ENV".to_string(),
+                default_value: None,
+                line_number: 2,
+            },
+        ];
 
         let analysis = DockerfileAnalysis {
             args: dockerfile_args,
@@ -0,0 +1,233 @@
+//! In-place edits of .ath source, as in `athena add-service`.
+//!
+//! Edits splice new text into the original source instead of re-printing the
+//! parsed file, so existing content, comments and layout are kept byte for
+//! byte. New blocks use the layout of the bundled examples: one directive per
+//! line, no indentation, a blank line between blocks.
+
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedValidationError};
+use crate::athena::parser::{parse_athena_file, services_section_end};
+
+/// A SERVICE block to append, built from command-line values
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NewService {
+    pub name: String,
+    pub image: Option<String>,
+    /// `KEY=value` for a literal, `KEY` for a `{{KEY}}` template
+    pub env: Vec<String>,
+    /// `[HOST[-END]:]CONTAINER[/tcp|udp]`
+    pub ports: Vec<String>,
+    pub depends_on: Vec<String>,
+}
+
+impl NewService {
+    /// The block as .ath source, without a trailing newline
+    pub fn render(&self) -> AthenaResult<String> {
+        let mut lines = vec![format!("SERVICE {}", self.name)];
+        if let Some(image) = &self.image {
+            lines.push(format!("IMAGE-ID {}", quoted("--image", image)?));
+        }
+        for port in &self.ports {
+            lines.push(format!("PORT-MAPPING {}", port_mapping(port)?));
+        }
+        for variable in &self.env {
+            lines.push(format!("ENV-VARIABLE {}", env_variable(variable)?));
+        }
+        for dependency in &self.depends_on {
+            lines.push(format!("DEPENDS-ON {dependency}"));
+        }
+        lines.push("END SERVICE".to_string());
+        Ok(lines.join("\n"))
+    }
+}
+
+/// `source` with `service` appended after the last SERVICE block of the
+/// SERVICES SECTION. Fails if the name is taken, a dependency is unknown or
+/// the result would not parse.
+pub fn append_service(source: &str, service: &NewService) -> AthenaResult<String> {
+    let athena_file = parse_athena_file(source)?;
+    let names: Vec<&str> = athena_file
+        .services
+        .services
+        .iter()
+        .map(|s| s.name.as_str())
+        .collect();
+
+    if names.contains(&service.name.as_str()) {
+        return Err(invalid(
+            format!("Service '{}' already exists", service.name),
+            "Pick another --name, or edit the existing SERVICE block by hand".to_string(),
+        ));
+    }
+    for dependency in &service.depends_on {
+        if !names.contains(&dependency.as_str()) {
+            return Err(invalid(
+                format!("--depends-on: unknown service '{dependency}'"),
+                format!("Declared services: {}", names.join(", ")),
+            ));
+        }
+    }
+
+    let block = service.render()?;
+    // After any trailing comment on the END SERVICE line
+    let end = services_section_end(source)?;
+    let insert_at = source[end..].find('\n').map_or(source.len(), |offset| end + offset);
+    let edited = format!("{}\n\n{block}{}", &source[..insert_at], &source[insert_at..]);
+
+    // Catches names and values the grammar rejects, with its diagnostics
+    parse_athena_file(&edited)?;
+    Ok(edited)
+}
+
+fn invalid(message: String, suggestion: String) -> AthenaError {
+    AthenaError::validation_error_enhanced(
+        EnhancedValidationError::new(message).with_suggestion(suggestion),
+    )
+}
+
+/// Strings can't escape quotes or span lines
+fn quoted(flag: &str, value: &str) -> AthenaResult<String> {
+    if value.contains('"') || value.contains('\n') || value.contains('\r') {
+        return Err(invalid(
+            format!("{flag}: '{value}' contains a double quote or a line break"),
+            "Athena strings cannot contain either; pass the value through a {{TEMPLATE}} variable instead".to_string(),
+        ));
+    }
+    Ok(format!("\"{value}\""))
+}
+
+fn env_variable(variable: &str) -> AthenaResult<String> {
+    match variable.split_once('=') {
+        Some((key, _)) if is_env_key(key) => quoted("--env", variable),
+        None if is_env_key(variable) => Ok(format!("{{{{{variable}}}}}")),
+        _ => Err(invalid(
+            format!("--env: invalid variable '{variable}'"),
+            "Use KEY=value for a literal value or KEY for a {{KEY}} template, with KEY made of letters, digits and _".to_string(),
+        )),
+    }
+}
+
+fn is_env_key(key: &str) -> bool {
+    key.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn port_mapping(port: &str) -> AthenaResult<String> {
+    let invalid_port = || {
+        invalid(
+            format!("--port: invalid mapping '{port}'"),
+            "Use HOST:CONTAINER, e.g. 9090:9090, with an optional host range (8000-8002:80) and /udp".to_string(),
+        )
+    };
+    let is_number = |part: &str| !part.is_empty() && part.parse::<u16>().is_ok();
+
+    let (mapping, protocol) = match port.rsplit_once('/') {
+        Some((mapping, protocol @ ("tcp" | "udp"))) => (mapping, Some(protocol)),
+        Some(_) => return Err(invalid_port()),
+        None => (port, None),
+    };
+    let (host, container) = mapping.split_once(':').unwrap_or((mapping, mapping));
+    let host_parts_valid = match host.split_once('-') {
+        Some((start, end)) => is_number(start) && is_number(end),
+        None => is_number(host),
+    };
+    if !host_parts_valid || !is_number(container) {
+        return Err(invalid_port());
+    }
+
+    let mut line = format!("{host} TO {container}");
+    if let Some(protocol) = protocol {
+        line += &format!(" ({protocol})");
+    }
+    Ok(line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STACK: &str = "\
+// Demo stack
+SERVICES SECTION
+
+SERVICE api
+IMAGE-ID \"myorg/api:1.0\" // pinned
+END SERVICE // api
+";
+
+    fn worker() -> NewService {
+        NewService {
+            name: "worker".to_string(),
+            image: Some("myorg/worker:1.2".to_string()),
+            env: vec!["FOO=bar".to_string(), "API_TOKEN".to_string()],
+            ports: vec!["9090:9090".to_string(), "5353/udp".to_string()],
+            depends_on: vec!["api".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_append_keeps_existing_source() {
+        let edited = append_service(STACK, &worker()).unwrap();
+        assert_eq!(
+            edited,
+            "\
+// Demo stack
+SERVICES SECTION
+
+SERVICE api
+IMAGE-ID \"myorg/api:1.0\" // pinned
+END SERVICE // api
+
+SERVICE worker
+IMAGE-ID \"myorg/worker:1.2\"
+PORT-MAPPING 9090 TO 9090
+PORT-MAPPING 5353 TO 5353 (udp)
+ENV-VARIABLE \"FOO=bar\"
+ENV-VARIABLE {{API_TOKEN}}
+DEPENDS-ON api
+END SERVICE
+"
+        );
+
+        let athena_file = parse_athena_file(&edited).unwrap();
+        assert_eq!(athena_file.services.services.len(), 2);
+    }
+
+    #[test]
+    fn test_append_goes_before_environment_blocks() {
+        let source = "SERVICES SECTION\nSERVICE api\nIMAGE-ID nginx\nEND SERVICE\n\nENVIRONMENT \"prod\"\nSERVICE api\nREPLICAS 2\nEND SERVICE\nEND ENVIRONMENT";
+        let edited = append_service(source, &NewService {
+            name: "cache".to_string(),
+            image: Some("redis:7".to_string()),
+            ..NewService::default()
+        })
+        .unwrap();
+        assert!(edited.contains("END SERVICE\n\nSERVICE cache\nIMAGE-ID \"redis:7\"\nEND SERVICE\n\nENVIRONMENT \"prod\""));
+    }
+
+    #[test]
+    fn test_append_rejects_existing_name_and_unknown_dependency() {
+        let duplicate = NewService {
+            name: "api".to_string(),
+            ..NewService::default()
+        };
+        let error = append_service(STACK, &duplicate).unwrap_err();
+        assert!(error.to_string().contains("Service 'api' already exists"));
+
+        let dangling = NewService {
+            depends_on: vec!["db".to_string()],
+            ..worker()
+        };
+        let error = append_service(STACK, &dangling).unwrap_err();
+        assert!(error.to_string().contains("unknown service 'db'"));
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        assert!(port_mapping("80:http").is_err());
+        assert!(port_mapping("80:80/sctp").is_err());
+        assert_eq!(port_mapping("8000-8002:80").unwrap(), "8000-8002 TO 80");
+        assert!(env_variable("1FOO=bar").is_err());
+        assert!(quoted("--image", "evil\"image").is_err());
+    }
+}
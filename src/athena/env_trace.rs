@@ -0,0 +1,257 @@
+//! `athena env`: the environment a service ends up with, and where each
+//! value comes from.
+//!
+//! Values are resolved by the same merge as `athena build`; the trace then
+//! replays its layers in order to list every setting of a variable. Per input
+//! file a PRESET comes first and the service's own ENV-VARIABLE lines override
+//! it; later files override earlier ones, and the `--env` block comes last.
+
+use std::fmt;
+
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedValidationError};
+use crate::athena::merge::{apply_environment, parse_and_merge, SourceFile};
+use crate::athena::parser::ast::{EnvironmentVariable, Service, SourceLocation};
+use crate::athena::parser::parse_athena_file;
+use crate::athena::presets::find_preset;
+
+/// What set a value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvSource {
+    /// Filled in by `PRESET <name>`
+    Preset(String),
+    /// ENV-VARIABLE in the SERVICE block
+    Service,
+    /// ENV-VARIABLE in the service's override in `ENVIRONMENT "<name>"`
+    Environment(String),
+}
+
+impl fmt::Display for EnvSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvSource::Preset(name) => write!(f, "PRESET {name}"),
+            EnvSource::Service => f.write_str("ENV-VARIABLE"),
+            EnvSource::Environment(name) => write!(f, "ENVIRONMENT \"{name}\""),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnvValue {
+    Literal(String),
+    /// `{{KEY}}`: Compose substitutes `${KEY}` from the shell or `.env` when it runs
+    Template(String),
+}
+
+impl fmt::Display for EnvValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvValue::Literal(value) => f.write_str(value),
+            EnvValue::Template(name) => write!(f, "${{{name}}}"),
+        }
+    }
+}
+
+impl From<&EnvironmentVariable> for EnvValue {
+    fn from(variable: &EnvironmentVariable) -> Self {
+        match variable {
+            EnvironmentVariable::Template(name) => EnvValue::Template(name.clone()),
+            EnvironmentVariable::Literal(entry) => {
+                EnvValue::Literal(entry.split_once('=').map_or("", |(_, value)| value).to_string())
+            }
+        }
+    }
+}
+
+/// One setting of a variable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvSetting {
+    pub source: EnvSource,
+    /// The ENV-VARIABLE line, or the SERVICE line asking for a preset
+    pub location: Option<SourceLocation>,
+    pub value: EnvValue,
+}
+
+/// Every setting of one variable, lowest precedence first
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvTrace {
+    pub key: String,
+    pub settings: Vec<EnvSetting>,
+    /// What the service gets, as generated by `athena build`
+    pub value: EnvValue,
+}
+
+impl EnvTrace {
+    /// Index of the setting that wins
+    pub fn winner(&self) -> Option<usize> {
+        self.settings.len().checked_sub(1)
+    }
+}
+
+/// The environment of `service` in generation order, with the setting
+/// history of each variable
+pub fn trace_service_env(
+    sources: &[SourceFile],
+    service: &str,
+    environment: Option<&str>,
+) -> AthenaResult<Vec<EnvTrace>> {
+    let mut merged = parse_and_merge(sources)?;
+    if let Some(name) = environment {
+        apply_environment(&mut merged, name)?;
+    }
+    let services = &merged.services.services;
+    let Some(resolved) = services.iter().find(|s| s.name == service) else {
+        let available: Vec<&str> = services.iter().map(|s| s.name.as_str()).collect();
+        return Err(AthenaError::validation_error_enhanced(
+            EnhancedValidationError::new(format!("Unknown service '{service}'"))
+                .with_suggestion(format!("Available services: {}", available.join(", "))),
+        ));
+    };
+
+    let mut traces: Vec<EnvTrace> = resolved
+        .environment
+        .iter()
+        .map(|variable| EnvTrace {
+            key: variable.key().to_string(),
+            settings: Vec::new(),
+            value: variable.into(),
+        })
+        .collect();
+
+    // Already parsed once by parse_and_merge, so these can't fail
+    let files = sources
+        .iter()
+        .map(|source| parse_athena_file(source.content).map(|file| (source.path, file)))
+        .collect::<AthenaResult<Vec<_>>>()?;
+    for (path, file) in &files {
+        if let Some(declared) = file.services.services.iter().find(|s| s.name == service) {
+            replay(&mut traces, declared, path, EnvSource::Service);
+        }
+    }
+    if let Some(name) = environment {
+        for (path, file) in &files {
+            let overrides = file.environments.iter().filter(|e| e.name == name);
+            if let Some(declared) = overrides.flat_map(|e| &e.services).find(|s| s.name == service) {
+                replay(&mut traces, declared, path, EnvSource::Environment(name.to_string()));
+            }
+        }
+    }
+
+    Ok(traces)
+}
+
+/// Record what one declaration of the service sets: its preset's entries,
+/// then its own ENV-VARIABLE lines
+fn replay(traces: &mut [EnvTrace], declared: &Service, path: &str, source: EnvSource) {
+    let location = |line: usize| SourceLocation {
+        line,
+        file: Some(path.to_string()),
+    };
+    let mut record = |variable: &EnvironmentVariable, setting: EnvSetting| {
+        if let Some(trace) = traces.iter_mut().find(|t| t.key == variable.key()) {
+            trace.settings.push(setting);
+        }
+    };
+
+    if let Some(preset) = declared.preset.as_ref().and_then(|p| find_preset(&p.name)) {
+        for variable in preset.environment_for(&declared.name) {
+            record(&variable, EnvSetting {
+                source: EnvSource::Preset(preset.name.to_string()),
+                location: declared.location.as_ref().map(|l| location(l.line)),
+                value: (&variable).into(),
+            });
+        }
+    }
+    for variable in &declared.environment {
+        // Preset entries expanded at parse time have no line of their own
+        let Some(&line) = declared.env_lines.get(variable.key()) else {
+            continue;
+        };
+        record(variable, EnvSetting {
+            source: source.clone(),
+            location: Some(location(line)),
+            value: variable.into(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#"SERVICES SECTION
+SERVICE db
+PRESET postgres
+ENV-VARIABLE "POSTGRES_DB=shop"
+END SERVICE
+SERVICE api
+IMAGE-ID "api:latest"
+ENV-VARIABLE "LOG_LEVEL=info"
+ENV-VARIABLE {{API_TOKEN}}
+END SERVICE
+
+ENVIRONMENT "prod"
+SERVICE api
+ENV-VARIABLE "LOG_LEVEL=warn"
+END SERVICE
+END ENVIRONMENT
+"#;
+
+    const OVERRIDE: &str = "SERVICES SECTION\nSERVICE db\nENV-VARIABLE \"POSTGRES_DB=orders\"\nEND SERVICE\n";
+
+    fn sources<'a>(files: &[(&'a str, &'a str)]) -> Vec<SourceFile<'a>> {
+        files.iter().map(|&(path, content)| SourceFile { path, content }).collect()
+    }
+
+    fn trace<'a>(traces: &'a [EnvTrace], key: &str) -> &'a EnvTrace {
+        traces.iter().find(|t| t.key == key).unwrap()
+    }
+
+    #[test]
+    fn test_layers_are_replayed_in_precedence_order() {
+        let sources = sources(&[("base.ath", BASE), ("override.ath", OVERRIDE)]);
+        let traces = trace_service_env(&sources, "db", None).unwrap();
+
+        let keys: Vec<&str> = traces.iter().map(|t| t.key.as_str()).collect();
+        assert_eq!(keys, ["POSTGRES_USER", "POSTGRES_PASSWORD", "POSTGRES_DB"]);
+
+        let database = trace(&traces, "POSTGRES_DB");
+        assert_eq!(database.value, EnvValue::Literal("orders".to_string()));
+        let sources: Vec<String> = database
+            .settings
+            .iter()
+            .map(|s| format!("{} {} {}", s.source, s.location.as_ref().unwrap(), s.value))
+            .collect();
+        assert_eq!(
+            sources,
+            [
+                "PRESET postgres base.ath:2 ${POSTGRES_DB}",
+                "ENV-VARIABLE base.ath:4 shop",
+                "ENV-VARIABLE override.ath:3 orders",
+            ]
+        );
+        assert_eq!(database.winner(), Some(2));
+        assert_eq!(trace(&traces, "POSTGRES_USER").settings.len(), 1);
+    }
+
+    #[test]
+    fn test_environment_block_wins_when_applied() {
+        let sources = sources(&[("stack.ath", BASE)]);
+
+        let traces = trace_service_env(&sources, "api", None).unwrap();
+        assert_eq!(trace(&traces, "LOG_LEVEL").value, EnvValue::Literal("info".to_string()));
+        assert_eq!(trace(&traces, "API_TOKEN").value.to_string(), "${API_TOKEN}");
+
+        let traces = trace_service_env(&sources, "api", Some("prod")).unwrap();
+        let level = trace(&traces, "LOG_LEVEL");
+        assert_eq!(level.value, EnvValue::Literal("warn".to_string()));
+        assert_eq!(level.settings[1].source, EnvSource::Environment("prod".to_string()));
+        assert_eq!(level.settings[1].location.as_ref().unwrap().line, 14);
+    }
+
+    #[test]
+    fn test_unknown_service_lists_the_others() {
+        let sources = sources(&[("stack.ath", BASE)]);
+        let error = trace_service_env(&sources, "web", None).unwrap_err().to_string();
+        assert!(error.contains("Unknown service 'web'"), "{error}");
+    }
+}
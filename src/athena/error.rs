@@ -3,11 +3,23 @@ use thiserror::Error;
 
 pub type AthenaResult<T> = Result<T, AthenaError>;
 
+/// Process exit codes by failure class. Scripts rely on these, so existing
+/// values must never change; `athena info --exit-codes` prints this table.
+pub const EXIT_CODES: &[(i32, &str)] = &[
+    (0, "Success"),
+    (1, "Other error: no single .ath file to auto-detect, invalid athena.toml, serialization failure, failed post-process hook"),
+    (2, "Invalid command-line usage (unknown flag, conflicting options)"),
+    (3, "Parse error in a .ath file"),
+    (4, "Validation failed, or 'athena verify' found a modified or stale file"),
+    (5, "A file could not be read or written"),
+    (6, "An option the chosen target or output format doesn't support (--minimal with Terraform, --annotate-sources with JSON)"),
+];
+
 #[derive(Error, Debug)]
 #[allow(clippy::enum_variant_names)]
 pub enum AthenaError {
     #[error("{0}")]
-    ParseError(EnhancedParseError),
+    ParseError(Box<EnhancedParseError>),
 
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
@@ -20,6 +32,14 @@ pub enum AthenaError {
 
     #[error("{0}")]
     ValidationError(EnhancedValidationError),
+
+    /// Options that can't be combined, or don't fit the input
+    #[error("{0}")]
+    UsageError(String),
+
+    /// An option the chosen target or output format has no equivalent for
+    #[error("{0}")]
+    IncompatibleError(String),
 }
 
 #[derive(Debug, Clone)]
@@ -34,6 +54,10 @@ pub struct EnhancedParseError {
     pub location: Option<ErrorLocation>,
     pub suggestion: Option<String>,
     pub file_content: Option<String>,
+    /// Named in the message when several files are parsed together
+    pub file_path: Option<String>,
+    /// Further errors found in the same run, reported after this one
+    pub others: Vec<EnhancedParseError>,
 }
 
 #[derive(Debug, Clone)]
@@ -45,27 +69,7 @@ pub struct EnhancedValidationError {
 
 impl fmt::Display for EnhancedParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let Some(location) = &self.location {
-            write!(
-                f,
-                "Parse error at line {}, column {}: {}",
-                location.line, location.column, self.message
-            )?;
-
-            if let Some(content) = &self.file_content {
-                if let Some(context_display) = self.format_context(content) {
-                    write!(f, "\n{context_display}")?;
-                }
-            }
-        } else {
-            write!(f, "Parse error: {}", self.message)?;
-        }
-
-        if let Some(suggestion) = &self.suggestion {
-            write!(f, "\n\nSuggestion: {suggestion}")?;
-        }
-
-        Ok(())
+        self.write_to(f, self.file_content.as_deref())
     }
 }
 
@@ -92,6 +96,8 @@ impl EnhancedParseError {
             location: None,
             suggestion: None,
             file_content: None,
+            file_path: None,
+            others: Vec::new(),
         }
     }
 
@@ -110,6 +116,57 @@ impl EnhancedParseError {
         self
     }
 
+    pub fn with_file_path(mut self, path: String) -> Self {
+        for other in &mut self.others {
+            other.file_path = Some(path.clone());
+        }
+        self.file_path = Some(path);
+        self
+    }
+
+    pub fn with_others(mut self, others: Vec<EnhancedParseError>) -> Self {
+        self.others = others;
+        self
+    }
+
+    /// Errors recovered from later SERVICE blocks share the first error's
+    /// file content rather than carrying a copy each
+    fn write_to(&self, f: &mut fmt::Formatter<'_>, content: Option<&str>) -> fmt::Result {
+        let file = self
+            .file_path
+            .as_ref()
+            .map(|path| format!(" in {path}"))
+            .unwrap_or_default();
+
+        if let Some(location) = &self.location {
+            write!(
+                f,
+                "Parse error{file} at line {}, column {}: {}",
+                location.line, location.column, self.message
+            )?;
+
+            if let Some(context_display) = content.and_then(|content| self.format_context(content)) {
+                write!(f, "\n{context_display}")?;
+            }
+        } else {
+            write!(f, "Parse error{file}: {}", self.message)?;
+        }
+
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, "\n\nSuggestion: {suggestion}")?;
+        }
+
+        if !self.others.is_empty() {
+            write!(f, "\n\n{} more error(s) found:", self.others.len())?;
+            for other in &self.others {
+                write!(f, "\n\n")?;
+                other.write_to(f, other.file_content.as_deref().or(content))?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn format_context(&self, content: &str) -> Option<String> {
         let location = self.location.as_ref()?;
         let lines: Vec<&str> = content.lines().collect();
@@ -169,10 +226,11 @@ impl EnhancedValidationError {
             .with_services(vec![service.to_string(), dependency.to_string()])
     }
 
-    pub fn circular_dependency(service: &str) -> Self {
-        let message = format!(
-            "Circular dependency detected involving service '{service}'"
-        );
+    /// `cycle` is a closed path such as `["a", "b", "a"]`
+    pub fn circular_dependency(cycle: &[String]) -> Self {
+        let message = format!("Circular dependency detected: {}", cycle.join(" -> "));
+        let mut services = cycle.to_vec();
+        services.pop();
 
         let suggestion =
             "Check the DEPENDS-ON declarations in your .ath file and remove circular dependencies"
@@ -180,13 +238,25 @@ impl EnhancedValidationError {
 
         Self::new(message)
             .with_suggestion(suggestion)
-            .with_services(vec![service.to_string()])
+            .with_services(services)
     }
 }
 
 impl AthenaError {
+    /// Exit code for this failure class, see [`EXIT_CODES`]
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            AthenaError::ConfigError(_) | AthenaError::YamlError(_) => 1,
+            AthenaError::UsageError(_) => 2,
+            AthenaError::ParseError(_) => 3,
+            AthenaError::ValidationError(_) => 4,
+            AthenaError::IoError(_) => 5,
+            AthenaError::IncompatibleError(_) => 6,
+        }
+    }
+
     pub fn parse_error_enhanced(error: EnhancedParseError) -> Self {
-        AthenaError::ParseError(error)
+        AthenaError::ParseError(Box::new(error))
     }
 
     pub fn config_error<T: Into<String>>(msg: T) -> Self {
@@ -196,4 +266,12 @@ impl AthenaError {
     pub fn validation_error_enhanced(error: EnhancedValidationError) -> Self {
         AthenaError::ValidationError(error)
     }
+
+    pub fn usage_error<T: Into<String>>(msg: T) -> Self {
+        AthenaError::UsageError(msg.into())
+    }
+
+    pub fn incompatible_error<T: Into<String>>(msg: T) -> Self {
+        AthenaError::IncompatibleError(msg.into())
+    }
 }
\ No newline at end of file
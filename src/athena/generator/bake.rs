@@ -0,0 +1,261 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::defaults::DefaultsEngine;
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedValidationError};
+use crate::athena::parser::ast::AthenaFile;
+
+/// Syntax of the generated bake file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BakeFormat {
+    Hcl,
+    Json,
+}
+
+/// Generated bake file plus one info message per skipped service
+#[derive(Debug)]
+pub struct BakeOutput {
+    pub content: String,
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct BakeFile {
+    group: BTreeMap<&'static str, BakeGroup>,
+    target: BTreeMap<String, BakeTarget>,
+}
+
+#[derive(Debug, Serialize)]
+struct BakeGroup {
+    targets: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct BakeTarget {
+    context: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dockerfile: Option<String>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    args: BTreeMap<String, String>,
+    tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    platforms: Vec<String>,
+}
+
+/// Generate a `docker buildx bake` file with one target per buildable service.
+/// A service's PLATFORM pins its target; the others build for `platforms`.
+pub fn generate_bake_file(
+    athena_file: &AthenaFile,
+    registry: Option<&str>,
+    platforms: &[String],
+    format: BakeFormat,
+) -> AthenaResult<BakeOutput> {
+    let project_name = athena_file.get_project_name();
+    let mut skipped = Vec::new();
+    let mut targets = BTreeMap::new();
+
+    for service in &athena_file.services.services {
+        let Some(build) = DefaultsEngine::create_build_config(service, &project_name) else {
+            skipped.push(format!(
+                "Service '{}' uses a prebuilt image and has no build section, skipping",
+                service.name
+            ));
+            continue;
+        };
+
+        let tag = match registry {
+            Some(registry) => format!("{}/{}", registry.trim_end_matches('/'), service.name),
+            None => service.name.clone(),
+        };
+
+        targets.insert(
+            service.name.clone(),
+            BakeTarget {
+                context: build.context,
+                dockerfile: build.dockerfile,
+                args: build.args.unwrap_or_default().into_iter().collect(),
+                tags: vec![tag],
+                platforms: match &service.platform {
+                    Some(platform) => vec![platform.clone()],
+                    None => platforms.to_vec(),
+                },
+            },
+        );
+    }
+
+    if targets.is_empty() {
+        return Err(AthenaError::validation_error_enhanced(
+            EnhancedValidationError::new("No buildable services found for the bake file".to_string())
+                .with_suggestion(
+                    "Services with an IMAGE-ID and no BUILD-ARGS are pulled, not built. Add BUILD-ARGS or remove IMAGE-ID to build from a Dockerfile".to_string(),
+                ),
+        ));
+    }
+
+    let bake = BakeFile {
+        group: BTreeMap::from([(
+            "default",
+            BakeGroup {
+                targets: targets.keys().cloned().collect(),
+            },
+        )]),
+        target: targets,
+    };
+
+    let content = match format {
+        BakeFormat::Json => serde_json::to_string_pretty(&bake)
+            .map(|json| json + "\n")
+            .map_err(|e| {
+                AthenaError::config_error(format!("Failed to serialize bake file: {e}"))
+            })?,
+        BakeFormat::Hcl => render_hcl(&bake, athena_file),
+    };
+
+    Ok(BakeOutput { content, skipped })
+}
+
+fn render_hcl(bake: &BakeFile, athena_file: &AthenaFile) -> String {
+    let mut hcl = String::new();
+    let _ = writeln!(
+        hcl,
+        "# Generated by Athena v{} from {} deployment\n",
+        env!("CARGO_PKG_VERSION"),
+        athena_file.get_project_name()
+    );
+
+    for (name, group) in &bake.group {
+        let _ = writeln!(hcl, "group \"{name}\" {{");
+        let _ = writeln!(hcl, "  targets = [{}]", quoted_list(&group.targets));
+        let _ = writeln!(hcl, "}}");
+    }
+
+    for (name, target) in &bake.target {
+        let _ = writeln!(hcl, "\ntarget \"{name}\" {{");
+        let _ = writeln!(hcl, "  context = {}", hcl_string(&target.context));
+        if let Some(dockerfile) = &target.dockerfile {
+            let _ = writeln!(hcl, "  dockerfile = {}", hcl_string(dockerfile));
+        }
+        if !target.args.is_empty() {
+            let _ = writeln!(hcl, "  args = {{");
+            for (key, value) in &target.args {
+                let _ = writeln!(hcl, "    {key} = {}", hcl_string(value));
+            }
+            let _ = writeln!(hcl, "  }}");
+        }
+        let _ = writeln!(hcl, "  tags = [{}]", quoted_list(&target.tags));
+        if !target.platforms.is_empty() {
+            let _ = writeln!(hcl, "  platforms = [{}]", quoted_list(&target.platforms));
+        }
+        let _ = writeln!(hcl, "}}");
+    }
+
+    hcl
+}
+
+fn quoted_list(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|v| hcl_string(v))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Quote a value for HCL, escaping interpolation so values are taken literally
+fn hcl_string(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace("${", "$${");
+    format!("\"{escaped}\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::parse_athena_file;
+
+    const INPUT: &str = r#"
+        DEPLOYMENT-ID BAKE_TEST
+        SERVICES SECTION
+        SERVICE api
+        BUILD-ARGS NODE_VERSION="20"
+        END SERVICE
+        SERVICE worker
+        END SERVICE
+        SERVICE cache
+        IMAGE-ID "redis:7-alpine"
+        END SERVICE
+    "#;
+
+    #[test]
+    fn test_hcl_targets_and_default_group() {
+        let athena_file = parse_athena_file(INPUT).unwrap();
+        let output =
+            generate_bake_file(&athena_file, Some("ghcr.io/acme/"), &[], BakeFormat::Hcl).unwrap();
+
+        assert!(output.content.contains("group \"default\" {\n  targets = [\"api\", \"worker\"]\n}"));
+        assert!(output.content.contains("target \"api\" {"));
+        assert!(output.content.contains("    NODE_VERSION = \"20\""));
+        assert!(output.content.contains("tags = [\"ghcr.io/acme/api\"]"));
+        assert!(!output.content.contains("target \"cache\""));
+        assert!(!output.content.contains("platforms"));
+
+        assert_eq!(output.skipped.len(), 1);
+        assert!(output.skipped[0].contains("'cache'"));
+    }
+
+    #[test]
+    fn test_json_format() {
+        let athena_file = parse_athena_file(INPUT).unwrap();
+        let output = generate_bake_file(&athena_file, None, &[], BakeFormat::Json).unwrap();
+
+        let bake: serde_json::Value = serde_json::from_str(&output.content).unwrap();
+        assert_eq!(bake["group"]["default"]["targets"][1], "worker");
+        assert_eq!(bake["target"]["worker"]["tags"][0], "worker");
+        assert_eq!(bake["target"]["api"]["args"]["NODE_VERSION"], "20");
+    }
+
+    #[test]
+    fn test_no_buildable_services_is_an_error() {
+        let athena_file = parse_athena_file(
+            r#"
+            SERVICES SECTION
+            SERVICE cache
+            IMAGE-ID "redis:7-alpine"
+            END SERVICE
+        "#,
+        )
+        .unwrap();
+
+        assert!(generate_bake_file(&athena_file, None, &[], BakeFormat::Hcl).is_err());
+    }
+
+    #[test]
+    fn test_platforms_default_and_per_service_pin() {
+        let athena_file = parse_athena_file(
+            r#"
+            SERVICES SECTION
+            SERVICE api
+            END SERVICE
+            SERVICE legacy
+            PLATFORM "linux/amd64"
+            END SERVICE
+        "#,
+        )
+        .unwrap();
+        let platforms = vec!["linux/amd64".to_string(), "linux/arm64".to_string()];
+        let output = generate_bake_file(&athena_file, None, &platforms, BakeFormat::Hcl).unwrap();
+
+        assert!(output.content.contains(
+            "target \"api\" {\n  context = \".\"\n  dockerfile = \"Dockerfile\"\n  tags = [\"api\"]\n  platforms = [\"linux/amd64\", \"linux/arm64\"]\n}"
+        ));
+        assert!(output.content.contains("  tags = [\"legacy\"]\n  platforms = [\"linux/amd64\"]\n}"));
+    }
+
+    #[test]
+    fn test_hcl_string_escaping() {
+        assert_eq!(hcl_string("a\"b"), "\"a\\\"b\"");
+        assert_eq!(hcl_string("${HOME}"), "\"$${HOME}\"");
+    }
+}
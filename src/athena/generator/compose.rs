@@ -1,20 +1,29 @@
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write;
+use std::path::Path;
 
 use super::defaults::{DefaultsEngine, EnhancedDockerService};
+use super::minimal::minimize;
+use super::options::{ComposeFormat, GeneratorOptions};
+use crate::athena::dependencies::check_cycles;
 use crate::athena::dockerfile::{analyze_dockerfile, validate_build_args_against_dockerfile};
 use crate::athena::error::{
     AthenaError, AthenaResult, EnhancedValidationError,
 };
-use crate::athena::parser::ast::{AthenaFile, NetworkDriver, VolumeDefinition};
+use crate::athena::mounts::{is_runtime_socket, rebase_relative_path};
+use crate::athena::parser::ast::{
+    AthenaFile, DependencyCondition, DeployMode, NetworkDefinition, NetworkDriver, NetworkMode, RestartPolicy, ServiceKind,
+    SourceLocation, VolumeDefinition,
+};
+use crate::athena::provenance::ANNOTATION_PREFIX;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DockerCompose {
-    services: IndexMap<String, EnhancedDockerService>,
+    pub(super) services: IndexMap<String, EnhancedDockerService>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    networks: Option<BTreeMap<String, DockerNetwork>>,
+    pub(super) networks: Option<BTreeMap<String, DockerNetwork>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     volumes: Option<BTreeMap<String, DockerVolume>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -23,13 +32,26 @@ pub struct DockerCompose {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DockerNetwork {
-    driver: String,
+    pub(super) driver: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(super) attachable: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    attachable: Option<bool>,
+    pub(super) encrypted: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    encrypted: Option<bool>,
+    pub(super) ingress: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    ingress: Option<bool>,
+    pub(super) ipam: Option<DockerIpam>,
+}
+
+/// Address management, emitted when the network declares a SUBNET
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DockerIpam {
+    config: Vec<DockerIpamConfig>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DockerIpamConfig {
+    subnet: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -39,11 +61,29 @@ pub struct DockerVolume {
 
 /// Generate optimized Docker Compose with intelligent defaults
 pub fn generate_docker_compose(athena_file: &AthenaFile) -> AthenaResult<String> {
+    generate_docker_compose_with_options(athena_file, &GeneratorOptions::default())
+}
+
+/// Generate Docker Compose honouring output settings such as reproducibility
+pub fn generate_docker_compose_with_options(
+    athena_file: &AthenaFile,
+    options: &GeneratorOptions,
+) -> AthenaResult<String> {
+    let compose = build_compose(athena_file, options)?;
+    render_compose(&compose, athena_file, options)
+}
+
+/// Build and validate the Compose model without serializing it, so that
+/// targets such as podman can adjust it first
+pub(super) fn build_compose(
+    athena_file: &AthenaFile,
+    options: &GeneratorOptions,
+) -> AthenaResult<DockerCompose> {
     let project_name = athena_file.get_project_name();
     let network_name = athena_file.get_network_name();
 
     let mut compose = DockerCompose {
-        name: Some(project_name.to_lowercase().replace('_', "-")),
+        name: Some(athena_file.get_compose_project_name()),
         services: IndexMap::new(),
         networks: None,
         volumes: None,
@@ -62,25 +102,92 @@ pub fn generate_docker_compose(athena_file: &AthenaFile) -> AthenaResult<String>
     // Sort services in dependency order (no-deps first, then dependents)
     let sorted_services = topological_sort_services(&athena_file.services.services);
 
+    let default_dns = athena_file.get_default_dns();
+    let output_root = options.output_root();
+
     // Convert services using intelligent defaults, inserting in topological order
     for service in &sorted_services {
-        let enhanced_service =
+        let mut enhanced_service =
             DefaultsEngine::create_enhanced_service(service, &network_name, &project_name);
+        if enhanced_service.dns.is_none() && !default_dns.is_empty() {
+            enhanced_service.dns = Some(default_dns.to_vec());
+        }
+        let external_hosts = athena_file.external_hosts_for(service);
+        if !external_hosts.is_empty() {
+            enhanced_service
+                .extra_hosts
+                .get_or_insert_with(Vec::new)
+                .extend(external_hosts.iter().map(|h| format!("{}:{}", h.hostname, h.ip)));
+        }
+        if let Some(labels) = enhanced_service.labels.as_mut() {
+            for (key, value) in athena_file.get_default_labels().into_iter().flatten() {
+                labels.insert(key.clone(), value.clone());
+            }
+            labels.extend(service.labels.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+        // Like bind mounts, the build context resolves against the output file
+        if let Some(build) = enhanced_service.build.as_mut() {
+            if !output_root.as_os_str().is_empty() {
+                build.context = rebase_relative_path(&output_root, &build.context);
+            }
+        }
+        // The generation date label would defeat byte-stable output
+        if options.reproducible() {
+            if let Some(labels) = enhanced_service.labels.as_mut() {
+                labels.remove("athena.generated");
+            }
+        }
         compose
             .services
             .insert(service.name.clone(), enhanced_service);
     }
 
     // Fast validation with enhanced error reporting
-    validate_compose_enhanced(&compose, athena_file)?;
+    validate_compose_enhanced(&compose, athena_file, options.base_dir())?;
+
+    Ok(compose)
+}
+
+/// Serialize a Compose model to commented, readable YAML, or to JSON
+pub(super) fn render_compose(
+    compose: &DockerCompose,
+    athena_file: &AthenaFile,
+    options: &GeneratorOptions,
+) -> AthenaResult<String> {
+    if options.minimal() {
+        let document = minimize(compose, athena_file)?;
+        return render_document(&document, athena_file, options);
+    }
+    render_document(compose, athena_file, options)
+}
+
+fn render_document<T: Serialize>(
+    compose: &T,
+    athena_file: &AthenaFile,
+    options: &GeneratorOptions,
+) -> AthenaResult<String> {
+    if options.compose_format() == ComposeFormat::Json {
+        // Going through Value sorts every object's keys, so output is stable
+        let json = serde_json::to_value(compose)
+            .and_then(|value| serde_json::to_string_pretty(&value))
+            .map_err(|e| AthenaError::config_error(format!("Failed to serialize Compose JSON: {e}")))?;
+        return Ok(json + "\n");
+    }
 
     // Generate optimized YAML
-    let yaml = serde_yaml::to_string(&compose).map_err(AthenaError::YamlError)?;
+    let yaml = serde_yaml::to_string(compose).map_err(AthenaError::YamlError)?;
 
     // Improve formatting for better readability
-    let formatted_yaml = improve_yaml_formatting(yaml);
+    let mut formatted_yaml = improve_yaml_formatting(yaml);
+    if options.annotate_sources() {
+        formatted_yaml = annotate_sources(&formatted_yaml, athena_file);
+    }
 
-    Ok(add_enhanced_yaml_comments(formatted_yaml, athena_file))
+    Ok(add_enhanced_yaml_comments(
+        formatted_yaml,
+        athena_file,
+        !options.reproducible(),
+    ))
 }
 
 /// Sort services in topological order: services with no dependencies first,
@@ -99,8 +206,8 @@ fn topological_sort_services(services: &[crate::athena::parser::ast::Service]) -
         in_degree.entry(service.name.as_str()).or_insert(0);
         dependents.entry(service.name.as_str()).or_default();
         for dep in &service.depends_on {
-            if name_to_service.contains_key(dep.as_str()) {
-                dependents.entry(dep.as_str()).or_default().push(&service.name);
+            if name_to_service.contains_key(dep.service.as_str()) {
+                dependents.entry(dep.service.as_str()).or_default().push(&service.name);
                 *in_degree.entry(service.name.as_str()).or_insert(0) += 1;
             }
         }
@@ -183,6 +290,11 @@ fn create_optimized_networks(athena_file: &AthenaFile) -> BTreeMap<String, Docke
                     attachable: network_def.attachable,
                     encrypted: network_def.encrypted,
                     ingress: network_def.ingress,
+                    ipam: network_def.subnet.as_ref().map(|subnet| DockerIpam {
+                        config: vec![DockerIpamConfig {
+                            subnet: subnet.clone(),
+                        }],
+                    }),
                 },
             );
         }
@@ -198,6 +310,7 @@ fn create_optimized_networks(athena_file: &AthenaFile) -> BTreeMap<String, Docke
                 attachable: None,
                 encrypted: None,
                 ingress: None,
+                ipam: None,
             },
         );
     }
@@ -223,6 +336,7 @@ fn create_optimized_volumes(volume_defs: &[VolumeDefinition]) -> BTreeMap<String
 fn validate_compose_enhanced(
     compose: &DockerCompose,
     athena_file: &AthenaFile,
+    base_dir: &Path,
 ) -> AthenaResult<()> {
     // Pre-allocate for better performance
     let service_names: std::collections::HashSet<String> =
@@ -243,7 +357,7 @@ fn validate_compose_enhanced(
 
         // Enhanced dependency validation
         if let Some(deps) = &service.depends_on {
-            for dep in deps {
+            for dep in deps.names() {
                 if !service_names.contains(dep) {
                     let available: Vec<String> = service_names.iter().cloned().collect();
                     return Err(AthenaError::validation_error_enhanced(
@@ -269,6 +383,30 @@ fn validate_compose_enhanced(
         }
     }
 
+    // Swarm deploy mode must be consistent with replicas
+    validate_deploy_modes(athena_file)?;
+
+    // Fixed container names must be unique and cannot be scaled
+    validate_container_names(athena_file)?;
+
+    validate_reservations(athena_file)?;
+
+    // NETWORK-MODE replaces the service's own network stack
+    validate_network_modes(athena_file, &service_names)?;
+
+    // Jobs exit once done, so they can't restart forever or become healthy
+    validate_jobs(athena_file)?;
+
+    validate_external_services(athena_file)?;
+
+    validate_network_attachments(athena_file)?;
+
+    // The Docker socket gives the container control of the host
+    validate_runtime_sockets(compose, athena_file)?;
+
+    // WATCH REBUILD rebuilds the image, so there has to be something to build
+    validate_watch_rules(compose)?;
+
     // Fast circular dependency detection
     detect_circular_dependencies_optimized(compose)?;
 
@@ -276,97 +414,454 @@ fn validate_compose_enhanced(
     detect_port_conflicts(compose)?;
 
     // Advanced validation: BUILD-ARGS vs Dockerfile ARGs
-    validate_dockerfile_build_args(athena_file)?;
+    validate_dockerfile_build_args(athena_file, base_dir)?;
 
     Ok(())
 }
 
-/// Validate port mapping format
-fn is_valid_port_mapping(port_mapping: &str) -> bool {
-    // Basic validation for format like "8080:80" or "8080:80/tcp"
-    let parts: Vec<&str> = port_mapping.split(':').collect();
-    if parts.len() < 2 {
-        return false;
+/// Validate that global services don't also declare a replica count
+fn validate_deploy_modes(athena_file: &AthenaFile) -> AthenaResult<()> {
+    for service in &athena_file.services.services {
+        let Some(swarm) = &service.swarm_config else {
+            continue;
+        };
+
+        if swarm.mode == Some(DeployMode::Global) {
+            if let Some(replicas) = swarm.replicas {
+                return Err(AthenaError::validation_error_enhanced(
+                    EnhancedValidationError::new(format!(
+                        "Service '{}' uses DEPLOY-MODE global and REPLICAS {replicas}, which are mutually exclusive",
+                        service.name
+                    ))
+                    .with_suggestion(
+                        "Remove REPLICAS: a global service runs exactly one task on every node. Use DEPLOY-MODE replicated to control the count".to_string(),
+                    )
+                    .with_services(vec![service.name.clone()]),
+                ));
+            }
+        }
     }
 
-    // Validate host port
-    if parts[0].parse::<u16>().is_err() {
-        return false;
+    Ok(())
+}
+
+/// A RESERVE value can't exceed the matching RESOURCE-LIMITS value, or Swarm
+/// refuses to schedule the service
+fn validate_reservations(athena_file: &AthenaFile) -> AthenaResult<()> {
+    for service in &athena_file.services.services {
+        let (Some(limits), Some(reservations)) = (&service.resources, &service.reservations) else {
+            continue;
+        };
+
+        let mut conflicts = Vec::new();
+        if let Some(memory) = reservations.memory.filter(|memory| *memory > limits.memory) {
+            conflicts.push(format!("MEMORY {memory} above the {} limit", limits.memory));
+        }
+        if let (Some(cpus), Ok(limit)) = (&reservations.cpus, limits.cpu.parse::<f64>()) {
+            if cpus.parse::<f64>().is_ok_and(|cpus| cpus > limit) {
+                conflicts.push(format!("CPUS {cpus} above the {} limit", limits.cpu));
+            }
+        }
+
+        if !conflicts.is_empty() {
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "Service '{}' reserves {}",
+                    service.name,
+                    conflicts.join(" and ")
+                ))
+                .with_suggestion("Lower RESERVE or raise RESOURCE-LIMITS: a reservation must fit within the limit".to_string())
+                .with_services(vec![service.name.clone()]),
+            ));
+        }
     }
 
-    // Validate container port (may include protocol)
-    let container_part = parts[1];
-    let container_port = if container_part.contains('/') {
-        container_part.split('/').next().unwrap_or("")
-    } else {
-        container_part
-    };
+    Ok(())
+}
 
-    container_port.parse::<u16>().is_ok()
+fn validate_container_names(athena_file: &AthenaFile) -> AthenaResult<()> {
+    let mut owners: HashMap<&str, &str> = HashMap::new();
+
+    for service in &athena_file.services.services {
+        let Some(container_name) = service.container_name.as_deref() else {
+            continue;
+        };
+
+        if let Some(owner) = owners.insert(container_name, &service.name) {
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "Services '{owner}' and '{}' both use CONTAINER-NAME '{container_name}'",
+                    service.name
+                ))
+                .with_suggestion("Give each service a distinct CONTAINER-NAME or remove it to let Compose name the containers".to_string())
+                .with_services(vec![owner.to_string(), service.name.clone()]),
+            ));
+        }
+
+        if let Some(replicas) = service.swarm_config.as_ref().and_then(|s| s.replicas).filter(|r| *r > 1) {
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "Service '{}' sets CONTAINER-NAME '{container_name}' and REPLICAS {replicas}, but a fixed name allows only one container",
+                    service.name
+                ))
+                .with_suggestion("Remove CONTAINER-NAME to run several replicas".to_string())
+                .with_services(vec![service.name.clone()]),
+            ));
+        }
+    }
+
+    Ok(())
 }
 
-/// Optimized circular dependency detection using iterative DFS
-fn detect_circular_dependencies_optimized(compose: &DockerCompose) -> AthenaResult<()> {
-    use std::collections::HashSet;
+/// Mounting the Docker or containerd socket, in short or long syntax, needs
+/// ALLOW DOCKER SOCKET on the service; warning ATH010 reports it from then on
+fn validate_runtime_sockets(compose: &DockerCompose, athena_file: &AthenaFile) -> AthenaResult<()> {
+    for (service_name, service) in &compose.services {
+        let allowed = athena_file
+            .services
+            .services
+            .iter()
+            .any(|s| s.name == *service_name && s.allow_docker_socket);
+        if allowed {
+            continue;
+        }
 
-    let mut visited = HashSet::new();
-    let mut temp_visited = HashSet::new();
+        let mut sources = service.volumes.iter().flatten().map(|volume| volume.source());
+        if let Some(socket) = sources.find(|source| is_runtime_socket(source)) {
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "Service '{service_name}' mounts the container runtime socket {socket}, which gives it root-equivalent control of the host"
+                ))
+                .with_suggestion(format!(
+                    "Remove the mount, or add ALLOW DOCKER SOCKET to service '{service_name}' if it really needs the daemon"
+                ))
+                .with_services(vec![service_name.clone()]),
+            ));
+        }
+    }
+
+    Ok(())
+}
 
-    for service_name in compose.services.keys() {
-        if !visited.contains(service_name)
-            && has_cycle_iterative(service_name, compose, &mut visited, &mut temp_visited)? {
+/// USES must name an EXTERNAL SERVICE, and an external name must not shadow a service
+fn validate_external_services(athena_file: &AthenaFile) -> AthenaResult<()> {
+    let services = &athena_file.services.services;
+
+    for external in athena_file.externals() {
+        if services.iter().any(|s| s.name == external.name) {
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "EXTERNAL SERVICE '{}' has the same name as a service in the stack",
+                    external.name
+                ))
+                .with_suggestion("Rename one of them; containers could not tell which one the name refers to".to_string())
+                .with_services(vec![external.name.clone()]),
+            ));
+        }
+    }
+
+    for service in services {
+        for name in &service.uses {
+            if athena_file.find_external(name).is_some() {
+                continue;
+            }
+            let declared: Vec<&str> = athena_file.externals().iter().map(|e| e.name.as_str()).collect();
+            let suggestion = if services.iter().any(|s| &s.name == name) {
+                format!("'{name}' is part of the stack; use DEPENDS-ON {name} instead")
+            } else if declared.is_empty() {
+                format!("Declare it in the ENVIRONMENT SECTION: EXTERNAL SERVICE {name} HOST \"host.docker.internal\" PORT 5432")
+            } else {
+                format!("Declared external services: {}", declared.join(", "))
+            };
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "Service '{}' USES '{name}', which is not an EXTERNAL SERVICE",
+                    service.name
+                ))
+                .with_suggestion(suggestion)
+                .with_services(vec![service.name.clone()]),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// ON NETWORK must name a declared network, and static addresses must be
+/// unique, inside the network's SUBNET and on a single container
+fn validate_network_attachments(athena_file: &AthenaFile) -> AthenaResult<()> {
+    let declared: Vec<&NetworkDefinition> = athena_file
+        .environment
+        .iter()
+        .flat_map(|env| &env.networks)
+        .collect();
+    let project_network = athena_file.get_network_name();
+    let mut addresses: HashMap<(&str, &str), &str> = HashMap::new();
+
+    for service in &athena_file.services.services {
+        let invalid = |message: String, suggestion: String| {
+            Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(message)
+                    .with_suggestion(suggestion)
+                    .with_services(vec![service.name.clone()]),
+            ))
+        };
+
+        if service.network_mode.is_some() && !service.networks.is_empty() {
+            return invalid(
+                format!(
+                    "Service '{}' uses NETWORK-MODE and ON NETWORK, which Compose can't combine",
+                    service.name
+                ),
+                "Remove NETWORK-MODE, or drop the ON NETWORK lines".to_string(),
+            );
+        }
+
+        for attachment in &service.networks {
+            let definition = declared.iter().find(|n| n.name == attachment.network);
+            if definition.is_none() && attachment.network != project_network {
+                let known: Vec<&str> = if declared.is_empty() {
+                    vec![project_network.as_str()]
+                } else {
+                    declared.iter().map(|n| n.name.as_str()).collect()
+                };
+                return invalid(
+                    format!(
+                        "Service '{}' joins network '{}', which is not declared",
+                        service.name, attachment.network
+                    ),
+                    format!(
+                        "Add NETWORK-NAME {} to the ENVIRONMENT SECTION, or use one of: {}",
+                        attachment.network,
+                        known.join(", ")
+                    ),
+                );
+            }
+
+            let Some(address) = &attachment.ipv4_address else {
+                continue;
+            };
+            if service.swarm_config.as_ref().and_then(|s| s.replicas).is_some_and(|r| r > 1) {
+                return invalid(
+                    format!(
+                        "Service '{}' has a static IPV4 on '{}' but runs several replicas",
+                        service.name, attachment.network
+                    ),
+                    "Every replica would claim the same address; drop IPV4 or use REPLICAS 1".to_string(),
+                );
+            }
+            let outside_subnet = address
+                .parse()
+                .ok()
+                .and_then(|ip| definition.and_then(|n| n.subnet_contains(ip)))
+                == Some(false);
+            if outside_subnet {
+                let subnet = definition.and_then(|n| n.subnet.as_deref()).unwrap_or_default();
+                return invalid(
+                    format!(
+                        "Service '{}' uses IPV4 {address}, which is outside the SUBNET {subnet} of network '{}'",
+                        service.name, attachment.network
+                    ),
+                    format!("Pick an address inside {subnet}"),
+                );
+            }
+            if let Some(other) =
+                addresses.insert((attachment.network.as_str(), address.as_str()), service.name.as_str())
+            {
+                return invalid(
+                    format!(
+                        "Services '{other}' and '{}' both use IPV4 {address} on network '{}'",
+                        service.name, attachment.network
+                    ),
+                    "Give each service its own address".to_string(),
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn validate_jobs(athena_file: &AthenaFile) -> AthenaResult<()> {
+    let services = &athena_file.services.services;
+    let is_job = |name: &str| {
+        services
+            .iter()
+            .any(|s| s.name == name && s.kind == Some(ServiceKind::Job))
+    };
+
+    for service in services {
+        if service.kind == Some(ServiceKind::Job)
+            && matches!(service.restart, Some(RestartPolicy::Always | RestartPolicy::UnlessStopped))
+        {
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "Job '{}' would be restarted every time it completes",
+                    service.name
+                ))
+                .with_suggestion("Remove RESTART-POLICY (jobs default to 'no') or use RESTART-POLICY on-failure to retry failed runs".to_string())
+                .with_services(vec![service.name.clone()]),
+            ));
+        }
+
+        for dependency in &service.depends_on {
+            if dependency.condition == Some(DependencyCondition::Healthy) && is_job(&dependency.service) {
                 return Err(AthenaError::validation_error_enhanced(
-                    EnhancedValidationError::circular_dependency(service_name),
+                    EnhancedValidationError::new(format!(
+                        "Service '{}' waits for job '{}' to become healthy, but jobs exit instead",
+                        service.name, dependency.service
+                    ))
+                    .with_suggestion(format!(
+                        "Use DEPENDS-ON {} COMPLETED to start once the job has succeeded",
+                        dependency.service
+                    ))
+                    .with_services(vec![service.name.clone(), dependency.service.clone()]),
                 ));
             }
+        }
     }
 
     Ok(())
 }
 
-/// Iterative cycle detection for better performance and stack safety
-fn has_cycle_iterative(
-    start_service: &str,
-    compose: &DockerCompose,
-    visited: &mut std::collections::HashSet<String>,
-    temp_visited: &mut std::collections::HashSet<String>,
-) -> AthenaResult<bool> {
-    use std::collections::VecDeque;
+fn validate_watch_rules(compose: &DockerCompose) -> AthenaResult<()> {
+    for (name, service) in &compose.services {
+        let Some(develop) = &service.develop else {
+            continue;
+        };
+
+        let rebuild = develop.watch.iter().find(|w| w.action == "rebuild");
+        if let (Some(rule), None) = (rebuild, &service.build) {
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "Service '{name}' uses WATCH REBUILD \"{}\" but is pulled from an image, so there is nothing to rebuild",
+                    rule.path
+                ))
+                .with_suggestion("Add BUILD-ARGS or remove IMAGE-ID to build the service from a Dockerfile, or use WATCH SYNC+RESTART".to_string())
+                .with_services(vec![name.clone()]),
+            ));
+        }
+    }
 
-    let mut stack = VecDeque::new();
-    stack.push_back((start_service.to_string(), false));
+    Ok(())
+}
 
-    while let Some((service, is_return)) = stack.pop_back() {
-        if is_return {
-            temp_visited.remove(&service);
+fn validate_network_modes(
+    athena_file: &AthenaFile,
+    service_names: &std::collections::HashSet<String>,
+) -> AthenaResult<()> {
+    for service in &athena_file.services.services {
+        let Some(mode) = &service.network_mode else {
             continue;
+        };
+        let mode_value = mode.to_compose_value();
+        let conflict = |message: String, suggestion: &str| {
+            Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(message)
+                    .with_suggestion(suggestion.to_string())
+                    .with_services(vec![service.name.clone()]),
+            ))
+        };
+
+        if let NetworkMode::Service(target) = mode {
+            if target == &service.name || !service_names.contains(target) {
+                let available: Vec<String> = service_names
+                    .iter()
+                    .filter(|name| *name != &service.name)
+                    .cloned()
+                    .collect();
+                return Err(AthenaError::validation_error_enhanced(
+                    EnhancedValidationError::service_reference(&service.name, target, &available),
+                ));
+            }
+            if !service.dns.is_empty() || !service.extra_hosts.is_empty() || service.hostname.is_some() {
+                return conflict(
+                    format!(
+                        "Service '{}' uses NETWORK-MODE {mode_value}, which shares DNS, hosts and hostname with '{target}'",
+                        service.name
+                    ),
+                    "Move DNS, EXTRA-HOST and HOSTNAME to the service that owns the network stack",
+                );
+            }
         }
 
-        if temp_visited.contains(&service) {
-            return Ok(true); // Cycle detected
+        if service.expose_via.is_some() {
+            return conflict(
+                format!(
+                    "Service '{}' uses NETWORK-MODE {mode_value} and EXPOSE-VIA, but it is not attached to the project network",
+                    service.name
+                ),
+                "Remove NETWORK-MODE so the reverse proxy can reach the service by name",
+            );
         }
 
-        if visited.contains(&service) {
+        if service.ports.is_empty() {
             continue;
         }
 
-        visited.insert(service.clone());
-        temp_visited.insert(service.clone());
-
-        // Add return marker
-        stack.push_back((service.clone(), true));
-
-        // Add dependencies
-        if let Some(service_def) = compose.services.get(&service) {
-            if let Some(deps) = &service_def.depends_on {
-                for dep in deps {
-                    stack.push_back((dep.clone(), false));
-                }
+        let has_swarm = service.swarm_config.is_some();
+        match mode {
+            NetworkMode::Host if has_swarm => {
+                return conflict(
+                    format!(
+                        "Service '{}' publishes ports with NETWORK-MODE host, which Swarm does not support",
+                        service.name
+                    ),
+                    "Remove PORT-MAPPING: with host networking the container already listens on the node's ports",
+                );
+            }
+            NetworkMode::None | NetworkMode::Service(_) => {
+                return conflict(
+                    format!(
+                        "Service '{}' publishes ports but NETWORK-MODE {mode_value} gives it no network of its own",
+                        service.name
+                    ),
+                    "Remove PORT-MAPPING, or publish the ports on the service that owns the network stack",
+                );
             }
+            NetworkMode::Host => {}
         }
     }
 
-    Ok(false)
+    Ok(())
+}
+
+/// Validate port mapping format
+fn is_valid_port_mapping(port_mapping: &str) -> bool {
+    // Basic validation for format like "8080:80", "8080:80/tcp" or "8080-8082:80"
+    let parts: Vec<&str> = port_mapping.split(':').collect();
+    if parts.len() < 2 {
+        return false;
+    }
+
+    // Validate host port or range
+    if parts[0].split('-').any(|port| port.parse::<u16>().is_err()) {
+        return false;
+    }
+
+    // Validate container port (may include protocol)
+    let container_part = parts[1];
+    let container_port = if container_part.contains('/') {
+        container_part.split('/').next().unwrap_or("")
+    } else {
+        container_part
+    };
+
+    container_port.parse::<u16>().is_ok()
+}
+
+/// Reject DEPENDS-ON cycles, reporting the full path
+fn detect_circular_dependencies_optimized(compose: &DockerCompose) -> AthenaResult<()> {
+    let graph: Vec<(&str, Vec<&str>)> = compose
+        .services
+        .iter()
+        .map(|(name, service)| {
+            let deps = service.depends_on.as_ref().map(|d| d.names()).unwrap_or_default();
+            (name.as_str(), deps)
+        })
+        .collect();
+
+    check_cycles(&graph)
 }
 
 /// Detect port conflicts between services
@@ -379,7 +874,10 @@ fn detect_port_conflicts(compose: &DockerCompose) -> AthenaResult<()> {
     for (service_name, service) in &compose.services {
         if let Some(ports) = &service.ports {
             for port_mapping in ports {
-                if let Some(host_port) = extract_host_port(port_mapping) {
+                let Some(host_ports) = extract_host_port(port_mapping) else {
+                    continue;
+                };
+                for host_port in expand_host_ports(&host_ports) {
                     port_to_services
                         .entry(host_port)
                         .or_default()
@@ -424,6 +922,17 @@ fn extract_host_port(port_mapping: &str) -> Option<String> {
     }
 }
 
+/// Expand a host range such as "8080-8082" into its individual ports
+fn expand_host_ports(host_ports: &str) -> Vec<String> {
+    match host_ports.split_once('-') {
+        Some((start, end)) => match (start.parse::<u16>(), end.parse::<u16>()) {
+            (Ok(start), Ok(end)) => (start..=end).map(|port| port.to_string()).collect(),
+            _ => vec![host_ports.to_string()],
+        },
+        None => vec![host_ports.to_string()],
+    }
+}
+
 /// Generate port suggestions for conflicts
 fn generate_port_suggestions(base_port: &str, count: usize) -> String {
     if let Ok(port_num) = base_port.parse::<u16>() {
@@ -437,11 +946,11 @@ fn generate_port_suggestions(base_port: &str, count: usize) -> String {
     }
 }
 
-/// Validate BUILD-ARGS against Dockerfile ARGs (intelligent validation).
-fn validate_dockerfile_build_args(athena_file: &AthenaFile) -> AthenaResult<()> {
+/// Validate BUILD-ARGS against the ARGs of the Dockerfile next to the .ath file.
+fn validate_dockerfile_build_args(athena_file: &AthenaFile, base_dir: &Path) -> AthenaResult<()> {
     for service in &athena_file.services.services {
         if let Some(build_args) = &service.build_args {
-            let dockerfile_path = "Dockerfile";
+            let dockerfile_path = base_dir.join("Dockerfile");
 
             let dockerfile_analysis = match analyze_dockerfile(dockerfile_path) {
                 Ok(analysis) => analysis,
@@ -510,8 +1019,60 @@ fn improve_yaml_formatting(yaml: String) -> String {
     formatted_lines.join("\n")
 }
 
+/// Put `# from stack.ath:42 (SERVICE api)` above each service, volume and
+/// network declared in the source. Networks and volumes Athena adds itself,
+/// such as the default network, carry no annotation.
+fn annotate_sources(yaml: &str, athena_file: &AthenaFile) -> String {
+    let environment = athena_file.environment.as_ref();
+    let locate = |section: &str, key: &str| -> Option<(&'static str, &SourceLocation)> {
+        match section {
+            "services" => athena_file
+                .services
+                .services
+                .iter()
+                .find(|s| s.name == key)
+                .and_then(|s| Some(("SERVICE", s.location.as_ref()?))),
+            "volumes" => environment?
+                .volumes
+                .iter()
+                .find(|v| v.name == key)
+                .and_then(|v| Some(("VOLUME", v.location.as_ref()?))),
+            "networks" => environment?
+                .networks
+                .iter()
+                .find(|n| n.name == key)
+                .and_then(|n| Some(("NETWORK-NAME", n.location.as_ref()?))),
+            _ => None,
+        }
+    };
+
+    let mut section = "";
+    let mut annotated = Vec::new();
+    for line in yaml.lines() {
+        if !line.is_empty() && !line.starts_with(' ') {
+            section = line.strip_suffix(':').unwrap_or("");
+        } else if let Some(key) = line
+            .strip_prefix("  ")
+            .and_then(|rest| rest.strip_suffix(':'))
+            .filter(|key| !key.starts_with([' ', '-', '#']))
+        {
+            // serde_yaml quotes names that would otherwise read as numbers or booleans
+            let key = key.trim_matches(|c| c == '"' || c == '\'');
+            if let Some((kind, location)) = locate(section, key) {
+                annotated.push(format!("{ANNOTATION_PREFIX}{location} ({kind} {key})"));
+            }
+        }
+        annotated.push(line.to_string());
+    }
+    annotated.join("\n")
+}
+
 /// Add enhanced YAML comments with metadata and optimization notes
-fn add_enhanced_yaml_comments(yaml: String, athena_file: &AthenaFile) -> String {
+fn add_enhanced_yaml_comments(
+    yaml: String,
+    athena_file: &AthenaFile,
+    include_timestamp: bool,
+) -> String {
     let mut result = String::with_capacity(yaml.len() + 500);
 
     let _ = writeln!(
@@ -528,17 +1089,39 @@ fn add_enhanced_yaml_comments(yaml: String, athena_file: &AthenaFile) -> String
         }
     }
 
-    let _ = writeln!(
-        result,
-        "# Generated: {}",
-        chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
-    );
+    if include_timestamp {
+        let _ = writeln!(
+            result,
+            "# Generated: {}",
+            chrono::Utc::now().format("%Y-%m-%d %H:%M:%S UTC")
+        );
+    }
 
     let _ = writeln!(
         result,
         "# Features: Intelligent defaults, optimized networking, enhanced health checks\n"
     );
 
+    if !athena_file.externals().is_empty() {
+        let _ = writeln!(result, "# External services (not started by this file):");
+        for external in athena_file.externals() {
+            let users: Vec<&str> = athena_file
+                .services
+                .services
+                .iter()
+                .filter(|s| s.uses.contains(&external.name))
+                .map(|s| s.name.as_str())
+                .collect();
+            let used_by = if users.is_empty() {
+                "unused".to_string()
+            } else {
+                format!("used by {}", users.join(", "))
+            };
+            let _ = writeln!(result, "#   {} at {} ({used_by})", external.name, external.address());
+        }
+        result.push('\n');
+    }
+
     let service_count = athena_file.services.services.len();
     let _ = writeln!(
         result,
@@ -567,6 +1150,7 @@ mod tests {
         service.image = Some("python:3.11-slim".to_string());
         service.ports.push(PortMapping {
             host_port: 8000,
+            host_port_end: None,
             container_port: 8000,
             protocol: Protocol::Tcp,
         });
@@ -585,6 +1169,193 @@ mod tests {
         assert!(!yaml.contains("container_name:"));
     }
 
+    #[test]
+    fn test_job_services() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE db
+            IMAGE-ID "postgres:16"
+            END SERVICE
+            SERVICE migrations
+            IMAGE-ID "migrate/migrate"
+            KIND job
+            DEPENDS-ON db HEALTHY
+            DEPLOY-MODE replicated
+            END SERVICE
+            SERVICE api
+            IMAGE-ID "node:20"
+            DEPENDS-ON migrations COMPLETED
+            DEPENDS-ON db
+            END SERVICE
+        "#;
+        let athena_file = crate::athena::parser::parse_athena_file(input).unwrap();
+        let yaml = generate_docker_compose(&athena_file).unwrap();
+        let compose: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+
+        let migrations = &compose["services"]["migrations"];
+        assert_eq!(migrations["restart"], "no");
+        assert!(migrations.get("healthcheck").is_none());
+        assert_eq!(migrations["deploy"]["restart_policy"]["condition"], "none");
+        assert_eq!(migrations["depends_on"]["db"]["condition"], "service_healthy");
+
+        let api = &compose["services"]["api"];
+        assert_eq!(api["depends_on"]["migrations"]["condition"], "service_completed_successfully");
+        assert_eq!(api["depends_on"]["db"]["condition"], "service_started");
+
+        let input = input.replace("DEPENDS-ON migrations COMPLETED", "DEPENDS-ON migrations HEALTHY");
+        let athena_file = crate::athena::parser::parse_athena_file(&input).unwrap();
+        let err = generate_docker_compose(&athena_file).unwrap_err().to_string();
+        assert!(err.contains("waits for job 'migrations' to become healthy"));
+        assert!(err.contains("DEPENDS-ON migrations COMPLETED"));
+
+        let input = input.replace("KIND job", "KIND job\nRESTART-POLICY always");
+        let athena_file = crate::athena::parser::parse_athena_file(&input).unwrap();
+        let err = generate_docker_compose(&athena_file).unwrap_err().to_string();
+        assert!(err.contains("Job 'migrations' would be restarted"));
+    }
+
+    #[test]
+    fn test_watch_rules() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE api
+            WATCH SYNC "./src" TO "/app/src"
+            WATCH REBUILD "./go.mod"
+            END SERVICE
+        "#;
+        let athena_file = crate::athena::parser::parse_athena_file(input).unwrap();
+        let yaml = generate_docker_compose(&athena_file).unwrap();
+        let compose: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+
+        let watch = &compose["services"]["api"]["develop"]["watch"];
+        assert_eq!(watch[0]["action"], "sync");
+        assert_eq!(watch[0]["target"], "/app/src");
+        assert_eq!(watch[1]["action"], "rebuild");
+        assert!(watch[1].get("target").is_none());
+
+        let input = input.replace("SERVICE api", "SERVICE api\nIMAGE-ID \"node:20\"");
+        let athena_file = crate::athena::parser::parse_athena_file(&input).unwrap();
+        let err = generate_docker_compose(&athena_file).unwrap_err().to_string();
+        assert!(err.contains("uses WATCH REBUILD \"./go.mod\" but is pulled from an image"));
+    }
+
+    #[test]
+    fn test_external_services() {
+        let input = r#"
+            ENVIRONMENT SECTION
+            EXTERNAL SERVICE shared_db HOST "host.docker.internal" PORT 5432
+            EXTERNAL SERVICE ldap HOST "10.0.0.12"
+            EXTERNAL SERVICE smtp HOST "mail.example.com" PORT 25
+            SERVICES SECTION
+            SERVICE api
+            IMAGE-ID "api:latest"
+            USES shared_db
+            USES ldap
+            USES smtp
+            ENV-VARIABLE "DATABASE_URL=postgres://app@shared_db:5432/app"
+            END SERVICE
+        "#;
+        let athena_file = crate::athena::parser::parse_athena_file(input).unwrap();
+        assert!(crate::athena::warnings::collect_warnings(&athena_file).warnings.is_empty());
+
+        let yaml = generate_docker_compose(&athena_file).unwrap();
+        assert!(yaml.contains("#   shared_db at host.docker.internal:5432 (used by api)"));
+        assert!(!yaml.contains("\n  shared_db:"), "external services are not emitted");
+
+        let compose: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+        let extra_hosts = &compose["services"]["api"]["extra_hosts"];
+        assert_eq!(extra_hosts[0], "shared_db:host-gateway");
+        assert_eq!(extra_hosts[1], "ldap:10.0.0.12");
+        assert!(extra_hosts.get(2).is_none(), "DNS hosts can't be mapped");
+
+        let input = input.replace("USES ldap", "USES cache");
+        let athena_file = crate::athena::parser::parse_athena_file(&input).unwrap();
+        let err = generate_docker_compose(&athena_file).unwrap_err().to_string();
+        assert!(err.contains("USES 'cache', which is not an EXTERNAL SERVICE"));
+    }
+
+    #[test]
+    fn test_network_aliases_and_static_addresses() {
+        let input = r#"
+            ENVIRONMENT SECTION
+            NETWORK-NAME frontend
+            NETWORK-NAME backend SUBNET "172.28.0.0/16"
+            SERVICES SECTION
+            SERVICE db
+            IMAGE-ID "postgres:16"
+            ON NETWORK backend ALIAS "db.legacy.local"
+            ON NETWORK backend ALIAS "postgres.legacy.local" IPV4 "172.28.0.10"
+            END SERVICE
+            SERVICE web
+            IMAGE-ID "nginx:alpine"
+            ON NETWORK backend
+            END SERVICE
+        "#;
+        let athena_file = crate::athena::parser::parse_athena_file(input).unwrap();
+        let yaml = generate_docker_compose(&athena_file).unwrap();
+        let compose: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+
+        assert_eq!(compose["networks"]["backend"]["ipam"]["config"][0]["subnet"], "172.28.0.0/16");
+        let db = &compose["services"]["db"]["networks"];
+        assert!(db["frontend"].is_mapping(), "project network stays attached");
+        assert_eq!(db["backend"]["aliases"][0], "db.legacy.local");
+        assert_eq!(db["backend"]["aliases"][1], "postgres.legacy.local");
+        assert_eq!(db["backend"]["ipv4_address"], "172.28.0.10");
+        let web = &compose["services"]["web"]["networks"];
+        assert_eq!(web[0], "frontend", "no options keeps the list form");
+        assert_eq!(web[1], "backend");
+
+        let error_for = |from: &str, to: &str| {
+            let athena_file = crate::athena::parser::parse_athena_file(&input.replace(from, to)).unwrap();
+            generate_docker_compose(&athena_file).unwrap_err().to_string()
+        };
+        assert!(error_for("ON NETWORK backend\n", "ON NETWORK storage\n")
+            .contains("joins network 'storage', which is not declared"));
+        assert!(error_for("\"172.28.0.10\"", "\"10.0.0.10\"")
+            .contains("outside the SUBNET 172.28.0.0/16"));
+        assert!(error_for("ON NETWORK backend\n", "ON NETWORK backend IPV4 \"172.28.0.10\"\n")
+            .contains("Services 'db' and 'web' both use IPV4 172.28.0.10"));
+        assert!(error_for("ON NETWORK backend\n", "ON NETWORK backend\nNETWORK-MODE host\n")
+            .contains("uses NETWORK-MODE and ON NETWORK"));
+        assert!(error_for("IMAGE-ID \"postgres:16\"", "IMAGE-ID \"postgres:16\"\nREPLICAS 2")
+            .contains("static IPV4 on 'backend' but runs several replicas"));
+    }
+
+    #[test]
+    fn test_source_annotations() {
+        let input = "DEPLOYMENT-ID SHOP
+ENVIRONMENT SECTION
+NETWORK-NAME backend
+VOLUME pg_data
+SERVICES SECTION
+SERVICE db
+IMAGE-ID \"postgres:16\"
+VOLUME-MAPPING \"pg_data\" TO \"/var/lib/postgresql/data\"
+END SERVICE
+SERVICE api
+IMAGE-ID \"myorg/api\"
+DEPENDS-ON db
+END SERVICE";
+        let athena_file = crate::athena::parser::parse_athena_file(input).unwrap();
+        let options = GeneratorOptions::new().with_reproducible(true);
+        let plain = generate_docker_compose_with_options(&athena_file, &options).unwrap();
+        let annotated =
+            generate_docker_compose_with_options(&athena_file, &options.clone().with_annotate_sources(true)).unwrap();
+
+        assert!(annotated.contains("\n  # from line 6 (SERVICE db)\n  db:\n"));
+        assert!(annotated.contains("\n\n  # from line 10 (SERVICE api)\n  api:\n"));
+        assert!(annotated.contains("\n  # from line 3 (NETWORK-NAME backend)\n  backend:\n"));
+        assert!(annotated.contains("\n  # from line 4 (VOLUME pg_data)\n  pg_data:\n"));
+
+        let stripped: String = annotated
+            .split_inclusive('\n')
+            .filter(|line| !line.starts_with(ANNOTATION_PREFIX))
+            .collect();
+        assert_eq!(stripped, plain);
+        let parsed = |yaml: &str| serde_yaml::from_str::<serde_yaml::Value>(yaml).unwrap();
+        assert_eq!(parsed(&annotated), parsed(&plain));
+    }
+
     #[test]
     fn test_extract_host_port() {
         assert_eq!(extract_host_port("8080:80"), Some("8080".to_string()));
@@ -1,28 +1,31 @@
 use std::collections::{BTreeMap, HashMap};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
+use crate::athena::mounts::{has_drive_letter, is_bind_mount, normalize_mount_path};
 use crate::athena::parser::ast::{
-    EnvironmentVariable, FailureAction, PortMapping, Protocol, ResourceLimits, RestartPolicy,
-    Service, SwarmConfig, VolumeMapping,
+    ByteSize, ContainerCommand, Dependency, DeployMode, Duration, EnvironmentVariable, ExtraHost, FailureAction, IpcMode, NetworkMode,
+    PidMode, PortMapping, Protocol, PullPolicy, ResourceLimits, ResourceReservations, RestartPolicy, Service, ServiceKind,
+    SwarmConfig, VolumeMapping, WatchRule,
 };
 
 /// Default Docker Compose configurations based on service patterns and Docker standards
 #[derive(Debug, Clone)]
 pub struct ServiceDefaults {
     pub restart_policy: RestartPolicy,
-    pub health_check_interval: String,
-    pub health_check_timeout: String,
+    pub health_check_interval: Duration,
+    pub health_check_timeout: Duration,
     pub health_check_retries: u32,
-    pub health_check_start_period: String,
+    pub health_check_start_period: Duration,
 }
 
 impl Default for ServiceDefaults {
     fn default() -> Self {
         Self {
             restart_policy: RestartPolicy::UnlessStopped,
-            health_check_interval: "30s".to_string(),
-            health_check_timeout: "10s".to_string(),
+            health_check_interval: Duration::from_secs(30),
+            health_check_timeout: Duration::from_secs(10),
             health_check_retries: 3,
-            health_check_start_period: "40s".to_string(),
+            health_check_start_period: Duration::from_secs(40),
         }
     }
 }
@@ -35,25 +38,179 @@ pub struct EnhancedDockerService {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub build: Option<BuildConfig>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub pull_policy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub ports: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub environment: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub command: Option<String>,
+    pub command: Option<ContainerCommand>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entrypoint: Option<ContainerCommand>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub init: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tty: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stdin_open: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub volumes: Option<Vec<String>>,
+    pub stop_signal: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub depends_on: Option<Vec<String>>,
+    pub stop_grace_period: Option<Duration>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volumes: Option<Vec<ComposeVolume>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub depends_on: Option<ComposeDependsOn>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub healthcheck: Option<EnhancedHealthCheck>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub develop: Option<ComposeDevelop>,
     pub restart: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub deploy: Option<EnhancedDeploy>,
-    pub networks: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_mode: Option<String>,
+    /// None when `network_mode` is set, since Compose rejects both together
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub networks: Option<ComposeNetworks>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipc: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extra_hosts: Option<Vec<String>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub labels: Option<BTreeMap<String, String>>,
 }
 
+/// Plain list of services, or the long mapping when any DEPENDS-ON sets a condition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ComposeDependsOn {
+    Short(Vec<String>),
+    Long(IndexMap<String, DependsOnCondition>),
+}
+
+impl ComposeDependsOn {
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            ComposeDependsOn::Short(names) => names.iter().map(String::as_str).collect(),
+            ComposeDependsOn::Long(entries) => entries.keys().map(String::as_str).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DependsOnCondition {
+    pub condition: String,
+}
+
+/// Plain list of networks, or the long mapping when an ON NETWORK line sets
+/// an alias or a static address
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ComposeNetworks {
+    Short(Vec<String>),
+    Long(IndexMap<String, ServiceNetwork>),
+}
+
+impl ComposeNetworks {
+    pub fn names(&self) -> Vec<&str> {
+        match self {
+            ComposeNetworks::Short(names) => names.iter().map(String::as_str).collect(),
+            ComposeNetworks::Long(entries) => entries.keys().map(String::as_str).collect(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ServiceNetwork {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipv4_address: Option<String>,
+}
+
+/// `develop` section read by `docker compose watch`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComposeDevelop {
+    pub watch: Vec<ComposeWatch>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComposeWatch {
+    pub action: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+}
+
+/// Short `source:target[:opts]` string, or the long mapping when an option
+/// has no short-syntax form
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ComposeVolume {
+    Short(String),
+    Long(LongVolume),
+}
+
+impl ComposeVolume {
+    /// Host side of the mount: a path or a named volume
+    pub fn source(&self) -> &str {
+        match self {
+            Self::Short(volume) => {
+                // `C:/data:/data` has a colon in its source
+                let skip = if has_drive_letter(volume) { 2 } else { 0 };
+                volume[skip..].find(':').map_or(volume.as_str(), |end| &volume[..skip + end])
+            }
+            Self::Long(volume) => &volume.source,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LongVolume {
+    #[serde(rename = "type")]
+    pub mount_type: String,
+    pub source: String,
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub consistency: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bind: Option<BindOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volume: Option<NamedVolumeOptions>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BindOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub propagation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub selinux: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NamedVolumeOptions {
+    pub nocopy: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BuildConfig {
     pub context: String,
@@ -66,14 +223,16 @@ pub struct BuildConfig {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EnhancedHealthCheck {
     pub test: Vec<String>,
-    pub interval: String,
-    pub timeout: String,
+    pub interval: Duration,
+    pub timeout: Duration,
     pub retries: u32,
-    pub start_period: String,
+    pub start_period: Duration,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EnhancedDeploy {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub resources: Option<EnhancedResources>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -99,15 +258,15 @@ pub struct ResourceSpec {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cpus: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub memory: Option<String>,
+    pub memory: Option<ByteSize>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EnhancedRestartPolicy {
     pub condition: String,
-    pub delay: String,
+    pub delay: Duration,
     pub max_attempts: u32,
-    pub window: String,
+    pub window: Duration,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -115,11 +274,11 @@ pub struct SwarmUpdateConfig {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parallelism: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub delay: Option<String>,
+    pub delay: Option<Duration>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub failure_action: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub monitor: Option<String>,
+    pub monitor: Option<Duration>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub max_failure_ratio: Option<f32>,
 }
@@ -131,6 +290,8 @@ pub enum ServiceType {
     Database,
     Cache,
     Proxy,
+    /// `KIND job`: runs once, never restarted or health-checked by default
+    Job,
     Generic,
 }
 
@@ -139,6 +300,10 @@ pub struct DefaultsEngine;
 impl DefaultsEngine {
     /// Detect service type from image and configuration for intelligent defaults
     pub fn detect_service_type(service: &Service) -> ServiceType {
+        if service.kind == Some(ServiceKind::Job) {
+            return ServiceType::Job;
+        }
+
         if let Some(image) = &service.image {
             let image_lower = image.to_lowercase();
             
@@ -175,31 +340,35 @@ impl DefaultsEngine {
         match service_type {
             ServiceType::Database => ServiceDefaults {
                 restart_policy: RestartPolicy::Always,
-                health_check_interval: "10s".to_string(),
-                health_check_timeout: "5s".to_string(),
+                health_check_interval: Duration::from_secs(10),
+                health_check_timeout: Duration::from_secs(5),
                 health_check_retries: 5,
-                health_check_start_period: "60s".to_string(),
+                health_check_start_period: Duration::from_secs(60),
             },
             ServiceType::Cache => ServiceDefaults {
                 restart_policy: RestartPolicy::Always,
-                health_check_interval: "15s".to_string(),
-                health_check_timeout: "3s".to_string(),
+                health_check_interval: Duration::from_secs(15),
+                health_check_timeout: Duration::from_secs(3),
                 health_check_retries: 3,
-                health_check_start_period: "20s".to_string(),
+                health_check_start_period: Duration::from_secs(20),
             },
             ServiceType::Proxy => ServiceDefaults {
                 restart_policy: RestartPolicy::Always,
-                health_check_interval: "20s".to_string(),
-                health_check_timeout: "5s".to_string(),
+                health_check_interval: Duration::from_secs(20),
+                health_check_timeout: Duration::from_secs(5),
                 health_check_retries: 3,
-                health_check_start_period: "30s".to_string(),
+                health_check_start_period: Duration::from_secs(30),
             },
             ServiceType::WebApp => ServiceDefaults {
                 restart_policy: RestartPolicy::UnlessStopped,
-                health_check_interval: "30s".to_string(),
-                health_check_timeout: "10s".to_string(),
+                health_check_interval: Duration::from_secs(30),
+                health_check_timeout: Duration::from_secs(10),
                 health_check_retries: 3,
-                health_check_start_period: "40s".to_string(),
+                health_check_start_period: Duration::from_secs(40),
+            },
+            ServiceType::Job => ServiceDefaults {
+                restart_policy: RestartPolicy::No,
+                ..ServiceDefaults::default()
             },
             ServiceType::Generic => ServiceDefaults::default(),
         }
@@ -215,7 +384,7 @@ impl DefaultsEngine {
         let defaults = Self::get_defaults_for_type(service_type);
         
         let build_config = Self::create_build_config(service, project_name);
-        let enhanced_service = EnhancedDockerService {
+        EnhancedDockerService {
             // If we have build config with args, don't use image (build takes precedence)
             image: if build_config.is_some() && service.build_args.is_some() { 
                 None 
@@ -223,27 +392,58 @@ impl DefaultsEngine {
                 service.image.clone() 
             },
             build: build_config,
+            pull_policy: service.pull_policy.map(|policy| match policy {
+                PullPolicy::Always => "always".to_string(),
+                PullPolicy::Missing => "missing".to_string(),
+                PullPolicy::Never => "never".to_string(),
+            }),
+            platform: service.platform.clone(),
+            profiles: (!service.profiles.is_empty()).then(|| service.profiles.clone()),
+            container_name: service.container_name.clone(),
             ports: Self::convert_ports(&service.ports),
             environment: Self::convert_environment(&service.environment),
             command: service.command.clone(),
+            entrypoint: service.entrypoint.clone(),
+            working_dir: service.working_dir.clone(),
+            user: service.user.clone(),
+            init: service.init,
+            tty: service.tty,
+            stdin_open: service.stdin_open,
+            stop_signal: service.stop_signal.clone(),
+            stop_grace_period: service.stop_grace_period,
+            hostname: service.hostname.clone(),
             volumes: Self::convert_volumes(&service.volumes),
-            depends_on: if service.depends_on.is_empty() { 
-                None 
-            } else { 
-                Some(service.depends_on.clone()) 
-            },
+            depends_on: Self::convert_depends_on(&service.depends_on),
             healthcheck: Self::convert_healthcheck(&service.health_check, &defaults, service_type, &service.ports),
+            develop: Self::convert_watch(&service.watch),
             restart: Self::convert_restart_policy(&service.restart, &defaults),
-            deploy: Self::convert_deploy(&service.resources, &service.swarm_config),
-            networks: vec![network_name.to_string()],
+            deploy: Self::convert_deploy(
+                &service.resources,
+                &service.reservations,
+                &service.swarm_config,
+                &service.restart.clone().or_else(|| {
+                    (service_type == ServiceType::Job).then_some(defaults.restart_policy.clone())
+                }),
+            ),
+            network_mode: service.network_mode.as_ref().map(NetworkMode::to_compose_value),
+            networks: Self::convert_networks(service, network_name),
+            pid: service.pid.map(|PidMode::Host| "host".to_string()),
+            ipc: service.ipc.map(|mode| match mode {
+                IpcMode::Host => "host".to_string(),
+                IpcMode::Shareable => "shareable".to_string(),
+            }),
+            dns: if service.dns.is_empty() {
+                None
+            } else {
+                Some(service.dns.clone())
+            },
+            extra_hosts: Self::convert_extra_hosts(&service.extra_hosts),
             labels: Some(Self::generate_labels(project_name, &service.name, service_type)),
-        };
-
-        enhanced_service
+        }
     }
     
     /// Create build configuration - prefer Dockerfile over image when no image is specified
-    fn create_build_config(service: &Service, _project_name: &str) -> Option<BuildConfig> {
+    pub fn create_build_config(service: &Service, _project_name: &str) -> Option<BuildConfig> {
         // If no image is specified OR if build_args are provided, use build configuration
         if service.image.is_none() || service.build_args.is_some() {
             Some(BuildConfig {
@@ -264,8 +464,8 @@ impl DefaultsEngine {
         let port_strings: Vec<String> = ports
             .iter()
             .map(|p| match p.protocol {
-                Protocol::Tcp => format!("{}:{}", p.host_port, p.container_port),
-                Protocol::Udp => format!("{}:{}/udp", p.host_port, p.container_port),
+                Protocol::Tcp => format!("{}:{}", p.host_ports(), p.container_port),
+                Protocol::Udp => format!("{}:{}/udp", p.host_ports(), p.container_port),
             })
             .collect();
         
@@ -286,8 +486,9 @@ impl DefaultsEngine {
                 EnvironmentVariable::Literal(value) => {
                     // If it's already in KEY=VALUE format, use as-is
                     // Otherwise, treat as a standalone value
+                    let value = escape_compose_dollars(value);
                     if value.contains('=') {
-                        env_list.push(value.clone());
+                        env_list.push(value);
                     } else {
                         env_list.push(format!("VALUE={value}"));
                     }
@@ -298,26 +499,115 @@ impl DefaultsEngine {
         Some(env_list)
     }
     
-    fn convert_volumes(volumes: &[VolumeMapping]) -> Option<Vec<String>> {
+    pub fn convert_volumes(volumes: &[VolumeMapping]) -> Option<Vec<ComposeVolume>> {
         if volumes.is_empty() {
             return None;
         }
         
-        let volume_strings: Vec<String> = volumes
-            .iter()
-            .map(|v| {
-                let mut volume_str = format!("{}:{}", v.host_path, v.container_path);
-                if !v.options.is_empty() {
-                    volume_str.push(':');
-                    volume_str.push_str(&v.options.join(","));
-                }
-                volume_str
-            })
-            .collect();
-        
-        Some(volume_strings)
+        Some(volumes.iter().map(Self::convert_volume).collect())
+    }
+
+    fn convert_volume(v: &VolumeMapping) -> ComposeVolume {
+        let source = normalize_mount_path(&v.host_path);
+        let target = normalize_mount_path(&v.container_path);
+
+        if !v.needs_long_syntax() {
+            let mut volume_str = format!("{source}:{target}");
+            if !v.options.is_empty() {
+                volume_str.push(':');
+                volume_str.push_str(&v.options.join(","));
+            }
+            return ComposeVolume::Short(volume_str);
+        }
+
+        let bind = is_bind_mount(&v.host_path);
+        ComposeVolume::Long(LongVolume {
+            mount_type: if bind { "bind" } else { "volume" }.to_string(),
+            source,
+            target,
+            read_only: v.read_only().then_some(true),
+            consistency: v.consistency().map(str::to_string),
+            bind: (bind && (v.propagation().is_some() || v.selinux_label().is_some())).then(|| BindOptions {
+                propagation: v.propagation().map(str::to_string),
+                selinux: v.selinux_label().map(str::to_string),
+            }),
+            volume: v.nocopy().then_some(NamedVolumeOptions { nocopy: true }),
+        })
     }
     
+    fn convert_depends_on(depends_on: &[Dependency]) -> Option<ComposeDependsOn> {
+        if depends_on.is_empty() {
+            return None;
+        }
+
+        if depends_on.iter().all(|d| d.condition.is_none()) {
+            return Some(ComposeDependsOn::Short(
+                depends_on.iter().map(|d| d.service.clone()).collect(),
+            ));
+        }
+
+        Some(ComposeDependsOn::Long(
+            depends_on
+                .iter()
+                .map(|d| {
+                    let condition = d.condition.map_or("service_started", |c| c.to_compose_value());
+                    (d.service.clone(), DependsOnCondition { condition: condition.to_string() })
+                })
+                .collect(),
+        ))
+    }
+
+    /// The project network first, then every ON NETWORK network in order
+    fn convert_networks(service: &Service, network_name: &str) -> Option<ComposeNetworks> {
+        if service.network_mode.is_some() {
+            return None;
+        }
+
+        let mut networks: IndexMap<String, ServiceNetwork> = IndexMap::new();
+        networks.insert(network_name.to_string(), ServiceNetwork::default());
+        for attachment in &service.networks {
+            let network = networks.entry(attachment.network.clone()).or_default();
+            network.aliases.clone_from(&attachment.aliases);
+            network.ipv4_address.clone_from(&attachment.ipv4_address);
+        }
+
+        if networks.values().all(|network| *network == ServiceNetwork::default()) {
+            Some(ComposeNetworks::Short(networks.into_keys().collect()))
+        } else {
+            Some(ComposeNetworks::Long(networks))
+        }
+    }
+
+    fn convert_watch(watch: &[WatchRule]) -> Option<ComposeDevelop> {
+        if watch.is_empty() {
+            return None;
+        }
+
+        Some(ComposeDevelop {
+            watch: watch
+                .iter()
+                .map(|rule| ComposeWatch {
+                    action: rule.action.to_compose_value().to_string(),
+                    path: rule.path.clone(),
+                    target: rule.target.clone(),
+                })
+                .collect(),
+        })
+    }
+
+    fn convert_extra_hosts(extra_hosts: &[ExtraHost]) -> Option<Vec<String>> {
+        if extra_hosts.is_empty() {
+            return None;
+        }
+
+        Some(
+            extra_hosts
+                .iter()
+                .map(|h| format!("{}:{}", h.hostname, h.ip))
+                .collect(),
+        )
+    }
+
     fn convert_healthcheck(
         health_check: &Option<String>,
         defaults: &ServiceDefaults,
@@ -328,10 +618,10 @@ impl DefaultsEngine {
         if let Some(cmd) = health_check {
             return Some(EnhancedHealthCheck {
                 test: vec!["CMD-SHELL".to_string(), cmd.clone()],
-                interval: defaults.health_check_interval.clone(),
-                timeout: defaults.health_check_timeout.clone(),
+                interval: defaults.health_check_interval,
+                timeout: defaults.health_check_timeout,
                 retries: defaults.health_check_retries,
-                start_period: defaults.health_check_start_period.clone(),
+                start_period: defaults.health_check_start_period,
             });
         }
 
@@ -344,27 +634,24 @@ impl DefaultsEngine {
                 Some(format!("curl -f http://localhost:{port}/ || exit 1"))
             }
             ServiceType::WebApp => {
-                if let Some(first_port) = ports.first() {
-                    Some(format!("curl -f http://localhost:{}/ || exit 1", first_port.container_port))
-                } else {
-                    None
-                }
+                ports.first().map(|first_port| {
+                    format!("curl -f http://localhost:{}/ || exit 1", first_port.container_port)
+                })
             }
+            ServiceType::Job => None,
             ServiceType::Generic => {
-                if let Some(first_port) = ports.first() {
-                    Some(format!("curl -f http://localhost:{}/ || exit 1", first_port.container_port))
-                } else {
-                    None
-                }
+                ports.first().map(|first_port| {
+                    format!("curl -f http://localhost:{}/ || exit 1", first_port.container_port)
+                })
             }
         };
 
         auto_cmd.map(|cmd| EnhancedHealthCheck {
             test: vec!["CMD-SHELL".to_string(), cmd],
-            interval: defaults.health_check_interval.clone(),
-            timeout: defaults.health_check_timeout.clone(),
+            interval: defaults.health_check_interval,
+            timeout: defaults.health_check_timeout,
             retries: defaults.health_check_retries,
-            start_period: defaults.health_check_start_period.clone(),
+            start_period: defaults.health_check_start_period,
         })
     }
     
@@ -382,38 +669,50 @@ impl DefaultsEngine {
     
     fn convert_deploy(
         resources: &Option<ResourceLimits>,
-        swarm_config: &Option<SwarmConfig>
+        reservations: &Option<ResourceReservations>,
+        swarm_config: &Option<SwarmConfig>,
+        restart: &Option<RestartPolicy>,
     ) -> Option<EnhancedDeploy> {
-        if resources.is_none() && swarm_config.is_none() {
+        if resources.is_none() && reservations.is_none() && swarm_config.is_none() {
             return None;
         }
 
-        let enhanced_resources = resources.as_ref().map(|res| EnhancedResources {
-            limits: Some(ResourceSpec {
-                cpus: Some(res.cpu.clone()),
-                memory: Some(res.memory.clone()),
-            }),
-            reservations: None,
+        let limits = resources.as_ref().map(|res| ResourceSpec {
+            cpus: Some(res.cpu.clone()),
+            memory: Some(res.memory),
         });
+        let reservations = reservations.as_ref().map(|res| ResourceSpec {
+            cpus: res.cpus.clone(),
+            memory: res.memory,
+        });
+        let enhanced_resources = (limits.is_some() || reservations.is_some())
+            .then_some(EnhancedResources { limits, reservations });
 
         // Only add deploy.restart_policy when Swarm features are active.
         // In plain Compose mode, the top-level `restart:` field is sufficient.
+        // An explicit RESTART-POLICY drives the Swarm condition so both agree.
         let has_swarm = swarm_config.as_ref().is_some_and(|s| {
-            s.replicas.is_some() || s.update_config.is_some() || s.labels.is_some()
+            s.mode.is_some() || s.replicas.is_some() || s.update_config.is_some() || s.labels.is_some()
         });
 
         let restart_policy = if has_swarm {
             Some(EnhancedRestartPolicy {
-                condition: "on-failure".to_string(),
-                delay: "5s".to_string(),
+                condition: match restart {
+                    Some(RestartPolicy::Always | RestartPolicy::UnlessStopped) => "any",
+                    Some(RestartPolicy::No) => "none",
+                    Some(RestartPolicy::OnFailure) | None => "on-failure",
+                }
+                .to_string(),
+                delay: Duration::from_secs(5),
                 max_attempts: 3,
-                window: "120s".to_string(),
+                window: Duration::from_secs(120),
             })
         } else {
             None
         };
 
         let mut enhanced_deploy = EnhancedDeploy {
+            mode: None,
             resources: enhanced_resources,
             restart_policy,
             replicas: None,
@@ -423,13 +722,17 @@ impl DefaultsEngine {
 
         // Add Swarm-specific configurations
         if let Some(swarm) = swarm_config {
+            enhanced_deploy.mode = swarm.mode.map(|mode| match mode {
+                DeployMode::Replicated => "replicated".to_string(),
+                DeployMode::Global => "global".to_string(),
+            });
             enhanced_deploy.replicas = swarm.replicas;
             enhanced_deploy.labels = swarm.labels.as_ref().map(|l| l.iter().map(|(k, v)| (k.clone(), v.clone())).collect());
             
             if let Some(update_config) = &swarm.update_config {
                 enhanced_deploy.update_config = Some(SwarmUpdateConfig {
                     parallelism: update_config.parallelism,
-                    delay: update_config.delay.clone(),
+                    delay: update_config.delay,
                     failure_action: update_config.failure_action.as_ref().map(|fa| {
                         match fa {
                             FailureAction::Continue => "continue".to_string(),
@@ -437,7 +740,7 @@ impl DefaultsEngine {
                             FailureAction::Rollback => "rollback".to_string(),
                         }
                     }),
-                    monitor: update_config.monitor.clone(),
+                    monitor: update_config.monitor,
                     max_failure_ratio: update_config.max_failure_ratio,
                 });
             }
@@ -457,10 +760,81 @@ impl DefaultsEngine {
     }
 }
 
+/// Escape `$` as `$$` so Compose doesn't interpolate it, keeping `$$` and
+/// intentional `${VAR}` / `${VAR:-default}` references as written
+fn escape_compose_dollars(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(dollar) = rest.find('$') {
+        escaped.push_str(&rest[..dollar]);
+        let after = &rest[dollar + 1..];
+        let kept = if after.starts_with('$') {
+            2
+        } else {
+            variable_reference_len(after).map_or(0, |len| len + 1)
+        };
+        if kept > 0 {
+            escaped.push_str(&rest[dollar..dollar + kept]);
+            rest = &rest[dollar + kept..];
+        } else {
+            escaped.push_str("$$");
+            rest = after;
+        }
+    }
+    escaped.push_str(rest);
+    escaped
+}
+
+/// Length of `{NAME}` or `{NAME<modifier>...}` at the start of `text`
+fn variable_reference_len(text: &str) -> Option<usize> {
+    let body = text.strip_prefix('{')?;
+    let close = body.find('}')?;
+    let inner = &body[..close];
+    let name_len = inner
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(inner.len());
+    let name = &inner[..name_len];
+    let modifier = &inner[name_len..];
+
+    let valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_');
+    let valid_modifier =
+        modifier.is_empty() || [":-", "-", ":?", "?", ":+", "+"].iter().any(|m| modifier.starts_with(m));
+    (valid_name && valid_modifier).then_some(close + 2)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_escape_compose_dollars() {
+        assert_eq!(escape_compose_dollars("PASSWORD=pa$word"), "PASSWORD=pa$$word");
+        assert_eq!(escape_compose_dollars("PRICE=$5 and $"), "PRICE=$$5 and $$");
+        assert_eq!(escape_compose_dollars("HOME_DIR=$HOME"), "HOME_DIR=$$HOME");
+        assert_eq!(escape_compose_dollars("ESCAPED=a$$b"), "ESCAPED=a$$b");
+        assert_eq!(escape_compose_dollars("URL=${BASE_URL}/api"), "URL=${BASE_URL}/api");
+        assert_eq!(escape_compose_dollars("LEVEL=${LOG_LEVEL:-info}"), "LEVEL=${LOG_LEVEL:-info}");
+        assert_eq!(escape_compose_dollars("BAD=${1NAME} ${} ${unclosed"), "BAD=$${1NAME} $${} $${unclosed");
+        assert_eq!(escape_compose_dollars("TOKEN=#x$é$"), "TOKEN=#x$$é$$");
+    }
+
+    #[test]
+    fn test_adversarial_literals_survive_yaml() {
+        let mut service = Service::new("api".to_string());
+        service.environment = vec![
+            EnvironmentVariable::Literal("SECRET=  a$b#c ".to_string()),
+            EnvironmentVariable::Literal("HASH=# not a comment".to_string()),
+            EnvironmentVariable::Literal("QUOTES='\"both\"'".to_string()),
+        ];
+        let environment = DefaultsEngine::convert_environment(&service.environment).unwrap();
+        let yaml = serde_yaml::to_string(&environment).unwrap();
+        let parsed: Vec<String> = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(
+            parsed,
+            ["SECRET=  a$$b#c ", "HASH=# not a comment", "QUOTES='\"both\"'"]
+        );
+    }
+
     #[test]
     fn test_service_type_detection() {
         let mut service = Service::new("test".to_string());
@@ -484,6 +858,7 @@ mod tests {
         service.image = Some("python:3.11-slim".to_string());
         service.ports.push(PortMapping {
             host_port: 8000,
+            host_port_end: None,
             container_port: 8000,
             protocol: Protocol::Tcp,
         });
@@ -496,7 +871,7 @@ mod tests {
         
         assert_eq!(enhanced.image, Some("python:3.11-slim".to_string()));
         assert_eq!(enhanced.restart, "unless-stopped");
-        assert_eq!(enhanced.networks, vec!["test_network"]);
+        assert_eq!(enhanced.networks, Some(ComposeNetworks::Short(vec!["test_network".to_string()])));
         assert!(enhanced.labels.is_some());
         assert!(enhanced.ports.is_some());
     }
@@ -547,4 +922,31 @@ mod tests {
         let build_config = enhanced.build.unwrap();
         assert_eq!(build_config.args, Some(build_args));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_volume_paths_use_forward_slashes() {
+        let volumes = vec![
+            VolumeMapping {
+                host_path: ".\\logs".to_string(),
+                container_path: "\\app\\logs".to_string(),
+                options: Vec::new(),
+            },
+            VolumeMapping {
+                host_path: "C:\\data".to_string(),
+                container_path: "/data".to_string(),
+                options: vec!["ro".to_string()],
+            },
+        ];
+
+        let converted = DefaultsEngine::convert_volumes(&volumes).unwrap();
+        assert_eq!(
+            converted,
+            vec![
+                ComposeVolume::Short("./logs:/app/logs".to_string()),
+                ComposeVolume::Short("C:/data:/data:ro".to_string()),
+            ]
+        );
+        let sources: Vec<&str> = converted.iter().map(ComposeVolume::source).collect();
+        assert_eq!(sources, ["./logs", "C:/data"]);
+    }
+}
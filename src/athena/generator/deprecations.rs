@@ -0,0 +1,219 @@
+use super::compose::DockerCompose;
+use super::defaults::EnhancedDockerService;
+
+/// When an entry of [`DEPRECATIONS`] applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Deprecated by the Compose spec for every deployment
+    Always,
+    /// Ignored by `docker stack deploy`, so only reported for Swarm stacks
+    Swarm,
+}
+
+/// A Compose construct Athena can emit that the spec deprecates or Swarm ignores
+#[derive(Debug)]
+pub struct Deprecation {
+    /// Compose key, as shown in the diagnostic
+    pub key: &'static str,
+    pub scope: Scope,
+    /// Whether the generated service uses the construct
+    pub emitted_by: fn(&EnhancedDockerService) -> bool,
+    pub note: &'static str,
+}
+
+/// Keep in step with the Compose spec; every entry needs a test below
+pub const DEPRECATIONS: &[Deprecation] = &[
+    Deprecation {
+        key: "restart",
+        scope: Scope::Swarm,
+        emitted_by: |s| s.deploy.as_ref().is_none_or(|d| d.restart_policy.is_none()),
+        note: "Swarm uses deploy.restart_policy and restarts these services on any exit; add DEPLOY-MODE, UPDATE-CONFIG or SWARM-LABELS to emit one from RESTART-POLICY",
+    },
+    Deprecation {
+        key: "container_name",
+        scope: Scope::Swarm,
+        emitted_by: |s| s.container_name.is_some(),
+        note: "Swarm names tasks itself; remove CONTAINER-NAME",
+    },
+    Deprecation {
+        key: "depends_on",
+        scope: Scope::Swarm,
+        emitted_by: |s| s.depends_on.is_some(),
+        note: "Swarm starts services in any order; make them retry until their dependencies are reachable",
+    },
+    Deprecation {
+        key: "build",
+        scope: Scope::Swarm,
+        emitted_by: |s| s.build.is_some(),
+        note: "build and push the image first (e.g. with `athena bake`) and reference it with IMAGE-ID",
+    },
+    Deprecation {
+        key: "network_mode",
+        scope: Scope::Swarm,
+        emitted_by: |s| s.network_mode.is_some(),
+        note: "Swarm services always join the stack's networks",
+    },
+    Deprecation {
+        key: "develop",
+        scope: Scope::Swarm,
+        emitted_by: |s| s.develop.is_some(),
+        note: "WATCH rules only apply to `docker compose watch` on a single host",
+    },
+    Deprecation {
+        key: "profiles",
+        scope: Scope::Swarm,
+        emitted_by: |s| s.profiles.is_some(),
+        note: "`docker stack deploy` starts every service regardless of PROFILE; deploy dev-only services from a separate file",
+    },
+];
+
+/// One warning per deprecated construct, naming every service that uses it
+pub(super) fn check_deprecations(compose: &DockerCompose) -> Vec<String> {
+    let swarm = is_swarm_stack(compose);
+
+    DEPRECATIONS
+        .iter()
+        .filter(|entry| entry.scope == Scope::Always || swarm)
+        .filter_map(|entry| {
+            let services: Vec<&str> = compose
+                .services
+                .iter()
+                .filter(|(_, service)| (entry.emitted_by)(service))
+                .map(|(name, _)| name.as_str())
+                .collect();
+            if services.is_empty() {
+                return None;
+            }
+
+            let reason = match entry.scope {
+                Scope::Always => "is deprecated by the Compose spec",
+                Scope::Swarm => "is ignored by `docker stack deploy`",
+            };
+            Some(format!(
+                "'{}' {reason} (services: {}): {}",
+                entry.key,
+                services.join(", "),
+                entry.note
+            ))
+        })
+        .collect()
+}
+
+/// Swarm-only deploy settings or an overlay network mean the stack targets Swarm
+pub(super) fn is_swarm_stack(compose: &DockerCompose) -> bool {
+    let swarm_deploy = compose.services.values().any(|service| {
+        service.deploy.as_ref().is_some_and(|d| {
+            d.mode.is_some() || d.update_config.is_some() || d.labels.is_some()
+        })
+    });
+    let overlay = compose
+        .networks
+        .iter()
+        .flatten()
+        .any(|(_, network)| network.driver == "overlay");
+
+    swarm_deploy || overlay
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::generator::compose::build_compose;
+    use crate::athena::generator::options::GeneratorOptions;
+    use crate::athena::parser::parse_athena_file;
+
+    fn warnings_for(input: &str) -> Vec<String> {
+        let athena_file = parse_athena_file(input).unwrap();
+        let compose = build_compose(&athena_file, &GeneratorOptions::default()).unwrap();
+        check_deprecations(&compose)
+    }
+
+    fn swarm_stack(service: &str) -> String {
+        format!(
+            "SERVICES SECTION\nSERVICE web\nIMAGE-ID nginx\nDEPLOY-MODE replicated\nEND SERVICE\n{service}"
+        )
+    }
+
+    fn warning_for_key<'a>(warnings: &'a [String], key: &str) -> Option<&'a String> {
+        warnings.iter().find(|w| w.starts_with(&format!("'{key}'")))
+    }
+
+    #[test]
+    fn test_every_entry_is_tested() {
+        let tested = [
+            "restart",
+            "container_name",
+            "depends_on",
+            "build",
+            "network_mode",
+            "develop",
+            "profiles",
+        ];
+        let keys: Vec<&str> = DEPRECATIONS.iter().map(|d| d.key).collect();
+        assert_eq!(keys, tested);
+    }
+
+    #[test]
+    fn test_plain_compose_is_not_reported() {
+        let warnings = warnings_for(
+            "SERVICES SECTION\nSERVICE db\nIMAGE-ID postgres\nEND SERVICE\nSERVICE api\nCONTAINER-NAME \"api\"\nDEPENDS-ON db\nEND SERVICE",
+        );
+        assert!(warnings.is_empty());
+    }
+
+    #[test]
+    fn test_restart_without_restart_policy() {
+        let warnings = warnings_for(&swarm_stack("SERVICE cache\nIMAGE-ID redis\nEND SERVICE"));
+        let warning = warning_for_key(&warnings, "restart").unwrap();
+        assert!(warning.contains("(services: cache)"));
+        assert!(warning.contains("ignored by `docker stack deploy`"));
+    }
+
+    #[test]
+    fn test_container_name() {
+        let warnings = warnings_for(&swarm_stack(
+            "SERVICE cache\nIMAGE-ID redis\nCONTAINER-NAME \"cache\"\nEND SERVICE",
+        ));
+        assert!(warning_for_key(&warnings, "container_name").unwrap().contains("cache"));
+    }
+
+    #[test]
+    fn test_depends_on() {
+        let warnings = warnings_for(&swarm_stack(
+            "SERVICE cache\nIMAGE-ID redis\nDEPENDS-ON web\nEND SERVICE",
+        ));
+        assert!(warning_for_key(&warnings, "depends_on").unwrap().contains("cache"));
+    }
+
+    #[test]
+    fn test_build() {
+        let warnings = warnings_for(&swarm_stack("SERVICE api\nEND SERVICE"));
+        let warning = warning_for_key(&warnings, "build").unwrap();
+        assert!(warning.contains("(services: api)"));
+        assert!(warning.contains("athena bake"));
+    }
+
+    #[test]
+    fn test_network_mode() {
+        let warnings = warnings_for(&swarm_stack(
+            "SERVICE sidecar\nIMAGE-ID alpine\nNETWORK-MODE none\nEND SERVICE",
+        ));
+        assert!(warning_for_key(&warnings, "network_mode").unwrap().contains("sidecar"));
+    }
+
+    #[test]
+    fn test_develop() {
+        let warnings = warnings_for(&swarm_stack(
+            "SERVICE api\nWATCH SYNC \"./src\" TO \"/app/src\"\nEND SERVICE",
+        ));
+        assert!(warning_for_key(&warnings, "develop").unwrap().contains("docker compose watch"));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let warnings = warnings_for(&swarm_stack(
+            "SERVICE adminer\nIMAGE-ID adminer\nPROFILE dev\nEND SERVICE",
+        ));
+        assert!(warning_for_key(&warnings, "profiles").unwrap().contains("(services: adminer)"));
+    }
+}
@@ -0,0 +1,259 @@
+//! `athena build --minimal`: leave out what Compose would assume anyway.
+//!
+//! Works on the serialized document, after every other adjustment, so it only
+//! ever removes keys or switches to an equivalent shorter form.
+
+use serde_yaml::{Mapping, Value};
+
+use super::compose::DockerCompose;
+use super::deprecations::is_swarm_stack;
+use crate::athena::error::{AthenaError, AthenaResult};
+use crate::athena::parser::ast::AthenaFile;
+use crate::athena::parser::units::Duration;
+
+/// Service keys and the value Compose uses when they are absent, whatever
+/// the image. `stop_signal` and the healthcheck timings are not here: without
+/// them the image's STOPSIGNAL and HEALTHCHECK settings apply.
+const SERVICE_DEFAULTS: &[(&str, DefaultValue)] = &[
+    ("restart", DefaultValue::Text("no")),
+    ("pull_policy", DefaultValue::Text("missing")),
+    ("init", DefaultValue::Bool(false)),
+    ("tty", DefaultValue::Bool(false)),
+    ("stdin_open", DefaultValue::Bool(false)),
+    ("stop_grace_period", DefaultValue::Duration(Duration::from_secs(10))),
+];
+
+const DEPLOY_DEFAULTS: &[(&str, DefaultValue)] = &[("mode", DefaultValue::Text("replicated")), ("replicas", DefaultValue::Number(1))];
+
+#[derive(Debug, Clone, Copy)]
+enum DefaultValue {
+    Text(&'static str),
+    Bool(bool),
+    Number(u64),
+    Duration(Duration),
+}
+
+impl DefaultValue {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            DefaultValue::Text(text) => value.as_str() == Some(text),
+            DefaultValue::Bool(flag) => value.as_bool() == Some(flag),
+            DefaultValue::Number(number) => value.as_u64() == Some(number),
+            DefaultValue::Duration(duration) => value.as_str().and_then(|v| v.parse().ok()) == Some(duration),
+        }
+    }
+}
+
+/// The Compose model as a document without default-valued keys. The project
+/// network is dropped in favour of Compose's implicit `default` network when
+/// Athena made it up and nothing configures it.
+pub(super) fn minimize(compose: &DockerCompose, athena_file: &AthenaFile) -> AthenaResult<Value> {
+    // Through text rather than `to_value`, which widens f32 fields such as
+    // max_failure_ratio and would print 0.2 as 0.20000000298023224
+    let yaml = serde_yaml::to_string(compose).map_err(AthenaError::YamlError)?;
+    let mut document: Value = serde_yaml::from_str(&yaml).map_err(AthenaError::YamlError)?;
+    let swarm = is_swarm_stack(compose);
+
+    for (_, service) in mapping_entries(&mut document, "services") {
+        let Some(service) = service.as_mapping_mut() else {
+            continue;
+        };
+        remove_defaults(service, SERVICE_DEFAULTS);
+        if let Some(deploy) = service.get_mut("deploy").and_then(Value::as_mapping_mut) {
+            remove_defaults(deploy, DEPLOY_DEFAULTS);
+            if deploy.is_empty() {
+                service.shift_remove("deploy");
+            }
+        }
+        shorten_depends_on(service);
+    }
+
+    // `docker stack deploy` defaults to overlay, so bridge stays explicit there
+    if !swarm {
+        for (_, network) in mapping_entries(&mut document, "networks") {
+            if let Some(network) = network.as_mapping_mut() {
+                remove_defaults(network, &[("driver", DefaultValue::Text("bridge"))]);
+            }
+        }
+    }
+    for (_, volume) in mapping_entries(&mut document, "volumes") {
+        if let Some(volume) = volume.as_mapping_mut() {
+            remove_defaults(volume, &[("driver", DefaultValue::Text("local"))]);
+        }
+    }
+
+    let declares_networks = athena_file.environment.as_ref().is_some_and(|env| !env.networks.is_empty());
+    if !swarm && !declares_networks {
+        use_default_network(&mut document, &athena_file.get_network_name());
+    }
+
+    Ok(document)
+}
+
+fn mapping_entries<'a>(document: &'a mut Value, section: &str) -> impl Iterator<Item = (&'a Value, &'a mut Value)> {
+    document
+        .get_mut(section)
+        .and_then(Value::as_mapping_mut)
+        .into_iter()
+        .flat_map(|mapping| mapping.iter_mut())
+}
+
+fn remove_defaults(mapping: &mut Mapping, defaults: &[(&str, DefaultValue)]) {
+    for (key, default) in defaults {
+        if mapping.get(*key).is_some_and(|value| default.matches(value)) {
+            mapping.shift_remove(*key);
+        }
+    }
+}
+
+/// `depends_on` entries that only wait for the container to start are a plain list
+fn shorten_depends_on(service: &mut Mapping) {
+    let Some(entries) = service.get("depends_on").and_then(Value::as_mapping) else {
+        return;
+    };
+    let started_only = entries.values().all(|entry| {
+        entry.as_mapping().is_some_and(|entry| {
+            entry.len() == 1 && entry.get("condition").and_then(Value::as_str) == Some("service_started")
+        })
+    });
+    if started_only {
+        let names = entries.keys().cloned().collect();
+        service.insert("depends_on".into(), Value::Sequence(names));
+    }
+}
+
+/// Remove the only network when it has no settings and every service joins
+/// it without aliases or addresses; Compose then attaches them all to
+/// `default`, a bridge network of its own
+fn use_default_network(document: &mut Value, network_name: &str) {
+    let Some(networks) = document.get("networks").and_then(Value::as_mapping) else {
+        return;
+    };
+    let unconfigured = networks.len() == 1
+        && networks
+            .get(network_name)
+            .is_some_and(|network| network.as_mapping().is_some_and(Mapping::is_empty));
+    if !unconfigured {
+        return;
+    }
+
+    let only_this_network = Value::Sequence(vec![network_name.into()]);
+    let services = document.get("services").and_then(Value::as_mapping);
+    let plain = services.into_iter().flat_map(Mapping::values).all(|service| {
+        service.get("network_mode").is_some() || service.get("networks") == Some(&only_this_network)
+    });
+    if !plain {
+        return;
+    }
+
+    for (_, service) in mapping_entries(document, "services") {
+        if let Some(service) = service.as_mapping_mut() {
+            service.shift_remove("networks");
+        }
+    }
+    if let Some(document) = document.as_mapping_mut() {
+        document.shift_remove("networks");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::generator::compose::build_compose;
+    use crate::athena::generator::GeneratorOptions;
+    use crate::athena::parser::parse_athena_file;
+
+    fn minimal(input: &str) -> Value {
+        let athena_file = parse_athena_file(input).unwrap();
+        let compose = build_compose(&athena_file, &GeneratorOptions::default()).unwrap();
+        minimize(&compose, &athena_file).unwrap()
+    }
+
+    #[test]
+    fn test_defaults_are_left_out() {
+        let document = minimal(
+            r#"
+            SERVICES SECTION
+            SERVICE api
+            IMAGE-ID "api:latest"
+            RESTART-POLICY no
+            HEALTH-CHECK "curl -f http://localhost/health"
+            STOP-SIGNAL SIGTERM
+            STOP-GRACE-PERIOD 20s
+            END SERVICE
+            "#,
+        );
+
+        let api = &document["services"]["api"];
+        assert!(api.get("restart").is_none());
+        assert_eq!(api["stop_grace_period"], "20s", "non-default values stay");
+        assert_eq!(api["healthcheck"]["timeout"], "10s");
+        assert!(document.get("networks").is_none());
+        assert!(api.get("networks").is_none());
+    }
+
+    #[test]
+    fn test_keys_the_image_can_default_stay() {
+        let document = minimal(
+            r#"
+            SERVICES SECTION
+            SERVICE proxy
+            IMAGE-ID "nginx:1.27"
+            HEALTH-CHECK "curl -f http://localhost/"
+            STOP-SIGNAL SIGTERM
+            END SERVICE
+            "#,
+        );
+
+        // nginx images set STOPSIGNAL SIGQUIT, and an image's HEALTHCHECK
+        // timings fill in whatever the service leaves out
+        let proxy = &document["services"]["proxy"];
+        assert_eq!(proxy["stop_signal"], "SIGTERM");
+        assert_eq!(proxy["healthcheck"]["retries"], 3);
+    }
+
+    #[test]
+    fn test_declared_or_configured_networks_stay() {
+        let declared = minimal(
+            r#"
+            ENVIRONMENT SECTION
+            NETWORK-NAME app_net
+            SERVICES SECTION
+            SERVICE api
+            IMAGE-ID "api:latest"
+            END SERVICE
+            "#,
+        );
+        assert_eq!(declared["networks"]["app_net"], Value::Mapping(Mapping::new()));
+        assert_eq!(declared["services"]["api"]["networks"][0], "app_net");
+
+        let swarm = minimal(
+            r#"
+            SERVICES SECTION
+            SERVICE api
+            IMAGE-ID "api:latest"
+            DEPLOY-MODE global
+            END SERVICE
+            "#,
+        );
+        let network = swarm["networks"].as_mapping().unwrap().values().next().unwrap();
+        assert_eq!(network["driver"], "bridge", "Swarm defaults to overlay");
+    }
+
+    #[test]
+    fn test_started_dependencies_use_the_short_form() {
+        let document = minimal(
+            r#"
+            SERVICES SECTION
+            SERVICE db
+            IMAGE-ID postgres:16
+            END SERVICE
+            SERVICE api
+            IMAGE-ID "api:latest"
+            DEPENDS-ON db STARTED
+            END SERVICE
+            "#,
+        );
+        assert_eq!(document["services"]["api"]["depends_on"], Value::Sequence(vec!["db".into()]));
+    }
+}
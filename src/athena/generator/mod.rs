@@ -1,4 +1,24 @@
+pub mod bake;
 pub mod compose;
 pub mod defaults;
+pub mod deprecations;
+pub mod minimal;
+pub mod nginx;
+pub mod options;
+pub mod podman;
+pub mod prefix;
+pub mod schema;
+pub mod scripts;
+pub mod select;
+pub mod terraform;
 
-pub use compose::generate_docker_compose;
\ No newline at end of file
+pub use bake::{generate_bake_file, BakeFormat};
+pub use compose::{generate_docker_compose, generate_docker_compose_with_options};
+pub use nginx::generate_nginx_config;
+pub use options::{generate, ComposeFormat, GeneratedOutput, GeneratorOptions, OutputTarget};
+pub use podman::{generate_podman_compose, SelinuxLabel};
+pub use prefix::parse_stack_prefix;
+pub use schema::check_compose_schema;
+pub use scripts::{generate_volume_scripts, VolumeScripts};
+pub use select::{select_services, ServiceSelection};
+pub use terraform::{generate_terraform_docker, TerraformOutput};
\ No newline at end of file
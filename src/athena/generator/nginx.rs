@@ -0,0 +1,203 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedValidationError};
+use crate::athena::parser::ast::{AthenaFile, ReverseProxy, Service};
+
+/// Proxy headers applied to every generated location block
+const PROXY_HEADERS: &[(&str, &str)] = &[
+    ("Host", "$host"),
+    ("X-Real-IP", "$remote_addr"),
+    ("X-Forwarded-For", "$proxy_add_x_forwarded_for"),
+    ("X-Forwarded-Proto", "$scheme"),
+    ("Upgrade", "$http_upgrade"),
+    ("Connection", "$connection_upgrade"),
+];
+
+struct NginxRoute<'a> {
+    service: &'a Service,
+    path: &'a str,
+    port: u16,
+}
+
+/// Generate an nginx conf.d snippet with one upstream and location per exposed service
+pub fn generate_nginx_config(athena_file: &AthenaFile) -> AthenaResult<String> {
+    let routes = collect_routes(athena_file)?;
+
+    let mut config = String::new();
+    let _ = writeln!(
+        config,
+        "# Generated by Athena v{} from {} deployment",
+        env!("CARGO_PKG_VERSION"),
+        athena_file.get_project_name()
+    );
+    let _ = writeln!(config, "# Services exposed: {}\n", routes.len());
+
+    // Only WebSocket handshakes ask for an upgrade; other requests close as usual
+    let _ = writeln!(config, "map $http_upgrade $connection_upgrade {{");
+    let _ = writeln!(config, "    default upgrade;");
+    let _ = writeln!(config, "    '' close;");
+    let _ = writeln!(config, "}}\n");
+
+    for route in &routes {
+        let _ = writeln!(config, "upstream {} {{", route.service.name);
+        let _ = writeln!(config, "    server {}:{};", route.service.name, route.port);
+        let _ = writeln!(config, "}}\n");
+    }
+
+    let _ = writeln!(config, "server {{");
+    let _ = writeln!(config, "    listen 80;");
+    let _ = writeln!(config, "    server_name _;");
+
+    for route in &routes {
+        let _ = writeln!(config);
+        let _ = writeln!(config, "    location {} {{", route.path);
+        let _ = writeln!(config, "        proxy_pass http://{};", route.service.name);
+        let _ = writeln!(config, "        proxy_http_version 1.1;");
+        for (header, value) in PROXY_HEADERS {
+            let _ = writeln!(config, "        proxy_set_header {header} {value};");
+        }
+        let _ = writeln!(config, "    }}");
+    }
+
+    let _ = writeln!(config, "}}");
+
+    Ok(config)
+}
+
+/// Collect services exposed through nginx, sorted by path for stable output
+fn collect_routes(athena_file: &AthenaFile) -> AthenaResult<Vec<NginxRoute<'_>>> {
+    let mut routes: BTreeMap<&str, NginxRoute<'_>> = BTreeMap::new();
+
+    for service in &athena_file.services.services {
+        let Some(exposure) = &service.expose_via else {
+            continue;
+        };
+
+        match exposure.proxy {
+            ReverseProxy::Nginx => {}
+        }
+
+        let path = exposure.path.as_str();
+        if !path.starts_with('/') {
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "Service '{}' is exposed via nginx on path '{path}', which must start with '/'",
+                    service.name
+                ))
+                .with_suggestion(format!("Use EXPOSE-VIA nginx PATH \"/{path}\""))
+                .with_services(vec![service.name.clone()]),
+            ));
+        }
+        // These would end the location directive or open a block of their own
+        if let Some(c) = path.chars().find(|c| c.is_whitespace() || matches!(c, '{' | '}' | ';')) {
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "Service '{}' is exposed via nginx on path '{path}', which contains {c:?}",
+                    service.name
+                ))
+                .with_suggestion("Use a path without whitespace, '{', '}' or ';'".to_string())
+                .with_services(vec![service.name.clone()]),
+            ));
+        }
+
+        let port = service.ports.first().map(|p| p.container_port).ok_or_else(|| {
+            AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "Service '{}' is exposed via nginx but has no PORT-MAPPING to route to",
+                    service.name
+                ))
+                .with_suggestion("Add PORT-MAPPING <host_port> TO <container_port> to the service".to_string())
+                .with_services(vec![service.name.clone()]),
+            )
+        })?;
+
+        if let Some(existing) = routes.get(path) {
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "Services '{}' and '{}' both claim nginx path '{path}'",
+                    existing.service.name, service.name
+                ))
+                .with_suggestion("Give each service a distinct EXPOSE-VIA nginx PATH".to_string())
+                .with_services(vec![existing.service.name.clone(), service.name.clone()]),
+            ));
+        }
+
+        routes.insert(path, NginxRoute { service, path, port });
+    }
+
+    Ok(routes.into_values().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::ast::{PortMapping, Protocol, ProxyExposure};
+
+    fn exposed_service(name: &str, path: &str, port: Option<u16>) -> Service {
+        let mut service = Service::new(name.to_string());
+        service.image = Some("node:20-alpine".to_string());
+        if let Some(port) = port {
+            service.ports.push(PortMapping {
+                host_port: port,
+                host_port_end: None,
+                container_port: port,
+                protocol: Protocol::Tcp,
+            });
+        }
+        service.expose_via = Some(ProxyExposure {
+            proxy: ReverseProxy::Nginx,
+            path: path.to_string(),
+        });
+        service
+    }
+
+    #[test]
+    fn test_generates_upstreams_and_locations() {
+        let mut athena_file = AthenaFile::new();
+        athena_file.services.services.push(exposed_service("api", "/api/", Some(8000)));
+        athena_file.services.services.push(exposed_service("web", "/", Some(3000)));
+        athena_file.services.services.push(Service::new("db".to_string()));
+
+        let config = generate_nginx_config(&athena_file).unwrap();
+        assert!(config.contains("upstream api {\n    server api:8000;\n}"));
+        assert!(config.contains("upstream web {\n    server web:3000;\n}"));
+        assert!(config.contains("location /api/ {\n        proxy_pass http://api;"));
+        assert!(config.contains("proxy_set_header X-Forwarded-For $proxy_add_x_forwarded_for;"));
+        assert!(config.contains("map $http_upgrade $connection_upgrade {\n    default upgrade;\n    '' close;\n}"));
+        assert!(config.contains("proxy_set_header Connection $connection_upgrade;"));
+        assert!(!config.contains("db"));
+        // Locations are ordered by path
+        assert!(config.find("location / {").unwrap() < config.find("location /api/ {").unwrap());
+    }
+
+    #[test]
+    fn test_duplicate_path_is_an_error() {
+        let mut athena_file = AthenaFile::new();
+        athena_file.services.services.push(exposed_service("api", "/api/", Some(8000)));
+        athena_file.services.services.push(exposed_service("api_v2", "/api/", Some(8001)));
+
+        let err = generate_nginx_config(&athena_file).unwrap_err().to_string();
+        assert!(err.contains("Services 'api' and 'api_v2' both claim nginx path '/api/'"));
+    }
+
+    #[test]
+    fn test_path_that_breaks_the_directive_is_an_error() {
+        for path in ["/api /admin", "/api;", "/api{", "/api}"] {
+            let mut athena_file = AthenaFile::new();
+            athena_file.services.services.push(exposed_service("api", path, Some(8000)));
+
+            let err = generate_nginx_config(&athena_file).unwrap_err().to_string();
+            assert!(err.contains(&format!("on path '{path}', which contains")), "{err}");
+        }
+    }
+
+    #[test]
+    fn test_exposed_service_without_port_is_an_error() {
+        let mut athena_file = AthenaFile::new();
+        athena_file.services.services.push(exposed_service("api", "/api/", None));
+
+        let err = generate_nginx_config(&athena_file).unwrap_err().to_string();
+        assert!(err.contains("has no PORT-MAPPING"));
+    }
+}
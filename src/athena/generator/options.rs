@@ -0,0 +1,386 @@
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+use super::compose::{build_compose, render_compose};
+use super::deprecations::check_deprecations;
+use super::podman::{generate_podman_compose, SelinuxLabel};
+use super::prefix::prefixed;
+use super::terraform::generate_terraform_docker;
+use crate::athena::error::AthenaResult;
+use crate::athena::mounts::{rebase_bind_mounts, relative_dir};
+use crate::athena::parser::ast::AthenaFile;
+
+/// File format produced by [`generate`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputTarget {
+    /// Docker Compose YAML
+    #[default]
+    Compose,
+    /// Compose YAML adjusted for rootless podman-compose
+    Podman,
+    /// Terraform JSON for the kreuzwerker/docker provider
+    TerraformDocker,
+}
+
+impl OutputTarget {
+    pub fn default_file_name(self) -> &'static str {
+        match self {
+            OutputTarget::Compose | OutputTarget::Podman => "docker-compose.yml",
+            OutputTarget::TerraformDocker => "main.tf.json",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            OutputTarget::Compose => "docker-compose.yml",
+            OutputTarget::Podman => "podman compose file",
+            OutputTarget::TerraformDocker => "Terraform config",
+        }
+    }
+
+}
+
+/// Serialization of Compose output (the Terraform target is always JSON)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ComposeFormat {
+    #[default]
+    Yaml,
+    /// Pretty-printed JSON with keys sorted, and no header comments
+    Json,
+}
+
+/// Every setting that changes generated output, with defaults matching a
+/// plain `athena build`.
+///
+/// ```
+/// use athena::athena::generator::{GeneratorOptions, OutputTarget};
+///
+/// let options = GeneratorOptions::new()
+///     .with_target(OutputTarget::Podman)
+///     .with_reproducible(true);
+/// assert!(options.reproducible());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct GeneratorOptions {
+    target: OutputTarget,
+    compose_format: ComposeFormat,
+    reproducible: bool,
+    selinux_label: Option<SelinuxLabel>,
+    systemd_note: bool,
+    annotate_sources: bool,
+    minimal: bool,
+    base_dir: Option<PathBuf>,
+    output_dir: Option<PathBuf>,
+    stack_prefix: Option<String>,
+}
+
+impl GeneratorOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_target(mut self, target: OutputTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub fn with_compose_format(mut self, compose_format: ComposeFormat) -> Self {
+        self.compose_format = compose_format;
+        self
+    }
+
+    /// Omit the generation timestamp so identical input yields identical bytes
+    pub fn with_reproducible(mut self, reproducible: bool) -> Self {
+        self.reproducible = reproducible;
+        self
+    }
+
+    /// SELinux relabeling for bind mounts (podman only)
+    pub fn with_selinux_label(mut self, label: Option<SelinuxLabel>) -> Self {
+        self.selinux_label = label;
+        self
+    }
+
+    /// Prepend a note on running the stack under systemd (podman only)
+    pub fn with_systemd_note(mut self, systemd_note: bool) -> Self {
+        self.systemd_note = systemd_note;
+        self
+    }
+
+    /// Comment each service, volume and network with the .ath line declaring
+    /// it (YAML output only)
+    pub fn with_annotate_sources(mut self, annotate_sources: bool) -> Self {
+        self.annotate_sources = annotate_sources;
+        self
+    }
+
+    /// Leave out keys Compose would default to anyway (Compose targets only)
+    pub fn with_minimal(mut self, minimal: bool) -> Self {
+        self.minimal = minimal;
+        self
+    }
+
+    /// Directory of the .ath file, which its relative paths resolve against;
+    /// BUILD-ARGS are checked against the Dockerfile there
+    pub fn with_base_dir(mut self, base_dir: impl Into<PathBuf>) -> Self {
+        self.base_dir = Some(base_dir.into());
+        self
+    }
+
+    /// Directory the output is written to. Relative bind mount sources and
+    /// build contexts are rewritten to reach the base directory from there,
+    /// since Compose resolves them against the generated file
+    pub fn with_output_dir(mut self, output_dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = Some(output_dir.into());
+        self
+    }
+
+    /// Prefix the project name, networks, named volumes, secrets and container
+    /// names, and the value of `{{stack_prefix}}` in ENV-VARIABLE literals
+    pub fn with_stack_prefix(mut self, stack_prefix: Option<String>) -> Self {
+        self.stack_prefix = stack_prefix;
+        self
+    }
+
+    pub fn target(&self) -> OutputTarget {
+        self.target
+    }
+
+    pub fn compose_format(&self) -> ComposeFormat {
+        self.compose_format
+    }
+
+    /// Whether the output is JSON, whichever target produced it
+    pub fn writes_json(&self) -> bool {
+        self.target == OutputTarget::TerraformDocker || self.compose_format == ComposeFormat::Json
+    }
+
+    /// Whether the output carries `#` header comments (and so a provenance stamp)
+    pub fn supports_comments(&self) -> bool {
+        !self.writes_json()
+    }
+
+    pub fn default_file_name(&self) -> &'static str {
+        match (self.target, self.compose_format) {
+            (OutputTarget::Compose | OutputTarget::Podman, ComposeFormat::Json) => "docker-compose.json",
+            (target, _) => target.default_file_name(),
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match (self.target, self.compose_format) {
+            (OutputTarget::Compose, ComposeFormat::Json) => "Compose JSON",
+            (OutputTarget::Podman, ComposeFormat::Json) => "podman compose JSON",
+            (target, _) => target.description(),
+        }
+    }
+
+    pub fn reproducible(&self) -> bool {
+        self.reproducible
+    }
+
+    pub fn selinux_label(&self) -> Option<SelinuxLabel> {
+        self.selinux_label
+    }
+
+    pub fn systemd_note(&self) -> bool {
+        self.systemd_note
+    }
+
+    pub fn annotate_sources(&self) -> bool {
+        self.annotate_sources
+    }
+
+    pub fn minimal(&self) -> bool {
+        self.minimal
+    }
+
+    /// The current directory unless [`with_base_dir`](Self::with_base_dir) set one
+    pub fn base_dir(&self) -> &Path {
+        self.base_dir.as_deref().unwrap_or_else(|| Path::new("."))
+    }
+
+    /// The base directory unless [`with_output_dir`](Self::with_output_dir) set one
+    pub fn output_dir(&self) -> &Path {
+        self.output_dir.as_deref().unwrap_or_else(|| self.base_dir())
+    }
+
+    /// Path from the output directory to the base directory, which relative
+    /// paths in the output go through; empty when they are the same
+    pub fn output_root(&self) -> PathBuf {
+        if self.output_dir() == self.base_dir() {
+            return PathBuf::new();
+        }
+        relative_dir(self.output_dir(), self.base_dir())
+    }
+
+    pub fn stack_prefix(&self) -> Option<&str> {
+        self.stack_prefix.as_deref()
+    }
+
+    /// Stable description of every setting, used to key cached output
+    pub fn fingerprint(&self) -> String {
+        format!("{self:?}")
+    }
+}
+
+/// Generated file contents plus non-fatal notes about ignored settings
+#[derive(Debug)]
+pub struct GeneratedOutput {
+    pub content: String,
+    pub warnings: Vec<String>,
+}
+
+/// Generate the file selected by `options.target()`
+pub fn generate(athena_file: &AthenaFile, options: &GeneratorOptions) -> AthenaResult<GeneratedOutput> {
+    let athena_file = &*prefixed(athena_file, options.stack_prefix())?;
+    let athena_file = &*rebased(athena_file, options);
+    match options.target() {
+        OutputTarget::Compose => {
+            let compose = build_compose(athena_file, options)?;
+            Ok(GeneratedOutput {
+                content: render_compose(&compose, athena_file, options)?,
+                warnings: check_deprecations(&compose),
+            })
+        }
+        OutputTarget::Podman => {
+            let podman = generate_podman_compose(athena_file, options)?;
+            Ok(GeneratedOutput {
+                content: podman.yaml,
+                warnings: podman.warnings,
+            })
+        }
+        OutputTarget::TerraformDocker => {
+            let terraform = generate_terraform_docker(athena_file, options)?;
+            Ok(GeneratedOutput {
+                content: terraform.config,
+                warnings: terraform.dropped,
+            })
+        }
+    }
+}
+
+/// `athena_file` with its bind mounts relative to the output directory
+fn rebased<'a>(athena_file: &'a AthenaFile, options: &GeneratorOptions) -> Cow<'a, AthenaFile> {
+    let root = options.output_root();
+    if root.as_os_str().is_empty() {
+        return Cow::Borrowed(athena_file);
+    }
+    let mut rebased = athena_file.clone();
+    if rebase_bind_mounts(&mut rebased, &root) {
+        Cow::Owned(rebased)
+    } else {
+        Cow::Borrowed(athena_file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::parse_athena_file;
+    use std::path::Path;
+
+    const STACK: &str = r#"
+        DEPLOYMENT-ID SNAPSHOT_STACK
+        VERSION-ID 1.0.0
+
+        SERVICES SECTION
+
+        SERVICE api
+        IMAGE-ID node:20-alpine
+        PORT-MAPPING 3000 TO 3000
+        VOLUME-MAPPING "./src" TO "/app/src"
+        DEPENDS-ON cache
+        REPLICAS 2
+        END SERVICE
+
+        SERVICE cache
+        IMAGE-ID redis:7-alpine
+        END SERVICE
+    "#;
+
+    /// Compare against `snapshots/<name>`; set ATHENA_UPDATE_SNAPSHOTS=1 to rewrite them
+    fn assert_snapshot(name: &str, actual: &str) {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/athena/generator/snapshots")
+            .join(name);
+
+        if std::env::var_os("ATHENA_UPDATE_SNAPSHOTS").is_some() {
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+
+        let expected = std::fs::read_to_string(&path)
+            .unwrap_or_else(|_| panic!("missing snapshot {}", path.display()));
+        assert_eq!(actual, expected, "output differs from snapshot {name}");
+    }
+
+    #[test]
+    fn test_option_matrix_snapshots() {
+        let athena_file = parse_athena_file(STACK).unwrap();
+        let reproducible = GeneratorOptions::new().with_reproducible(true);
+
+        let matrix = [
+            ("compose.yml", reproducible.clone()),
+            (
+                "podman.yml",
+                reproducible.clone().with_target(OutputTarget::Podman),
+            ),
+            (
+                "podman_selinux_systemd.yml",
+                reproducible
+                    .clone()
+                    .with_target(OutputTarget::Podman)
+                    .with_selinux_label(Some(SelinuxLabel::Private))
+                    .with_systemd_note(true),
+            ),
+            (
+                "terraform.tf.json",
+                reproducible.with_target(OutputTarget::TerraformDocker),
+            ),
+        ];
+
+        for (snapshot, options) in matrix {
+            let output = generate(&athena_file, &options).unwrap();
+            assert_snapshot(snapshot, &output.content);
+        }
+    }
+
+    #[test]
+    fn test_default_options_include_timestamp() {
+        let athena_file = parse_athena_file(STACK).unwrap();
+
+        let stamped = generate(&athena_file, &GeneratorOptions::new()).unwrap();
+        let reproducible = generate(&athena_file, &GeneratorOptions::new().with_reproducible(true)).unwrap();
+
+        assert!(stamped.content.contains("# Generated: "));
+        assert!(!reproducible.content.contains("# Generated: "));
+    }
+
+    #[test]
+    fn test_json_compose_has_sorted_keys_and_no_comments() {
+        let athena_file = parse_athena_file(STACK).unwrap();
+        let options = GeneratorOptions::new().with_compose_format(ComposeFormat::Json);
+
+        let output = generate(&athena_file, &options).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&output.content).unwrap();
+
+        assert!(!output.content.contains('#'));
+        assert_eq!(json["services"]["api"]["image"], "node:20-alpine");
+        let keys: Vec<&String> = json["services"].as_object().unwrap().keys().collect();
+        assert_eq!(keys, vec!["api", "cache"]);
+        assert!(!options.supports_comments());
+        assert_eq!(options.default_file_name(), "docker-compose.json");
+    }
+
+    #[test]
+    fn test_target_warnings_are_collected() {
+        let athena_file = parse_athena_file(STACK).unwrap();
+
+        let podman = generate(&athena_file, &GeneratorOptions::new().with_target(OutputTarget::Podman)).unwrap();
+        assert!(podman.warnings.iter().any(|w| w.contains("ignores REPLICAS")));
+
+        let compose = generate(&athena_file, &GeneratorOptions::new()).unwrap();
+        assert!(compose.warnings.is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+use std::fmt::Write;
+
+use super::compose::{build_compose, render_compose};
+use super::defaults::DefaultsEngine;
+use super::options::GeneratorOptions;
+use crate::athena::error::AthenaResult;
+use crate::athena::mounts::is_bind_mount;
+use crate::athena::parser::ast::{AthenaFile, VolumeMapping};
+
+/// SELinux relabeling applied to bind mounts for rootless podman
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelinuxLabel {
+    /// `:z` - content shared between containers
+    Shared,
+    /// `:Z` - content private to one container
+    Private,
+}
+
+impl SelinuxLabel {
+    fn as_option(self) -> &'static str {
+        match self {
+            SelinuxLabel::Shared => "z",
+            SelinuxLabel::Private => "Z",
+        }
+    }
+}
+
+/// Compose YAML adjusted for podman-compose, plus warnings for ignored features
+#[derive(Debug)]
+pub struct PodmanOutput {
+    pub yaml: String,
+    pub warnings: Vec<String>,
+}
+
+/// Generate a Compose file that podman-compose accepts
+pub fn generate_podman_compose(
+    athena_file: &AthenaFile,
+    options: &GeneratorOptions,
+) -> AthenaResult<PodmanOutput> {
+    let mut compose = build_compose(athena_file, options)?;
+    let mut warnings = Vec::new();
+
+    for service in &athena_file.services.services {
+        let Some(generated) = compose.services.get_mut(&service.name) else {
+            continue;
+        };
+
+        if let Some(label) = options.selinux_label() {
+            let labeled: Vec<VolumeMapping> = service
+                .volumes
+                .iter()
+                .map(|v| with_selinux_label(v, label))
+                .collect();
+            generated.volumes = DefaultsEngine::convert_volumes(&labeled);
+        }
+
+        // podman-compose has no scheduler: keep resource limits, drop the rest
+        if let Some(deploy) = generated.deploy.as_mut() {
+            if deploy.replicas.is_some() {
+                warnings.push(format!(
+                    "Service '{}': podman-compose ignores REPLICAS; only one container will run",
+                    service.name
+                ));
+            }
+            if deploy.mode.is_some() || deploy.update_config.is_some() || deploy.labels.is_some() {
+                warnings.push(format!(
+                    "Service '{}': Swarm-only settings (DEPLOY-MODE, UPDATE-CONFIG, SWARM-LABELS) were dropped for podman",
+                    service.name
+                ));
+            }
+
+            deploy.mode = None;
+            deploy.replicas = None;
+            deploy.update_config = None;
+            deploy.labels = None;
+            deploy.restart_policy = None;
+        }
+        if generated.deploy.as_ref().is_some_and(|d| d.resources.is_none()) {
+            generated.deploy = None;
+        }
+    }
+
+    if let Some(networks) = compose.networks.as_mut() {
+        for (name, network) in networks.iter_mut() {
+            if network.driver == "overlay" {
+                warnings.push(format!(
+                    "Network '{name}': podman does not support overlay networks, using bridge"
+                ));
+                network.driver = "bridge".to_string();
+            }
+            network.attachable = None;
+            network.encrypted = None;
+            network.ingress = None;
+        }
+    }
+
+    let mut yaml = render_compose(&compose, athena_file, options)?;
+    if options.systemd_note() {
+        if options.supports_comments() {
+            yaml = add_systemd_note(yaml, athena_file);
+        } else {
+            warnings.push("--systemd-note was ignored because JSON output cannot carry comments".to_string());
+        }
+    }
+
+    Ok(PodmanOutput { yaml, warnings })
+}
+
+fn with_selinux_label(volume: &VolumeMapping, label: SelinuxLabel) -> VolumeMapping {
+    let mut volume = volume.clone();
+    let already_labeled = volume.options.iter().any(|o| o == "z" || o == "Z");
+
+    if is_bind_mount(&volume.host_path) && !already_labeled {
+        volume.options.push(label.as_option().to_string());
+    }
+
+    volume
+}
+
+fn add_systemd_note(yaml: String, athena_file: &AthenaFile) -> String {
+    let mut note = String::new();
+    let project = athena_file.get_compose_project_name();
+
+    let _ = writeln!(note, "# Podman: start with `podman-compose up -d`, then run the stack under systemd with");
+    let _ = writeln!(note, "#   podman generate systemd --new --files --name <container>");
+    let _ = writeln!(note, "# and enable the units with `systemctl --user enable --now container-{project}-<service>.service`");
+
+    note + &yaml
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::parse_athena_file;
+
+    const INPUT: &str = r#"
+        DEPLOYMENT-ID PODMAN_TEST
+        SERVICES SECTION
+        SERVICE web
+        IMAGE-ID "nginx:alpine"
+        VOLUME-MAPPING "./html" TO "/usr/share/nginx/html" (ro)
+        VOLUME-MAPPING "cache" TO "/var/cache/nginx"
+        RESOURCE-LIMITS CPU "0.5" MEMORY "256M"
+        REPLICAS 3
+        END SERVICE
+    "#;
+
+    #[test]
+    fn test_selinux_label_applies_to_bind_mounts_only() {
+        let athena_file = parse_athena_file(INPUT).unwrap();
+        let options = GeneratorOptions::new().with_selinux_label(Some(SelinuxLabel::Private));
+
+        let output = generate_podman_compose(&athena_file, &options).unwrap();
+        assert!(output.yaml.contains("./html:/usr/share/nginx/html:ro,Z"));
+        assert!(output.yaml.contains("- cache:/var/cache/nginx\n"));
+    }
+
+    #[test]
+    fn test_swarm_keys_are_dropped_with_warning() {
+        let athena_file = parse_athena_file(INPUT).unwrap();
+        let output = generate_podman_compose(&athena_file, &GeneratorOptions::default()).unwrap();
+
+        assert!(!output.yaml.contains("replicas"));
+        assert!(!output.yaml.contains("restart_policy"));
+        assert!(output.yaml.contains("memory: 256M"));
+        assert_eq!(output.warnings.len(), 1);
+        assert!(output.warnings[0].contains("ignores REPLICAS"));
+    }
+
+    #[test]
+    fn test_systemd_note() {
+        let athena_file = parse_athena_file(INPUT).unwrap();
+        let options = GeneratorOptions::new().with_systemd_note(true);
+
+        let output = generate_podman_compose(&athena_file, &options).unwrap();
+        assert!(output.yaml.starts_with("# Podman:"));
+        assert!(output.yaml.contains("podman generate systemd"));
+    }
+}
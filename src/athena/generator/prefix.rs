@@ -0,0 +1,324 @@
+//! `athena build --stack-prefix`: namespace a stack's resources so that
+//! builds of several branches can run side by side on one host.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedValidationError};
+use crate::athena::mounts::is_bind_mount;
+use crate::athena::parser::ast::{
+    AthenaFile, DeploymentSection, EnvironmentSection, EnvironmentVariable, NetworkDefinition, Service,
+};
+
+/// Placeholder in ENV-VARIABLE literals replaced by the prefix
+pub const STACK_PREFIX_VARIABLE: &str = "{{stack_prefix}}";
+
+/// `--stack-prefix` value: a letter or digit, then letters, digits, `_` and
+/// `-`, so that every prefixed name, the Compose project name included, stays
+/// a valid Docker name
+pub fn parse_stack_prefix(prefix: &str) -> Result<String, String> {
+    let mut chars = prefix.chars();
+    match chars.next() {
+        None => Err("the prefix is empty".to_string()),
+        Some(c) if !c.is_ascii_alphanumeric() => Err(format!("'{prefix}' must start with a letter or digit")),
+        _ if chars.any(|c| !c.is_ascii_alphanumeric() && !matches!(c, '_' | '-')) => Err(format!(
+            "'{prefix}' may only contain letters, digits, '_' and '-'"
+        )),
+        _ => Ok(prefix.to_string()),
+    }
+}
+
+/// `athena_file` as generated under `prefix`; borrowed without one. Using
+/// `{{stack_prefix}}` without a prefix is an error rather than an empty value.
+pub fn prefixed<'a>(athena_file: &'a AthenaFile, prefix: Option<&str>) -> AthenaResult<Cow<'a, AthenaFile>> {
+    let Some(prefix) = prefix else {
+        if let Some(service) = athena_file.services.services.iter().find(|service| uses_placeholder(service)) {
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "Service '{}' uses {STACK_PREFIX_VARIABLE} in ENV-VARIABLE, but no stack prefix was given",
+                    service.name
+                ))
+                .with_suggestion(format!(
+                    "Build with --stack-prefix <PREFIX>, or write the value without {STACK_PREFIX_VARIABLE}"
+                ))
+                .with_services(vec![service.name.clone()]),
+            ));
+        }
+        return Ok(Cow::Borrowed(athena_file));
+    };
+    let mut athena_file = athena_file.clone();
+    apply_stack_prefix(&mut athena_file, prefix);
+    Ok(Cow::Owned(athena_file))
+}
+
+fn uses_placeholder(service: &Service) -> bool {
+    service.environment.iter().any(|variable| {
+        matches!(variable, EnvironmentVariable::Literal(value) if value.contains(STACK_PREFIX_VARIABLE))
+    })
+}
+
+/// Rename the deployment, and so the Compose project, the declared networks,
+/// volumes and secrets and every CONTAINER-NAME to `<prefix>-<name>`, along
+/// with each ON NETWORK and VOLUME-MAPPING referring to them, and fill in
+/// `{{stack_prefix}}` in ENV-VARIABLE literals.
+///
+/// The project network Athena adds when none is declared is declared under
+/// its prefixed name, since it is as shared as any other. Named volumes are
+/// prefixed whether or not a VOLUME declares them. Service names are left
+/// alone: they are only visible inside the stack.
+pub fn apply_stack_prefix(athena_file: &mut AthenaFile, prefix: &str) {
+    let prefixed = |name: &str| format!("{prefix}-{name}");
+
+    let project_network = athena_file.get_network_name();
+    let project = prefixed(&athena_file.get_project_name());
+    match athena_file.deployment.as_mut() {
+        Some(deployment) => deployment.deployment_id = project,
+        None => {
+            athena_file.deployment = Some(DeploymentSection {
+                deployment_id: project,
+                version_id: None,
+            })
+        }
+    }
+
+    let environment = athena_file.environment.get_or_insert_with(|| EnvironmentSection {
+        networks: Vec::new(),
+        volumes: Vec::new(),
+        secrets: Default::default(),
+        dns: Vec::new(),
+        externals: Vec::new(),
+        default_labels: None,
+    });
+    if environment.networks.is_empty() {
+        environment.networks.push(NetworkDefinition {
+            name: project_network,
+            driver: None,
+            attachable: None,
+            encrypted: None,
+            ingress: None,
+            subnet: None,
+            location: None,
+        });
+    }
+
+    let mut networks = HashMap::new();
+    for network in &mut environment.networks {
+        let name = prefixed(&network.name);
+        networks.insert(std::mem::replace(&mut network.name, name.clone()), name);
+    }
+    for volume in &mut environment.volumes {
+        volume.name = prefixed(&volume.name);
+    }
+    environment.secrets = std::mem::take(&mut environment.secrets)
+        .into_iter()
+        .map(|(name, value)| (prefixed(&name), value))
+        .collect();
+
+    for service in &mut athena_file.services.services {
+        if let Some(container_name) = service.container_name.as_mut() {
+            *container_name = prefixed(container_name);
+        }
+        for attachment in &mut service.networks {
+            if let Some(name) = networks.get(&attachment.network) {
+                attachment.network = name.clone();
+            }
+        }
+        // Paths are bind mounts; anything else is a volume of this stack
+        for mount in &mut service.volumes {
+            if !is_bind_mount(&mount.host_path) {
+                mount.host_path = prefixed(&mount.host_path);
+            }
+        }
+        for variable in &mut service.environment {
+            if let EnvironmentVariable::Literal(value) = variable {
+                if value.contains(STACK_PREFIX_VARIABLE) {
+                    *value = value.replace(STACK_PREFIX_VARIABLE, prefix);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::generator::{check_compose_schema, generate, GeneratorOptions, OutputTarget};
+    use crate::athena::parser::parse_athena_file;
+
+    const STACK: &str = r#"DEPLOYMENT-ID shop
+ENVIRONMENT SECTION
+NETWORK-NAME backend
+NETWORK-NAME frontend
+VOLUME pg_data
+VOLUME uploads
+SECRET db_password "s3cret"
+
+SERVICES SECTION
+SERVICE db
+IMAGE-ID "postgres:16"
+CONTAINER-NAME "shop-db"
+VOLUME-MAPPING "pg_data" TO "/var/lib/postgresql/data"
+VOLUME-MAPPING "./init.sql" TO "/docker-entrypoint-initdb.d/init.sql" (ro)
+ON NETWORK backend
+END SERVICE
+
+SERVICE api
+IMAGE-ID "shop/api:1.0"
+ENV-VARIABLE "UPLOAD_VOLUME={{stack_prefix}}-uploads"
+VOLUME-MAPPING "uploads" TO "/srv/uploads"
+DEPENDS-ON db
+ON NETWORK backend ALIAS api-internal
+ON NETWORK frontend
+END SERVICE
+"#;
+
+    fn prefixed_stack() -> AthenaFile {
+        let mut athena_file = parse_athena_file(STACK).unwrap();
+        apply_stack_prefix(&mut athena_file, "feature123");
+        athena_file
+    }
+
+    #[test]
+    fn test_declarations_and_references_are_renamed_together() {
+        let athena_file = prefixed_stack();
+        assert_eq!(athena_file.get_project_name(), "feature123-shop");
+        let environment = athena_file.environment.as_ref().unwrap();
+        let networks: Vec<&str> = environment.networks.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(networks, ["feature123-backend", "feature123-frontend"]);
+        let volumes: Vec<&str> = environment.volumes.iter().map(|v| v.name.as_str()).collect();
+        assert_eq!(volumes, ["feature123-pg_data", "feature123-uploads"]);
+        assert_eq!(environment.secrets.keys().collect::<Vec<_>>(), ["feature123-db_password"]);
+
+        let db = &athena_file.services.services[0];
+        assert_eq!(db.name, "db");
+        assert_eq!(db.container_name.as_deref(), Some("feature123-shop-db"));
+        assert_eq!(db.volumes[0].host_path, "feature123-pg_data");
+        assert_eq!(db.volumes[1].host_path, "./init.sql");
+        assert_eq!(db.networks[0].network, "feature123-backend");
+
+        let api = &athena_file.services.services[1];
+        let attached: Vec<&str> = api.networks.iter().map(|n| n.network.as_str()).collect();
+        assert_eq!(attached, ["feature123-backend", "feature123-frontend"]);
+        assert_eq!(api.networks[0].aliases, ["api-internal"]);
+        assert_eq!(api.volumes[0].host_path, "feature123-uploads");
+        assert_eq!(api.depends_on[0].service, "db");
+        assert_eq!(
+            api.environment[0],
+            EnvironmentVariable::Literal("UPLOAD_VOLUME=feature123-uploads".to_string())
+        );
+    }
+
+    #[test]
+    fn test_every_reference_in_the_output_is_declared() {
+        let athena_file = parse_athena_file(STACK).unwrap();
+        // A leading digit is a valid Docker name but not a Terraform resource name
+        for prefix in ["feature123", "42"] {
+            let options = GeneratorOptions::new().with_stack_prefix(Some(prefix.to_string()));
+            let compose = generate(&athena_file, &options).unwrap().content;
+            assert_eq!(check_compose_schema(&compose), Vec::<String>::new());
+
+            let document: serde_yaml::Value = serde_yaml::from_str(&compose).unwrap();
+            let names = |section: &str| -> Vec<String> {
+                document[section]
+                    .as_mapping()
+                    .unwrap()
+                    .keys()
+                    .map(|key| key.as_str().unwrap().to_string())
+                    .collect()
+            };
+            assert_eq!(document["name"], format!("{prefix}-shop"));
+            assert_eq!(names("services"), ["db", "api"]);
+            assert_eq!(names("networks"), [format!("{prefix}-backend"), format!("{prefix}-frontend")]);
+            assert_eq!(names("volumes"), [format!("{prefix}-pg_data"), format!("{prefix}-uploads")]);
+            assert_eq!(document["services"]["db"]["container_name"], format!("{prefix}-shop-db"));
+            assert_eq!(document["services"]["api"]["depends_on"][0], "db");
+
+            // Nothing in the output still uses an unprefixed name
+            for old in ["backend", "frontend", "pg_data", "uploads"] {
+                let stray = compose.lines().find(|line| {
+                    line.contains(old) && !line.contains(&format!("{prefix}-{old}")) && !line.contains("api-internal")
+                });
+                assert_eq!(stray, None, "'{old}' left unprefixed");
+            }
+
+            let terraform = generate(&athena_file, &options.with_target(OutputTarget::TerraformDocker)).unwrap().content;
+            let document: serde_json::Value = serde_json::from_str(&terraform).unwrap();
+            let resources = &document["resource"];
+            let volumes: Vec<&str> = resources["docker_volume"]
+                .as_object()
+                .unwrap()
+                .values()
+                .map(|volume| volume["name"].as_str().unwrap())
+                .collect();
+            assert_eq!(volumes, [format!("{prefix}-pg_data"), format!("{prefix}-uploads")]);
+            let containers: Vec<&str> = resources["docker_container"]
+                .as_object()
+                .unwrap()
+                .values()
+                .map(|container| container["name"].as_str().unwrap())
+                .collect();
+            assert_eq!(containers, [format!("{prefix}-api"), format!("{prefix}-shop-db")]);
+
+            // Every resource name is an identifier, and every reference points at one
+            for kind in ["docker_container", "docker_network", "docker_volume"] {
+                for key in resources[kind].as_object().unwrap().keys() {
+                    assert!(key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_'), "{kind}.{key}");
+                }
+                for reference in terraform.split(&format!("{kind}.")).skip(1) {
+                    let key: String =
+                        reference.chars().take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-')).collect();
+                    assert!(resources[kind][&key].is_object(), "{kind}.{key} is not declared");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_project_network_is_declared_with_the_prefix() {
+        let mut athena_file =
+            parse_athena_file("DEPLOYMENT-ID shop\nSERVICES SECTION\nSERVICE api\nIMAGE-ID \"api:1.0\"\nEND SERVICE\n")
+                .unwrap();
+        apply_stack_prefix(&mut athena_file, "pr-42");
+        let compose = generate(&athena_file, &GeneratorOptions::new().with_minimal(true)).unwrap().content;
+        let document: serde_yaml::Value = serde_yaml::from_str(&compose).unwrap();
+        assert!(document["networks"]["pr-42-shop_network"].is_mapping(), "{compose}");
+        assert_eq!(document["services"]["api"]["networks"][0], "pr-42-shop_network");
+    }
+
+    #[test]
+    fn test_placeholder_without_a_prefix_is_an_error() {
+        let athena_file = parse_athena_file(STACK).unwrap();
+        let error = prefixed(&athena_file, None).unwrap_err();
+        assert_eq!(error.exit_code(), 4);
+        let error = error.to_string();
+        assert!(error.contains("Service 'api' uses {{stack_prefix}} in ENV-VARIABLE"), "{error}");
+        assert!(error.contains("--stack-prefix"), "{error}");
+
+        let plain = parse_athena_file("SERVICES SECTION\nSERVICE api\nIMAGE-ID \"api:1.0\"\nEND SERVICE\n").unwrap();
+        assert!(matches!(prefixed(&plain, None), Ok(Cow::Borrowed(_))));
+    }
+
+    #[test]
+    fn test_undeclared_named_volumes_are_prefixed() {
+        let mut athena_file = parse_athena_file(
+            "SERVICES SECTION\nSERVICE db\nIMAGE-ID \"postgres:16\"\nVOLUME-MAPPING \"pgdata\" TO \"/var/lib/postgresql/data\"\nVOLUME-MAPPING \"./init.sql\" TO \"/docker-entrypoint-initdb.d/init.sql\"\nEND SERVICE\n",
+        )
+        .unwrap();
+        apply_stack_prefix(&mut athena_file, "f1");
+        let db = &athena_file.services.services[0];
+        assert_eq!(db.volumes[0].host_path, "f1-pgdata");
+        assert_eq!(db.volumes[1].host_path, "./init.sql");
+        assert_eq!(athena_file.get_compose_project_name(), "f1-athena-project");
+    }
+
+    #[test]
+    fn test_prefix_follows_docker_name_rules() {
+        assert_eq!(parse_stack_prefix("feature123"), Ok("feature123".to_string()));
+        assert_eq!(parse_stack_prefix("pr-42_b1"), Ok("pr-42_b1".to_string()));
+        // '.' is fine in a container name but not in a Compose project name
+        assert!(parse_stack_prefix("pr-42.b1").is_err());
+        assert!(parse_stack_prefix("").is_err());
+        assert!(parse_stack_prefix("-feature").is_err());
+        assert!(parse_stack_prefix("feature/123").is_err());
+    }
+}
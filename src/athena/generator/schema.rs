@@ -0,0 +1,242 @@
+//! Structural self-check of a Compose document, for output Athena didn't
+//! write itself, such as what a post-process hook returns.
+//!
+//! This is not the full Compose schema: it checks the shape of the file and
+//! that every name a service refers to is declared, which is what a
+//! transform most easily breaks.
+//!
+//! It reads the document as plain YAML rather than deserializing it into
+//! [`DockerCompose`](super::compose::DockerCompose) for the generator's own
+//! validation. That model only has the shapes Athena writes, so valid Compose
+//! such as long-form `ports`, an `environment` mapping or a network without a
+//! `driver` (as `--minimal` writes it) would not load, and most of that
+//! validation looks at the .ath file, which a hook can't change.
+
+use serde_yaml::{Mapping, Value};
+
+use crate::athena::mounts::{has_drive_letter, is_bind_mount};
+
+const TOP_LEVEL_KEYS: &[&str] = &["version", "name", "include", "services", "networks", "volumes", "secrets", "configs"];
+
+/// Every problem found in `content`, YAML or JSON; empty when it looks like
+/// a Compose file
+pub fn check_compose_schema(content: &str) -> Vec<String> {
+    let document: Value = match serde_yaml::from_str(content) {
+        Ok(document) => document,
+        Err(e) => return vec![format!("not valid YAML: {e}")],
+    };
+    let Value::Mapping(document) = document else {
+        return vec!["the document must be a mapping with a top-level services key".to_string()];
+    };
+
+    let mut problems = Vec::new();
+    for key in document.keys() {
+        match key.as_str() {
+            Some(key) if TOP_LEVEL_KEYS.contains(&key) || key.starts_with("x-") => {}
+            _ => problems.push(format!("unknown top-level key {}", describe(key))),
+        }
+    }
+
+    let declared = |section: &str, problems: &mut Vec<String>| -> Vec<String> {
+        match document.get(section) {
+            None | Some(Value::Null) => Vec::new(),
+            Some(Value::Mapping(entries)) => entries.keys().filter_map(Value::as_str).map(str::to_string).collect(),
+            Some(_) => {
+                problems.push(format!("top-level {section} must be a mapping"));
+                Vec::new()
+            }
+        }
+    };
+    let networks = declared("networks", &mut problems);
+    let volumes = declared("volumes", &mut problems);
+    let secrets = declared("secrets", &mut problems);
+    let configs = declared("configs", &mut problems);
+
+    let services = match document.get("services") {
+        Some(Value::Mapping(services)) if !services.is_empty() => services,
+        Some(Value::Mapping(_)) => {
+            problems.push("services is empty".to_string());
+            return problems;
+        }
+        Some(_) => {
+            problems.push("services must be a mapping of service names".to_string());
+            return problems;
+        }
+        None => {
+            problems.push("missing top-level services".to_string());
+            return problems;
+        }
+    };
+    let service_names: Vec<&str> = services.keys().filter_map(Value::as_str).collect();
+
+    for (name, service) in services {
+        let Some(name) = name.as_str() else {
+            problems.push(format!("service name {} must be a string", describe(name)));
+            continue;
+        };
+        let Value::Mapping(service) = service else {
+            problems.push(format!("service '{name}' must be a mapping"));
+            continue;
+        };
+        let mut problem = |text: String| problems.push(format!("service '{name}': {text}"));
+
+        if !["image", "build", "extends"].iter().any(|key| service.contains_key(*key)) {
+            problem("has neither image nor build".to_string());
+        }
+        for key in ["ports", "volumes", "secrets", "configs", "dns", "extra_hosts", "profiles"] {
+            if service.get(key).is_some_and(|value| !value.is_sequence()) {
+                problem(format!("{key} must be a list"));
+            }
+        }
+
+        for dependency in names(service.get("depends_on")) {
+            if !service_names.contains(&dependency.as_str()) {
+                problem(format!("depends on undefined service '{dependency}'"));
+            }
+        }
+        if let Some(target) = service.get("network_mode").and_then(Value::as_str).and_then(|m| m.strip_prefix("service:")) {
+            if !service_names.contains(&target) {
+                problem(format!("network_mode refers to undefined service '{target}'"));
+            }
+        }
+        for network in names(service.get("networks")) {
+            if network != "default" && !networks.contains(&network) {
+                problem(format!("joins undefined network '{network}'"));
+            }
+        }
+        for volume in named_volumes(service) {
+            if !volumes.contains(&volume) {
+                problem(format!("mounts undefined volume '{volume}'"));
+            }
+        }
+        for (key, declared) in [("secrets", &secrets), ("configs", &configs)] {
+            for source in sources(service.get(key)) {
+                if !declared.contains(&source) {
+                    problem(format!("uses undefined {} '{source}'", key.trim_end_matches('s')));
+                }
+            }
+        }
+    }
+    problems
+}
+
+/// Names from the list or mapping form of `depends_on` and `networks`
+fn names(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Sequence(items)) => items.iter().filter_map(Value::as_str).map(str::to_string).collect(),
+        Some(Value::Mapping(entries)) => entries.keys().filter_map(Value::as_str).map(str::to_string).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// `source` of each secret or config, short form or long
+fn sources(value: Option<&Value>) -> Vec<String> {
+    let Some(Value::Sequence(items)) = value else {
+        return Vec::new();
+    };
+    items
+        .iter()
+        .filter_map(|item| match item {
+            Value::String(name) => Some(name.clone()),
+            Value::Mapping(entry) => entry.get("source").and_then(Value::as_str).map(str::to_string),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Named volumes a service mounts; paths are bind mounts and need no declaration
+fn named_volumes(service: &Mapping) -> Vec<String> {
+    let Some(Value::Sequence(mounts)) = service.get("volumes") else {
+        return Vec::new();
+    };
+    mounts
+        .iter()
+        .filter_map(|mount| match mount {
+            // `name:/path[:mode]`; a bare container path is an anonymous volume
+            Value::String(spec) if !has_drive_letter(spec) => spec.split_once(':').map(|(source, _)| source.to_string()),
+            Value::Mapping(entry) if entry.get("type").and_then(Value::as_str) == Some("volume") => {
+                entry.get("source").and_then(Value::as_str).map(str::to_string)
+            }
+            _ => None,
+        })
+        .filter(|source| !source.is_empty() && !is_bind_mount(source))
+        .collect()
+}
+
+fn describe(key: &Value) -> String {
+    match key.as_str() {
+        Some(key) => format!("'{key}'"),
+        None => format!("{key:?}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_document_passes() {
+        let compose = r#"
+name: shop
+x-common: &common
+  restart: unless-stopped
+services:
+  db:
+    image: postgres:16
+    volumes: ["pg_data:/var/lib/postgresql/data", "./init.sql:/docker-entrypoint-initdb.d/init.sql:ro"]
+    secrets: [db_password]
+  api:
+    build: {context: .}
+    depends_on: {db: {condition: service_healthy}}
+    networks: [backend]
+  proxy:
+    image: nginx
+    network_mode: service:api
+networks:
+  backend: {}
+volumes:
+  pg_data:
+secrets:
+  db_password: {file: ./db_password.txt}
+"#;
+        assert_eq!(check_compose_schema(compose), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_dangling_references_are_reported() {
+        let compose = r#"
+services:
+  api:
+    image: api
+    depends_on: [cache]
+    networks: {backend: {aliases: [api]}}
+    volumes: [{type: volume, source: data, target: /data}]
+    configs: [{source: app_config, target: /etc/app.yml}]
+  sidecar:
+    command: ["envoy"]
+"#;
+        assert_eq!(
+            check_compose_schema(compose),
+            [
+                "service 'api': depends on undefined service 'cache'",
+                "service 'api': joins undefined network 'backend'",
+                "service 'api': mounts undefined volume 'data'",
+                "service 'api': uses undefined config 'app_config'",
+                "service 'sidecar': has neither image nor build",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_document_shape() {
+        assert!(check_compose_schema("services: [\n")[0].starts_with("not valid YAML"));
+        assert_eq!(check_compose_schema("- a\n"), ["the document must be a mapping with a top-level services key"]);
+        assert_eq!(
+            check_compose_schema("service:\n  api:\n    image: api\n"),
+            ["unknown top-level key 'service'", "missing top-level services"]
+        );
+        assert_eq!(check_compose_schema("services:\n  api:\n    image: api\n    ports: \"80:80\"\n"), [
+            "service 'api': ports must be a list"
+        ]);
+    }
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedValidationError};
+use crate::athena::mounts::is_bind_mount;
+use crate::athena::parser::ast::AthenaFile;
+
+/// Image that runs tar against each volume
+const HELPER_IMAGE: &str = "alpine";
+
+/// POSIX sh scripts backing up and restoring the stack's named volumes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VolumeScripts {
+    pub backup: String,
+    pub restore: String,
+}
+
+/// Generate `backup.sh` and `restore.sh` for every named volume: those
+/// declared with VOLUME and those mounted by a VOLUME-MAPPING
+pub fn generate_volume_scripts(athena_file: &AthenaFile) -> AthenaResult<VolumeScripts> {
+    let volumes = collect_volumes(athena_file);
+    if volumes.is_empty() {
+        return Err(AthenaError::validation_error_enhanced(
+            EnhancedValidationError::new("The stack has no named volumes to back up".to_string())
+                .with_suggestion(
+                    "Declare one with VOLUME name in the ENVIRONMENT SECTION and mount it with VOLUME-MAPPING \"name\" TO \"/path\"; bind mounts live on the host and are backed up with it".to_string(),
+                ),
+        ));
+    }
+
+    let project = athena_file.get_compose_project_name();
+    let header = |purpose: &str, usage: &str| {
+        let mut header = String::new();
+        let _ = writeln!(header, "#!/bin/sh");
+        let _ = writeln!(
+            header,
+            "# Generated by Athena v{} from {} deployment",
+            env!("CARGO_PKG_VERSION"),
+            athena_file.get_project_name()
+        );
+        let _ = writeln!(header, "# {purpose}");
+        let _ = writeln!(header, "#");
+        let _ = writeln!(header, "# Volumes of Compose project \"{project}\" (volume: services):");
+        for (volume, services) in &volumes {
+            let users = if services.is_empty() {
+                "not mounted".to_string()
+            } else {
+                services.join(", ")
+            };
+            let _ = writeln!(header, "#   {project}_{volume}: {users}");
+        }
+        let _ = writeln!(header, "#");
+        let _ = writeln!(header, "# Usage: {usage}");
+        let _ = writeln!(header, "set -eu\n");
+        let _ = writeln!(header, "PROJECT=\"{project}\"");
+        let _ = writeln!(header, "BACKUP_DIR=\"${{1:-.}}\"");
+        header
+    };
+
+    let mut backup = header(
+        "Archive each named volume to <volume>.tar.gz",
+        "./backup.sh [DIR]  (archives go to DIR, the current directory by default)",
+    );
+    backup += r#"mkdir -p "$BACKUP_DIR"
+BACKUP_DIR=$(cd "$BACKUP_DIR" && pwd)
+
+backup() {
+    volume="${PROJECT}_$1"
+    echo "Backing up $volume to $BACKUP_DIR/$1.tar.gz"
+"#;
+    let _ = writeln!(
+        backup,
+        "    docker run --rm -v \"$volume:/data:ro\" -v \"$BACKUP_DIR:/backup\" {HELPER_IMAGE} \\"
+    );
+    backup += "        tar czf \"/backup/$1.tar.gz\" -C /data .\n}\n\n";
+
+    let mut restore = header(
+        "Replace each named volume's content with <volume>.tar.gz; stop the stack first",
+        "./restore.sh [DIR]  (archives are read from DIR, the current directory by default)",
+    );
+    restore += r#"BACKUP_DIR=$(cd "$BACKUP_DIR" && pwd)
+
+restore() {
+    volume="${PROJECT}_$1"
+    archive="$BACKUP_DIR/$1.tar.gz"
+    if [ ! -f "$archive" ]; then
+        echo "Skipping $volume: $archive not found" >&2
+        return 0
+    fi
+    echo "Restoring $volume from $archive"
+    # Labeled like Compose's own volumes so `docker compose up` adopts it
+    docker volume create --label "com.docker.compose.project=$PROJECT" \
+        --label "com.docker.compose.volume=$1" "$volume" >/dev/null
+"#;
+    let _ = writeln!(
+        restore,
+        "    docker run --rm -v \"$volume:/data\" -v \"$BACKUP_DIR:/backup:ro\" {HELPER_IMAGE} \\"
+    );
+    restore += "        sh -c \"find /data -mindepth 1 -delete && tar xzf '/backup/$1.tar.gz' -C /data\"\n}\n\n";
+
+    for volume in volumes.keys() {
+        let _ = writeln!(backup, "backup \"{volume}\"");
+        let _ = writeln!(restore, "restore \"{volume}\"");
+    }
+
+    Ok(VolumeScripts { backup, restore })
+}
+
+/// Named volumes with the services mounting them, sorted for stable output
+fn collect_volumes(athena_file: &AthenaFile) -> BTreeMap<&str, Vec<&str>> {
+    let mut volumes: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    if let Some(environment) = &athena_file.environment {
+        for volume in &environment.volumes {
+            volumes.entry(volume.name.as_str()).or_default();
+        }
+    }
+
+    for service in &athena_file.services.services {
+        for mapping in &service.volumes {
+            if is_bind_mount(&mapping.host_path) {
+                continue;
+            }
+            let services = volumes.entry(mapping.host_path.as_str()).or_default();
+            if !services.contains(&service.name.as_str()) {
+                services.push(&service.name);
+            }
+        }
+    }
+    volumes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::parse_athena_file;
+
+    const STACK: &str = r#"DEPLOYMENT-ID SHOP_APP
+ENVIRONMENT SECTION
+VOLUME pg_data
+VOLUME uploads
+VOLUME spare
+SERVICES SECTION
+SERVICE db
+IMAGE-ID postgres:16
+VOLUME-MAPPING "pg_data" TO "/var/lib/postgresql/data"
+VOLUME-MAPPING "./init" TO "/docker-entrypoint-initdb.d"
+END SERVICE
+SERVICE api
+IMAGE-ID myorg/api
+VOLUME-MAPPING "uploads" TO "/srv/uploads"
+VOLUME-MAPPING "cache" TO "/tmp/cache"
+END SERVICE
+SERVICE worker
+IMAGE-ID myorg/worker
+VOLUME-MAPPING "uploads" TO "/srv/uploads" (ro)
+END SERVICE"#;
+
+    #[test]
+    fn test_each_named_volume_appears_once() {
+        let scripts = generate_volume_scripts(&parse_athena_file(STACK).unwrap()).unwrap();
+
+        for volume in ["cache", "pg_data", "spare", "uploads"] {
+            let backups = scripts.backup.lines().filter(|l| *l == format!("backup \"{volume}\"")).count();
+            let restores = scripts.restore.lines().filter(|l| *l == format!("restore \"{volume}\"")).count();
+            assert_eq!((backups, restores), (1, 1), "volume {volume}");
+        }
+        assert!(!scripts.backup.contains("./init"));
+    }
+
+    #[test]
+    fn test_manifest_and_project_name() {
+        let scripts = generate_volume_scripts(&parse_athena_file(STACK).unwrap()).unwrap();
+
+        assert!(scripts.backup.contains("PROJECT=\"shop-app\""));
+        assert!(scripts.backup.contains("#   shop-app_uploads: api, worker\n"));
+        assert!(scripts.backup.contains("#   shop-app_spare: not mounted\n"));
+        assert!(scripts.restore.contains("#   shop-app_pg_data: db\n"));
+    }
+
+    #[test]
+    fn test_scripts_avoid_bashisms() {
+        let scripts = generate_volume_scripts(&parse_athena_file(STACK).unwrap()).unwrap();
+        for script in [&scripts.backup, &scripts.restore] {
+            assert!(script.starts_with("#!/bin/sh\n"));
+            for bashism in ["[[", "function ", "local ", "$(<", "==", "source "] {
+                assert!(!script.contains(bashism), "found {bashism}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_stack_without_named_volumes_is_rejected() {
+        let athena_file = parse_athena_file(
+            "SERVICES SECTION\nSERVICE web\nIMAGE-ID nginx\nVOLUME-MAPPING \"./html\" TO \"/usr/share/nginx/html\"\nEND SERVICE",
+        )
+        .unwrap();
+        let error = generate_volume_scripts(&athena_file).unwrap_err();
+        assert!(error.to_string().contains("no named volumes"));
+    }
+}
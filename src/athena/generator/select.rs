@@ -0,0 +1,180 @@
+//! `athena build --only`: generate part of a stack.
+
+use std::collections::HashSet;
+
+use crate::athena::dependencies::with_dependencies;
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedValidationError};
+use crate::athena::parser::ast::{AthenaFile, NetworkMode};
+
+/// Services named by `--only`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServiceSelection {
+    pub services: Vec<String>,
+    /// Keep what the services depend on too; false with `--no-deps`
+    pub with_dependencies: bool,
+}
+
+/// Reduce the file to the selected services and the declared networks and
+/// volumes they use. Without dependencies, DEPENDS-ON entries pointing
+/// outside the selection are dropped, and a note for each is returned.
+pub fn select_services(athena_file: &mut AthenaFile, selection: &ServiceSelection) -> AthenaResult<Vec<String>> {
+    let services = &athena_file.services.services;
+    for name in &selection.services {
+        if !services.iter().any(|s| s.name == *name) {
+            let available: Vec<&str> = services.iter().map(|s| s.name.as_str()).collect();
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!("--only names unknown service '{name}'"))
+                    .with_suggestion(format!("Available services: {}", available.join(", "))),
+            ));
+        }
+    }
+
+    let roots: Vec<&str> = selection.services.iter().map(String::as_str).collect();
+    let kept: HashSet<String> = if selection.with_dependencies {
+        with_dependencies(services, &roots).into_iter().map(str::to_string).collect()
+    } else {
+        selection.services.iter().cloned().collect()
+    };
+    athena_file.services.services.retain(|s| kept.contains(&s.name));
+
+    let mut notes = Vec::new();
+    for service in &mut athena_file.services.services {
+        if let Some(NetworkMode::Service(target)) = &service.network_mode {
+            if !kept.contains(target) {
+                return Err(AthenaError::validation_error_enhanced(
+                    EnhancedValidationError::new(format!(
+                        "Service '{}' shares the network namespace of '{target}', which --no-deps leaves out",
+                        service.name
+                    ))
+                    .with_suggestion(format!("Add {target} to --only, or drop --no-deps"))
+                    .with_services(vec![service.name.clone(), target.clone()]),
+                ));
+            }
+        }
+
+        let name = &service.name;
+        service.depends_on.retain(|dependency| {
+            let keep = kept.contains(&dependency.service);
+            if !keep {
+                notes.push(format!(
+                    "Service '{name}' depends on '{}', which --no-deps leaves out; dropped it from depends_on",
+                    dependency.service
+                ));
+            }
+            keep
+        });
+    }
+
+    let services = &athena_file.services.services;
+    if let Some(environment) = &mut athena_file.environment {
+        environment
+            .volumes
+            .retain(|volume| services.iter().flat_map(|s| &s.volumes).any(|m| m.host_path == volume.name));
+        // The first network is the project network every service joins
+        let mut index = 0;
+        environment.networks.retain(|network| {
+            index += 1;
+            index == 1
+                || services
+                    .iter()
+                    .flat_map(|s| &s.networks)
+                    .any(|attachment| attachment.network == network.name)
+        });
+    }
+
+    Ok(notes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::parse_athena_file;
+
+    /// api needs auth and cache, which both need db
+    const DIAMOND: &str = r#"
+        ENVIRONMENT SECTION
+        NETWORK-NAME app_net
+        NETWORK-NAME jobs_net
+        VOLUME pg_data
+        VOLUME queue_data
+        SERVICES SECTION
+        SERVICE db
+        IMAGE-ID postgres:16
+        VOLUME-MAPPING "pg_data" TO "/var/lib/postgresql/data"
+        END SERVICE
+        SERVICE auth
+        IMAGE-ID myorg/auth
+        DEPENDS-ON db
+        END SERVICE
+        SERVICE cache
+        IMAGE-ID redis:7
+        DEPENDS-ON db
+        END SERVICE
+        SERVICE api
+        IMAGE-ID myorg/api
+        DEPENDS-ON auth
+        DEPENDS-ON cache
+        END SERVICE
+        SERVICE worker
+        IMAGE-ID myorg/worker
+        VOLUME-MAPPING "queue_data" TO "/data"
+        ON NETWORK jobs_net
+        END SERVICE
+    "#;
+
+    fn names(athena_file: &AthenaFile) -> Vec<&str> {
+        athena_file.services.services.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_only_keeps_transitive_dependencies() {
+        let mut athena_file = parse_athena_file(DIAMOND).unwrap();
+        let selection = ServiceSelection {
+            services: vec!["api".to_string()],
+            with_dependencies: true,
+        };
+
+        let notes = select_services(&mut athena_file, &selection).unwrap();
+        assert!(notes.is_empty());
+        assert_eq!(names(&athena_file), ["db", "auth", "cache", "api"]);
+
+        let environment = athena_file.environment.as_ref().unwrap();
+        let volumes: Vec<&str> = environment.volumes.iter().map(|v| v.name.as_str()).collect();
+        let networks: Vec<&str> = environment.networks.iter().map(|n| n.name.as_str()).collect();
+        assert_eq!(volumes, ["pg_data"]);
+        assert_eq!(networks, ["app_net"]);
+    }
+
+    #[test]
+    fn test_no_deps_drops_dependencies_outside_the_selection() {
+        let mut athena_file = parse_athena_file(DIAMOND).unwrap();
+        let selection = ServiceSelection {
+            services: vec!["api".to_string(), "auth".to_string(), "worker".to_string()],
+            with_dependencies: false,
+        };
+
+        let notes = select_services(&mut athena_file, &selection).unwrap();
+        assert_eq!(names(&athena_file), ["auth", "api", "worker"]);
+        let api = &athena_file.services.services[1];
+        let deps: Vec<&str> = api.depends_on.iter().map(|d| d.service.as_str()).collect();
+        assert_eq!(deps, ["auth"]);
+        assert_eq!(notes.len(), 2, "{notes:#?}");
+        assert!(notes[0].contains("Service 'auth' depends on 'db'"));
+        assert!(notes[1].contains("Service 'api' depends on 'cache'"));
+
+        let networks = &athena_file.environment.as_ref().unwrap().networks;
+        assert_eq!(networks.len(), 2, "jobs_net is still used by worker");
+    }
+
+    #[test]
+    fn test_unknown_service_is_rejected() {
+        let mut athena_file = parse_athena_file(DIAMOND).unwrap();
+        let selection = ServiceSelection {
+            services: vec!["apii".to_string()],
+            with_dependencies: true,
+        };
+
+        let error = select_services(&mut athena_file, &selection).unwrap_err().to_string();
+        assert!(error.contains("--only names unknown service 'apii'"), "{error}");
+    }
+}
@@ -0,0 +1,807 @@
+use indexmap::IndexMap;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use super::defaults::DefaultsEngine;
+use super::options::GeneratorOptions;
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedValidationError};
+use crate::athena::mounts::{has_drive_letter, is_bind_mount, normalize_mount_path};
+use crate::athena::parser::ast::{
+    AthenaFile, ContainerCommand, Duration, EnvironmentVariable, IpcMode, NetworkAttachment, NetworkDriver, NetworkMode, PidMode,
+    Protocol, RestartPolicy, Service,
+};
+
+/// Result of a Terraform generation: the `.tf.json` document plus one
+/// message per construct that the docker provider can't express.
+#[derive(Debug)]
+pub struct TerraformOutput {
+    pub config: String,
+    pub dropped: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TerraformDocument {
+    terraform: TerraformSettings,
+    provider: BTreeMap<&'static str, BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    variable: BTreeMap<String, TerraformVariable>,
+    resource: TerraformResources,
+}
+
+#[derive(Debug, Serialize)]
+struct TerraformSettings {
+    required_providers: BTreeMap<&'static str, RequiredProvider>,
+}
+
+#[derive(Debug, Serialize)]
+struct RequiredProvider {
+    source: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct TerraformVariable {
+    #[serde(rename = "type")]
+    var_type: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct TerraformResources {
+    docker_network: BTreeMap<String, TfNetwork>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    docker_volume: BTreeMap<String, TfVolume>,
+    docker_container: IndexMap<String, TfContainer>,
+}
+
+#[derive(Debug, Serialize)]
+struct TfNetwork {
+    name: String,
+    driver: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    attachable: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ingress: Option<bool>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    ipam_config: Vec<TfIpamConfig>,
+}
+
+#[derive(Debug, Serialize)]
+struct TfVolume {
+    name: String,
+}
+
+#[derive(Debug, Serialize)]
+struct TfContainer {
+    name: String,
+    image: String,
+    restart: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    ports: Vec<TfPort>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    env: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    command: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entrypoint: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    working_dir: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hostname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    init: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tty: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stdin_open: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_signal: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    destroy_grace_seconds: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    dns: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    host: Vec<TfHost>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    volumes: Vec<TfMount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    healthcheck: Option<TfHealthcheck>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    network_mode: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    networks_advanced: Vec<TfNetworkAttachment>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pid_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ipc_mode: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TfPort {
+    internal: u16,
+    external: u16,
+    protocol: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct TfHost {
+    host: String,
+    ip: String,
+}
+
+#[derive(Debug, Serialize)]
+struct TfMount {
+    container_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    volume_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    read_only: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct TfHealthcheck {
+    test: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TfNetworkAttachment {
+    name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    aliases: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ipv4_address: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TfIpamConfig {
+    subnet: String,
+}
+
+/// Generate a `.tf.json` configuration for the kreuzwerker/docker provider.
+/// A container without CONTAINER-NAME is named after its service, under the
+/// stack prefix if there is one.
+pub fn generate_terraform_docker(athena_file: &AthenaFile, options: &GeneratorOptions) -> AthenaResult<TerraformOutput> {
+    let network_name = athena_file.get_network_name();
+    let default_dns = athena_file.get_default_dns();
+
+    let mut dropped = Vec::new();
+    let mut variables = BTreeMap::new();
+
+    let docker_network = create_networks(athena_file)?;
+    let volume_names: Vec<&str> = athena_file
+        .environment
+        .iter()
+        .flat_map(|env| &env.volumes)
+        .map(|v| v.name.as_str())
+        .collect();
+    check_unique_keys("Volumes", &volume_names)?;
+    let docker_volume: BTreeMap<String, TfVolume> = volume_names
+        .iter()
+        .map(|name| (resource_key(name), TfVolume { name: name.to_string() }))
+        .collect();
+
+    let service_names: Vec<&str> = athena_file.services.services.iter().map(|s| s.name.as_str()).collect();
+    check_unique_keys("Services", &service_names)?;
+
+    // Services without an image get no container, so nothing may point at them
+    let skipped: Vec<&str> = athena_file
+        .services
+        .services
+        .iter()
+        .filter(|s| s.image.is_none())
+        .map(|s| s.name.as_str())
+        .collect();
+
+    let mut docker_container = IndexMap::new();
+    for service in &athena_file.services.services {
+        report_dropped(service, &mut dropped);
+
+        let Some(image) = service.image.clone() else {
+            dropped.push(format!(
+                "Service '{}' has no IMAGE-ID; Terraform can't build images, so the service was skipped",
+                service.name
+            ));
+            continue;
+        };
+
+        if let Some(NetworkMode::Service(target)) = &service.network_mode {
+            if skipped.contains(&target.as_str()) {
+                return Err(AthenaError::validation_error_enhanced(
+                    EnhancedValidationError::new(format!(
+                        "Service '{}' shares the network namespace of '{target}', which has no IMAGE-ID and gets no container in Terraform output",
+                        service.name
+                    ))
+                    .with_suggestion(format!("Give '{target}' an IMAGE-ID, or build the image first and reference it"))
+                    .with_services(vec![service.name.clone(), target.clone()]),
+                ));
+            }
+        }
+        for dependency in service.depends_on.iter().filter(|d| skipped.contains(&d.service.as_str())) {
+            dropped.push(format!(
+                "Service '{}': DEPENDS-ON {} was dropped, since '{}' was skipped",
+                service.name, dependency.service, dependency.service
+            ));
+        }
+
+        let dns = if service.dns.is_empty() {
+            default_dns.to_vec()
+        } else {
+            service.dns.clone()
+        };
+
+        let container = TfContainer {
+            name: service.container_name.clone().unwrap_or_else(|| match options.stack_prefix() {
+                Some(prefix) => format!("{prefix}-{}", service.name),
+                None => service.name.clone(),
+            }),
+            image,
+            restart: convert_restart_policy(service),
+            ports: service
+                .ports
+                .iter()
+                .map(|p| TfPort {
+                    internal: p.container_port,
+                    external: p.host_port,
+                    protocol: match p.protocol {
+                        Protocol::Tcp => "tcp",
+                        Protocol::Udp => "udp",
+                    },
+                })
+                .collect(),
+            env: convert_environment(service, &mut variables)?,
+            command: service.command.as_ref().map(convert_command),
+            entrypoint: service.entrypoint.as_ref().map(convert_command),
+            working_dir: service.working_dir.clone(),
+            user: service.user.clone(),
+            hostname: service.hostname.clone(),
+            init: service.init,
+            tty: service.tty,
+            stdin_open: service.stdin_open,
+            stop_signal: service.stop_signal.clone(),
+            destroy_grace_seconds: service.stop_grace_period.map(Duration::as_secs_ceil),
+            dns,
+            host: service
+                .extra_hosts
+                .iter()
+                .chain(&athena_file.external_hosts_for(service))
+                .map(|h| TfHost {
+                    host: h.hostname.clone(),
+                    ip: h.ip.clone(),
+                })
+                .collect(),
+            volumes: convert_volumes(service, &docker_volume),
+            healthcheck: service.health_check.as_ref().map(|cmd| TfHealthcheck {
+                test: vec!["CMD-SHELL".to_string(), escape_interpolation(cmd)],
+            }),
+            network_mode: service.network_mode.as_ref().map(|mode| match mode {
+                NetworkMode::Service(target) => format!("container:${{docker_container.{}.id}}", resource_key(target)),
+                other => other.to_compose_value(),
+            }),
+            networks_advanced: convert_networks(service, &network_name),
+            pid_mode: service.pid.map(|PidMode::Host| "host".to_string()),
+            ipc_mode: service.ipc.map(|mode| match mode {
+                IpcMode::Host => "host".to_string(),
+                IpcMode::Shareable => "shareable".to_string(),
+            }),
+            depends_on: service
+                .depends_on
+                .iter()
+                .filter(|dep| !skipped.contains(&dep.service.as_str()))
+                .map(|dep| format!("docker_container.{}", resource_key(&dep.service)))
+                .collect(),
+        };
+
+        docker_container.insert(resource_key(&service.name), container);
+    }
+
+    let document = TerraformDocument {
+        terraform: TerraformSettings {
+            required_providers: BTreeMap::from([(
+                "docker",
+                RequiredProvider {
+                    source: "kreuzwerker/docker",
+                },
+            )]),
+        },
+        provider: BTreeMap::from([("docker", BTreeMap::new())]),
+        variable: variables,
+        resource: TerraformResources {
+            docker_network,
+            docker_volume,
+            docker_container,
+        },
+    };
+
+    let config = serde_json::to_string_pretty(&document).map_err(|e| {
+        AthenaError::config_error(format!("Failed to serialize Terraform configuration: {e}"))
+    })?;
+
+    Ok(TerraformOutput {
+        config: config + "\n",
+        dropped,
+    })
+}
+
+/// The project network, then every ON NETWORK network with its options
+fn convert_networks(service: &Service, network_name: &str) -> Vec<TfNetworkAttachment> {
+    if service.network_mode.is_some() {
+        return Vec::new();
+    }
+
+    let mut attachments: Vec<NetworkAttachment> = vec![NetworkAttachment::new(network_name)];
+    for attachment in &service.networks {
+        match attachments.iter_mut().find(|a| a.network == attachment.network) {
+            Some(existing) => *existing = attachment.clone(),
+            None => attachments.push(attachment.clone()),
+        }
+    }
+
+    attachments
+        .into_iter()
+        .map(|attachment| TfNetworkAttachment {
+            name: format!("${{docker_network.{}.name}}", resource_key(&attachment.network)),
+            aliases: attachment.aliases,
+            ipv4_address: attachment.ipv4_address,
+        })
+        .collect()
+}
+
+fn create_networks(athena_file: &AthenaFile) -> AthenaResult<BTreeMap<String, TfNetwork>> {
+    let declared: Vec<_> = athena_file.environment.iter().flat_map(|env| &env.networks).collect();
+    check_unique_keys("Networks", &declared.iter().map(|net| net.name.as_str()).collect::<Vec<_>>())?;
+
+    let mut networks: BTreeMap<String, TfNetwork> = declared
+        .into_iter()
+        .map(|net| {
+            let driver = match net.driver {
+                Some(NetworkDriver::Overlay) => "overlay",
+                Some(NetworkDriver::Host) => "host",
+                Some(NetworkDriver::None) => "none",
+                Some(NetworkDriver::Bridge) | None => "bridge",
+            };
+            (
+                resource_key(&net.name),
+                TfNetwork {
+                    name: net.name.clone(),
+                    driver: driver.to_string(),
+                    attachable: net.attachable,
+                    ingress: net.ingress,
+                    ipam_config: net
+                        .subnet
+                        .iter()
+                        .map(|subnet| TfIpamConfig {
+                            subnet: subnet.clone(),
+                        })
+                        .collect(),
+                },
+            )
+        })
+        .collect();
+
+    if networks.is_empty() {
+        let name = athena_file.get_network_name();
+        networks.insert(
+            resource_key(&name),
+            TfNetwork {
+                name,
+                driver: "bridge".to_string(),
+                attachable: None,
+                ingress: None,
+                ipam_config: Vec::new(),
+            },
+        );
+    }
+
+    Ok(networks)
+}
+
+/// Resource names must start with a letter or `_` and hold only letters,
+/// digits, `_` and `-`; the real name is kept in the `name` attribute.
+fn resource_key(name: &str) -> String {
+    let key: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' || c == '-' { c } else { '_' })
+        .collect();
+    if key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+        key
+    } else {
+        format!("_{key}")
+    }
+}
+
+/// Two names that only differ in characters `resource_key` replaces would
+/// end up as the same resource
+fn check_unique_keys(kind: &str, names: &[&str]) -> AthenaResult<()> {
+    let mut seen: BTreeMap<String, &str> = BTreeMap::new();
+    for name in names {
+        match seen.get(&resource_key(name)) {
+            Some(other) if other != name => {
+                return Err(AthenaError::validation_error_enhanced(
+                    EnhancedValidationError::new(format!(
+                        "{kind} '{other}' and '{name}' both become the Terraform resource '{}'",
+                        resource_key(name)
+                    ))
+                    .with_suggestion("Rename one of them so they differ in letters, digits, '_' or '-'".to_string()),
+                ));
+            }
+            _ => {
+                seen.insert(resource_key(name), name);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Record every directive the docker provider has no equivalent for
+fn report_dropped(service: &Service, dropped: &mut Vec<String>) {
+    if service.swarm_config.is_some() {
+        dropped.push(format!(
+            "Service '{}': Swarm deploy settings (REPLICAS, DEPLOY-MODE, UPDATE-CONFIG, LABELS) are not supported by docker_container and were dropped",
+            service.name
+        ));
+    }
+    if service.resources.is_some() {
+        dropped.push(format!(
+            "Service '{}': RESOURCE-LIMITS were dropped; set memory/cpu_shares on the container manually",
+            service.name
+        ));
+    }
+    if service.reservations.is_some() {
+        dropped.push(format!(
+            "Service '{}': RESERVE was dropped; docker_container has no resource reservations",
+            service.name
+        ));
+    }
+    if service.build_args.is_some() {
+        dropped.push(format!(
+            "Service '{}': BUILD-ARGS were dropped; build the image before applying",
+            service.name
+        ));
+    }
+    for port in service.ports.iter().filter(|p| p.host_port_end.is_some()) {
+        dropped.push(format!(
+            "Service '{}': host port range {} was published as {} only, since docker_container runs a single container",
+            service.name,
+            port.host_ports(),
+            port.host_port
+        ));
+    }
+    if service.pull_policy.is_some() {
+        dropped.push(format!(
+            "Service '{}': PULL-POLICY was dropped; use a docker_image resource to control pulls",
+            service.name
+        ));
+    }
+    for dependency in service.depends_on.iter().filter(|d| d.condition.is_some()) {
+        dropped.push(format!(
+            "Service '{}': the condition on DEPENDS-ON {} was dropped; docker_container only orders creation",
+            service.name, dependency.service
+        ));
+    }
+    if !service.watch.is_empty() {
+        dropped.push(format!(
+            "Service '{}': WATCH rules were dropped; they only apply to `docker compose watch`",
+            service.name
+        ));
+    }
+    if service.platform.is_some() {
+        dropped.push(format!(
+            "Service '{}': PLATFORM was dropped; set platform on a docker_image resource instead",
+            service.name
+        ));
+    }
+    if !service.profiles.is_empty() {
+        dropped.push(format!(
+            "Service '{}': PROFILE was dropped; Terraform creates every container, use count or separate modules instead",
+            service.name
+        ));
+    }
+    for volume in service.volumes.iter().filter(|v| v.needs_long_syntax()) {
+        dropped.push(format!(
+            "Service '{}': consistency, propagation and nocopy options on '{}' were dropped",
+            service.name, volume.container_path
+        ));
+    }
+}
+
+fn convert_restart_policy(service: &Service) -> String {
+    let service_type = DefaultsEngine::detect_service_type(service);
+    let defaults = DefaultsEngine::get_defaults_for_type(service_type);
+
+    match service.restart.as_ref().unwrap_or(&defaults.restart_policy) {
+        RestartPolicy::Always => "always".to_string(),
+        RestartPolicy::UnlessStopped => "unless-stopped".to_string(),
+        RestartPolicy::OnFailure => "on-failure".to_string(),
+        RestartPolicy::No => "no".to_string(),
+    }
+}
+
+/// Template variables become Terraform input variables instead of shell
+/// interpolation. A literal needs a `KEY=` part: the provider has no other
+/// way to name it.
+fn convert_environment(
+    service: &Service,
+    variables: &mut BTreeMap<String, TerraformVariable>,
+) -> AthenaResult<Vec<String>> {
+    service
+        .environment
+        .iter()
+        .map(|env_var| match env_var {
+            EnvironmentVariable::Template(var_name) => {
+                variables.insert(var_name.clone(), TerraformVariable { var_type: "string" });
+                Ok(format!("{var_name}=${{var.{var_name}}}"))
+            }
+            EnvironmentVariable::Literal(value) if value.contains('=') => Ok(escape_interpolation(value)),
+            EnvironmentVariable::Literal(value) => Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(format!(
+                    "Service '{}' has ENV-VARIABLE \"{value}\" without a name",
+                    service.name
+                ))
+                .with_suggestion(format!("Write it as ENV-VARIABLE \"NAME={value}\""))
+                .with_services(vec![service.name.clone()]),
+            )),
+        })
+        .collect()
+}
+
+/// The provider only takes exec-form lists, so shell commands are wrapped in `sh -c`
+fn convert_command(command: &ContainerCommand) -> Vec<String> {
+    match command {
+        ContainerCommand::Shell(cmd) => vec![
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            escape_interpolation(cmd),
+        ],
+        ContainerCommand::Exec(args) => args.iter().map(|a| escape_interpolation(a)).collect(),
+    }
+}
+
+fn convert_volumes(service: &Service, declared: &BTreeMap<String, TfVolume>) -> Vec<TfMount> {
+    service
+        .volumes
+        .iter()
+        .map(|v| {
+            let read_only = v.read_only().then_some(true);
+            let container_path = normalize_mount_path(&v.container_path);
+
+            if is_bind_mount(&v.host_path) {
+                // The provider requires absolute host paths
+                let host_path = normalize_mount_path(&v.host_path);
+                let host_path = if host_path.starts_with('/') || has_drive_letter(&host_path) {
+                    host_path
+                } else if host_path.starts_with('~') {
+                    format!("${{pathexpand(\"{host_path}\")}}")
+                } else {
+                    format!(
+                        "${{abspath(\"${{path.module}}/{}\")}}",
+                        host_path.trim_start_matches("./")
+                    )
+                };
+                TfMount {
+                    container_path,
+                    host_path: Some(host_path),
+                    volume_name: None,
+                    read_only,
+                }
+            } else {
+                let volume_name = if declared.values().any(|volume| volume.name == v.host_path) {
+                    format!("${{docker_volume.{}.name}}", resource_key(&v.host_path))
+                } else {
+                    v.host_path.clone()
+                };
+                TfMount {
+                    container_path,
+                    host_path: None,
+                    volume_name: Some(volume_name),
+                    read_only,
+                }
+            }
+        })
+        .collect()
+}
+
+/// Literal `${` would be read as Terraform interpolation
+fn escape_interpolation(value: &str) -> String {
+    value.replace("${", "$${")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::parse_athena_file;
+
+    #[test]
+    fn test_generates_containers_networks_and_volumes() {
+        let input = r#"
+            DEPLOYMENT-ID TF_TEST
+            ENVIRONMENT SECTION
+            VOLUME db_data
+            SERVICES SECTION
+            SERVICE database
+            IMAGE-ID "postgres:16"
+            VOLUME-MAPPING "db_data" TO "/var/lib/postgresql/data"
+            ENV-VARIABLE {{POSTGRES_PASSWORD}}
+            END SERVICE
+            SERVICE api
+            IMAGE-ID "node:20-alpine"
+            PORT-MAPPING 8080 TO 3000
+            VOLUME-MAPPING "./config" TO "/app/config" (ro)
+            COMMAND "npm start"
+            DEPENDS-ON database
+            END SERVICE
+        "#;
+
+        let athena_file = parse_athena_file(input).unwrap();
+        let output = generate_terraform_docker(&athena_file, &GeneratorOptions::new()).unwrap();
+        assert!(output.dropped.is_empty());
+
+        let doc: serde_json::Value = serde_json::from_str(&output.config).unwrap();
+        assert_eq!(doc["terraform"]["required_providers"]["docker"]["source"], "kreuzwerker/docker");
+        assert_eq!(doc["variable"]["POSTGRES_PASSWORD"]["type"], "string");
+        assert_eq!(doc["resource"]["docker_volume"]["db_data"]["name"], "db_data");
+        assert!(doc["resource"]["docker_network"]["tf_test_network"].is_object());
+
+        let database = &doc["resource"]["docker_container"]["database"];
+        assert_eq!(database["env"][0], "POSTGRES_PASSWORD=${var.POSTGRES_PASSWORD}");
+        assert_eq!(database["volumes"][0]["volume_name"], "${docker_volume.db_data.name}");
+
+        let api = &doc["resource"]["docker_container"]["api"];
+        assert_eq!(api["ports"][0]["internal"], 3000);
+        assert_eq!(api["ports"][0]["external"], 8080);
+        assert_eq!(api["command"][2], "npm start");
+        assert_eq!(api["depends_on"][0], "docker_container.database");
+        assert_eq!(api["volumes"][0]["host_path"], "${abspath(\"${path.module}/config\")}");
+        assert_eq!(api["volumes"][0]["read_only"], true);
+    }
+
+    #[test]
+    fn test_network_attachments() {
+        let input = r#"
+            ENVIRONMENT SECTION
+            NETWORK-NAME frontend
+            NETWORK-NAME backend SUBNET "172.28.0.0/16"
+            SERVICES SECTION
+            SERVICE db
+            IMAGE-ID "postgres:16"
+            ON NETWORK backend ALIAS "db.legacy.local" IPV4 "172.28.0.10"
+            END SERVICE
+        "#;
+
+        let athena_file = parse_athena_file(input).unwrap();
+        let output = generate_terraform_docker(&athena_file, &GeneratorOptions::new()).unwrap();
+        let doc: serde_json::Value = serde_json::from_str(&output.config).unwrap();
+
+        let backend = &doc["resource"]["docker_network"]["backend"];
+        assert_eq!(backend["ipam_config"][0]["subnet"], "172.28.0.0/16");
+        let networks = &doc["resource"]["docker_container"]["db"]["networks_advanced"];
+        assert_eq!(networks[0]["name"], "${docker_network.frontend.name}");
+        assert!(networks[0].get("aliases").is_none());
+        assert_eq!(networks[1]["name"], "${docker_network.backend.name}");
+        assert_eq!(networks[1]["aliases"][0], "db.legacy.local");
+        assert_eq!(networks[1]["ipv4_address"], "172.28.0.10");
+    }
+
+    #[test]
+    fn test_unsupported_constructs_are_reported() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE web
+            IMAGE-ID "nginx:alpine"
+            RESOURCE-LIMITS CPU "0.5" MEMORY "512M"
+            REPLICAS 3
+            END SERVICE
+            SERVICE builder
+            BUILD-ARGS NODE_VERSION="20"
+            END SERVICE
+        "#;
+
+        let athena_file = parse_athena_file(input).unwrap();
+        let output = generate_terraform_docker(&athena_file, &GeneratorOptions::new()).unwrap();
+
+        assert_eq!(output.dropped.len(), 4);
+        assert!(output.dropped.iter().any(|d| d.contains("Swarm deploy settings")));
+        assert!(output.dropped.iter().any(|d| d.contains("RESOURCE-LIMITS")));
+        assert!(output.dropped.iter().any(|d| d.contains("'builder' has no IMAGE-ID")));
+
+        let doc: serde_json::Value = serde_json::from_str(&output.config).unwrap();
+        assert!(doc["resource"]["docker_container"]["builder"].is_null());
+    }
+
+    #[test]
+    fn test_dependencies_on_skipped_services_are_dropped() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE builder
+            BUILD-ARGS NODE_VERSION="20"
+            END SERVICE
+            SERVICE cache
+            IMAGE-ID "redis:7"
+            END SERVICE
+            SERVICE api
+            IMAGE-ID "node:20-alpine"
+            DEPENDS-ON builder
+            DEPENDS-ON cache
+            END SERVICE
+        "#;
+
+        let output = generate_terraform_docker(&parse_athena_file(input).unwrap(), &GeneratorOptions::new()).unwrap();
+        let doc: serde_json::Value = serde_json::from_str(&output.config).unwrap();
+        assert_eq!(doc["resource"]["docker_container"]["api"]["depends_on"], serde_json::json!(["docker_container.cache"]));
+        assert!(output.dropped.iter().any(|d| d == "Service 'api': DEPENDS-ON builder was dropped, since 'builder' was skipped"));
+    }
+
+    #[test]
+    fn test_resource_keys_are_valid_identifiers() {
+        let input = r#"
+            ENVIRONMENT SECTION
+            NETWORK-NAME edge.net
+            VOLUME 1data
+            SERVICES SECTION
+            SERVICE web.frontend
+            IMAGE-ID "nginx:alpine"
+            VOLUME-MAPPING "1data" TO "/data"
+            VOLUME-MAPPING "~/cfg" TO "/etc/app" (ro)
+            ON NETWORK edge.net
+            END SERVICE
+            SERVICE 2api
+            IMAGE-ID "node:20-alpine"
+            NETWORK-MODE service:web.frontend
+            DEPENDS-ON web.frontend
+            END SERVICE
+        "#;
+
+        let output = generate_terraform_docker(&parse_athena_file(input).unwrap(), &GeneratorOptions::new()).unwrap();
+        let doc: serde_json::Value = serde_json::from_str(&output.config).unwrap();
+        let resources = &doc["resource"];
+
+        assert_eq!(resources["docker_network"]["edge_net"]["name"], "edge.net");
+        assert_eq!(resources["docker_volume"]["_1data"]["name"], "1data");
+
+        let web = &resources["docker_container"]["web_frontend"];
+        assert_eq!(web["name"], "web.frontend");
+        assert_eq!(web["volumes"][0]["volume_name"], "${docker_volume._1data.name}");
+        assert_eq!(web["volumes"][1]["host_path"], "${pathexpand(\"~/cfg\")}");
+        assert_eq!(web["networks_advanced"][0]["name"], "${docker_network.edge_net.name}");
+
+        let api = &resources["docker_container"]["_2api"];
+        assert_eq!(api["name"], "2api");
+        assert_eq!(api["network_mode"], "container:${docker_container.web_frontend.id}");
+        assert_eq!(api["depends_on"][0], "docker_container.web_frontend");
+    }
+
+    #[test]
+    fn test_names_sharing_a_resource_key_are_rejected() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE web.app
+            IMAGE-ID "nginx:alpine"
+            END SERVICE
+            SERVICE web_app
+            IMAGE-ID "nginx:alpine"
+            END SERVICE
+        "#;
+
+        let error = generate_terraform_docker(&parse_athena_file(input).unwrap(), &GeneratorOptions::new()).unwrap_err();
+        assert_eq!(error.exit_code(), 4);
+        assert!(error.to_string().contains("Services 'web.app' and 'web_app' both become the Terraform resource 'web_app'"), "{error}");
+    }
+
+    #[test]
+    fn test_literal_without_a_name_is_rejected() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE api
+            IMAGE-ID "node:20-alpine"
+            ENV-VARIABLE "production"
+            END SERVICE
+        "#;
+
+        let error = generate_terraform_docker(&parse_athena_file(input).unwrap(), &GeneratorOptions::new()).unwrap_err();
+        assert_eq!(error.exit_code(), 4);
+        assert!(error.to_string().contains("Service 'api' has ENV-VARIABLE \"production\" without a name"), "{error}");
+    }
+}
@@ -0,0 +1,121 @@
+//! `DEFAULT LABELS FROM "labels.yml"`: organization-wide labels read from a
+//! YAML mapping and applied to every service.
+//!
+//! The parser only records the path; the CLI reads the file before
+//! generating, relative to the .ath file that declared it.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_yaml::Value;
+
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedValidationError};
+use crate::athena::parser::ast::AthenaFile;
+
+/// Where the DEFAULT LABELS FROM file is, if the file declares one. A relative
+/// path resolves against the directory of the declaring .ath file, or against
+/// `base_dir` when the file was parsed without a name.
+pub fn default_labels_path(athena_file: &AthenaFile, base_dir: &Path) -> Option<PathBuf> {
+    let default_labels = athena_file.environment.as_ref()?.default_labels.as_ref()?;
+    let declared_in = default_labels
+        .location
+        .as_ref()
+        .and_then(|l| l.file.as_deref())
+        .and_then(|file| Path::new(file).parent());
+    Some(declared_in.unwrap_or(base_dir).join(&default_labels.path))
+}
+
+/// Read the DEFAULT LABELS FROM file into the parsed file
+pub fn load_default_labels(athena_file: &mut AthenaFile, base_dir: &Path) -> AthenaResult<()> {
+    let Some(path) = default_labels_path(athena_file, base_dir) else {
+        return Ok(());
+    };
+    let content = fs::read_to_string(&path).map_err(|e| {
+        AthenaError::validation_error_enhanced(
+            EnhancedValidationError::new(format!("Cannot read DEFAULT LABELS FROM {}: {e}", path.display()))
+                .with_suggestion("The path is relative to the .ath file declaring it".to_string()),
+        )
+    })?;
+    let labels = parse_labels(&content).map_err(|reason| {
+        AthenaError::validation_error_enhanced(
+            EnhancedValidationError::new(format!("Invalid labels file {}: {reason}", path.display()))
+                .with_suggestion("Write one `key: value` pair per line, e.g. org.opencontainers.image.licenses: MIT".to_string()),
+        )
+    })?;
+
+    if let Some(default_labels) = athena_file.environment.as_mut().and_then(|e| e.default_labels.as_mut()) {
+        default_labels.labels = labels;
+    }
+    Ok(())
+}
+
+/// A flat mapping of label keys to scalars; numbers and booleans become text
+fn parse_labels(content: &str) -> Result<HashMap<String, String>, String> {
+    let document: Value = serde_yaml::from_str(content).map_err(|e| e.to_string())?;
+    let mapping = match document {
+        Value::Mapping(mapping) => mapping,
+        Value::Null => return Ok(HashMap::new()),
+        _ => return Err("expected a mapping of label keys to values".to_string()),
+    };
+
+    mapping
+        .into_iter()
+        .map(|(key, value)| {
+            let key = key.as_str().ok_or("label keys must be strings")?.to_string();
+            let value = match value {
+                Value::String(text) => text,
+                Value::Number(number) => number.to_string(),
+                Value::Bool(flag) => flag.to_string(),
+                _ => return Err(format!("label '{key}' must have a string, number or boolean value")),
+            };
+            Ok((key, value))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::parse_athena_file;
+    use tempfile::TempDir;
+
+    const STACK: &str = r#"
+        ENVIRONMENT SECTION
+        DEFAULT LABELS FROM "ops/labels.yml"
+        SERVICES SECTION
+        SERVICE api
+        IMAGE-ID "api:latest"
+        END SERVICE
+    "#;
+
+    #[test]
+    fn test_labels_are_read_relative_to_the_base_dir() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("ops")).unwrap();
+        fs::write(
+            temp_dir.path().join("ops/labels.yml"),
+            "com.acme.team: payments\ncom.acme.tier: 2\norg.opencontainers.image.licenses: MIT\n",
+        )
+        .unwrap();
+
+        let mut athena_file = parse_athena_file(STACK).unwrap();
+        load_default_labels(&mut athena_file, temp_dir.path()).unwrap();
+        let labels = athena_file.get_default_labels().unwrap();
+        assert_eq!(labels["com.acme.team"], "payments");
+        assert_eq!(labels["com.acme.tier"], "2");
+        assert_eq!(labels.len(), 3);
+    }
+
+    #[test]
+    fn test_missing_or_malformed_files_are_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut athena_file = parse_athena_file(STACK).unwrap();
+        let error = load_default_labels(&mut athena_file, temp_dir.path()).unwrap_err().to_string();
+        assert!(error.contains("Cannot read DEFAULT LABELS FROM"), "{error}");
+
+        assert!(parse_labels("- a\n- b\n").is_err());
+        assert!(parse_labels("team:\n  name: payments\n").unwrap_err().contains("label 'team'"));
+        assert!(parse_labels("").unwrap().is_empty());
+    }
+}
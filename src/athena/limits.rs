@@ -0,0 +1,190 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedValidationError};
+use crate::athena::parser::ast::AthenaFile;
+
+/// Project config file holding a `[limits]` table, read from the working directory
+pub const CONFIG_FILE: &str = "athena.toml";
+
+/// Guardrails against runaway input, e.g. a templated .ath that expands to
+/// hundreds of services. The defaults are far above any hand-written stack.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Limits {
+    /// Services in the merged file
+    pub services: usize,
+    /// ENV-VARIABLE entries across all services
+    pub env_vars: usize,
+    /// Size of the generated output in bytes
+    pub output_bytes: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            services: 500,
+            env_vars: 10_000,
+            output_bytes: 10 * 1024 * 1024,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    limits: Limits,
+}
+
+impl Limits {
+    const KEYS: &'static [&'static str] = &["services", "env_vars", "output_bytes"];
+
+    /// Defaults overridden by the `[limits]` table of `config`, if the file exists
+    pub fn load(config: &Path) -> AthenaResult<Self> {
+        let content = match fs::read_to_string(config) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(AthenaError::IoError(e)),
+        };
+
+        toml::from_str::<ConfigFile>(&content)
+            .map(|config| config.limits)
+            .map_err(|e| {
+                AthenaError::config_error(format!(
+                    "Invalid {}: {}",
+                    config.display(),
+                    e.message()
+                ))
+            })
+    }
+
+    /// Apply a `key=value` override as given to `--limit`
+    pub fn apply_override(&mut self, spec: &str) -> AthenaResult<()> {
+        let invalid = |message: String| {
+            AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(message).with_suggestion(format!(
+                    "Use --limit <key>=<count> with one of: {}",
+                    Self::KEYS.join(", ")
+                )),
+            )
+        };
+
+        let Some((key, value)) = spec.split_once('=') else {
+            return Err(invalid(format!("Invalid limit '{spec}': expected key=value")));
+        };
+        let value: usize = value
+            .trim()
+            .parse()
+            .map_err(|_| invalid(format!("Invalid limit '{spec}': '{value}' is not a count")))?;
+
+        match key.trim() {
+            "services" => self.services = value,
+            "env_vars" => self.env_vars = value,
+            "output_bytes" => self.output_bytes = value,
+            other => return Err(invalid(format!("Unknown limit '{other}'"))),
+        }
+        Ok(())
+    }
+
+    /// Check the parsed file; runs before generation
+    pub fn check(&self, athena_file: &AthenaFile) -> AthenaResult<()> {
+        let services = &athena_file.services.services;
+        check_count("services", services.len(), self.services, "service")?;
+
+        let env_vars = services.iter().map(|s| s.environment.len()).sum();
+        check_count("env_vars", env_vars, self.env_vars, "environment variable")
+    }
+
+    /// Check the generated output before it is written
+    pub fn check_output_size(&self, bytes: usize) -> AthenaResult<()> {
+        check_count("output_bytes", bytes, self.output_bytes, "byte of output")
+    }
+}
+
+fn check_count(key: &str, actual: usize, limit: usize, noun: &str) -> AthenaResult<()> {
+    if actual <= limit {
+        return Ok(());
+    }
+
+    Err(AthenaError::validation_error_enhanced(
+        EnhancedValidationError::new(format!(
+            "{actual} {noun}s exceed the limit of {limit} ({key})"
+        ))
+        .with_suggestion(format!(
+            "Raise it with --limit {key}={actual} or in the [limits] table of {CONFIG_FILE}, or pass --no-limits"
+        )),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::parse_athena_file;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_overrides() {
+        let mut limits = Limits::default();
+        limits.apply_override("services=2").unwrap();
+        limits.apply_override(" env_vars = 5").unwrap();
+        assert_eq!(limits.services, 2);
+        assert_eq!(limits.env_vars, 5);
+
+        let err = limits.apply_override("service=2").unwrap_err().to_string();
+        assert!(err.contains("Unknown limit 'service'"));
+        assert!(limits.apply_override("services=lots").is_err());
+        assert!(limits.apply_override("services").is_err());
+    }
+
+    #[test]
+    fn test_check_counts_services_and_env_vars() {
+        let athena_file = parse_athena_file(
+            r#"
+            SERVICES SECTION
+            SERVICE api
+            ENV-VARIABLE {{A}}
+            ENV-VARIABLE {{B}}
+            END SERVICE
+            SERVICE worker
+            ENV-VARIABLE {{C}}
+            END SERVICE
+        "#,
+        )
+        .unwrap();
+
+        assert!(Limits::default().check(&athena_file).is_ok());
+
+        let limits = Limits { services: 1, ..Limits::default() };
+        let err = limits.check(&athena_file).unwrap_err().to_string();
+        assert!(err.contains("2 services exceed the limit of 1 (services)"));
+        assert!(err.contains("--limit services=2"));
+
+        let limits = Limits { env_vars: 2, ..Limits::default() };
+        let err = limits.check(&athena_file).unwrap_err().to_string();
+        assert!(err.contains("3 environment variables exceed the limit of 2"));
+
+        let limits = Limits { output_bytes: 10, ..Limits::default() };
+        assert!(limits.check_output_size(10).is_ok());
+        assert!(limits.check_output_size(11).is_err());
+    }
+
+    #[test]
+    fn test_load_config_file() {
+        assert_eq!(
+            Limits::load(Path::new("does-not-exist.toml")).unwrap(),
+            Limits::default()
+        );
+
+        let mut config = NamedTempFile::new().unwrap();
+        writeln!(config, "[limits]\nservices = 1000").unwrap();
+        let limits = Limits::load(config.path()).unwrap();
+        assert_eq!(limits.services, 1000);
+        assert_eq!(limits.env_vars, Limits::default().env_vars);
+
+        let mut config = NamedTempFile::new().unwrap();
+        writeln!(config, "[limits]\nservice = 1000").unwrap();
+        assert!(Limits::load(config.path()).is_err());
+    }
+}
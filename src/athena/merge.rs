@@ -0,0 +1,615 @@
+//! Layering of several .ath files, as in `athena build base.ath prod.ath`.
+//!
+//! Later files override earlier ones: scalar directives replace, BUILD-ARGS,
+//! secrets and labels merge key by key, and list directives append.
+//! Identical port mappings and dependencies are kept once, and a later volume
+//! or environment entry replaces the one for the same container path or name.
+//! `ENVIRONMENT "name"` blocks are applied with the same rules, after all files.
+
+use std::collections::HashMap;
+
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedValidationError};
+use crate::athena::parser::ast::{
+    AthenaFile, DeploymentSection, EnvironmentOverride, EnvironmentSection, Service, SwarmConfig,
+};
+use crate::athena::parser::parse_athena_file;
+use crate::athena::presets::expand_presets;
+
+/// One input file: the path shown in diagnostics and its contents
+#[derive(Debug, Clone, Copy)]
+pub struct SourceFile<'a> {
+    pub path: &'a str,
+    pub content: &'a str,
+}
+
+/// Parse every file and fold them into one, before any validation runs, so
+/// checks such as dependency cycles see the final service graph
+pub fn parse_and_merge(sources: &[SourceFile]) -> AthenaResult<AthenaFile> {
+    let mut merged: Option<AthenaFile> = None;
+    // Which file last set IMAGE-ID / BUILD-ARGS for each service
+    let mut image_origin: HashMap<String, usize> = HashMap::new();
+    let mut build_origin: HashMap<String, usize> = HashMap::new();
+
+    for (index, source) in sources.iter().enumerate() {
+        let mut athena_file = parse_athena_file(source.content).map_err(|e| match e {
+            AthenaError::ParseError(parse) if sources.len() > 1 => {
+                AthenaError::parse_error_enhanced(parse.with_file_path(source.path.to_string()))
+            }
+            // Duplicate declarations name lines, so say which file they are in
+            AthenaError::ValidationError(mut validation) if sources.len() > 1 => {
+                validation.message = format!("{}: {}", source.path, validation.message);
+                AthenaError::ValidationError(validation)
+            }
+            other => other,
+        })?;
+        if sources.len() > 1 {
+            for suppression in &mut athena_file.suppressions {
+                suppression.file = Some(source.path.to_string());
+            }
+        }
+        record_file(&mut athena_file, source.path);
+
+        for service in &athena_file.services.services {
+            if let Some(base) = merged.as_ref().and_then(|m| find_service(m, &service.name)) {
+                check_image_build_conflict(base, service, index, sources, &image_origin, &build_origin)?;
+            }
+            if service.image.is_some() {
+                image_origin.insert(service.name.clone(), index);
+            }
+            if service.build_args.is_some() {
+                build_origin.insert(service.name.clone(), index);
+            }
+        }
+
+        merged = Some(match merged {
+            None => athena_file,
+            Some(mut base) => {
+                merge_file(&mut base, athena_file);
+                base
+            }
+        });
+    }
+
+    Ok(merged.unwrap_or_default())
+}
+
+/// Overlay the services of `ENVIRONMENT name` onto the file, as if they came
+/// from one more input file. The file keeps no environments afterwards.
+pub fn apply_environment(athena_file: &mut AthenaFile, name: &str) -> AthenaResult<()> {
+    let environments = std::mem::take(&mut athena_file.environments);
+    let Some(environment) = environments.iter().find(|e| e.name == name) else {
+        let names: Vec<&str> = environments.iter().map(|e| e.name.as_str()).collect();
+        let suggestion = if names.is_empty() {
+            "Declare one with ENVIRONMENT \"name\" ... END ENVIRONMENT after the SERVICES SECTION".to_string()
+        } else {
+            format!("Declared environments: {}", names.join(", "))
+        };
+        return Err(AthenaError::validation_error_enhanced(
+            EnhancedValidationError::new(format!("Unknown environment '{name}'")).with_suggestion(suggestion),
+        ));
+    };
+
+    let mut overlay = AthenaFile::new();
+    overlay.services.services = environment.services.clone();
+    expand_presets(&mut overlay);
+    merge_file(athena_file, overlay);
+    Ok(())
+}
+
+/// Name the file in every declaration's source location
+fn record_file(athena_file: &mut AthenaFile, path: &str) {
+    let environment_services = athena_file
+        .environments
+        .iter_mut()
+        .flat_map(|e| e.services.iter_mut());
+    let service_locations = athena_file
+        .services
+        .services
+        .iter_mut()
+        .chain(environment_services)
+        .map(|s| &mut s.location);
+
+    let (volumes, networks, default_labels) = match athena_file.environment.as_mut() {
+        Some(environment) => (
+            environment.volumes.as_mut_slice(),
+            environment.networks.as_mut_slice(),
+            environment.default_labels.as_mut(),
+        ),
+        None => (Default::default(), Default::default(), None),
+    };
+    let locations = service_locations
+        .chain(volumes.iter_mut().map(|v| &mut v.location))
+        .chain(networks.iter_mut().map(|n| &mut n.location))
+        .chain(default_labels.map(|d| &mut d.location));
+
+    for location in locations.flatten() {
+        location.file = Some(path.to_string());
+    }
+}
+
+fn find_service<'a>(athena_file: &'a AthenaFile, name: &str) -> Option<&'a Service> {
+    athena_file.services.services.iter().find(|s| s.name == name)
+}
+
+/// BUILD-ARGS wins over IMAGE-ID in generated output, so an image from one
+/// file and a build from another would silently discard one of them
+fn check_image_build_conflict(
+    base: &Service,
+    overlay: &Service,
+    index: usize,
+    sources: &[SourceFile],
+    image_origin: &HashMap<String, usize>,
+    build_origin: &HashMap<String, usize>,
+) -> AthenaResult<()> {
+    let image_only = |s: &Service| s.image.is_some() && s.build_args.is_none();
+    let build_only = |s: &Service| s.build_args.is_some() && s.image.is_none();
+
+    let (image_file, build_file) = if image_only(base) && build_only(overlay) {
+        (image_origin.get(&base.name).copied(), Some(index))
+    } else if build_only(base) && image_only(overlay) {
+        (Some(index), build_origin.get(&base.name).copied())
+    } else {
+        return Ok(());
+    };
+    let (Some(image_file), Some(build_file)) = (image_file, build_file) else {
+        return Ok(());
+    };
+
+    let locate = |file: usize, keyword: &str| {
+        let source = &sources[file];
+        match directive_line(source.content, &base.name, keyword) {
+            Some(line) => format!("{}:{line}", source.path),
+            None => source.path.to_string(),
+        }
+    };
+
+    Err(AthenaError::validation_error_enhanced(
+        EnhancedValidationError::new(format!(
+            "Service '{}' uses IMAGE-ID at {} but is built with BUILD-ARGS at {}",
+            base.name,
+            locate(image_file, "IMAGE-ID"),
+            locate(build_file, "BUILD-ARGS")
+        ))
+        .with_suggestion(
+            "Set both IMAGE-ID and BUILD-ARGS in the same file to build and tag the image, or keep only one of them"
+                .to_string(),
+        )
+        .with_services(vec![base.name.clone()]),
+    ))
+}
+
+/// 1-based line of the first `keyword` directive inside `SERVICE <name>`
+fn directive_line(content: &str, service: &str, keyword: &str) -> Option<usize> {
+    let mut in_service = false;
+
+    for (index, line) in content.lines().enumerate() {
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("SERVICE"), Some(name)) => in_service = name == service,
+            (Some("END"), Some("SERVICE")) => in_service = false,
+            (Some(word), _) if in_service && word == keyword => return Some(index + 1),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn merge_file(base: &mut AthenaFile, overlay: AthenaFile) {
+    if let Some(deployment) = overlay.deployment {
+        let version_id = deployment
+            .version_id
+            .or_else(|| base.deployment.take().and_then(|d| d.version_id));
+        base.deployment = Some(DeploymentSection {
+            deployment_id: deployment.deployment_id,
+            version_id,
+        });
+    }
+
+    if let Some(environment) = overlay.environment {
+        match base.environment.as_mut() {
+            Some(existing) => merge_environment(existing, environment),
+            None => base.environment = Some(environment),
+        }
+    }
+
+    base.suppressions.extend(overlay.suppressions);
+    for environment in overlay.environments {
+        match base.environments.iter_mut().find(|e| e.name == environment.name) {
+            Some(existing) => merge_environment_override(existing, environment),
+            None => base.environments.push(environment),
+        }
+    }
+
+    for service in overlay.services.services {
+        match base.services.services.iter_mut().find(|s| s.name == service.name) {
+            Some(existing) => merge_service(existing, service),
+            None => base.services.services.push(service),
+        }
+    }
+}
+
+fn merge_environment_override(base: &mut EnvironmentOverride, overlay: EnvironmentOverride) {
+    for service in overlay.services {
+        match base.services.iter_mut().find(|s| s.name == service.name) {
+            Some(existing) => merge_service(existing, service),
+            None => base.services.push(service),
+        }
+    }
+}
+
+fn merge_environment(base: &mut EnvironmentSection, overlay: EnvironmentSection) {
+    upsert_by(&mut base.networks, overlay.networks, |n| n.name.clone());
+    upsert_by(&mut base.volumes, overlay.volumes, |v| v.name.clone());
+    base.secrets.extend(overlay.secrets);
+    upsert_by(&mut base.dns, overlay.dns, Clone::clone);
+    upsert_by(&mut base.externals, overlay.externals, |e| e.name.clone());
+    if overlay.default_labels.is_some() {
+        base.default_labels = overlay.default_labels;
+    }
+}
+
+fn merge_service(base: &mut Service, overlay: Service) {
+    macro_rules! replace_if_set {
+        ($($field:ident),* $(,)?) => {
+            $(if overlay.$field.is_some() {
+                base.$field = overlay.$field;
+            })*
+        };
+    }
+    replace_if_set!(
+        image,
+        command,
+        entrypoint,
+        working_dir,
+        user,
+        init,
+        tty,
+        stdin_open,
+        stop_grace_period,
+        stop_signal,
+        health_check,
+        restart,
+        resources,
+        reservations,
+        hostname,
+        expose_via,
+        container_name,
+        pull_policy,
+        platform,
+        kind,
+        pid,
+        ipc,
+        network_mode,
+        preset,
+    );
+
+    upsert_by(&mut base.ports, overlay.ports, |p| {
+        (p.host_port, p.host_port_end, p.container_port, p.protocol.clone())
+    });
+    upsert_by(&mut base.volumes, overlay.volumes, |v| v.container_path.clone());
+    upsert_by(&mut base.environment, overlay.environment, |e| e.key().to_string());
+    upsert_by(&mut base.depends_on, overlay.depends_on, |d| d.service.clone());
+    upsert_by(&mut base.watch, overlay.watch, |w| (w.action, w.path.clone()));
+    upsert_by(&mut base.dns, overlay.dns, Clone::clone);
+    upsert_by(&mut base.profiles, overlay.profiles, Clone::clone);
+    upsert_by(&mut base.uses, overlay.uses, Clone::clone);
+    upsert_by(&mut base.networks, overlay.networks, |n| n.network.clone());
+    upsert_by(&mut base.extra_hosts, overlay.extra_hosts, |h| h.hostname.clone());
+
+    if let Some(build_args) = overlay.build_args {
+        base.build_args.get_or_insert_with(HashMap::new).extend(build_args);
+    }
+    if let Some(swarm) = overlay.swarm_config {
+        merge_swarm(base.swarm_config.get_or_insert_with(SwarmConfig::default), swarm);
+    }
+    base.labels.extend(overlay.labels);
+    base.allow_unresolved_hosts |= overlay.allow_unresolved_hosts;
+    base.allow_docker_socket |= overlay.allow_docker_socket;
+    upsert_by(&mut base.allowed_secrets, overlay.allowed_secrets, Clone::clone);
+    base.env_lines.extend(overlay.env_lines);
+}
+
+fn merge_swarm(base: &mut SwarmConfig, overlay: SwarmConfig) {
+    if overlay.mode.is_some() {
+        base.mode = overlay.mode;
+    }
+    if overlay.replicas.is_some() {
+        base.replicas = overlay.replicas;
+    }
+    if overlay.update_config.is_some() {
+        base.update_config = overlay.update_config;
+    }
+    if let Some(labels) = overlay.labels {
+        base.labels.get_or_insert_with(HashMap::new).extend(labels);
+    }
+}
+
+/// Append `overlay` entries, replacing an existing entry with the same key in place
+fn upsert_by<T, K: PartialEq>(base: &mut Vec<T>, overlay: Vec<T>, key: impl Fn(&T) -> K) {
+    for entry in overlay {
+        let entry_key = key(&entry);
+        match base.iter_mut().find(|existing| key(existing) == entry_key) {
+            Some(existing) => *existing = entry,
+            None => base.push(entry),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#"
+DEPLOYMENT-ID SHOP
+VERSION-ID 1.0.0
+
+SERVICES SECTION
+
+SERVICE api
+IMAGE-ID "shop/api:latest"
+PORT-MAPPING 8080 TO 80
+ENV-VARIABLE "LOG_LEVEL=debug"
+ENV-VARIABLE {{DATABASE_URL}}
+END SERVICE
+
+SERVICE db
+IMAGE-ID "postgres:16"
+END SERVICE
+"#;
+
+    fn merge(files: &[(&'static str, &'static str)]) -> AthenaResult<AthenaFile> {
+        let sources: Vec<SourceFile> = files
+            .iter()
+            .map(|&(path, content)| SourceFile { path, content })
+            .collect();
+        parse_and_merge(&sources)
+    }
+
+    #[test]
+    fn test_later_files_override_earlier_ones() {
+        let prod = r#"
+DEPLOYMENT-ID SHOP_PROD
+
+SERVICES SECTION
+
+SERVICE api
+IMAGE-ID "shop/api:1.4.2"
+PORT-MAPPING 8080 TO 80
+PORT-MAPPING 8443 TO 443
+ENV-VARIABLE "LOG_LEVEL=warn"
+REPLICAS 3
+END SERVICE
+
+SERVICE cache
+IMAGE-ID "redis:7"
+END SERVICE
+"#;
+        let merged = merge(&[("base.ath", BASE), ("prod.ath", prod)]).unwrap();
+
+        let deployment = merged.deployment.as_ref().unwrap();
+        assert_eq!(deployment.deployment_id, "SHOP_PROD");
+        assert_eq!(deployment.version_id.as_deref(), Some("1.0.0"));
+
+        let names: Vec<&str> = merged.services.services.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["api", "db", "cache"]);
+
+        let api = &merged.services.services[0];
+        assert_eq!(api.image.as_deref(), Some("shop/api:1.4.2"));
+        assert_eq!(api.ports.len(), 2);
+        assert_eq!(api.environment.len(), 2);
+        assert!(api.environment.iter().any(|e| e.key() == "LOG_LEVEL" && matches!(
+            e,
+            crate::athena::parser::ast::EnvironmentVariable::Literal(v) if v == "LOG_LEVEL=warn"
+        )));
+        assert_eq!(api.swarm_config.as_ref().and_then(|s| s.replicas), Some(3));
+
+        // Services are located where first declared
+        let location = |index: usize| merged.services.services[index].location.as_ref().unwrap().to_string();
+        assert_eq!(location(0), "base.ath:7");
+        assert_eq!(location(2), "prod.ath:14");
+    }
+
+    #[test]
+    fn test_every_service_field_is_carried_over() {
+        let overlay = r#"
+SERVICES SECTION
+SERVICE api
+IMAGE-ID "shop/api:2.0"
+PORT-MAPPING 8080 TO 80
+ENV-VARIABLE "API_TOKEN=abc123" // athena:allow-secret
+COMMAND ["serve"]
+ENTRYPOINT ["/entrypoint.sh"]
+WORKDIR "/app"
+USER "1000:1000"
+INIT TRUE
+TTY TRUE
+STDIN-OPEN TRUE
+STOP-GRACE-PERIOD 30s
+STOP-SIGNAL SIGQUIT
+VOLUME-MAPPING "./data" TO "/data"
+DEPENDS-ON db
+HEALTH-CHECK "curl -f http://localhost/health"
+RESTART-POLICY always
+RESOURCE-LIMITS CPU "0.5" MEMORY "512M"
+RESERVE MEMORY 256M
+REPLICAS 2
+DNS "10.0.0.2"
+EXTRA-HOST "registry.internal" "10.0.0.9"
+HOSTNAME "api-1"
+EXPOSE-VIA nginx PATH "/api/"
+WATCH SYNC "./src" TO "/app/src"
+CONTAINER-NAME "shop-api"
+PULL-POLICY always
+PLATFORM "linux/arm64"
+KIND job
+PROFILE tools
+PID host
+IPC shareable
+NETWORK-MODE host
+PRESET redis
+LABEL com.acme.owner="payments"
+ALLOW UNRESOLVED HOSTS
+ALLOW DOCKER SOCKET
+USES billing
+ON NETWORK backend
+END SERVICE
+"#;
+        let mut overlay = parse_athena_file(overlay).unwrap().services.services.remove(0);
+        overlay.build_args = Some(HashMap::from([("VERSION".to_string(), "2.0".to_string())]));
+
+        // Naming every field, so a new one fails to compile until it is covered here
+        let Service {
+            name: _,
+            image,
+            ports,
+            environment,
+            command,
+            entrypoint,
+            working_dir,
+            user,
+            init,
+            tty,
+            stdin_open,
+            stop_grace_period,
+            stop_signal,
+            volumes,
+            depends_on,
+            health_check,
+            restart,
+            resources,
+            reservations,
+            build_args,
+            swarm_config,
+            dns,
+            extra_hosts,
+            hostname,
+            expose_via,
+            watch,
+            container_name,
+            pull_policy,
+            platform,
+            kind,
+            profiles,
+            pid,
+            ipc,
+            network_mode,
+            preset,
+            labels,
+            allow_unresolved_hosts,
+            allow_docker_socket,
+            allowed_secrets,
+            env_lines,
+            uses,
+            networks,
+            location: _,
+        } = &overlay;
+        let set = [
+            image.is_some(),
+            !ports.is_empty(),
+            !environment.is_empty(),
+            command.is_some(),
+            entrypoint.is_some(),
+            working_dir.is_some(),
+            user.is_some(),
+            init.is_some(),
+            tty.is_some(),
+            stdin_open.is_some(),
+            stop_grace_period.is_some(),
+            stop_signal.is_some(),
+            !volumes.is_empty(),
+            !depends_on.is_empty(),
+            health_check.is_some(),
+            restart.is_some(),
+            resources.is_some(),
+            reservations.is_some(),
+            build_args.is_some(),
+            swarm_config.is_some(),
+            !dns.is_empty(),
+            !extra_hosts.is_empty(),
+            hostname.is_some(),
+            expose_via.is_some(),
+            !watch.is_empty(),
+            container_name.is_some(),
+            pull_policy.is_some(),
+            platform.is_some(),
+            kind.is_some(),
+            !profiles.is_empty(),
+            pid.is_some(),
+            ipc.is_some(),
+            network_mode.is_some(),
+            preset.is_some(),
+            !labels.is_empty(),
+            *allow_unresolved_hosts,
+            *allow_docker_socket,
+            !allowed_secrets.is_empty(),
+            !env_lines.is_empty(),
+            !uses.is_empty(),
+            !networks.is_empty(),
+        ];
+        assert_eq!(set.iter().position(|set| !set), None, "the overlay leaves a field unset");
+
+        let mut merged = Service::new("api".to_string());
+        merged.location = overlay.location.clone();
+        merge_service(&mut merged, overlay.clone());
+        assert_eq!(
+            serde_json::to_value(&merged).unwrap(),
+            serde_json::to_value(&overlay).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_image_and_build_in_different_files_conflict() {
+        let build = "SERVICES SECTION\n\nSERVICE api\nBUILD-ARGS NODE_ENV=\"production\"\nEND SERVICE\n";
+        let err = merge(&[("base.ath", BASE), ("build.ath", build)])
+            .unwrap_err()
+            .to_string();
+
+        assert!(err.contains("Service 'api' uses IMAGE-ID at base.ath:8"), "{err}");
+        assert!(err.contains("BUILD-ARGS at build.ath:4"), "{err}");
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_file() {
+        let err = merge(&[("base.ath", BASE), ("broken.ath", "SERVICES SECTION\nSERVICE\n")])
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("broken.ath"), "{err}");
+    }
+
+    #[test]
+    fn test_environment_overrides_use_file_merge_rules() {
+        let staging = r#"
+ENVIRONMENT "staging"
+SERVICE api
+IMAGE-ID "shop/api:rc"
+ENV-VARIABLE "LOG_LEVEL=info"
+REPLICAS 2
+END SERVICE
+SERVICE mailhog
+IMAGE-ID mailhog/mailhog
+END SERVICE
+END ENVIRONMENT
+"#;
+        let content = format!("{BASE}{staging}");
+        let sources = [SourceFile { path: "stack.ath", content: &content }];
+
+        let mut athena_file = parse_and_merge(&sources).unwrap();
+        assert_eq!(athena_file.services.services[0].image.as_deref(), Some("shop/api:latest"));
+
+        apply_environment(&mut athena_file, "staging").unwrap();
+        assert!(athena_file.environments.is_empty());
+        let api = &athena_file.services.services[0];
+        assert_eq!(api.image.as_deref(), Some("shop/api:rc"));
+        assert_eq!(api.swarm_config.as_ref().and_then(|s| s.replicas), Some(2));
+        assert_eq!(api.ports.len(), 1);
+        let env: Vec<String> = api.environment.iter().map(|e| format!("{e:?}")).collect();
+        assert!(env[0].contains("LOG_LEVEL=info"), "{env:?}");
+        assert_eq!(athena_file.services.services[2].name, "mailhog");
+
+        let mut athena_file = parse_and_merge(&sources).unwrap();
+        let err = apply_environment(&mut athena_file, "prod").unwrap_err().to_string();
+        assert!(err.contains("Unknown environment 'prod'"));
+        assert!(err.contains("Declared environments: staging"));
+    }
+}
@@ -1,8 +1,19 @@
 pub mod error;
 pub mod parser;
 pub mod generator;
+pub mod dependencies;
 pub mod dockerfile;
+pub mod edit;
+pub mod env_trace;
+pub mod labels;
+pub mod limits;
+pub mod merge;
+pub mod mounts;
+pub mod presets;
+pub mod provenance;
+pub mod report;
+pub mod warnings;
 
 pub use error::{AthenaError, AthenaResult};
 pub use parser::parse_athena_file;
-pub use generator::generate_docker_compose;
\ No newline at end of file
+pub use generator::{generate, generate_docker_compose, GeneratorOptions};
\ No newline at end of file
@@ -0,0 +1,369 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::athena::parser::ast::{AthenaFile, VolumeMapping};
+
+/// What kind of host source a bind mount is expected to have.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ExpectedSource {
+    File,
+    Directory,
+}
+
+/// Check that bind-mount sources exist on disk.
+///
+/// Relative host paths are resolved against `base_dir` (the directory of the
+/// .ath file). Named volumes, home-relative paths and absolute paths outside
+/// `base_dir` are skipped. Returns one warning per problematic mount.
+pub fn check_bind_mounts<P: AsRef<Path>>(athena_file: &AthenaFile, base_dir: P) -> Vec<String> {
+    let base_dir = base_dir.as_ref();
+    let canonical_base = base_dir
+        .canonicalize()
+        .unwrap_or_else(|_| base_dir.to_path_buf());
+
+    let mut warnings = Vec::new();
+
+    for service in &athena_file.services.services {
+        for volume in &service.volumes {
+            let Some(source) = resolve_bind_source(&volume.host_path, base_dir, &canonical_base)
+            else {
+                continue;
+            };
+
+            if let Some(warning) = check_source(&service.name, volume, &source) {
+                warnings.push(warning);
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Returns `true` when the host side of a mapping refers to a path rather than a named volume.
+pub fn is_bind_mount(host_path: &str) -> bool {
+    host_path.starts_with('.')
+        || host_path.starts_with('/')
+        || host_path.starts_with('~')
+        || host_path.contains('/')
+        || host_path.contains('\\')
+        || has_drive_letter(host_path)
+}
+
+/// Convert Windows separators to forward slashes, which Compose expects on every OS.
+///
+/// Applies to both sides of a mapping: `.\logs` becomes `./logs` and
+/// `C:\data` becomes `C:/data`.
+pub fn normalize_mount_path(path: &str) -> String {
+    path.replace('\\', "/")
+}
+
+/// Host paths of the Docker and containerd API sockets, including rootless
+/// daemons under `$XDG_RUNTIME_DIR` and Docker Desktop's Windows named pipe.
+/// Mounting one hands the container control of the host's containers.
+pub fn is_runtime_socket(host_path: &str) -> bool {
+    let path = normalize_mount_path(host_path);
+    let path = path.trim_end_matches('/');
+    path.ends_with("/docker.sock")
+        || path.ends_with("/containerd.sock")
+        || path.eq_ignore_ascii_case("//./pipe/docker_engine")
+}
+
+/// Windows absolute path such as `C:\...` or `C:/...`
+pub fn has_drive_letter(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/')
+}
+
+/// Rewrite relative bind mount sources, which are relative to the .ath file,
+/// to go through `root`, the path from the generated file's directory to the
+/// .ath file's: Compose and Terraform resolve them against the generated file.
+/// Returns whether anything changed.
+pub fn rebase_bind_mounts(athena_file: &mut AthenaFile, root: &Path) -> bool {
+    if root.as_os_str().is_empty() {
+        return false;
+    }
+
+    let mut changed = false;
+    for service in &mut athena_file.services.services {
+        for volume in &mut service.volumes {
+            let normalized = normalize_mount_path(&volume.host_path);
+            if !is_bind_mount(&normalized)
+                || normalized.starts_with('~')
+                || Path::new(&normalized).is_absolute()
+                || has_drive_letter(&normalized)
+            {
+                continue;
+            }
+            volume.host_path = rebase_relative_path(root, &normalized);
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// `to` relative to `from`, or `to` made absolute when the two share no root.
+/// Empty when both are the same directory.
+pub fn relative_dir(from: &Path, to: &Path) -> PathBuf {
+    let absolute = |dir: &Path| dir.canonicalize().or_else(|_| std::path::absolute(dir));
+    let (Ok(from), Ok(to)) = (absolute(from), absolute(to)) else {
+        return PathBuf::new();
+    };
+
+    let common = from.components().zip(to.components()).take_while(|(a, b)| a == b).count();
+    if common == 0 {
+        return to;
+    }
+    let mut relative = PathBuf::new();
+    for _ in from.components().skip(common) {
+        relative.push("..");
+    }
+    relative.extend(to.components().skip(common));
+    relative
+}
+
+/// `root` joined with the relative `path`, spelled the way Compose recognises
+/// a relative path: starting with `.`
+pub fn rebase_relative_path(root: &Path, path: &str) -> String {
+    let joined = root.join(path);
+    if joined.is_absolute() {
+        // Only when the output and the .ath file share no root, e.g. two drives
+        return normalize_mount_path(&joined.to_string_lossy());
+    }
+
+    let mut parts: Vec<&str> = Vec::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if parts.last().is_some_and(|last| *last != "..") => {
+                parts.pop();
+            }
+            component => parts.push(component.as_os_str().to_str().unwrap_or_default()),
+        }
+    }
+
+    match parts.first() {
+        None => ".".to_string(),
+        Some(&"..") => parts.join("/"),
+        Some(_) => format!("./{}", parts.join("/")),
+    }
+}
+
+/// Resolve the host path of a bind mount, or `None` if it should not be checked.
+fn resolve_bind_source(host_path: &str, base_dir: &Path, canonical_base: &Path) -> Option<PathBuf> {
+    if !is_bind_mount(host_path) || host_path.starts_with('~') {
+        return None;
+    }
+
+    let normalized = normalize_mount_path(host_path);
+    let path = Path::new(&normalized);
+    if path.is_absolute() || has_drive_letter(&normalized) {
+        // Only absolute paths that live inside the project are worth checking
+        return path.starts_with(canonical_base).then(|| path.to_path_buf());
+    }
+
+    Some(base_dir.join(path))
+}
+
+fn check_source(service_name: &str, volume: &VolumeMapping, source: &Path) -> Option<String> {
+    let expected = expected_source_kind(&volume.container_path);
+
+    if !source.exists() {
+        let hint = match expected {
+            ExpectedSource::File => {
+                "Docker will create an empty directory where a file is expected; create the file before starting the stack"
+            }
+            ExpectedSource::Directory => {
+                "Docker will create an empty directory; create it or fix the path"
+            }
+        };
+        let kind = match expected {
+            ExpectedSource::File => "file",
+            ExpectedSource::Directory => "directory",
+        };
+        return Some(format!(
+            "Service '{service_name}': bind mount source {kind} '{}' does not exist (mounted at '{}')\n{hint}",
+            volume.host_path, volume.container_path
+        ));
+    }
+
+    if expected == ExpectedSource::File && source.is_dir() {
+        return Some(format!(
+            "Service '{service_name}': bind mount source '{}' is a directory but target '{}' looks like a file",
+            volume.host_path, volume.container_path
+        ));
+    }
+
+    None
+}
+
+/// A container path whose last component has an extension (e.g. `nginx.conf`) is expected to be a file.
+fn expected_source_kind(container_path: &str) -> ExpectedSource {
+    let has_extension = Path::new(container_path)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| {
+            name.rfind('.')
+                .is_some_and(|dot| dot > 0 && dot < name.len() - 1)
+        });
+
+    if has_extension {
+        ExpectedSource::File
+    } else {
+        ExpectedSource::Directory
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::ast::Service;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn athena_file_with_volume(host_path: &str, container_path: &str) -> AthenaFile {
+        let mut service = Service::new("app".to_string());
+        service.volumes.push(VolumeMapping {
+            host_path: host_path.to_string(),
+            container_path: container_path.to_string(),
+            options: Vec::new(),
+        });
+
+        let mut athena_file = AthenaFile::new();
+        athena_file.services.services.push(service);
+        athena_file
+    }
+
+    #[test]
+    fn test_is_bind_mount() {
+        assert!(is_bind_mount("./data"));
+        assert!(is_bind_mount("../shared"));
+        assert!(is_bind_mount("/var/run/docker.sock"));
+        assert!(is_bind_mount("config/app"));
+        assert!(is_bind_mount(".\\logs"));
+        assert!(is_bind_mount("C:\\data"));
+        assert!(is_bind_mount("D:/data"));
+        assert!(!is_bind_mount("postgres_data"));
+    }
+
+    #[test]
+    fn test_is_runtime_socket() {
+        assert!(is_runtime_socket("/var/run/docker.sock"));
+        assert!(is_runtime_socket("/run/docker.sock/"));
+        assert!(is_runtime_socket("${XDG_RUNTIME_DIR}/docker.sock"));
+        assert!(is_runtime_socket("/run/containerd/containerd.sock"));
+        assert!(is_runtime_socket("\\\\.\\pipe\\docker_engine"));
+        assert!(!is_runtime_socket("/var/run/docker"));
+        assert!(!is_runtime_socket("./my-docker.sock.bak"));
+        assert!(!is_runtime_socket("docker_sock"));
+    }
+
+    #[test]
+    fn test_normalize_mount_path() {
+        assert_eq!(normalize_mount_path(".\\logs"), "./logs");
+        assert_eq!(normalize_mount_path("..\\shared\\cache"), "../shared/cache");
+        assert_eq!(normalize_mount_path("C:\\Users\\dev\\data"), "C:/Users/dev/data");
+        assert_eq!(normalize_mount_path("\\app\\logs"), "/app/logs");
+        assert_eq!(normalize_mount_path("./already/fine"), "./already/fine");
+    }
+
+    #[test]
+    fn test_windows_relative_path_is_resolved() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("logs")).unwrap();
+
+        let athena_file = athena_file_with_volume(".\\logs", "/app/logs");
+        assert!(check_bind_mounts(&athena_file, temp_dir.path()).is_empty());
+
+        let athena_file = athena_file_with_volume("C:\\data", "/data");
+        assert!(check_bind_mounts(&athena_file, temp_dir.path()).is_empty());
+    }
+
+    #[test]
+    fn test_expected_source_kind() {
+        assert_eq!(expected_source_kind("/etc/nginx/nginx.conf"), ExpectedSource::File);
+        assert_eq!(expected_source_kind("/etc/app"), ExpectedSource::Directory);
+        assert_eq!(expected_source_kind("/home/app/.cache"), ExpectedSource::Directory);
+    }
+
+    #[test]
+    fn test_missing_relative_directory_warns() {
+        let temp_dir = TempDir::new().unwrap();
+        let athena_file = athena_file_with_volume("./config", "/etc/app");
+
+        let warnings = check_bind_mounts(&athena_file, temp_dir.path());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("directory './config' does not exist"));
+    }
+
+    #[test]
+    fn test_missing_file_target_warns_as_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let athena_file = athena_file_with_volume("./nginx.conf", "/etc/nginx/nginx.conf");
+
+        let warnings = check_bind_mounts(&athena_file, temp_dir.path());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("file './nginx.conf' does not exist"));
+    }
+
+    #[test]
+    fn test_directory_source_for_file_target_warns() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("nginx.conf")).unwrap();
+        let athena_file = athena_file_with_volume("./nginx.conf", "/etc/nginx/nginx.conf");
+
+        let warnings = check_bind_mounts(&athena_file, temp_dir.path());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("is a directory"));
+    }
+
+    #[test]
+    fn test_existing_sources_and_skipped_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("config")).unwrap();
+
+        for (host, container) in [
+            ("./config", "/etc/app"),
+            ("postgres_data", "/var/lib/postgresql/data"),
+            ("/definitely/not/in/repo", "/data"),
+            ("~/cache", "/cache"),
+        ] {
+            let athena_file = athena_file_with_volume(host, container);
+            assert!(
+                check_bind_mounts(&athena_file, temp_dir.path()).is_empty(),
+                "{host} should not produce a warning"
+            );
+        }
+    }
+
+    #[test]
+    fn test_relative_sources_are_rebased_on_the_output_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("sub")).unwrap();
+        fs::create_dir_all(temp_dir.path().join("out")).unwrap();
+        let root = relative_dir(&temp_dir.path().join("out"), &temp_dir.path().join("sub"));
+        assert_eq!(root, Path::new("../sub"));
+        assert_eq!(relative_dir(temp_dir.path(), &temp_dir.path().join("sub")), Path::new("sub"));
+        assert_eq!(relative_dir(temp_dir.path(), temp_dir.path()), Path::new(""));
+
+        assert_eq!(rebase_relative_path(&root, "."), "../sub");
+        assert_eq!(rebase_relative_path(Path::new("sub"), "./html"), "./sub/html");
+        assert_eq!(rebase_relative_path(Path::new("sub"), "../shared"), "./shared");
+        assert_eq!(rebase_relative_path(&root, "../../cache"), "../../cache");
+
+        for (host, rebased) in [
+            ("./html", "../sub/html"),
+            ("config/app", "../sub/config/app"),
+            (".\\logs", "../sub/logs"),
+            ("postgres_data", "postgres_data"),
+            ("/etc/hosts", "/etc/hosts"),
+            ("~/cache", "~/cache"),
+            ("C:\\data", "C:\\data"),
+        ] {
+            let mut athena_file = athena_file_with_volume(host, "/data");
+            rebase_bind_mounts(&mut athena_file, &root);
+            assert_eq!(athena_file.services.services[0].volumes[0].host_path, rebased, "{host}");
+        }
+    }
+}
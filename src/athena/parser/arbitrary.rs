@@ -0,0 +1,207 @@
+//! Proptest strategies for random Athena files that pass validation.
+//!
+//! Services get unique names and host ports, and only depend on services
+//! declared before them, so the dependency graph can't have cycles. Each
+//! service draws a random subset of the other directives.
+
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+use proptest::sample::Index;
+
+use super::ast::{
+    AthenaFile, ByteSize, ContainerCommand, Dependency, DependencyCondition, DeploymentSection,
+    EnvironmentVariable, PortMapping, Protocol, ResourceLimits, RestartPolicy, Service,
+    ServicesSection, SwarmConfig, VolumeMapping,
+};
+
+/// Largest number of services in a generated file
+pub const MAX_SERVICES: usize = 8;
+
+/// A whole file: an optional DEPLOYMENT-ID and 1 to [`MAX_SERVICES`] services
+pub fn arb_athena_file() -> impl Strategy<Value = AthenaFile> {
+    let services = (1..=MAX_SERVICES)
+        .prop_flat_map(|count| (0..count).map(arb_service).collect::<Vec<_>>());
+
+    (option::of("[a-z][a-z0-9_]{0,15}"), services).prop_map(|(deployment_id, services)| {
+        AthenaFile {
+            deployment: deployment_id.map(|deployment_id| DeploymentSection {
+                deployment_id,
+                version_id: None,
+            }),
+            environment: None,
+            services: ServicesSection { services },
+            environments: Vec::new(),
+            suppressions: Vec::new(),
+        }
+    })
+}
+
+/// Name of the service at `index`, as used by [`arb_service`]
+pub fn service_name(index: usize) -> String {
+    format!("svc{index}")
+}
+
+/// The `index`-th service of a file; it may only depend on services before it
+pub fn arb_service(index: usize) -> impl Strategy<Value = Service> {
+    let image = option::of(prop_oneof![
+        Just("nginx:alpine"),
+        Just("postgres:16"),
+        Just("redis:7-alpine"),
+        Just("node:20-slim"),
+    ]);
+    let basics = (
+        image,
+        vec(1u16..=65535, 0..3),
+        vec(arb_environment_variable(), 0..4),
+        option::of("[a-z]{1,8}( [a-z0-9-]{1,8}){0,2}"),
+        option::of(arb_restart_policy()),
+    );
+    let extras = (
+        option::of("/[a-z]{1,8}"),
+        any::<bool>(),
+        option::of("curl -f http://localhost/[a-z]{0,8}"),
+        option::of(1u32..=4),
+        any::<bool>(),
+        vec((any::<Index>(), arb_dependency_condition()), 0..3),
+    );
+
+    (basics, extras).prop_map(
+        move |(
+            (image, container_ports, environment, command, restart),
+            (working_dir, limited, health_check, replicas, data_volume, dependencies),
+        )| {
+            let mut service = Service::new(service_name(index));
+            service.image = image.map(str::to_string);
+            service.ports = unique_ports(index, container_ports);
+            service.environment = dedup_by_key(environment);
+            service.command = command.map(ContainerCommand::Shell);
+            service.restart = restart;
+            service.working_dir = working_dir;
+            service.health_check = health_check;
+            if limited {
+                service.resources = Some(ResourceLimits {
+                    cpu: "0.5".to_string(),
+                    memory: ByteSize::from_bytes(512 << 20),
+                });
+            }
+            service.swarm_config = replicas.map(|replicas| SwarmConfig {
+                replicas: Some(replicas),
+                ..SwarmConfig::new()
+            });
+            if data_volume {
+                service.volumes.push(VolumeMapping {
+                    host_path: format!("./data/{}", service.name),
+                    container_path: "/data".to_string(),
+                    options: Vec::new(),
+                });
+            }
+            service.depends_on = earlier_dependencies(index, dependencies);
+            service
+        },
+    )
+}
+
+fn arb_environment_variable() -> impl Strategy<Value = EnvironmentVariable> {
+    prop_oneof![
+        "[A-Z][A-Z0-9_]{0,11}".prop_map(EnvironmentVariable::Template),
+        ("[A-Z][A-Z0-9_]{0,11}", "[a-z0-9.]{0,12}")
+            .prop_map(|(key, value)| EnvironmentVariable::Literal(format!("{key}={value}"))),
+    ]
+}
+
+fn arb_restart_policy() -> impl Strategy<Value = RestartPolicy> {
+    prop_oneof![
+        Just(RestartPolicy::Always),
+        Just(RestartPolicy::UnlessStopped),
+        Just(RestartPolicy::OnFailure),
+        Just(RestartPolicy::No),
+    ]
+}
+
+/// COMPLETED and HEALTHY need a job or a health check on the other side, so
+/// only the conditions valid for any service are drawn
+fn arb_dependency_condition() -> impl Strategy<Value = Option<DependencyCondition>> {
+    prop_oneof![Just(None), Just(Some(DependencyCondition::Started))]
+}
+
+/// Host ports are derived from the service index so no two services clash
+fn unique_ports(index: usize, container_ports: Vec<u16>) -> Vec<PortMapping> {
+    container_ports
+        .into_iter()
+        .enumerate()
+        .map(|(n, container_port)| PortMapping {
+            host_port: (10_000 + index * 10 + n) as u16,
+            host_port_end: None,
+            container_port,
+            protocol: Protocol::Tcp,
+        })
+        .collect()
+}
+
+fn dedup_by_key(environment: Vec<EnvironmentVariable>) -> Vec<EnvironmentVariable> {
+    let mut unique: Vec<EnvironmentVariable> = Vec::new();
+    for variable in environment {
+        if !unique.iter().any(|v| v.key() == variable.key()) {
+            unique.push(variable);
+        }
+    }
+    unique
+}
+
+fn earlier_dependencies(
+    index: usize,
+    picks: Vec<(Index, Option<DependencyCondition>)>,
+) -> Vec<Dependency> {
+    if index == 0 {
+        return Vec::new();
+    }
+
+    let mut dependencies: Vec<Dependency> = Vec::new();
+    for (pick, condition) in picks {
+        let service = service_name(pick.index(index));
+        if !dependencies.iter().any(|d| d.service == service) {
+            dependencies.push(Dependency { service, condition });
+        }
+    }
+    dependencies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::generator::generate_docker_compose;
+    use serde_yaml::Value;
+
+    /// Names under `depends_on`, in either the list or the long form
+    fn dependency_names(service: &Value) -> Vec<String> {
+        let mut names: Vec<String> = match &service["depends_on"] {
+            Value::Sequence(names) => names.iter().filter_map(|n| n.as_str()).map(String::from).collect(),
+            Value::Mapping(long) => long.keys().filter_map(|n| n.as_str()).map(String::from).collect(),
+            _ => Vec::new(),
+        };
+        names.sort();
+        names
+    }
+
+    proptest! {
+        #[test]
+        fn test_generated_compose_keeps_services_and_dependencies(athena_file in arb_athena_file()) {
+            let yaml = generate_docker_compose(&athena_file).expect("valid files generate");
+            let compose: Value = serde_yaml::from_str(&yaml).expect("output parses as YAML");
+
+            let services = compose["services"].as_mapping().expect("services mapping");
+            prop_assert_eq!(services.len(), athena_file.services.services.len());
+
+            for service in &athena_file.services.services {
+                let generated = &compose["services"][service.name.as_str()];
+                prop_assert!(generated.is_mapping(), "service {} is missing", service.name);
+
+                let mut expected: Vec<String> =
+                    service.depends_on.iter().map(|d| d.service.clone()).collect();
+                expected.sort();
+                prop_assert_eq!(dependency_names(generated), expected);
+            }
+        }
+    }
+}
@@ -1,11 +1,59 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+
+pub use super::units::{ByteSize, Duration};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AthenaFile {
     pub deployment: Option<DeploymentSection>,
     pub environment: Option<EnvironmentSection>,
     pub services: ServicesSection,
+    /// `ENVIRONMENT "name"` blocks, applied by `athena build --env`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub environments: Vec<EnvironmentOverride>,
+    /// `// athena:ignore` comments, applied when warnings are collected
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressions: Vec<Suppression>,
+}
+
+/// Services of an `ENVIRONMENT "name" ... END ENVIRONMENT` block, merged over
+/// the SERVICES SECTION the same way a later file is
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvironmentOverride {
+    pub name: String,
+    pub services: Vec<Service>,
+}
+
+/// A `// athena:ignore CODE` comment silencing one warning code
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suppression {
+    pub code: String,
+    /// Service whose block holds the comment, or whose SERVICE line follows it;
+    /// None applies the suppression to the whole file
+    pub service: Option<String>,
+    /// 1-based line of the comment
+    pub line: usize,
+    /// Source file, recorded when several files are merged
+    pub file: Option<String>,
+}
+
+/// Where a SERVICE, VOLUME or NETWORK-NAME was declared, for `--annotate-sources`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    /// 1-based line of the declaration
+    pub line: usize,
+    /// Source file, recorded by `parse_and_merge`
+    pub file: Option<String>,
+}
+
+impl std::fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{file}:{}", self.line),
+            None => write!(f, "line {}", self.line),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +67,58 @@ pub struct EnvironmentSection {
     pub networks: Vec<NetworkDefinition>,
     pub volumes: Vec<VolumeDefinition>,
     pub secrets: HashMap<String, String>,
+    pub dns: Vec<String>,
+    /// `EXTERNAL SERVICE` declarations
+    #[serde(default)]
+    pub externals: Vec<ExternalService>,
+    /// `DEFAULT LABELS FROM`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_labels: Option<DefaultLabels>,
+}
+
+/// Labels every service gets unless it sets the same key with LABEL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DefaultLabels {
+    /// The YAML file as written, relative to the .ath file declaring it
+    pub path: String,
+    pub location: Option<SourceLocation>,
+    /// Contents of the file, read by `labels::load_default_labels`
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+}
+
+/// A service outside the stack, e.g. a database shared by several projects
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalService {
+    pub name: String,
+    pub host: String,
+    pub port: Option<u16>,
+}
+
+impl ExternalService {
+    /// `extra_hosts` entry that makes the name resolve inside containers. Only
+    /// an IP or the Docker host can be mapped; other hosts are used by name.
+    pub fn extra_host(&self) -> Option<ExtraHost> {
+        let ip = if self.host == "host.docker.internal" {
+            "host-gateway"
+        } else if self.host.parse::<IpAddr>().is_ok() {
+            &self.host
+        } else {
+            return None;
+        };
+        Some(ExtraHost {
+            hostname: self.name.clone(),
+            ip: ip.to_string(),
+        })
+    }
+
+    /// `host:port`, or just the host
+    pub fn address(&self) -> String {
+        match self.port {
+            Some(port) => format!("{}:{port}", self.host),
+            None => self.host.clone(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +128,31 @@ pub struct NetworkDefinition {
     pub attachable: Option<bool>,
     pub encrypted: Option<bool>,
     pub ingress: Option<bool>,
+    /// `SUBNET` in CIDR notation, e.g. `172.28.0.0/16`
+    #[serde(default)]
+    pub subnet: Option<String>,
+    #[serde(default)]
+    pub location: Option<SourceLocation>,
+}
+
+impl NetworkDefinition {
+    /// Whether `address` lies in the network's IPv4 `SUBNET`; None without one
+    pub fn subnet_contains(&self, address: Ipv4Addr) -> Option<bool> {
+        let (IpAddr::V4(network), prefix) = parse_cidr(self.subnet.as_deref()?)? else {
+            return None;
+        };
+        let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+        Some(u32::from(address) & mask == u32::from(network) & mask)
+    }
+}
+
+/// Split `address/prefix` CIDR notation, checking the prefix fits the address family
+pub fn parse_cidr(value: &str) -> Option<(IpAddr, u8)> {
+    let (address, prefix) = value.split_once('/')?;
+    let address: IpAddr = address.parse().ok()?;
+    let prefix: u8 = prefix.parse().ok()?;
+    let max = if address.is_ipv4() { 32 } else { 128 };
+    (prefix <= max).then_some((address, prefix))
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +167,8 @@ pub enum NetworkDriver {
 pub struct VolumeDefinition {
     pub name: String,
     pub options: Vec<String>,
+    #[serde(default)]
+    pub location: Option<SourceLocation>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,24 +182,116 @@ pub struct Service {
     pub image: Option<String>,
     pub ports: Vec<PortMapping>,
     pub environment: Vec<EnvironmentVariable>,
-    pub command: Option<String>,
+    pub command: Option<ContainerCommand>,
+    pub entrypoint: Option<ContainerCommand>,
+    pub working_dir: Option<String>,
+    pub user: Option<String>,
+    pub init: Option<bool>,
+    pub tty: Option<bool>,
+    pub stdin_open: Option<bool>,
+    pub stop_grace_period: Option<Duration>,
+    /// `SIGTERM`-style name sent by `docker stop`; Docker defaults to SIGTERM
+    pub stop_signal: Option<String>,
     pub volumes: Vec<VolumeMapping>,
-    pub depends_on: Vec<String>,
+    pub depends_on: Vec<Dependency>,
     pub health_check: Option<String>,
     pub restart: Option<RestartPolicy>,
     pub resources: Option<ResourceLimits>,
+    pub reservations: Option<ResourceReservations>,
     pub build_args: Option<HashMap<String, String>>,
     pub swarm_config: Option<SwarmConfig>,
+    pub dns: Vec<String>,
+    pub extra_hosts: Vec<ExtraHost>,
+    pub hostname: Option<String>,
+    pub expose_via: Option<ProxyExposure>,
+    /// `WATCH` rules for `docker compose watch`
+    pub watch: Vec<WatchRule>,
+    pub container_name: Option<String>,
+    pub pull_policy: Option<PullPolicy>,
+    /// `os/arch[/variant]`, e.g. `linux/arm64`
+    pub platform: Option<String>,
+    /// Set by `KIND`; None is a long-running service
+    pub kind: Option<ServiceKind>,
+    /// Compose profiles from `PROFILE`; empty means the service always runs
+    pub profiles: Vec<String>,
+    pub pid: Option<PidMode>,
+    pub ipc: Option<IpcMode>,
+    pub network_mode: Option<NetworkMode>,
+    /// Set by `PRESET`; expanded into the fields above after parsing
+    pub preset: Option<ServicePreset>,
+    /// `LABEL` entries, over the DEFAULT LABELS FROM file
+    #[serde(default)]
+    pub labels: HashMap<String, String>,
+    /// Set by `ALLOW UNRESOLVED HOSTS`; silences the unresolved-host warning
+    pub allow_unresolved_hosts: bool,
+    /// Set by `ALLOW DOCKER SOCKET`; mounting the Docker or containerd socket
+    /// is an error without it
+    pub allow_docker_socket: bool,
+    /// ENV-VARIABLE keys marked `// athena:allow-secret`; skipped by the secret lint
+    pub allowed_secrets: Vec<String>,
+    /// Line of the ENV-VARIABLE setting each key; preset entries have none
+    #[serde(default)]
+    pub env_lines: HashMap<String, usize>,
+    /// `EXTERNAL SERVICE` names from `USES`
+    pub uses: Vec<String>,
+    /// `ON NETWORK` lines, one entry per network
+    pub networks: Vec<NetworkAttachment>,
+    /// The SERVICE line; with several input files, the first file declaring it
+    #[serde(default)]
+    pub location: Option<SourceLocation>,
+}
+
+/// A network the service joins besides the project network, or options for
+/// the project network itself
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NetworkAttachment {
+    pub network: String,
+    pub aliases: Vec<String>,
+    pub ipv4_address: Option<String>,
+}
+
+impl NetworkAttachment {
+    pub fn new(network: impl Into<String>) -> Self {
+        Self {
+            network: network.into(),
+            aliases: Vec::new(),
+            ipv4_address: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ServicePreset {
+    pub name: String,
+    pub version: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortMapping {
     pub host_port: u16,
+    /// Last port of a host range such as `8080-8082`, one per replica
+    pub host_port_end: Option<u16>,
     pub container_port: u16,
     pub protocol: Protocol,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+impl PortMapping {
+    /// Host side as written in Compose: `8080` or `8080-8082`
+    pub fn host_ports(&self) -> String {
+        match self.host_port_end {
+            Some(end) => format!("{}-{end}", self.host_port),
+            None => self.host_port.to_string(),
+        }
+    }
+
+    /// Number of host ports published, i.e. how many replicas can bind them
+    pub fn host_port_count(&self) -> u32 {
+        self.host_port_end
+            .map_or(1, |end| u32::from(end) - u32::from(self.host_port) + 1)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
 pub enum Protocol {
     #[default]
     Tcp,
@@ -85,6 +304,25 @@ pub enum EnvironmentVariable {
     Literal(String),      // "actual value"
 }
 
+impl EnvironmentVariable {
+    /// Variable name, i.e. the template name or the part before `=`
+    pub fn key(&self) -> &str {
+        match self {
+            EnvironmentVariable::Template(name) => name,
+            EnvironmentVariable::Literal(value) => value.split_once('=').map_or(value.as_str(), |(key, _)| key),
+        }
+    }
+}
+
+/// Command or entrypoint, kept in the form it was written so that signal
+/// handling semantics (shell vs exec) survive generation.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ContainerCommand {
+    Shell(String),     // "npm start"
+    Exec(Vec<String>), // ["npm", "start"]
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VolumeMapping {
     pub host_path: String,
@@ -92,6 +330,136 @@ pub struct VolumeMapping {
     pub options: Vec<String>,
 }
 
+impl VolumeMapping {
+    pub const ACCESS_MODES: &'static [&'static str] = &["ro", "rw"];
+    pub const SELINUX_LABELS: &'static [&'static str] = &["z", "Z"];
+    /// Docker Desktop file sharing modes for bind mounts
+    pub const CONSISTENCY: &'static [&'static str] = &["cached", "delegated", "consistent"];
+    pub const PROPAGATION: &'static [&'static str] =
+        &["private", "rprivate", "shared", "rshared", "slave", "rslave"];
+    /// Don't copy image content into an empty named volume
+    pub const NOCOPY: &'static str = "nocopy";
+
+    fn option_in(&self, allowed: &[&str]) -> Option<&str> {
+        self.options
+            .iter()
+            .map(String::as_str)
+            .find(|option| allowed.contains(option))
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.option_in(&["ro"]).is_some()
+    }
+
+    pub fn selinux_label(&self) -> Option<&str> {
+        self.option_in(Self::SELINUX_LABELS)
+    }
+
+    pub fn consistency(&self) -> Option<&str> {
+        self.option_in(Self::CONSISTENCY)
+    }
+
+    pub fn propagation(&self) -> Option<&str> {
+        self.option_in(Self::PROPAGATION)
+    }
+
+    pub fn nocopy(&self) -> bool {
+        self.option_in(&[Self::NOCOPY]).is_some()
+    }
+
+    /// Consistency, propagation and nocopy only exist in the long mount syntax
+    pub fn needs_long_syntax(&self) -> bool {
+        self.consistency().is_some() || self.propagation().is_some() || self.nocopy()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExtraHost {
+    pub hostname: String,
+    pub ip: String,
+}
+
+/// Route from a reverse proxy to a service, declared with EXPOSE-VIA
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProxyExposure {
+    pub proxy: ReverseProxy,
+    pub path: String,
+}
+
+/// A `develop.watch` entry, declared with WATCH
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct WatchRule {
+    pub action: WatchAction,
+    /// Host path, relative to the project directory
+    pub path: String,
+    /// Container path; required by the sync actions
+    pub target: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum WatchAction {
+    Sync,
+    SyncRestart,
+    Rebuild,
+}
+
+impl WatchAction {
+    pub fn to_compose_value(self) -> &'static str {
+        match self {
+            WatchAction::Sync => "sync",
+            WatchAction::SyncRestart => "sync+restart",
+            WatchAction::Rebuild => "rebuild",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ReverseProxy {
+    Nginx,
+}
+
+/// A `DEPENDS-ON` entry
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Dependency {
+    pub service: String,
+    /// None leaves the Compose default, i.e. wait for the container to start
+    pub condition: Option<DependencyCondition>,
+}
+
+impl Dependency {
+    pub fn new(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            condition: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DependencyCondition {
+    Started,
+    Healthy,
+    Completed,
+}
+
+impl DependencyCondition {
+    /// Value of `depends_on.<service>.condition` in Compose
+    pub fn to_compose_value(self) -> &'static str {
+        match self {
+            DependencyCondition::Started => "service_started",
+            DependencyCondition::Healthy => "service_healthy",
+            DependencyCondition::Completed => "service_completed_successfully",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum ServiceKind {
+    Service,
+    /// Runs to completion once, e.g. database migrations
+    Job,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum RestartPolicy {
     Always,
@@ -100,25 +468,76 @@ pub enum RestartPolicy {
     No,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PullPolicy {
+    Always,
+    Missing,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum PidMode {
+    Host,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum IpcMode {
+    Host,
+    Shareable,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NetworkMode {
+    Host,
+    None,
+    /// Join the network namespace of another service
+    Service(String),
+}
+
+impl NetworkMode {
+    /// Value of the Compose `network_mode` key
+    pub fn to_compose_value(&self) -> String {
+        match self {
+            NetworkMode::Host => "host".to_string(),
+            NetworkMode::None => "none".to_string(),
+            NetworkMode::Service(name) => format!("service:{name}"),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceLimits {
     pub cpu: String,
-    pub memory: String,
+    pub memory: ByteSize,
+}
+
+/// `RESERVE`: resources Swarm guarantees the service, within its RESOURCE-LIMITS
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ResourceReservations {
+    pub cpus: Option<String>,
+    pub memory: Option<ByteSize>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SwarmConfig {
+    pub mode: Option<DeployMode>,
     pub replicas: Option<u32>,
     pub update_config: Option<UpdateConfig>,
     pub labels: Option<HashMap<String, String>>,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DeployMode {
+    Replicated,
+    Global,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateConfig {
     pub parallelism: Option<u32>,
-    pub delay: Option<String>,
+    pub delay: Option<Duration>,
     pub failure_action: Option<FailureAction>,
-    pub monitor: Option<String>,
+    pub monitor: Option<Duration>,
     pub max_failure_ratio: Option<f32>,
 }
 
@@ -143,9 +562,28 @@ impl AthenaFile {
             services: ServicesSection {
                 services: Vec::new(),
             },
+            environments: Vec::new(),
+            suppressions: Vec::new(),
         }
     }
 
+    pub fn externals(&self) -> &[ExternalService] {
+        self.environment.as_ref().map_or(&[], |env| &env.externals)
+    }
+
+    pub fn find_external(&self, name: &str) -> Option<&ExternalService> {
+        self.externals().iter().find(|external| external.name == name)
+    }
+
+    /// `extra_hosts` entries for the external services a service USES
+    pub fn external_hosts_for(&self, service: &Service) -> Vec<ExtraHost> {
+        service
+            .uses
+            .iter()
+            .filter_map(|name| self.find_external(name)?.extra_host())
+            .collect()
+    }
+
     pub fn get_project_name(&self) -> String {
         self.deployment
             .as_ref()
@@ -153,6 +591,29 @@ impl AthenaFile {
             .unwrap_or_else(|| "athena-project".to_string())
     }
 
+    /// The `name:` of the generated Compose file, which prefixes its
+    /// containers, networks and volumes
+    pub fn get_compose_project_name(&self) -> String {
+        self.get_project_name().to_lowercase().replace('_', "-")
+    }
+
+    /// DNS servers declared in the ENVIRONMENT SECTION, applied to every service
+    /// that doesn't declare its own.
+    pub fn get_default_dns(&self) -> &[String] {
+        self.environment
+            .as_ref()
+            .map(|e| e.dns.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Labels loaded from DEFAULT LABELS FROM, empty until the file is read
+    pub fn get_default_labels(&self) -> Option<&HashMap<String, String>> {
+        self.environment
+            .as_ref()
+            .and_then(|e| e.default_labels.as_ref())
+            .map(|d| &d.labels)
+    }
+
     pub fn get_network_name(&self) -> String {
         self.environment
             .as_ref()
@@ -162,6 +623,20 @@ impl AthenaFile {
             .unwrap_or_else(|| format!("{}_network", self.get_project_name().to_lowercase()))
     }
 
+    /// Whether the stack is meant for `docker stack deploy`: an overlay
+    /// network, or a service with DEPLOY-MODE, UPDATE-CONFIG or SWARM-LABELS.
+    /// REPLICAS alone also scales plain Compose, so it doesn't count.
+    pub fn targets_swarm(&self) -> bool {
+        let overlay = self
+            .environment
+            .iter()
+            .flat_map(|e| &e.networks)
+            .any(|net| matches!(net.driver, Some(NetworkDriver::Overlay)));
+        let swarm_settings = self.services.services.iter().filter_map(|s| s.swarm_config.as_ref()).any(|swarm| {
+            swarm.mode.is_some() || swarm.update_config.is_some() || swarm.labels.is_some()
+        });
+        overlay || swarm_settings
+    }
 }
 
 impl Service {
@@ -172,13 +647,44 @@ impl Service {
             ports: Vec::new(),
             environment: Vec::new(),
             command: None,
+            entrypoint: None,
+            working_dir: None,
+            user: None,
+            init: None,
+            tty: None,
+            stdin_open: None,
+            stop_grace_period: None,
+            stop_signal: None,
             volumes: Vec::new(),
             depends_on: Vec::new(),
             health_check: None,
             restart: None,
             resources: None,
+            reservations: None,
             build_args: None,
             swarm_config: None,
+            dns: Vec::new(),
+            extra_hosts: Vec::new(),
+            hostname: None,
+            expose_via: None,
+            watch: Vec::new(),
+            container_name: None,
+            pull_policy: None,
+            platform: None,
+            kind: None,
+            profiles: Vec::new(),
+            pid: None,
+            ipc: None,
+            network_mode: None,
+            preset: None,
+            labels: HashMap::new(),
+            allow_unresolved_hosts: false,
+            allow_docker_socket: false,
+            allowed_secrets: Vec::new(),
+            env_lines: HashMap::new(),
+            uses: Vec::new(),
+            networks: Vec::new(),
+            location: None,
         }
     }
 }
@@ -192,6 +698,7 @@ impl Default for SwarmConfig {
 impl SwarmConfig {
     pub fn new() -> Self {
         Self {
+            mode: None,
             replicas: None,
             update_config: None,
             labels: None,
@@ -1,5 +1,8 @@
+#[cfg(any(test, feature = "arbitrary"))]
+pub mod arbitrary;
 pub mod ast;
 #[allow(clippy::module_inception)]
 pub mod parser;
+pub mod units;
 
-pub use parser::parse_athena_file;
\ No newline at end of file
+pub use parser::{check_platform, dsl_keywords, parse_athena_file, services_section_end};
\ No newline at end of file
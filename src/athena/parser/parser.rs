@@ -1,12 +1,18 @@
 use pest::Parser;
 use pest_derive::Parser;
 use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
 
-use crate::athena::error::{AthenaError, AthenaResult, EnhancedParseError};
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedParseError, EnhancedValidationError};
+use crate::athena::mounts::is_bind_mount;
+use crate::athena::presets::{expand_presets, find_preset, preset_names};
+use crate::athena::warnings::WARNING_CODES;
 use super::ast::{
-    AthenaFile, DeploymentSection, EnvironmentSection, EnvironmentVariable, FailureAction,
-    NetworkDefinition, NetworkDriver, PortMapping, Protocol, ResourceLimits, RestartPolicy,
-    Service, ServicesSection, SwarmConfig, UpdateConfig, VolumeDefinition, VolumeMapping,
+    parse_cidr, AthenaFile, ByteSize, Duration, ContainerCommand, Dependency, DependencyCondition, DeployMode, DefaultLabels, DeploymentSection, EnvironmentOverride,
+    EnvironmentSection, EnvironmentVariable, ExternalService, ExtraHost, FailureAction, IpcMode, NetworkAttachment, NetworkDefinition, NetworkMode, NetworkDriver, PortMapping,
+    PidMode, Protocol, ProxyExposure, PullPolicy, ResourceLimits, ResourceReservations, RestartPolicy, ReverseProxy, Service, SourceLocation,
+    ServiceKind, ServicePreset, ServicesSection, Suppression, SwarmConfig, UpdateConfig, VolumeDefinition, VolumeMapping,
+    WatchAction, WatchRule,
 };
 
 #[derive(Parser)]
@@ -14,34 +20,14 @@ use super::ast::{
 pub struct AthenaParser;
 
 pub fn parse_athena_file(input: &str) -> AthenaResult<AthenaFile> {
-    let pairs = AthenaParser::parse(Rule::athena_file, input)
-        .map_err(|e| {
-            // Extract location information from Pest error
-            let (line, column) = match &e.location {
-                pest::error::InputLocation::Pos(pos) => {
-                    let line_col = pest::Position::new(input, *pos)
-                        .map(|p| (p.line_col().0, p.line_col().1))
-                        .unwrap_or((1, 1));
-                    line_col
-                }
-                pest::error::InputLocation::Span((start, _)) => {
-                    let line_col = pest::Position::new(input, *start)
-                        .map(|p| (p.line_col().0, p.line_col().1))
-                        .unwrap_or((1, 1));
-                    line_col
-                }
-            };
-            
-            // Create enhanced error with context and suggestions
-            let enhanced_error = create_enhanced_parse_error(&e, line, column, input);
-            AthenaError::parse_error_enhanced(enhanced_error)
-        })?;
+    let pairs = parse_pairs(input)?;
 
     let mut athena_file = AthenaFile::new();
 
     for pair in pairs {
         match pair.as_rule() {
             Rule::athena_file => {
+                let declarations = pair.clone();
                 for inner_pair in pair.into_inner() {
                     match inner_pair.as_rule() {
                         Rule::deployment_section => {
@@ -53,22 +39,167 @@ pub fn parse_athena_file(input: &str) -> AthenaResult<AthenaFile> {
                         Rule::services_section => {
                             athena_file.services = parse_services_section(inner_pair)?;
                         }
+                        Rule::environment_override => {
+                            let environment = parse_environment_override(inner_pair)?;
+                            if athena_file.environments.iter().any(|e| e.name == environment.name) {
+                                return Err(AthenaError::parse_error_enhanced(
+                                    EnhancedParseError::new(format!(
+                                        "ENVIRONMENT \"{}\" is declared twice",
+                                        environment.name
+                                    ))
+                                    .with_suggestion("Put all overrides for an environment in one block".to_string()),
+                                ));
+                            }
+                            athena_file.environments.push(environment);
+                        }
                         Rule::EOI => {} // End of input
-                        _ => return Err(AthenaError::ParseError(
+                        _ => return Err(AthenaError::parse_error_enhanced(
                             EnhancedParseError::new(format!("Unexpected rule: {:?}", inner_pair.as_rule()))
                         )),
                     }
                 }
+                check_duplicate_declarations(declarations)?;
             }
-            _ => return Err(AthenaError::ParseError(
+            _ => return Err(AthenaError::parse_error_enhanced(
                 EnhancedParseError::new("Expected athena_file rule".to_string())
             )),
         }
     }
 
+    expand_presets(&mut athena_file);
+    athena_file.suppressions = collect_suppressions(input)?;
+
     Ok(athena_file)
 }
 
+fn parse_pairs(input: &str) -> AthenaResult<pest::iterators::Pairs<'_, Rule>> {
+    AthenaParser::parse(Rule::athena_file, input).map_err(|e| {
+        let (line, column) = error_position(&e, input);
+
+        // Create enhanced error with context and suggestions, followed by
+        // whatever else is broken in the other SERVICE blocks
+        let enhanced_error = create_enhanced_parse_error(&e, line, column, input)
+            .with_others(recover_service_errors(input, line));
+        AthenaError::parse_error_enhanced(enhanced_error)
+    })
+}
+
+/// Byte offset just past the last SERVICE block of the SERVICES SECTION, or
+/// past its header when it has none
+pub fn services_section_end(input: &str) -> AthenaResult<usize> {
+    parse_pairs(input)?
+        .flat_map(|pair| pair.into_inner())
+        .find(|pair| pair.as_rule() == Rule::services_section)
+        .map(|section| section.as_span().end())
+        .ok_or_else(|| {
+            AthenaError::parse_error_enhanced(EnhancedParseError::new(
+                "Missing SERVICES SECTION".to_string(),
+            ))
+        })
+}
+
+fn error_position(error: &pest::error::Error<Rule>, input: &str) -> (usize, usize) {
+    let pos = match error.location {
+        pest::error::InputLocation::Pos(pos) => pos,
+        pest::error::InputLocation::Span((start, _)) => start,
+    };
+    pest::Position::new(input, pos)
+        .map(|p| p.line_col())
+        .unwrap_or((1, 1))
+}
+
+/// Parse each `SERVICE ... END SERVICE` block on its own so one run reports
+/// every broken block. Blocks are found in a single pass over the lines and
+/// each one is parsed once, so recovery stays linear in the file size.
+/// Errors on `skip_line` are left out, as the caller already reports them.
+fn recover_service_errors(input: &str, skip_line: usize) -> Vec<EnhancedParseError> {
+    let mut errors = Vec::new();
+    // Line index and byte offset of the block being scanned
+    let mut block: Option<(usize, usize)> = None;
+    let mut offset = 0;
+
+    for (index, line) in input.split_inclusive('\n').enumerate() {
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("SERVICE"), _) => {
+                // A block without END SERVICE runs up to the next SERVICE
+                if let Some(start) = block.replace((index, offset)) {
+                    errors.extend(check_service_block(input, start, offset));
+                }
+            }
+            (Some("END"), Some("SERVICE")) => {
+                if let Some(start) = block.take() {
+                    errors.extend(check_service_block(input, start, offset + line.len()));
+                }
+            }
+            _ => {}
+        }
+        offset += line.len();
+    }
+    if let Some(start) = block {
+        errors.extend(check_service_block(input, start, input.len()));
+    }
+
+    errors.retain(|e| e.location.as_ref().is_none_or(|l| l.line != skip_line));
+    errors
+}
+
+fn check_service_block(input: &str, (first_line, start): (usize, usize), end: usize) -> Option<EnhancedParseError> {
+    let block = &input[start..end];
+
+    let mut error = match AthenaParser::parse(Rule::service_block, block) {
+        Err(e) => {
+            let (line, column) = error_position(&e, block);
+            create_enhanced_parse_error(&e, line, column, block)
+        }
+        Ok(mut pairs) => {
+            let service = pairs.next()?.into_inner().next()?;
+            match parse_service(service) {
+                Err(AthenaError::ParseError(e)) => *e,
+                _ => return None,
+            }
+        }
+    };
+
+    // Locations are relative to the block; the first error carries the file content
+    error.file_content = None;
+    if let Some(location) = error.location.as_mut() {
+        location.line += first_line;
+    }
+    Some(error)
+}
+
+/// Every keyword of the DSL, read from the grammar so the list can't drift.
+pub fn dsl_keywords() -> Vec<&'static str> {
+    let grammar: &'static str = include_str!("grammar.pest");
+    let mut keywords = Vec::new();
+    let mut literal_start = None;
+    let mut escaped = false;
+
+    for (index, c) in grammar.char_indices() {
+        match (c, literal_start) {
+            (_, Some(_)) if escaped => escaped = false,
+            ('\\', Some(_)) => escaped = true,
+            ('"', None) => literal_start = Some(index + 1),
+            ('"', Some(start)) => {
+                let literal = &grammar[start..index];
+                let is_keyword = literal.len() > 1
+                    && literal.starts_with(|c: char| c.is_ascii_uppercase())
+                    && literal.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-');
+                if is_keyword {
+                    keywords.push(literal);
+                }
+                literal_start = None;
+            }
+            _ => {}
+        }
+    }
+
+    keywords.sort_unstable();
+    keywords.dedup();
+    keywords
+}
+
 fn parse_deployment_section(pair: pest::iterators::Pair<Rule>) -> AthenaResult<DeploymentSection> {
     let mut deployment_id = None;
     let mut version_id = None;
@@ -90,7 +221,7 @@ fn parse_deployment_section(pair: pest::iterators::Pair<Rule>) -> AthenaResult<D
     }
 
     let deployment_id = deployment_id.ok_or_else(|| 
-        AthenaError::ParseError(EnhancedParseError::new("Missing deployment ID".to_string()))
+        AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing deployment ID".to_string()))
     )?;
 
     Ok(DeploymentSection {
@@ -103,6 +234,9 @@ fn parse_environment_section(pair: pest::iterators::Pair<Rule>) -> AthenaResult<
     let mut networks = Vec::new();
     let mut volumes = Vec::new();
     let mut secrets = HashMap::new();
+    let mut dns = Vec::new();
+    let mut externals: Vec<ExternalService> = Vec::new();
+    let mut default_labels = None;
 
     for inner_pair in pair.into_inner() {
         if inner_pair.as_rule() == Rule::environment_item {
@@ -123,6 +257,36 @@ fn parse_environment_section(pair: pest::iterators::Pair<Rule>) -> AthenaResult<
                             );
                         }
                     }
+                    Rule::default_dns => {
+                        if let Some(dns_pair) = item_pair.into_inner().next() {
+                            dns.push(parse_ip_literal(dns_pair, "DNS")?);
+                        }
+                    }
+                    Rule::default_labels => {
+                        let location = source_location(&item_pair);
+                        if let Some(path_pair) = item_pair.into_inner().next() {
+                            default_labels = Some(DefaultLabels {
+                                path: clean_string_value(path_pair.as_str()),
+                                location,
+                                labels: HashMap::new(),
+                            });
+                        }
+                    }
+                    Rule::external_service => {
+                        let (line, column) = item_pair.line_col();
+                        let external = parse_external_service(item_pair)?;
+                        if externals.iter().any(|e| e.name == external.name) {
+                            return Err(AthenaError::parse_error_enhanced(
+                                EnhancedParseError::new(format!(
+                                    "EXTERNAL SERVICE '{}' is declared twice",
+                                    external.name
+                                ))
+                                .with_location(line, column)
+                                .with_suggestion("Keep one declaration per external service".to_string()),
+                            ));
+                        }
+                        externals.push(external);
+                    }
                     _ => {}
                 }
             }
@@ -133,47 +297,49 @@ fn parse_environment_section(pair: pest::iterators::Pair<Rule>) -> AthenaResult<
         networks,
         volumes,
         secrets,
+        dns,
+        externals,
+        default_labels,
     })
 }
 
 fn parse_volume_definition(pair: pest::iterators::Pair<Rule>) -> AthenaResult<VolumeDefinition> {
+    let location = source_location(&pair);
     let mut name = None;
     let mut options = Vec::new();
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
-            Rule::identifier => {
-                name = Some(inner_pair.as_str().to_string());
+            Rule::resource_name => {
+                name = Some(parse_resource_name(inner_pair, ResourceKind::Volume)?);
             }
             Rule::volume_options => {
-                for option_pair in inner_pair.into_inner() {
-                    if let Rule::volume_option = option_pair.as_rule() {
-                        options.push(option_pair.as_str().to_string());
-                    }
-                }
+                options = parse_volume_options(inner_pair)?;
             }
             _ => {}
         }
     }
 
     let name = name.ok_or_else(|| 
-        AthenaError::ParseError(EnhancedParseError::new("Missing volume name".to_string()))
+        AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing volume name".to_string()))
     )?;
 
-    Ok(VolumeDefinition { name, options })
+    Ok(VolumeDefinition { name, options, location })
 }
 
 fn parse_network_definition(pair: pest::iterators::Pair<Rule>) -> AthenaResult<NetworkDefinition> {
+    let location = source_location(&pair);
     let mut name = None;
     let mut driver = None;
     let mut attachable = None;
     let mut encrypted = None;
     let mut ingress = None;
+    let mut subnet = None;
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
-            Rule::identifier => {
-                name = Some(inner_pair.as_str().to_string());
+            Rule::resource_name => {
+                name = Some(parse_resource_name(inner_pair, ResourceKind::Network)?);
             }
             Rule::network_options => {
                 for option_pair in inner_pair.into_inner() {
@@ -201,6 +367,9 @@ fn parse_network_definition(pair: pest::iterators::Pair<Rule>) -> AthenaResult<N
                                         ingress = Some(bool_val);
                                     }
                                 }
+                                Rule::string_value => {
+                                    subnet = Some(parse_subnet(opt_inner)?);
+                                }
                                 _ => {}
                             }
                         }
@@ -212,7 +381,7 @@ fn parse_network_definition(pair: pest::iterators::Pair<Rule>) -> AthenaResult<N
     }
 
     let name = name.ok_or_else(|| 
-        AthenaError::ParseError(EnhancedParseError::new("Missing network name".to_string()))
+        AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing network name".to_string()))
     )?;
 
     Ok(NetworkDefinition {
@@ -221,29 +390,401 @@ fn parse_network_definition(pair: pest::iterators::Pair<Rule>) -> AthenaResult<N
         attachable,
         encrypted,
         ingress,
+        subnet,
+        location,
     })
 }
 
+fn source_location(pair: &pest::iterators::Pair<Rule>) -> Option<SourceLocation> {
+    Some(SourceLocation {
+        line: pair.line_col().0,
+        file: None,
+    })
+}
+
+/// Parse a `SUBNET` in CIDR notation, e.g. `172.28.0.0/16` or `fd00:1::/64`
+fn parse_subnet(pair: pest::iterators::Pair<Rule>) -> AthenaResult<String> {
+    let value = clean_string_value(pair.as_str());
+    let (line, column) = pair.line_col();
+
+    if parse_cidr(&value).is_none() {
+        return Err(AthenaError::parse_error_enhanced(
+            EnhancedParseError::new(format!("Invalid SUBNET '{value}'"))
+                .with_location(line, column)
+                .with_suggestion("Use CIDR notation, e.g., SUBNET \"172.28.0.0/16\"".to_string()),
+        ));
+    }
+    Ok(value)
+}
+
+fn parse_network_attachment(pair: pest::iterators::Pair<Rule>) -> AthenaResult<NetworkAttachment> {
+    let (line, column) = pair.line_col();
+    let mut inner = pair.into_inner();
+    let name_pair = inner.next().ok_or_else(|| {
+        AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing network in ON NETWORK".to_string()))
+    })?;
+    let mut attachment = NetworkAttachment::new(parse_resource_name(name_pair, ResourceKind::Network)?);
+
+    for option in inner {
+        let rule = option.as_rule();
+        let Some(value_pair) = option.into_inner().next() else {
+            continue;
+        };
+        match rule {
+            Rule::network_alias => {
+                let alias = clean_string_value(value_pair.as_str());
+                if alias.is_empty() || alias.contains(char::is_whitespace) {
+                    let (line, column) = value_pair.line_col();
+                    return Err(AthenaError::parse_error_enhanced(
+                        EnhancedParseError::new(format!("Invalid network alias '{alias}'"))
+                            .with_location(line, column)
+                            .with_suggestion("Use a DNS name without spaces, e.g., ALIAS \"db.legacy.local\"".to_string()),
+                    ));
+                }
+                if !attachment.aliases.contains(&alias) {
+                    attachment.aliases.push(alias);
+                }
+            }
+            Rule::network_ipv4 => {
+                let address = clean_string_value(value_pair.as_str());
+                if address.parse::<Ipv4Addr>().is_err() {
+                    let (line, column) = value_pair.line_col();
+                    return Err(AthenaError::parse_error_enhanced(
+                        EnhancedParseError::new(format!("Invalid IPV4 address '{address}'"))
+                            .with_location(line, column)
+                            .with_suggestion("Use an IPv4 literal, e.g., IPV4 \"172.28.0.10\"".to_string()),
+                    ));
+                }
+                if attachment.ipv4_address.replace(address).is_some() {
+                    return Err(AthenaError::parse_error_enhanced(
+                        EnhancedParseError::new(format!(
+                            "ON NETWORK {} sets IPV4 more than once",
+                            attachment.network
+                        ))
+                        .with_location(line, column)
+                        .with_suggestion("A container has one address per network; keep a single IPV4".to_string()),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(attachment)
+}
+
+/// Fold repeated `ON NETWORK` lines for the same network into one attachment
+fn add_network_attachment(service: &mut Service, attachment: NetworkAttachment) -> AthenaResult<()> {
+    let Some(existing) = service.networks.iter_mut().find(|n| n.network == attachment.network) else {
+        service.networks.push(attachment);
+        return Ok(());
+    };
+
+    for alias in attachment.aliases {
+        if !existing.aliases.contains(&alias) {
+            existing.aliases.push(alias);
+        }
+    }
+    if let Some(address) = attachment.ipv4_address {
+        if existing.ipv4_address.as_ref().is_some_and(|current| *current != address) {
+            return Err(AthenaError::parse_error_enhanced(
+                EnhancedParseError::new(format!(
+                    "Service '{}' has two IPV4 addresses on network '{}'",
+                    service.name, existing.network
+                ))
+                .with_suggestion("A container has one address per network; keep a single IPV4".to_string()),
+            ));
+        }
+        existing.ipv4_address = Some(address);
+    }
+    Ok(())
+}
+
+/// Report every name the file declares more than once, with the line of each
+/// declaration. The last one is what would have been generated. A service
+/// repeated in an ENVIRONMENT block or a later file is an override instead.
+fn check_duplicate_declarations<'i>(file: pest::iterators::Pair<'i, Rule>) -> AthenaResult<()> {
+    let mut declarations: Vec<(ResourceKind, &'i str, Vec<usize>)> = Vec::new();
+    let mut declare = |kind: ResourceKind, name: Option<pest::iterators::Pair<'i, Rule>>| {
+        let Some(name) = name else {
+            return;
+        };
+        let line = name.line_col().0;
+        match declarations.iter_mut().find(|(k, n, _)| *k == kind && *n == name.as_str()) {
+            Some((_, _, lines)) => lines.push(line),
+            None => declarations.push((kind, name.as_str(), vec![line])),
+        }
+    };
+
+    for section in file.into_inner() {
+        match section.as_rule() {
+            Rule::services_section => {
+                for service in section.into_inner().filter(|p| p.as_rule() == Rule::service) {
+                    declare(ResourceKind::Service, service.into_inner().next());
+                }
+            }
+            Rule::environment_section => {
+                for item in section.into_inner().flat_map(|item| item.into_inner()) {
+                    let kind = match item.as_rule() {
+                        Rule::network_name => ResourceKind::Network,
+                        Rule::volume_def => ResourceKind::Volume,
+                        Rule::secret_def => ResourceKind::Secret,
+                        _ => continue,
+                    };
+                    declare(kind, item.into_inner().next());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let duplicates: Vec<_> = declarations.iter().filter(|(_, _, lines)| lines.len() > 1).collect();
+    let problems: Vec<String> = duplicates
+        .iter()
+        .map(|(kind, name, lines)| {
+            let (last, earlier) = lines.split_last().expect("duplicates have several lines");
+            let earlier: Vec<String> = earlier.iter().map(usize::to_string).collect();
+            format!(
+                "{} '{name}' is declared on lines {} and {last}; the one on line {last} would win",
+                kind.title(),
+                earlier.join(", ")
+            )
+        })
+        .collect();
+    let message = match problems.as_slice() {
+        [] => return Ok(()),
+        [problem] => problem.clone(),
+        _ => format!("{} names are declared more than once:\n  - {}", problems.len(), problems.join("\n  - ")),
+    };
+    let services = duplicates
+        .iter()
+        .filter(|(kind, _, _)| *kind == ResourceKind::Service)
+        .map(|(_, name, _)| name.to_string())
+        .collect();
+
+    Err(AthenaError::validation_error_enhanced(
+        EnhancedValidationError::new(message)
+            .with_suggestion(
+                "Rename or remove the extra declarations; to change a service for one stage, override it in an ENVIRONMENT block or a later file".to_string(),
+            )
+            .with_services(services),
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResourceKind {
+    Service,
+    Network,
+    Volume,
+    Profile,
+    Secret,
+}
+
+impl ResourceKind {
+    fn label(self) -> &'static str {
+        match self {
+            ResourceKind::Service => "service",
+            ResourceKind::Network => "network",
+            ResourceKind::Volume => "volume",
+            ResourceKind::Profile => "profile",
+            ResourceKind::Secret => "secret",
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            ResourceKind::Service => "Service",
+            ResourceKind::Network => "Network",
+            ResourceKind::Volume => "Volume",
+            ResourceKind::Profile => "Profile",
+            ResourceKind::Secret => "Secret",
+        }
+    }
+}
+
+/// Check a name against Docker's rules: `[a-zA-Z0-9._-]+` for services, and
+/// additionally an alphanumeric first character for networks, volumes and profiles.
+fn parse_resource_name(pair: pest::iterators::Pair<Rule>, kind: ResourceKind) -> AthenaResult<String> {
+    let name = pair.as_str();
+    let (line, column) = pair.line_col();
+
+    for (position, c) in name.chars().enumerate() {
+        let allowed = c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-');
+        let bad_first = position == 0 && kind != ResourceKind::Service && !c.is_ascii_alphanumeric();
+
+        if !allowed || bad_first {
+            let message = if allowed {
+                format!(
+                    "Invalid {} name '{name}': must start with a letter or digit, not '{c}'",
+                    kind.label()
+                )
+            } else {
+                format!(
+                    "Invalid character '{c}' in {} name '{name}' at position {}",
+                    kind.label(),
+                    position + 1
+                )
+            };
+            return Err(AthenaError::parse_error_enhanced(
+                EnhancedParseError::new(message)
+                    .with_location(line, column + position)
+                    .with_suggestion(format!(
+                        "{} names may only contain letters, digits, '.', '_' and '-'",
+                        kind.title()
+                    )),
+            ));
+        }
+    }
+
+    Ok(name.to_string())
+}
+
+/// Container names follow Docker's `[a-zA-Z0-9][a-zA-Z0-9_.-]*` rule
+fn parse_container_name(pair: pest::iterators::Pair<Rule>) -> AthenaResult<String> {
+    let (line, column) = pair.line_col();
+    let raw = pair.as_str();
+    let name = clean_string_value(raw);
+    let quote_offset = usize::from(raw.starts_with('"'));
+
+    for (position, c) in name.chars().enumerate() {
+        let allowed = c.is_ascii_alphanumeric() || (position > 0 && matches!(c, '.' | '_' | '-'));
+        if !allowed {
+            return Err(AthenaError::parse_error_enhanced(
+                EnhancedParseError::new(format!(
+                    "Invalid character '{c}' in container name '{name}' at position {}",
+                    position + 1
+                ))
+                .with_location(line, column + quote_offset + position)
+                .with_suggestion(
+                    "Container names must start with a letter or digit and may only contain letters, digits, '.', '_' and '-'".to_string(),
+                ),
+            ));
+        }
+    }
+
+    if name.is_empty() {
+        return Err(AthenaError::parse_error_enhanced(
+            EnhancedParseError::new("Container name cannot be empty".to_string()).with_location(line, column),
+        ));
+    }
+
+    Ok(name)
+}
+
 fn parse_services_section(pair: pest::iterators::Pair<Rule>) -> AthenaResult<ServicesSection> {
     let mut services = Vec::new();
+    let mut errors = Vec::new();
 
+    // Keep going past a broken service so every error is reported at once
     for inner_pair in pair.into_inner() {
         if let Rule::service = inner_pair.as_rule() {
-            services.push(parse_service(inner_pair)?);
+            match parse_service(inner_pair) {
+                Ok(service) => services.push(service),
+                Err(e) => errors.push(e),
+            }
+        }
+    }
+
+    match combine_errors(errors) {
+        Some(error) => Err(error),
+        None => Ok(ServicesSection { services }),
+    }
+}
+
+fn parse_environment_override(pair: pest::iterators::Pair<Rule>) -> AthenaResult<EnvironmentOverride> {
+    let mut inner = pair.into_inner();
+    let name_pair = inner
+        .next()
+        .ok_or_else(|| AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing environment name".to_string())))?;
+    let (line, column) = name_pair.line_col();
+    let name = clean_string_value(name_pair.as_str());
+
+    // The name becomes a directory with `--all-envs`, so keep it to a safe set
+    let valid = name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if !valid {
+        return Err(AthenaError::parse_error_enhanced(
+            EnhancedParseError::new(format!("Invalid environment name '{name}'"))
+                .with_location(line, column)
+                .with_suggestion(
+                    "Environment names start with a letter or digit and contain only letters, digits, '.', '_' and '-'"
+                        .to_string(),
+                ),
+        ));
+    }
+
+    let mut services = Vec::new();
+    let mut errors = Vec::new();
+    for service_pair in inner.filter(|p| p.as_rule() == Rule::service) {
+        match parse_service(service_pair) {
+            Ok(service) => services.push(service),
+            Err(e) => errors.push(e),
         }
     }
 
-    Ok(ServicesSection { services })
+    match combine_errors(errors) {
+        Some(error) => Err(error),
+        None => Ok(EnvironmentOverride { name, services }),
+    }
+}
+
+fn parse_external_service(pair: pest::iterators::Pair<Rule>) -> AthenaResult<ExternalService> {
+    let mut inner = pair.into_inner();
+    let (Some(name_pair), Some(host_pair)) = (inner.next(), inner.next()) else {
+        return Err(AthenaError::parse_error_enhanced(EnhancedParseError::new(
+            "EXTERNAL SERVICE needs a name and a HOST".to_string(),
+        )));
+    };
+
+    let port = inner
+        .next()
+        .map(|port_pair| {
+            let (line, column) = port_pair.line_col();
+            port_pair.as_str().parse::<u16>().map_err(|_| {
+                AthenaError::parse_error_enhanced(
+                    EnhancedParseError::new(format!("Invalid port '{}' in EXTERNAL SERVICE", port_pair.as_str()))
+                        .with_location(line, column)
+                        .with_suggestion("Use a port between 1 and 65535".to_string()),
+                )
+            })
+        })
+        .transpose()?;
+
+    Ok(ExternalService {
+        name: parse_resource_name(name_pair, ResourceKind::Service)?,
+        host: clean_string_value(host_pair.as_str()),
+        port,
+    })
+}
+
+/// The first error, with any further parse errors attached to it
+fn combine_errors(errors: Vec<AthenaError>) -> Option<AthenaError> {
+    let mut errors = errors.into_iter();
+    match errors.next()? {
+        AthenaError::ParseError(first) => {
+            let others = errors
+                .filter_map(|e| match e {
+                    AthenaError::ParseError(e) => Some(*e),
+                    _ => None,
+                })
+                .collect();
+            Some(AthenaError::parse_error_enhanced(first.with_others(others)))
+        }
+        other => Some(other),
+    }
 }
 
 fn parse_service(pair: pest::iterators::Pair<Rule>) -> AthenaResult<Service> {
     let mut service_name = None;
     let mut service = Service::new(String::new());
+    service.location = source_location(&pair);
 
     for inner_pair in pair.into_inner() {
         match inner_pair.as_rule() {
             Rule::service_name => {
-                service_name = Some(inner_pair.as_str().to_string());
+                if let Some(name_pair) = inner_pair.into_inner().next() {
+                    service_name = Some(parse_resource_name(name_pair, ResourceKind::Service)?);
+                }
             }
             Rule::service_items => {
                 for item_pair in inner_pair.into_inner() {
@@ -255,7 +796,7 @@ fn parse_service(pair: pest::iterators::Pair<Rule>) -> AthenaResult<Service> {
     }
 
     let service_name = service_name.ok_or_else(|| 
-        AthenaError::ParseError(EnhancedParseError::new("Missing service name".to_string()))
+        AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing service name".to_string()))
     )?;
 
     service.name = service_name;
@@ -274,19 +815,80 @@ fn parse_service_item(pair: pest::iterators::Pair<Rule>, service: &mut Service)
                 service.ports.push(parse_port_mapping(inner_pair)?);
             }
             Rule::env_variable => {
-                service.environment.push(parse_env_variable(inner_pair)?);
+                let allow_secret = has_trailing_comment(&inner_pair, ALLOW_SECRET_MARKER);
+                let line = inner_pair.line_col().0;
+                let variable = parse_env_variable(inner_pair)?;
+                if allow_secret {
+                    service.allowed_secrets.push(variable.key().to_string());
+                }
+                service.env_lines.insert(variable.key().to_string(), line);
+                service.environment.push(variable);
             }
             Rule::command_line => {
                 if let Some(cmd_pair) = inner_pair.into_inner().next() {
-                    service.command = Some(clean_string_value(cmd_pair.as_str()));
+                    service.command = Some(parse_command_value(cmd_pair)?);
+                }
+            }
+            Rule::entrypoint => {
+                if let Some(cmd_pair) = inner_pair.into_inner().next() {
+                    service.entrypoint = Some(parse_command_value(cmd_pair)?);
+                }
+            }
+            Rule::working_dir => {
+                if let Some(dir_pair) = inner_pair.into_inner().next() {
+                    service.working_dir = Some(clean_string_value(dir_pair.as_str()));
+                }
+            }
+            Rule::user => {
+                if let Some(user_pair) = inner_pair.into_inner().next() {
+                    service.user = Some(clean_string_value(user_pair.as_str()));
+                }
+            }
+            Rule::init_flag => {
+                service.init = parse_boolean_flag(inner_pair);
+            }
+            Rule::tty_flag => {
+                service.tty = parse_boolean_flag(inner_pair);
+            }
+            Rule::stdin_open_flag => {
+                service.stdin_open = parse_boolean_flag(inner_pair);
+            }
+            Rule::expose_via => {
+                let mut inner = inner_pair.into_inner();
+                if let (Some(_proxy_pair), Some(path_pair)) = (inner.next(), inner.next()) {
+                    service.expose_via = Some(ProxyExposure {
+                        proxy: ReverseProxy::Nginx,
+                        path: clean_string_value(path_pair.as_str()),
+                    });
+                }
+            }
+            Rule::watch => {
+                service.watch.push(parse_watch(inner_pair)?);
+            }
+            Rule::stop_grace_period => {
+                if let Some(period_pair) = inner_pair.into_inner().next() {
+                    service.stop_grace_period = Some(parse_duration(&period_pair)?);
+                }
+            }
+            Rule::stop_signal => {
+                if let Some(signal_pair) = inner_pair.into_inner().next() {
+                    service.stop_signal = Some(parse_stop_signal(&signal_pair)?);
                 }
             }
             Rule::volume_mapping => {
                 service.volumes.push(parse_volume_mapping(inner_pair)?);
             }
             Rule::depends_on => {
-                if let Some(dep_pair) = inner_pair.into_inner().next() {
-                    service.depends_on.push(dep_pair.as_str().to_string());
+                let mut parts = inner_pair.into_inner();
+                if let Some(dep_pair) = parts.next() {
+                    let mut dependency =
+                        Dependency::new(parse_resource_name(dep_pair, ResourceKind::Service)?);
+                    dependency.condition = parts.next().map(|condition| match condition.as_str() {
+                        "HEALTHY" => DependencyCondition::Healthy,
+                        "COMPLETED" => DependencyCondition::Completed,
+                        _ => DependencyCondition::Started,
+                    });
+                    service.depends_on.push(dependency);
                 }
             }
             Rule::health_check => {
@@ -297,9 +899,64 @@ fn parse_service_item(pair: pest::iterators::Pair<Rule>, service: &mut Service)
             Rule::restart_policy => {
                 service.restart = Some(parse_restart_policy(inner_pair)?);
             }
+            Rule::pull_policy => {
+                service.pull_policy = Some(parse_pull_policy(inner_pair)?);
+            }
+            Rule::service_kind => {
+                service.kind = Some(match parse_enum_word(inner_pair, "service kind", &["service", "job"])? {
+                    "job" => ServiceKind::Job,
+                    _ => ServiceKind::Service,
+                });
+            }
+            Rule::pid_mode => {
+                parse_enum_word(inner_pair, "PID mode", &["host"])?;
+                service.pid = Some(PidMode::Host);
+            }
+            Rule::ipc_mode => {
+                service.ipc = Some(match parse_enum_word(inner_pair, "IPC mode", &["host", "shareable"])? {
+                    "host" => IpcMode::Host,
+                    _ => IpcMode::Shareable,
+                });
+            }
+            Rule::network_mode => {
+                service.network_mode = Some(parse_network_mode(inner_pair)?);
+            }
+            Rule::preset => {
+                let mut inner = inner_pair.into_inner();
+                if let Some(name_pair) = inner.next() {
+                    let names = preset_names();
+                    let (line, column) = name_pair.line_col();
+                    let name = name_pair.as_str();
+                    if find_preset(name).is_none() {
+                        return Err(AthenaError::parse_error_enhanced(
+                            EnhancedParseError::new(format!("Invalid preset '{name}'"))
+                                .with_location(line, column)
+                                .with_suggestion(did_you_mean(name, &names)),
+                        ));
+                    }
+                    service.preset = Some(ServicePreset {
+                        name: name.to_string(),
+                        version: inner.next().map(|v| clean_string_value(v.as_str())),
+                    });
+                }
+            }
+            Rule::allow_unresolved_hosts => {
+                service.allow_unresolved_hosts = true;
+            }
+            Rule::allow_docker_socket => {
+                service.allow_docker_socket = true;
+            }
+            Rule::container_name => {
+                if let Some(name_pair) = inner_pair.into_inner().next() {
+                    service.container_name = Some(parse_container_name(name_pair)?);
+                }
+            }
             Rule::resource_limits => {
                 service.resources = Some(parse_resource_limits(inner_pair)?);
             }
+            Rule::resource_reservation => {
+                service.reservations = Some(parse_resource_reservation(inner_pair)?);
+            }
             Rule::build_args => {
                 service.build_args = Some(parse_build_args(inner_pair)?);
             }
@@ -309,16 +966,20 @@ fn parse_service_item(pair: pest::iterators::Pair<Rule>, service: &mut Service)
                     let (line, column) = replicas_pair.line_col();
                     
                     let replicas = replicas_str.parse::<u32>()
-                        .map_err(|_| {
+                        .ok()
+                        .filter(|&replicas| replicas > 0)
+                        .ok_or_else(|| {
                             let suggestion = if replicas_str.parse::<i32>().is_ok() && replicas_str.starts_with('-') {
                                 "Replicas must be a positive number. Use a value like: 1, 2, 3, 5, etc.".to_string()
+                            } else if replicas_str.trim_start_matches('0').is_empty() {
+                                "Replicas must be at least 1. To run a service on every node, use DEPLOY-MODE global instead".to_string()
                             } else if replicas_str.len() > 10 {
                                 "Replicas number is too large. Use a reasonable value like: 1, 2, 3, 5, 10, etc.".to_string()
                             } else {
                                 format!("'{replicas_str}' is not a valid number. Use a positive integer like: 1, 2, 3, 5, 10, etc.")
                             };
                             
-                            AthenaError::ParseError(
+                            AthenaError::parse_error_enhanced(
                                 EnhancedParseError::new("Invalid replicas number".to_string())
                                     .with_location(line, column)
                                     .with_suggestion(suggestion)
@@ -337,6 +998,67 @@ fn parse_service_item(pair: pest::iterators::Pair<Rule>, service: &mut Service)
                 service.swarm_config.get_or_insert_with(SwarmConfig::new)
                     .labels = Some(parse_swarm_labels(inner_pair)?);
             }
+            Rule::service_label => {
+                for label_pair in inner_pair.into_inner() {
+                    let mut parts = label_pair.into_inner();
+                    if let (Some(key), Some(value)) = (parts.next(), parts.next()) {
+                        service.labels.insert(key.as_str().to_string(), clean_string_value(value.as_str()));
+                    }
+                }
+            }
+            Rule::swarm_mode => {
+                if let Some(mode_pair) = inner_pair.into_inner().next() {
+                    service.swarm_config.get_or_insert_with(SwarmConfig::new)
+                        .mode = Some(match mode_pair.as_str() {
+                            "global" => DeployMode::Global,
+                            _ => DeployMode::Replicated,
+                        });
+                }
+            }
+            Rule::dns_server => {
+                if let Some(dns_pair) = inner_pair.into_inner().next() {
+                    service.dns.push(parse_ip_literal(dns_pair, "DNS")?);
+                }
+            }
+            Rule::extra_host => {
+                let mut inner = inner_pair.into_inner();
+                if let (Some(host_pair), Some(ip_pair)) = (inner.next(), inner.next()) {
+                    service.extra_hosts.push(ExtraHost {
+                        hostname: clean_string_value(host_pair.as_str()),
+                        ip: parse_ip_literal(ip_pair, "EXTRA-HOST")?,
+                    });
+                }
+            }
+            Rule::profile => {
+                if let Some(profile_pair) = inner_pair.into_inner().next() {
+                    let profile = parse_resource_name(profile_pair, ResourceKind::Profile)?;
+                    if !service.profiles.contains(&profile) {
+                        service.profiles.push(profile);
+                    }
+                }
+            }
+            Rule::uses => {
+                if let Some(name_pair) = inner_pair.into_inner().next() {
+                    let name = parse_resource_name(name_pair, ResourceKind::Service)?;
+                    if !service.uses.contains(&name) {
+                        service.uses.push(name);
+                    }
+                }
+            }
+            Rule::network_attachment => {
+                let attachment = parse_network_attachment(inner_pair)?;
+                add_network_attachment(service, attachment)?;
+            }
+            Rule::platform => {
+                if let Some(platform_pair) = inner_pair.into_inner().next() {
+                    service.platform = Some(parse_platform(platform_pair)?);
+                }
+            }
+            Rule::hostname => {
+                if let Some(hostname_pair) = inner_pair.into_inner().next() {
+                    service.hostname = Some(clean_string_value(hostname_pair.as_str()));
+                }
+            }
             _ => {}
         }
     }
@@ -345,23 +1067,39 @@ fn parse_service_item(pair: pest::iterators::Pair<Rule>, service: &mut Service)
 
 fn parse_port_mapping(pair: pest::iterators::Pair<Rule>) -> AthenaResult<PortMapping> {
     let mut inner = pair.into_inner();
-    let host_port = inner.next()
-        .ok_or_else(|| AthenaError::ParseError(EnhancedParseError::new("Missing host port".to_string())))?
-        .as_str()
-        .parse::<u16>()
-        .map_err(|_| AthenaError::ParseError(EnhancedParseError::new("Invalid host port".to_string())))?;
+    let host_pair = inner.next()
+        .ok_or_else(|| AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing host port".to_string())))?;
+    let (line, column) = host_pair.line_col();
+    let host_range = host_pair.as_str().to_string();
+
+    let mut host_ports = host_pair.into_inner().map(|p| {
+        p.as_str()
+            .parse::<u16>()
+            .map_err(|_| AthenaError::parse_error_enhanced(EnhancedParseError::new("Invalid host port".to_string())))
+    });
+    let host_port = host_ports.next().transpose()?
+        .ok_or_else(|| AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing host port".to_string())))?;
+    let host_port_end = host_ports.next().transpose()?;
+
+    if host_port_end.is_some_and(|end| end <= host_port) {
+        return Err(AthenaError::parse_error_enhanced(
+            EnhancedParseError::new(format!("Invalid host port range '{host_range}'"))
+                .with_location(line, column)
+                .with_suggestion("The range end must be greater than its start, e.g., PORT-MAPPING 8080-8082 TO 80".to_string()),
+        ));
+    }
 
     let container_port = inner.next()
-        .ok_or_else(|| AthenaError::ParseError(EnhancedParseError::new("Missing container port".to_string())))?
+        .ok_or_else(|| AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing container port".to_string())))?
         .as_str()
         .parse::<u16>()
-        .map_err(|_| AthenaError::ParseError(EnhancedParseError::new("Invalid container port".to_string())))?;
+        .map_err(|_| AthenaError::parse_error_enhanced(EnhancedParseError::new("Invalid container port".to_string())))?;
 
     let mut protocol = Protocol::Tcp;
     if let Some(protocol_pair) = inner.next() {
         if let Rule::port_protocol = protocol_pair.as_rule() {
             let proto_str = protocol_pair.into_inner().next()
-                .ok_or_else(|| AthenaError::ParseError(EnhancedParseError::new("Missing protocol".to_string())))?
+                .ok_or_else(|| AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing protocol".to_string())))?
                 .as_str();
             protocol = match proto_str {
                 "tcp" => Protocol::Tcp,
@@ -371,74 +1109,422 @@ fn parse_port_mapping(pair: pest::iterators::Pair<Rule>) -> AthenaResult<PortMap
         }
     }
 
-    Ok(PortMapping {
-        host_port,
-        container_port,
-        protocol,
+    Ok(PortMapping {
+        host_port,
+        host_port_end,
+        container_port,
+        protocol,
+    })
+}
+
+fn parse_env_variable(pair: pest::iterators::Pair<Rule>) -> AthenaResult<EnvironmentVariable> {
+    let inner = pair.into_inner().next()
+        .ok_or_else(|| AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing environment variable".to_string())))?;
+
+    match inner.as_rule() {
+        Rule::template_var => {
+            let var_name = inner.as_str().trim_start_matches("{{").trim_end_matches("}}").to_string();
+            Ok(EnvironmentVariable::Template(var_name))
+        }
+        Rule::string_value => {
+            Ok(EnvironmentVariable::Literal(clean_string_value(inner.as_str())))
+        }
+        _ => Err(AthenaError::parse_error_enhanced(EnhancedParseError::new("Invalid environment variable".to_string())))
+    }
+}
+
+/// Trailing `// athena:allow-secret` on an ENV-VARIABLE line
+const ALLOW_SECRET_MARKER: &str = "athena:allow-secret";
+
+/// Comments are dropped by the grammar, so look at the rest of the source line
+fn has_trailing_comment(pair: &pest::iterators::Pair<Rule>, marker: &str) -> bool {
+    let span = pair.as_span();
+    let rest = &span.get_input()[span.end()..];
+    rest.lines()
+        .next()
+        .and_then(|line| line.trim_start().strip_prefix("//"))
+        .is_some_and(|comment| comment.trim_start().starts_with(marker))
+}
+
+/// `// athena:ignore ATH005` on the line above a SERVICE or one of its directives
+const IGNORE_MARKER: &str = "athena:ignore";
+
+/// Comments are dropped by the grammar, so suppressions are read from the raw
+/// lines. A comment inside a SERVICE block, or right above its SERVICE line,
+/// applies to that service; anywhere else it applies to the whole file.
+fn collect_suppressions(input: &str) -> AthenaResult<Vec<Suppression>> {
+    let lines: Vec<&str> = input.lines().collect();
+    let mut suppressions = Vec::new();
+    let mut current_service: Option<&str> = None;
+
+    for (index, line) in lines.iter().enumerate() {
+        let trimmed = line.trim_start();
+        let mut words = trimmed.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("SERVICE"), Some(name)) => current_service = Some(name),
+            (Some("END"), Some("SERVICE")) => current_service = None,
+            _ => {}
+        }
+
+        let Some(codes) = trimmed
+            .strip_prefix("//")
+            .and_then(|comment| comment.trim_start().strip_prefix(IGNORE_MARKER))
+        else {
+            continue;
+        };
+        let service = current_service.or_else(|| {
+            let next = lines[index + 1..]
+                .iter()
+                .map(|l| l.trim())
+                .find(|l| !l.is_empty() && !l.starts_with("//"))?;
+            next.strip_prefix("SERVICE ").map(str::trim)
+        });
+
+        let codes: Vec<&str> = codes
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|code| !code.is_empty())
+            .collect();
+        if codes.is_empty() {
+            return Err(suppression_error("athena:ignore needs a warning code".to_string(), index, line));
+        }
+        for code in codes {
+            if !WARNING_CODES.iter().any(|entry| entry.code == code) {
+                return Err(suppression_error(format!("Unknown warning code '{code}'"), index, line));
+            }
+            suppressions.push(Suppression {
+                code: code.to_string(),
+                service: service.map(str::to_string),
+                line: index + 1,
+                file: None,
+            });
+        }
+    }
+
+    Ok(suppressions)
+}
+
+fn suppression_error(message: String, index: usize, line: &str) -> AthenaError {
+    let codes: Vec<&str> = WARNING_CODES.iter().map(|entry| entry.code).collect();
+    AthenaError::parse_error_enhanced(
+        EnhancedParseError::new(message)
+            .with_location(index + 1, line.len() - line.trim_start().len() + 1)
+            .with_suggestion(format!(
+                "Write `// athena:ignore <CODE>` with one of: {}",
+                codes.join(", ")
+            )),
+    )
+}
+
+fn parse_boolean_flag(pair: pest::iterators::Pair<Rule>) -> Option<bool> {
+    pair.into_inner().next().map(|value| value.as_str() == "TRUE")
+}
+
+fn parse_command_value(pair: pest::iterators::Pair<Rule>) -> AthenaResult<ContainerCommand> {
+    let inner = pair.into_inner().next()
+        .ok_or_else(|| AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing command".to_string())))?;
+
+    match inner.as_rule() {
+        Rule::exec_array => {
+            let args = inner
+                .into_inner()
+                .map(|arg| unescape_exec_arg(arg.as_str()))
+                .collect();
+            Ok(ContainerCommand::Exec(args))
+        }
+        Rule::string_value => Ok(ContainerCommand::Shell(clean_string_value(inner.as_str()))),
+        _ => Err(AthenaError::parse_error_enhanced(EnhancedParseError::new("Invalid command".to_string())))
+    }
+}
+
+fn parse_volume_mapping(pair: pest::iterators::Pair<Rule>) -> AthenaResult<VolumeMapping> {
+    let (line, column) = pair.line_col();
+    let mut inner = pair.into_inner();
+    let host_path = clean_string_value(
+        inner.next()
+            .ok_or_else(|| AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing host path".to_string())))?
+            .as_str()
+    );
+
+    let container_path = clean_string_value(
+        inner.next()
+            .ok_or_else(|| AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing container path".to_string())))?
+            .as_str()
+    );
+
+    let mut options = Vec::new();
+    if let Some(options_pair) = inner.next() {
+        if let Rule::volume_options = options_pair.as_rule() {
+            options = parse_volume_options(options_pair)?;
+        }
+    }
+
+    let mapping = VolumeMapping {
+        host_path,
+        container_path,
+        options,
+    };
+    check_volume_option_combination(&mapping).map_err(|(message, suggestion)| {
+        AthenaError::parse_error_enhanced(
+            EnhancedParseError::new(message)
+                .with_location(line, column)
+                .with_suggestion(suggestion),
+        )
+    })?;
+
+    Ok(mapping)
+}
+
+fn parse_volume_options(pair: pest::iterators::Pair<Rule>) -> AthenaResult<Vec<String>> {
+    let allowed = [
+        VolumeMapping::ACCESS_MODES,
+        VolumeMapping::SELINUX_LABELS,
+        VolumeMapping::CONSISTENCY,
+        VolumeMapping::PROPAGATION,
+        &[VolumeMapping::NOCOPY],
+    ]
+    .concat();
+
+    let mut options = Vec::new();
+    for option_pair in pair.into_inner() {
+        let value = option_pair.as_str();
+        if !allowed.contains(&value) {
+            let (line, column) = option_pair.line_col();
+            return Err(AthenaError::parse_error_enhanced(
+                EnhancedParseError::new(format!("Invalid volume option '{value}'"))
+                    .with_location(line, column)
+                    .with_suggestion(did_you_mean(value, &allowed)),
+            ));
+        }
+        options.push(value.to_string());
+    }
+
+    Ok(options)
+}
+
+/// Reject options that contradict each other or don't apply to the mount type
+fn check_volume_option_combination(mapping: &VolumeMapping) -> Result<(), (String, String)> {
+    let groups = [
+        ("access mode", VolumeMapping::ACCESS_MODES),
+        ("SELinux label", VolumeMapping::SELINUX_LABELS),
+        ("consistency", VolumeMapping::CONSISTENCY),
+        ("propagation", VolumeMapping::PROPAGATION),
+    ];
+    for (what, group) in groups {
+        let chosen: Vec<&str> = mapping
+            .options
+            .iter()
+            .map(String::as_str)
+            .filter(|option| group.contains(option))
+            .collect();
+        if chosen.len() > 1 {
+            return Err((
+                format!("Conflicting volume options '{}'", chosen.join("' and '")),
+                format!("Pick a single {what} for '{}'", mapping.container_path),
+            ));
+        }
+    }
+
+    if is_bind_mount(&mapping.host_path) {
+        if mapping.nocopy() {
+            return Err((
+                format!("Volume option 'nocopy' does not apply to bind mount '{}'", mapping.host_path),
+                "nocopy only affects named volumes; remove it".to_string(),
+            ));
+        }
+    } else if let Some(option) = mapping.consistency().or(mapping.propagation()) {
+        return Err((
+            format!("Volume option '{option}' does not apply to named volume '{}'", mapping.host_path),
+            "Consistency and propagation only apply to bind mounts such as \"./data\"".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+const PLATFORM_OS: &[&str] = &["linux", "windows"];
+const PLATFORM_ARCH: &[&str] = &["386", "amd64", "arm", "arm64", "ppc64le", "riscv64", "s390x"];
+
+fn parse_platform(pair: pest::iterators::Pair<Rule>) -> AthenaResult<String> {
+    let (line, column) = pair.line_col();
+    let platform = clean_string_value(pair.as_str());
+    check_platform(&platform).map_err(|(message, suggestion)| {
+        AthenaError::parse_error_enhanced(
+            EnhancedParseError::new(message)
+                .with_location(line, column)
+                .with_suggestion(suggestion),
+        )
+    })?;
+    Ok(platform)
+}
+
+/// Check an `os/arch[/variant]` platform as accepted by Docker, e.g. `linux/amd64` or
+/// `linux/arm/v7`. Returns the message and suggestion on failure.
+pub fn check_platform(platform: &str) -> Result<(), (String, String)> {
+    let parts: Vec<&str> = platform.split('/').collect();
+    let (os, arch) = match parts.as_slice() {
+        [os, arch] | [os, arch, _] if !os.is_empty() && !arch.is_empty() => (*os, *arch),
+        _ => {
+            return Err((
+                format!("Invalid platform '{platform}'"),
+                "Use os/arch with an optional variant, e.g. \"linux/amd64\" or \"linux/arm/v7\"".to_string(),
+            ))
+        }
+    };
+
+    if !PLATFORM_OS.contains(&os) {
+        return Err((
+            format!("Invalid platform OS '{os}'"),
+            did_you_mean(os, PLATFORM_OS),
+        ));
+    }
+
+    // Names reported by `uname -m` rather than the ones Docker uses
+    let arch_alias = match arch {
+        "x86_64" | "x86-64" => Some("amd64"),
+        "aarch64" => Some("arm64"),
+        _ => None,
+    };
+    if let Some(alias) = arch_alias {
+        return Err((
+            format!("Invalid platform architecture '{arch}'"),
+            format!("Docker calls this architecture '{alias}'. Use \"{os}/{alias}\""),
+        ));
+    }
+    if !PLATFORM_ARCH.contains(&arch) {
+        return Err((
+            format!("Invalid platform architecture '{arch}'"),
+            did_you_mean(arch, PLATFORM_ARCH),
+        ));
+    }
+
+    Ok(())
+}
+
+fn parse_watch(pair: pest::iterators::Pair<Rule>) -> AthenaResult<WatchRule> {
+    let (line, column) = pair.line_col();
+    let mut inner = pair.into_inner();
+    let (Some(action_pair), Some(path_pair)) = (inner.next(), inner.next()) else {
+        return Err(AthenaError::parse_error_enhanced(EnhancedParseError::new(
+            "Missing WATCH action or path".to_string(),
+        )));
+    };
+
+    let action = match action_pair.as_str() {
+        "SYNC+RESTART" => WatchAction::SyncRestart,
+        "REBUILD" => WatchAction::Rebuild,
+        _ => WatchAction::Sync,
+    };
+    let path = clean_string_value(path_pair.as_str());
+    let target = inner.next().map(|target| clean_string_value(target.as_str()));
+
+    if target.is_none() && action != WatchAction::Rebuild {
+        return Err(AthenaError::parse_error_enhanced(
+            EnhancedParseError::new(format!(
+                "WATCH {} \"{path}\" needs a container path to sync to",
+                action_pair.as_str()
+            ))
+            .with_location(line, column)
+            .with_suggestion(format!("Add the target: WATCH {} \"{path}\" TO \"/app\"", action_pair.as_str())),
+        ));
+    }
+
+    Ok(WatchRule { action, path, target })
+}
+
+fn parse_restart_policy(pair: pest::iterators::Pair<Rule>) -> AthenaResult<RestartPolicy> {
+    let value = parse_enum_word(pair, "restart policy", &["always", "unless-stopped", "on-failure", "no"])?;
+
+    Ok(match value {
+        "always" => RestartPolicy::Always,
+        "unless-stopped" => RestartPolicy::UnlessStopped,
+        "on-failure" => RestartPolicy::OnFailure,
+        _ => RestartPolicy::No,
+    })
+}
+
+fn parse_pull_policy(pair: pest::iterators::Pair<Rule>) -> AthenaResult<PullPolicy> {
+    let value = parse_enum_word(pair, "pull policy", &["always", "missing", "never"])?;
+
+    Ok(match value {
+        "always" => PullPolicy::Always,
+        "missing" => PullPolicy::Missing,
+        _ => PullPolicy::Never,
+    })
+}
+
+fn parse_network_mode(pair: pest::iterators::Pair<Rule>) -> AthenaResult<NetworkMode> {
+    let is_service = pair.as_str().contains("service:");
+    if is_service {
+        let name_pair = pair
+            .into_inner()
+            .next()
+            .and_then(|value| value.into_inner().next())
+            .ok_or_else(|| AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing service in NETWORK-MODE".to_string())))?;
+        return Ok(NetworkMode::Service(parse_resource_name(name_pair, ResourceKind::Service)?));
+    }
+
+    Ok(match parse_enum_word(pair, "network mode", &["host", "none"])? {
+        "host" => NetworkMode::Host,
+        _ => NetworkMode::None,
     })
 }
 
-fn parse_env_variable(pair: pest::iterators::Pair<Rule>) -> AthenaResult<EnvironmentVariable> {
-    let inner = pair.into_inner().next()
-        .ok_or_else(|| AthenaError::ParseError(EnhancedParseError::new("Missing environment variable".to_string())))?;
+/// Match the value of a keyword directive against its allowed words, suggesting
+/// the closest one when the value is a typo such as `unless_stopped`.
+fn parse_enum_word(
+    pair: pest::iterators::Pair<Rule>,
+    what: &str,
+    allowed: &[&'static str],
+) -> AthenaResult<&'static str> {
+    let value_pair = pair
+        .into_inner()
+        .next()
+        .ok_or_else(|| AthenaError::parse_error_enhanced(EnhancedParseError::new(format!("Missing {what}"))))?;
+    let value = value_pair.as_str();
 
-    match inner.as_rule() {
-        Rule::template_var => {
-            let var_name = inner.as_str().trim_start_matches("{{").trim_end_matches("}}").to_string();
-            Ok(EnvironmentVariable::Template(var_name))
-        }
-        Rule::string_value => {
-            Ok(EnvironmentVariable::Literal(clean_string_value(inner.as_str())))
-        }
-        _ => Err(AthenaError::ParseError(EnhancedParseError::new("Invalid environment variable".to_string())))
+    if let Some(word) = allowed.iter().find(|word| **word == value) {
+        return Ok(word);
     }
-}
 
-fn parse_volume_mapping(pair: pest::iterators::Pair<Rule>) -> AthenaResult<VolumeMapping> {
-    let mut inner = pair.into_inner();
-    let host_path = clean_string_value(
-        inner.next()
-            .ok_or_else(|| AthenaError::ParseError(EnhancedParseError::new("Missing host path".to_string())))?
-            .as_str()
-    );
+    let (line, column) = value_pair.line_col();
 
-    let container_path = clean_string_value(
-        inner.next()
-            .ok_or_else(|| AthenaError::ParseError(EnhancedParseError::new("Missing container path".to_string())))?
-            .as_str()
-    );
+    Err(AthenaError::parse_error_enhanced(
+        EnhancedParseError::new(format!("Invalid {what} '{value}'"))
+            .with_location(line, column)
+            .with_suggestion(did_you_mean(value, allowed)),
+    ))
+}
 
-    let mut options = Vec::new();
-    if let Some(options_pair) = inner.next() {
-        if let Rule::volume_options = options_pair.as_rule() {
-            for option_pair in options_pair.into_inner() {
-                if let Rule::volume_option = option_pair.as_rule() {
-                    options.push(option_pair.as_str().to_string());
-                }
-            }
-        }
-    }
+/// List the valid words, leading with the closest one when `value` looks like a typo
+fn did_you_mean(value: &str, allowed: &[&str]) -> String {
+    let normalized = value.to_lowercase().replace('_', "-");
+    let closest = allowed
+        .iter()
+        .map(|word| (edit_distance(&normalized, word), *word))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance);
 
-    Ok(VolumeMapping {
-        host_path,
-        container_path,
-        options,
-    })
+    let valid = format!("Valid values: {}", allowed.join(", "));
+    match closest {
+        Some((_, word)) => format!("Did you mean '{word}'? {valid}"),
+        None => valid,
+    }
 }
 
-fn parse_restart_policy(pair: pest::iterators::Pair<Rule>) -> AthenaResult<RestartPolicy> {
-    let mut inner = pair.into_inner();
-    let policy_str = inner.next()
-        .ok_or_else(|| AthenaError::ParseError(EnhancedParseError::new("Missing restart policy".to_string())))?
-        .as_str();
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
 
-    match policy_str {
-        "always" => Ok(RestartPolicy::Always),
-        "unless-stopped" => Ok(RestartPolicy::UnlessStopped),
-        "on-failure" => Ok(RestartPolicy::OnFailure),
-        "no" => Ok(RestartPolicy::No),
-        _ => Err(AthenaError::ParseError(EnhancedParseError::new(format!("Invalid restart policy: {policy_str}"))))
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
     }
+
+    previous[b.len()]
 }
 
 fn parse_resource_limits(pair: pest::iterators::Pair<Rule>) -> AthenaResult<ResourceLimits> {
@@ -447,18 +1533,139 @@ fn parse_resource_limits(pair: pest::iterators::Pair<Rule>) -> AthenaResult<Reso
     // The grammar expects: "CPU" string_value "MEMORY" string_value
     // But pest parses only the string values, skipping keywords
     if inner_pairs.len() != 2 {
-        return Err(AthenaError::ParseError(EnhancedParseError::new(
+        return Err(AthenaError::parse_error_enhanced(EnhancedParseError::new(
             format!("Expected 2 values for resource limits, got {}", inner_pairs.len())
         )));
     }
     
     // First string_value is CPU, second is MEMORY
     let cpu = clean_string_value(inner_pairs[0].as_str());
-    let memory = clean_string_value(inner_pairs[1].as_str());
+    let memory = parse_byte_size(&inner_pairs[1], "RESOURCE-LIMITS MEMORY")?;
 
     Ok(ResourceLimits { cpu, memory })
 }
 
+fn parse_resource_reservation(pair: pest::iterators::Pair<Rule>) -> AthenaResult<ResourceReservations> {
+    let mut reservations = ResourceReservations::default();
+
+    for option in pair.into_inner() {
+        let (line, column) = option.line_col();
+        let rule = option.as_rule();
+        let Some(value) = option.into_inner().next() else {
+            continue;
+        };
+        let error = |message: String, suggestion: &str| {
+            AthenaError::parse_error_enhanced(
+                EnhancedParseError::new(message)
+                    .with_location(line, column)
+                    .with_suggestion(suggestion.to_string()),
+            )
+        };
+        let repeated = |keyword: &str| {
+            error(
+                format!("RESERVE {keyword} is given twice"),
+                "Give each of MEMORY and CPUS at most once, e.g. RESERVE MEMORY 256M CPUS 0.25",
+            )
+        };
+
+        match rule {
+            Rule::reserve_memory => {
+                if reservations.memory.is_some() {
+                    return Err(repeated("MEMORY"));
+                }
+                reservations.memory = Some(parse_byte_size(&value, "RESERVE MEMORY")?);
+            }
+            Rule::reserve_cpus => {
+                if reservations.cpus.is_some() {
+                    return Err(repeated("CPUS"));
+                }
+                let cpus = clean_string_value(value.as_str());
+                if !cpus.parse::<f64>().is_ok_and(|n| n.is_finite() && n > 0.0) {
+                    return Err(error(
+                        format!("RESERVE CPUS must be a positive number, got '{cpus}'"),
+                        "Use a decimal number of CPUs, e.g. RESERVE CPUS 0.25",
+                    ));
+                }
+                reservations.cpus = Some(cpus);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(reservations)
+}
+
+/// A `time_value`, through the parser every duration shares
+fn parse_duration(pair: &pest::iterators::Pair<Rule>) -> AthenaResult<Duration> {
+    pair.as_str().parse().map_err(|message: String| {
+        let (line, column) = pair.line_col();
+        AthenaError::parse_error_enhanced(
+            EnhancedParseError::new(message)
+                .with_location(line, column)
+                .with_suggestion(Duration::FORMS.to_string()),
+        )
+    })
+}
+
+/// Signal names `docker stop` understands, without the SIG prefix
+const SIGNAL_NAMES: &[&str] = &[
+    "ABRT", "ALRM", "BUS", "CHLD", "CONT", "FPE", "HUP", "ILL", "INT", "IO", "IOT", "KILL", "PIPE",
+    "POLL", "PROF", "PWR", "QUIT", "SEGV", "STKFLT", "STOP", "SYS", "TERM", "TRAP", "TSTP", "TTIN",
+    "TTOU", "URG", "USR1", "USR2", "VTALRM", "WINCH", "XCPU", "XFSZ",
+];
+
+/// `SIGQUIT`, `quit` or `3`; names are normalized to `SIGQUIT`, numbers kept
+fn parse_stop_signal(pair: &pest::iterators::Pair<Rule>) -> AthenaResult<String> {
+    let value = clean_string_value(pair.as_str());
+    if let Ok(number) = value.parse::<u8>() {
+        if (1..=64).contains(&number) {
+            return Ok(number.to_string());
+        }
+    }
+
+    let upper = value.to_ascii_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    // Real-time signals are written as offsets, e.g. SIGRTMIN+3
+    let realtime = ["RTMIN", "RTMAX"].iter().any(|base| {
+        name.strip_prefix(base).is_some_and(|offset| {
+            offset.is_empty()
+                || offset
+                    .strip_prefix(['+', '-'])
+                    .is_some_and(|n| n.parse::<u8>().is_ok())
+        })
+    });
+    if SIGNAL_NAMES.contains(&name) || realtime {
+        return Ok(format!("SIG{name}"));
+    }
+
+    let (line, column) = pair.line_col();
+    Err(AthenaError::parse_error_enhanced(
+        EnhancedParseError::new(format!("Invalid STOP-SIGNAL '{value}'"))
+            .with_location(line, column)
+            .with_suggestion(
+                "Use a signal name such as SIGTERM, SIGINT or SIGQUIT, or its number, e.g. STOP-SIGNAL SIGQUIT".to_string(),
+            ),
+    ))
+}
+
+/// A quoted or bare size such as "512M"; `directive` names it in errors
+fn parse_byte_size(pair: &pest::iterators::Pair<Rule>, directive: &str) -> AthenaResult<ByteSize> {
+    let (line, column) = pair.line_col();
+    let error = |message: String| {
+        AthenaError::parse_error_enhanced(
+            EnhancedParseError::new(message)
+                .with_location(line, column)
+                .with_suggestion(ByteSize::FORMS.to_string()),
+        )
+    };
+
+    let size: ByteSize = clean_string_value(pair.as_str()).parse().map_err(error)?;
+    if size.is_zero() {
+        return Err(error(format!("{directive} must be greater than zero")));
+    }
+    Ok(size)
+}
+
 fn parse_build_args(pair: pest::iterators::Pair<Rule>) -> AthenaResult<HashMap<String, String>> {
     let mut build_args = HashMap::new();
     
@@ -467,11 +1674,11 @@ fn parse_build_args(pair: pest::iterators::Pair<Rule>) -> AthenaResult<HashMap<S
             let mut arg_parts = inner_pair.into_inner();
             
             let key = arg_parts.next()
-                .ok_or_else(|| AthenaError::ParseError(EnhancedParseError::new("Missing build arg key".to_string())))?
+                .ok_or_else(|| AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing build arg key".to_string())))?
                 .as_str().to_string();
             
             let value = arg_parts.next()
-                .ok_or_else(|| AthenaError::ParseError(EnhancedParseError::new("Missing build arg value".to_string())))?
+                .ok_or_else(|| AthenaError::parse_error_enhanced(EnhancedParseError::new("Missing build arg value".to_string())))?
                 .as_str();
             
             build_args.insert(key, clean_string_value(value));
@@ -479,7 +1686,7 @@ fn parse_build_args(pair: pest::iterators::Pair<Rule>) -> AthenaResult<HashMap<S
     }
     
     if build_args.is_empty() {
-        return Err(AthenaError::ParseError(EnhancedParseError::new(
+        return Err(AthenaError::parse_error_enhanced(EnhancedParseError::new(
             "BUILD-ARGS must contain at least one key=value pair".to_string()
         )));
     }
@@ -506,14 +1713,14 @@ fn parse_update_config(pair: pest::iterators::Pair<Rule>) -> AthenaResult<Update
                                 format!("'{parallelism_str}' is not a valid number. Use a positive integer like: 1, 2, 3, 4, etc.")
                             };
                             
-                            AthenaError::ParseError(
+                            AthenaError::parse_error_enhanced(
                                 EnhancedParseError::new("Invalid parallelism number".to_string())
                                     .with_location(line, column)
                                     .with_suggestion(suggestion)
                             )
                         })?);
                 } else if option_str.starts_with("DELAY") && value_pair.as_rule() == Rule::time_value {
-                    update_config.delay = Some(value_pair.as_str().to_string());
+                    update_config.delay = Some(parse_duration(&value_pair)?);
                 } else if option_str.starts_with("FAILURE-ACTION") && value_pair.as_rule() == Rule::failure_action {
                     let action_str = value_pair.as_str();
                     let (line, column) = value_pair.line_col();
@@ -523,7 +1730,7 @@ fn parse_update_config(pair: pest::iterators::Pair<Rule>) -> AthenaResult<Update
                         "PAUSE" => FailureAction::Pause,
                         "ROLLBACK" => FailureAction::Rollback,
                         _ => {
-                            return Err(AthenaError::ParseError(
+                            return Err(AthenaError::parse_error_enhanced(
                                 EnhancedParseError::new(format!("Invalid failure action: {action_str}"))
                                     .with_location(line, column)
                                     .with_suggestion("Valid failure actions are: CONTINUE, PAUSE, ROLLBACK".to_string())
@@ -531,14 +1738,14 @@ fn parse_update_config(pair: pest::iterators::Pair<Rule>) -> AthenaResult<Update
                         }
                     });
                 } else if option_str.starts_with("MONITOR") && value_pair.as_rule() == Rule::time_value {
-                    update_config.monitor = Some(value_pair.as_str().to_string());
+                    update_config.monitor = Some(parse_duration(&value_pair)?);
                 } else if option_str.starts_with("MAX-FAILURE-RATIO") && value_pair.as_rule() == Rule::decimal_value {
                     let ratio_str = value_pair.as_str();
                     let (line, column) = value_pair.line_col();
                     
                     update_config.max_failure_ratio = Some(ratio_str.parse::<f32>()
                         .map_err(|_| {
-                            AthenaError::ParseError(
+                            AthenaError::parse_error_enhanced(
                                 EnhancedParseError::new("Invalid max failure ratio".to_string())
                                     .with_location(line, column)
                                     .with_suggestion("Max failure ratio must be a number between 0.0 and 1.0, e.g., 0.1, 0.3, 0.5".to_string())
@@ -562,7 +1769,7 @@ fn parse_swarm_labels(pair: pest::iterators::Pair<Rule>) -> AthenaResult<HashMap
             let mut label_parts = inner_pair.into_inner();
             
             let key_pair = label_parts.next()
-                .ok_or_else(|| AthenaError::ParseError(
+                .ok_or_else(|| AthenaError::parse_error_enhanced(
                     EnhancedParseError::new("Missing label key".to_string())
                         .with_location(line, column)
                         .with_suggestion("Use format: SWARM-LABELS key=value, e.g., SWARM-LABELS environment=\"production\" tier=\"backend\"".to_string())
@@ -572,7 +1779,7 @@ fn parse_swarm_labels(pair: pest::iterators::Pair<Rule>) -> AthenaResult<HashMap
             let value_pair = label_parts.next()
                 .ok_or_else(|| {
                     let (key_line, key_column) = key_pair.line_col();
-                    AthenaError::ParseError(
+                    AthenaError::parse_error_enhanced(
                         EnhancedParseError::new(format!("Missing value for label key '{key}'"))
                             .with_location(key_line, key_column)
                             .with_suggestion(format!("Complete the label: {key}=\"value\", e.g., {key}=\"production\""))
@@ -585,7 +1792,7 @@ fn parse_swarm_labels(pair: pest::iterators::Pair<Rule>) -> AthenaResult<HashMap
     }
     
     if labels.is_empty() {
-        return Err(AthenaError::ParseError(
+        return Err(AthenaError::parse_error_enhanced(
             EnhancedParseError::new("SWARM-LABELS must contain at least one key=value pair".to_string())
                 .with_location(main_line, main_column)
                 .with_suggestion("Add at least one label: SWARM-LABELS environment=\"production\" tier=\"backend\"".to_string())
@@ -595,6 +1802,22 @@ fn parse_swarm_labels(pair: pest::iterators::Pair<Rule>) -> AthenaResult<HashMap
     Ok(labels)
 }
 
+/// Parse an IPv4 or IPv6 literal, failing with the directive's location on typos.
+fn parse_ip_literal(pair: pest::iterators::Pair<Rule>, directive: &str) -> AthenaResult<String> {
+    let value = clean_string_value(pair.as_str());
+    let (line, column) = pair.line_col();
+
+    value.parse::<IpAddr>().map_err(|_| {
+        AthenaError::parse_error_enhanced(
+            EnhancedParseError::new(format!("Invalid IP address '{value}' in {directive}"))
+                .with_location(line, column)
+                .with_suggestion("Use an IPv4 or IPv6 literal, e.g., \"10.0.0.2\" or \"fd00::53\"".to_string())
+        )
+    })?;
+
+    Ok(value)
+}
+
 fn clean_string_value(input: &str) -> String {
     input
         .strip_prefix('"')
@@ -603,6 +1826,24 @@ fn clean_string_value(input: &str) -> String {
         .to_string()
 }
 
+/// An exec-form argument without its quotes, with `\"` and `\\` unescaped.
+/// Any other backslash is kept, so `"C:\app"` still means what it says.
+fn unescape_exec_arg(input: &str) -> String {
+    let inner = clean_string_value(input);
+    let mut arg = String::with_capacity(inner.len());
+    let mut chars = inner.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(&escaped @ ('"' | '\\'))) => {
+                arg.push(escaped);
+                chars.next();
+            }
+            _ => arg.push(c),
+        }
+    }
+    arg
+}
+
 fn create_enhanced_parse_error(
     pest_error: &pest::error::Error<Rule>,
     line: usize,
@@ -612,7 +1853,14 @@ fn create_enhanced_parse_error(
     let base_message = format!("{pest_error}");
     
     // Extract meaningful error message from Pest error
+    let error_line = file_content.lines().nth(line.saturating_sub(1)).unwrap_or_default();
+    let unterminated_string = error_line.matches('"').count() % 2 == 1;
+
     let (clean_message, suggestion) = match &pest_error.variant {
+        pest::error::ErrorVariant::ParsingError { .. } if unterminated_string => (
+            "Unterminated string".to_string(),
+            Some("Close the string with a matching '\"' on the same line".to_string()),
+        ),
         pest::error::ErrorVariant::ParsingError { 
             positives, 
             negatives: _ 
@@ -657,6 +1905,11 @@ fn create_enhanced_parse_error(
                     "Invalid resource limits format".to_string(),
                     Some("Use RESOURCE-LIMITS CPU \"0.5\" MEMORY \"512M\" format".to_string())
                 )
+            } else if positives.contains(&Rule::reserve_memory) || positives.contains(&Rule::reserve_cpus) {
+                (
+                    "Invalid RESERVE format".to_string(),
+                    Some("Use RESERVE MEMORY 256M CPUS 0.25, with either part optional".to_string())
+                )
             } else if positives.contains(&Rule::build_args) {
                 (
                     "Invalid BUILD-ARGS format".to_string(),
@@ -677,8 +1930,9 @@ fn create_enhanced_parse_error(
                         Some("Each SERVICE block must be closed with 'END SERVICE'".to_string())
                     )
                 } else {
+                    let message = extract_clean_message(&base_message);
                     (
-                        extract_clean_message(&base_message),
+                        if message.is_empty() { pest_error.variant.message().into_owned() } else { message },
                         generate_generic_suggestion(positives)
                     )
                 }
@@ -765,6 +2019,15 @@ mod tests {
         assert_eq!(service.environment.len(), 1);
     }
 
+    #[test]
+    fn test_port_protocol_parsing() {
+        let input = "SERVICES SECTION\nSERVICE dns\nIMAGE-ID coredns\nPORT-MAPPING 53 TO 53 (udp)\nPORT-MAPPING 8080 TO 80 (tcp)\nEND SERVICE";
+        let athena_file = parse_athena_file(input).unwrap();
+        let ports = &athena_file.services.services[0].ports;
+        assert_eq!(ports[0].protocol, Protocol::Udp);
+        assert_eq!(ports[1].protocol, Protocol::Tcp);
+    }
+
     #[test]
     fn test_resource_limits_parsing() {
         let input = r#"RESOURCE-LIMITS CPU "0.5" MEMORY "512M""#;
@@ -795,6 +2058,41 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resource_limit_memory_is_a_byte_size() {
+        let parse = |memory: &str| {
+            let input = format!("RESOURCE-LIMITS CPU \"0.5\" MEMORY \"{memory}\"");
+            let pair = AthenaParser::parse(Rule::resource_limits, &input).unwrap().next().unwrap();
+            parse_resource_limits(pair).map(|limits| limits.memory)
+        };
+
+        assert_eq!(parse("2Gi").unwrap(), ByteSize::from_bytes(2 << 30));
+        let zero = parse("0M").unwrap_err().to_string();
+        assert!(zero.contains("RESOURCE-LIMITS MEMORY must be greater than zero"));
+        assert!(parse("512X").unwrap_err().to_string().contains("unknown unit 'X'"));
+    }
+
+    #[test]
+    fn test_resource_reservation_parsing() {
+        let parse = |input: &str| {
+            let pair = AthenaParser::parse(Rule::resource_reservation, input).unwrap().next().unwrap();
+            parse_resource_reservation(pair)
+        };
+
+        let both = parse("RESERVE MEMORY 256M CPUS 0.25").unwrap();
+        assert_eq!(both.memory, Some(ByteSize::from_bytes(256 << 20)));
+        assert_eq!(both.cpus.as_deref(), Some("0.25"));
+        let reordered = parse("RESERVE CPUS \"0.5\" MEMORY \"1G\"").unwrap();
+        assert_eq!(reordered.memory, Some(ByteSize::from_bytes(1 << 30)));
+        assert_eq!(reordered.cpus.as_deref(), Some("0.5"));
+        assert!(parse("RESERVE CPUS 1").unwrap().memory.is_none());
+
+        let twice = parse("RESERVE MEMORY 256M MEMORY 512M").unwrap_err().to_string();
+        assert!(twice.contains("RESERVE MEMORY is given twice"));
+        assert!(parse("RESERVE CPUS half").unwrap_err().to_string().contains("must be a positive number"));
+        assert!(parse("RESERVE MEMORY 0M").unwrap_err().to_string().contains("greater than zero"));
+    }
+
     #[test]
     fn test_build_args_parsing() {
         let input = r#"
@@ -824,6 +2122,337 @@ mod tests {
         assert_eq!(build_args.len(), 2);
     }
 
+    #[test]
+    fn test_dns_and_extra_host_parsing() {
+        let input = r#"
+            DEPLOYMENT-ID TEST_PROJECT
+
+            ENVIRONMENT SECTION
+            DNS "10.0.0.2"
+
+            SERVICES SECTION
+
+            SERVICE api
+            IMAGE-ID "alpine:latest"
+            DNS "fd00::53"
+            EXTRA-HOST "registry.internal" "10.0.0.9"
+            HOSTNAME "api-1"
+            END SERVICE
+        "#;
+
+        let athena_file = parse_athena_file(input).unwrap();
+        assert_eq!(athena_file.get_default_dns(), ["10.0.0.2".to_string()]);
+
+        let service = &athena_file.services.services[0];
+        assert_eq!(service.dns, vec!["fd00::53".to_string()]);
+        assert_eq!(service.extra_hosts.len(), 1);
+        assert_eq!(service.extra_hosts[0].hostname, "registry.internal");
+        assert_eq!(service.extra_hosts[0].ip, "10.0.0.9");
+        assert_eq!(service.hostname, Some("api-1".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_dns_ip_is_rejected() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE api
+            IMAGE-ID "alpine:latest"
+            DNS "10.0.0"
+            END SERVICE
+        "#;
+
+        let err = parse_athena_file(input).unwrap_err();
+        assert!(err.to_string().contains("Invalid IP address '10.0.0' in DNS"));
+    }
+
+    #[test]
+    fn test_command_forms_are_preserved() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE api
+            IMAGE-ID "python:3.12-slim"
+            COMMAND ["gunicorn", "app:app", "-b", "0.0.0.0:8000"]
+            ENTRYPOINT "/docker-entrypoint.sh"
+            WORKDIR "/app"
+            USER "1000:1000"
+            END SERVICE
+        "#;
+
+        let athena_file = parse_athena_file(input).unwrap();
+        let service = &athena_file.services.services[0];
+        assert_eq!(
+            service.command,
+            Some(ContainerCommand::Exec(vec![
+                "gunicorn".to_string(),
+                "app:app".to_string(),
+                "-b".to_string(),
+                "0.0.0.0:8000".to_string(),
+            ]))
+        );
+        assert_eq!(
+            service.entrypoint,
+            Some(ContainerCommand::Shell("/docker-entrypoint.sh".to_string()))
+        );
+        assert_eq!(service.working_dir, Some("/app".to_string()));
+        assert_eq!(service.user, Some("1000:1000".to_string()));
+    }
+
+    #[test]
+    fn test_exec_form_escapes() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE api
+            IMAGE-ID "alpine:3.20"
+            COMMAND ["sh", "-c", "echo \"hello\" > /tmp/out", "a\\b", "C:\app"]
+            END SERVICE
+        "#;
+
+        let athena_file = parse_athena_file(input).unwrap();
+        assert_eq!(
+            athena_file.services.services[0].command,
+            Some(ContainerCommand::Exec(vec![
+                "sh".to_string(),
+                "-c".to_string(),
+                r#"echo "hello" > /tmp/out"#.to_string(),
+                r"a\b".to_string(),
+                r"C:\app".to_string(),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_lifecycle_flags_parsing() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE debug
+            IMAGE-ID "alpine:latest"
+            INIT TRUE
+            TTY TRUE
+            STDIN-OPEN FALSE
+            STOP-GRACE-PERIOD 1m30s
+            STOP-SIGNAL quit
+            END SERVICE
+        "#;
+
+        let athena_file = parse_athena_file(input).unwrap();
+        let service = &athena_file.services.services[0];
+        assert_eq!(service.init, Some(true));
+        assert_eq!(service.tty, Some(true));
+        assert_eq!(service.stdin_open, Some(false));
+        assert_eq!(service.stop_grace_period, Some(Duration::from_secs(90)));
+        assert_eq!(service.stop_signal.as_deref(), Some("SIGQUIT"));
+    }
+
+    #[test]
+    fn test_stop_signal_forms() {
+        let signal = |value: &str| {
+            let input = format!("SERVICES SECTION\nSERVICE app\nIMAGE-ID alpine\nSTOP-SIGNAL {value}\nEND SERVICE");
+            parse_athena_file(&input).map(|file| file.services.services[0].stop_signal.clone().unwrap())
+        };
+
+        assert_eq!(signal("SIGTERM").unwrap(), "SIGTERM");
+        assert_eq!(signal("\"int\"").unwrap(), "SIGINT");
+        assert_eq!(signal("15").unwrap(), "15");
+        assert_eq!(signal("SIGRTMIN+3").unwrap(), "SIGRTMIN+3");
+        for invalid in ["SIGTREM", "0", "65", "RTMIN+x"] {
+            let error = signal(invalid).unwrap_err().to_string();
+            assert!(error.contains("Invalid STOP-SIGNAL"), "{invalid}: {error}");
+        }
+    }
+
+    #[test]
+    fn test_dsl_keywords_come_from_grammar() {
+        let keywords = dsl_keywords();
+        assert!(keywords.contains(&"IMAGE-ID"));
+        assert!(keywords.contains(&"EXPOSE-VIA"));
+        assert!(!keywords.contains(&"Z"));
+        assert!(keywords.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_resource_names_follow_docker_rules() {
+        let input = r#"
+            ENVIRONMENT SECTION
+            NETWORK-NAME app.net
+            SERVICES SECTION
+            SERVICE api.v2
+            IMAGE-ID "node:20"
+            END SERVICE
+        "#;
+        let athena_file = parse_athena_file(input).unwrap();
+        assert_eq!(athena_file.services.services[0].name, "api.v2");
+        assert_eq!(athena_file.get_network_name(), "app.net");
+
+        let input = "SERVICES SECTION\nSERVICE my@api\nIMAGE-ID nginx\nEND SERVICE";
+        let err = parse_athena_file(input).unwrap_err().to_string();
+        assert!(err.contains("Invalid character '@' in service name 'my@api' at position 3"));
+        assert!(err.contains("line 2, column 11"));
+
+        let input = "ENVIRONMENT SECTION\nVOLUME _data\nSERVICES SECTION";
+        let err = parse_athena_file(input).unwrap_err().to_string();
+        assert!(err.contains("Invalid volume name '_data': must start with a letter or digit"));
+    }
+
+    #[test]
+    fn test_container_name_and_pull_policy() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE api
+            IMAGE-ID "node:20"
+            CONTAINER-NAME "api-main"
+            PULL-POLICY missing
+            RESTART-POLICY on-failure
+            END SERVICE
+        "#;
+        let service = &parse_athena_file(input).unwrap().services.services[0];
+        assert_eq!(service.container_name.as_deref(), Some("api-main"));
+        assert_eq!(service.pull_policy, Some(PullPolicy::Missing));
+
+        let input = "SERVICES SECTION\nSERVICE api\nIMAGE-ID nginx\nRESTART-POLICY unless_stopped\nEND SERVICE";
+        let err = parse_athena_file(input).unwrap_err().to_string();
+        assert!(err.contains("Invalid restart policy 'unless_stopped'"));
+        assert!(err.contains("Did you mean 'unless-stopped'?"));
+
+        let input = "SERVICES SECTION\nSERVICE api\nIMAGE-ID nginx\nPULL-POLICY allways\nEND SERVICE";
+        let err = parse_athena_file(input).unwrap_err().to_string();
+        assert!(err.contains("Did you mean 'always'?"));
+
+        let input = "SERVICES SECTION\nSERVICE api\nIMAGE-ID nginx\nCONTAINER-NAME \"-api\"\nEND SERVICE";
+        let err = parse_athena_file(input).unwrap_err().to_string();
+        assert!(err.contains("Invalid character '-' in container name '-api' at position 1"));
+    }
+
+    #[test]
+    fn test_service_kind_and_dependency_conditions() {
+        let input = "SERVICES SECTION\nSERVICE seed\nKIND job\nDEPENDS-ON db HEALTHY\nDEPENDS-ON cache\nEND SERVICE";
+        let service = &parse_athena_file(input).unwrap().services.services[0];
+        assert_eq!(service.kind, Some(ServiceKind::Job));
+        assert_eq!(service.depends_on[0].condition, Some(DependencyCondition::Healthy));
+        assert_eq!(service.depends_on[1], Dependency::new("cache"));
+
+        let input = "SERVICES SECTION\nSERVICE seed\nKIND jobs\nEND SERVICE";
+        let err = parse_athena_file(input).unwrap_err().to_string();
+        assert!(err.contains("Invalid service kind 'jobs'"));
+        assert!(err.contains("Did you mean 'job'?"));
+    }
+
+    #[test]
+    fn test_watch_rules() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE api
+            WATCH SYNC "./src" TO "/app/src"
+            WATCH SYNC+RESTART "./config" TO "/app/config"
+            WATCH REBUILD "./go.mod"
+            END SERVICE
+        "#;
+        let service = &parse_athena_file(input).unwrap().services.services[0];
+        assert_eq!(service.watch.len(), 3);
+        assert_eq!(service.watch[0].target.as_deref(), Some("/app/src"));
+        assert_eq!(service.watch[1].action, WatchAction::SyncRestart);
+        assert_eq!(
+            service.watch[2],
+            WatchRule { action: WatchAction::Rebuild, path: "./go.mod".to_string(), target: None }
+        );
+
+        let input = "SERVICES SECTION\nSERVICE api\nWATCH SYNC \"./src\"\nEND SERVICE";
+        let err = parse_athena_file(input).unwrap_err().to_string();
+        assert!(err.contains("needs a container path to sync to"));
+    }
+
+    #[test]
+    fn test_platform() {
+        let input = "SERVICES SECTION\nSERVICE api\nPLATFORM \"linux/arm/v7\"\nEND SERVICE";
+        let service = &parse_athena_file(input).unwrap().services.services[0];
+        assert_eq!(service.platform.as_deref(), Some("linux/arm/v7"));
+
+        let input = "SERVICES SECTION\nSERVICE api\nPLATFORM \"linux/aarch64\"\nEND SERVICE";
+        let err = parse_athena_file(input).unwrap_err().to_string();
+        assert!(err.contains("Invalid platform architecture 'aarch64'"));
+        assert!(err.contains("Use \"linux/arm64\""));
+
+        let input = "SERVICES SECTION\nSERVICE api\nPLATFORM \"linx/amd64\"\nEND SERVICE";
+        let err = parse_athena_file(input).unwrap_err().to_string();
+        assert!(err.contains("Did you mean 'linux'?"));
+
+        assert!(check_platform("amd64").is_err());
+        assert!(check_platform("linux/amd64").is_ok());
+    }
+
+    #[test]
+    fn test_namespace_modes() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE sidecar
+            IMAGE-ID "alpine"
+            PID host
+            IPC host
+            NETWORK-MODE service:app.main
+            END SERVICE
+        "#;
+        let service = &parse_athena_file(input).unwrap().services.services[0];
+        assert_eq!(service.pid, Some(PidMode::Host));
+        assert_eq!(service.ipc, Some(IpcMode::Host));
+        assert_eq!(service.network_mode, Some(NetworkMode::Service("app.main".to_string())));
+
+        let input = "SERVICES SECTION\nSERVICE api\nIMAGE-ID nginx\nNETWORK-MODE hots\nEND SERVICE";
+        let err = parse_athena_file(input).unwrap_err().to_string();
+        assert!(err.contains("Did you mean 'host'?"));
+    }
+
+    #[test]
+    fn test_expose_via_parsing() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE api
+            IMAGE-ID "node:20-alpine"
+            PORT-MAPPING 3000 TO 3000
+            EXPOSE-VIA nginx PATH "/api/"
+            END SERVICE
+        "#;
+
+        let athena_file = parse_athena_file(input).unwrap();
+        let exposure = athena_file.services.services[0].expose_via.as_ref().unwrap();
+        assert_eq!(exposure.proxy, ReverseProxy::Nginx);
+        assert_eq!(exposure.path, "/api/");
+    }
+
+    #[test]
+    fn test_network_attachment_parsing() {
+        let input = r#"
+            ENVIRONMENT SECTION
+            NETWORK-NAME backend SUBNET "172.28.0.0/16"
+            SERVICES SECTION
+            SERVICE db
+            IMAGE-ID "postgres:16"
+            ON NETWORK backend ALIAS "db.legacy.local"
+            ON NETWORK backend ALIAS "db.legacy.local" IPV4 "172.28.0.10"
+            END SERVICE
+        "#;
+
+        let athena_file = parse_athena_file(input).unwrap();
+        let network = &athena_file.environment.as_ref().unwrap().networks[0];
+        assert_eq!(network.subnet.as_deref(), Some("172.28.0.0/16"));
+        assert_eq!(network.subnet_contains("172.28.3.4".parse().unwrap()), Some(true));
+        assert_eq!(network.subnet_contains("172.29.0.1".parse().unwrap()), Some(false));
+
+        let attachments = &athena_file.services.services[0].networks;
+        assert_eq!(attachments.len(), 1, "repeated lines fold into one attachment");
+        assert_eq!(attachments[0].aliases, ["db.legacy.local"]);
+        assert_eq!(attachments[0].ipv4_address.as_deref(), Some("172.28.0.10"));
+
+        let err = parse_athena_file(&input.replace("\"172.28.0.10\"", "\"172.28.0.300\"")).unwrap_err();
+        assert!(err.to_string().contains("Invalid IPV4 address '172.28.0.300'"));
+        let err = parse_athena_file(&input.replace("\"172.28.0.0/16\"", "\"172.28.0.0/33\"")).unwrap_err();
+        assert!(err.to_string().contains("Invalid SUBNET '172.28.0.0/33'"));
+        let err = parse_athena_file(&input.replace(
+            "ALIAS \"db.legacy.local\"\n",
+            "IPV4 \"172.28.0.11\"\n",
+        ))
+        .unwrap_err();
+        assert!(err.to_string().contains("two IPV4 addresses on network 'backend'"));
+    }
+
     #[test]
     fn test_build_args_single_pair() {
         let input = r#"BUILD-ARGS NODE_ENV="development""#;
@@ -863,4 +2492,114 @@ mod tests {
             Err(e) => panic!("Parse error: {:?}", e),
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_malformed_input_never_panics() {
+        let inputs = [
+            "SERVICES SECTION\nSERVICE api\nIMAGE-ID \"abc\nEND SERVICE",
+            "SERVICES SECTION\nSERVICE api\nCOMMAND [\"a\", \"b\nEND SERVICE",
+            "SERVICES SECTION\nSERVICE api\n\"",
+            "\"",
+            "SERVICES SECTION\nSERVICE\nSERVICE\nEND SERVICE\nEND SERVICE",
+        ];
+
+        for input in inputs {
+            assert!(parse_athena_file(input).is_err(), "{input:?} should not parse");
+        }
+    }
+
+    #[test]
+    fn test_reports_every_broken_service() {
+        let input = r#"SERVICES SECTION
+SERVICE api
+IMAGE-ID "api
+END SERVICE
+SERVICE web
+IMAGE-ID "nginx"
+END SERVICE
+SERVICE db
+RESTART-POLICY sometimes
+END SERVICE"#;
+
+        let err = parse_athena_file(input).unwrap_err().to_string();
+        assert!(err.contains("line 3, column 10: Unterminated string"), "{err}");
+        assert!(err.contains("1 more error(s) found"), "{err}");
+        assert!(err.contains("line 9, column 16: Invalid restart policy 'sometimes'"), "{err}");
+
+        // Without a syntax error, semantic errors are collected the same way
+        let input = "SERVICES SECTION\nSERVICE a\nRESTART-POLICY sometimes\nEND SERVICE\nSERVICE b\nRESTART-POLICY never\nEND SERVICE";
+        let err = parse_athena_file(input).unwrap_err().to_string();
+        assert!(err.contains("'sometimes'") && err.contains("'never'"), "{err}");
+    }
+
+    fn duplicate_error(input: &str) -> String {
+        match parse_athena_file(input) {
+            Err(AthenaError::ValidationError(error)) => error.message,
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_duplicate_service_reports_both_lines() {
+        let input = "SERVICES SECTION\nSERVICE api\nIMAGE-ID \"api:1\"\nEND SERVICE\nSERVICE api\nIMAGE-ID \"api:2\"\nEND SERVICE";
+        assert_eq!(
+            duplicate_error(input),
+            "Service 'api' is declared on lines 2 and 5; the one on line 5 would win"
+        );
+    }
+
+    #[test]
+    fn test_duplicate_environment_resources_report_both_lines() {
+        let cases = [
+            ("NETWORK-NAME app_net\nNETWORK-NAME app_net", "Network 'app_net' is declared on lines 2 and 3"),
+            ("VOLUME pg_data\nVOLUME pg_data", "Volume 'pg_data' is declared on lines 2 and 3"),
+            (
+                "SECRET db_password \"./a.txt\"\nSECRET db_password \"./b.txt\"",
+                "Secret 'db_password' is declared on lines 2 and 3",
+            ),
+        ];
+        for (items, expected) in cases {
+            let input = format!("ENVIRONMENT SECTION\n{items}\nSERVICES SECTION\nSERVICE api\nIMAGE-ID \"api\"\nEND SERVICE");
+            let message = duplicate_error(&input);
+            assert!(message.starts_with(expected), "{message}");
+            assert!(message.ends_with("the one on line 3 would win"), "{message}");
+        }
+    }
+
+    #[test]
+    fn test_every_duplicate_is_reported() {
+        let input = r#"ENVIRONMENT SECTION
+VOLUME data
+VOLUME data
+SERVICES SECTION
+SERVICE api
+IMAGE-ID "api"
+END SERVICE
+SERVICE api
+IMAGE-ID "api"
+END SERVICE
+SERVICE api
+IMAGE-ID "api"
+END SERVICE"#;
+
+        let message = duplicate_error(input);
+        assert!(message.starts_with("2 names are declared more than once:"), "{message}");
+        assert!(message.contains("\n  - Volume 'data' is declared on lines 2 and 3"), "{message}");
+        assert!(message.contains("\n  - Service 'api' is declared on lines 5, 8 and 11; the one on line 11 would win"), "{message}");
+    }
+
+    #[test]
+    fn test_environment_overrides_are_not_duplicates() {
+        let input = r#"SERVICES SECTION
+SERVICE api
+IMAGE-ID "api"
+END SERVICE
+
+ENVIRONMENT "prod"
+SERVICE api
+REPLICAS 3
+END SERVICE
+END ENVIRONMENT"#;
+        assert!(parse_athena_file(input).is_ok());
+    }
+}
@@ -0,0 +1,281 @@
+//! Duration and byte-size values shared by every directive that takes one.
+//!
+//! Both parse once into an exact integer and display in a canonical form
+//! Compose accepts, so `90s` and `1m30s` generate the same output. Sizes use
+//! powers of 1024 for every suffix, as Docker does: `2G`, `2GB` and `2Gi` are
+//! all 2147483648 bytes.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// A span of time with millisecond precision, e.g. `30s`, `1m30s` or `500ms`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Duration {
+    millis: u64,
+}
+
+const DURATION_UNITS: &[(&str, u64)] = &[("h", 3_600_000), ("m", 60_000), ("s", 1_000), ("ms", 1)];
+
+impl Duration {
+    /// Accepted forms, for error suggestions
+    pub const FORMS: &'static str =
+        "Use whole numbers of h, m, s or ms, optionally combined, e.g. 30s, 1m30s, 500ms or 2h";
+
+    pub const fn from_millis(millis: u64) -> Self {
+        Self { millis }
+    }
+
+    pub const fn from_secs(secs: u64) -> Self {
+        Self { millis: secs * 1_000 }
+    }
+
+    pub const fn as_millis(self) -> u64 {
+        self.millis
+    }
+
+    /// Whole seconds, rounding up, for consumers without sub-second precision
+    pub const fn as_secs_ceil(self) -> u64 {
+        self.millis.div_ceil(1_000)
+    }
+
+    pub const fn is_zero(self) -> bool {
+        self.millis == 0
+    }
+}
+
+impl FromStr for Duration {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.is_empty() {
+            return Err("Empty duration".to_string());
+        }
+
+        let mut millis: u64 = 0;
+        let mut rest = value;
+        while !rest.is_empty() {
+            let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            if digits == 0 {
+                return Err(format!("Invalid duration '{value}': expected a number before '{rest}'"));
+            }
+            let (number, after) = rest.split_at(digits);
+            let unit_len = after.len() - after.trim_start_matches(|c: char| c.is_ascii_alphabetic()).len();
+            let (unit, after) = after.split_at(unit_len);
+
+            let factor = DURATION_UNITS
+                .iter()
+                .find(|(name, _)| *name == unit)
+                .map(|(_, factor)| *factor)
+                .ok_or_else(|| match unit {
+                    "" => format!("Invalid duration '{value}': {number} has no unit"),
+                    _ => format!("Invalid duration '{value}': unknown unit '{unit}'"),
+                })?;
+            millis = number
+                .parse::<u64>()
+                .ok()
+                .and_then(|n| n.checked_mul(factor))
+                .and_then(|part| millis.checked_add(part))
+                .ok_or_else(|| format!("Duration '{value}' is too large"))?;
+            rest = after;
+        }
+
+        Ok(Self { millis })
+    }
+}
+
+/// Largest units first, zero parts omitted: `1h5m`, `1m30s`, `1s500ms`, `0s`
+impl fmt::Display for Duration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.millis == 0 {
+            return f.write_str("0s");
+        }
+        let mut rest = self.millis;
+        for (unit, factor) in DURATION_UNITS {
+            if rest >= *factor {
+                write!(f, "{}{unit}", rest / factor)?;
+                rest %= factor;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An amount of memory or storage, e.g. `512M` or `2Gi`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize {
+    bytes: u64,
+}
+
+/// Largest first, for display
+const BYTE_UNITS: &[(&str, u64)] = &[
+    ("T", 1 << 40),
+    ("G", 1 << 30),
+    ("M", 1 << 20),
+    ("K", 1 << 10),
+    ("B", 1),
+];
+
+impl ByteSize {
+    /// Accepted forms, for error suggestions
+    pub const FORMS: &'static str =
+        "Use a whole number with B, K, M, G or T (KB/KiB and friends also work; all are powers of 1024), e.g. 512M or 2G";
+
+    pub const fn from_bytes(bytes: u64) -> Self {
+        Self { bytes }
+    }
+
+    pub const fn as_bytes(self) -> u64 {
+        self.bytes
+    }
+
+    pub const fn is_zero(self) -> bool {
+        self.bytes == 0
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let digits = value.len() - value.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits == 0 {
+            return Err(format!("Invalid size '{value}': expected a number"));
+        }
+        let (number, suffix) = value.split_at(digits);
+
+        // A unit letter, optionally followed by B, i or iB
+        let upper = suffix.to_ascii_uppercase();
+        let mut chars = upper.chars();
+        let unit = chars.next().map_or("B".to_string(), String::from);
+        let factor = match (unit.as_str(), chars.as_str()) {
+            ("B", "") => Some(1),
+            (unit, "" | "B" | "I" | "IB") if unit != "B" => BYTE_UNITS
+                .iter()
+                .find(|(name, _)| *name == unit)
+                .map(|(_, factor)| *factor),
+            _ => None,
+        }
+        .ok_or_else(|| format!("Invalid size '{value}': unknown unit '{suffix}'"))?;
+
+        number
+            .parse::<u64>()
+            .ok()
+            .and_then(|n| n.checked_mul(factor))
+            .map(|bytes| Self { bytes })
+            .ok_or_else(|| format!("Size '{value}' is too large"))
+    }
+}
+
+/// The largest unit that divides the size exactly: `512M`, `1G`, `1536K`
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (unit, factor) = BYTE_UNITS
+            .iter()
+            .find(|(_, factor)| self.bytes > 0 && self.bytes.is_multiple_of(*factor))
+            .copied()
+            .unwrap_or(("B", 1));
+        write!(f, "{}{unit}", self.bytes / factor)
+    }
+}
+
+macro_rules! string_serde {
+    ($type:ty) => {
+        impl Serialize for $type {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                String::deserialize(deserializer)?
+                    .parse()
+                    .map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+string_serde!(Duration);
+string_serde!(ByteSize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_duration_forms() {
+        assert_eq!("30s".parse::<Duration>().unwrap(), Duration::from_secs(30));
+        assert_eq!("1m30s".parse::<Duration>().unwrap(), Duration::from_secs(90));
+        assert_eq!("90s".parse::<Duration>().unwrap().to_string(), "1m30s");
+        assert_eq!("500ms".parse::<Duration>().unwrap(), Duration::from_millis(500));
+        assert_eq!("2h".parse::<Duration>().unwrap().to_string(), "2h");
+        assert_eq!(Duration::from_millis(1_500).to_string(), "1s500ms");
+        assert_eq!(Duration::default().to_string(), "0s");
+        assert_eq!(Duration::from_millis(500).as_secs_ceil(), 1);
+    }
+
+    #[test]
+    fn test_invalid_durations() {
+        for value in ["", "30", "s", "30x", "1.5s", "-5s", "30 s", "99999999999999999999h"] {
+            assert!(value.parse::<Duration>().is_err(), "{value:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_byte_size_forms() {
+        let two_gib = ByteSize::from_bytes(2 << 30);
+        for value in ["2G", "2g", "2GB", "2Gi", "2GiB", "2gib", "2048M"] {
+            assert_eq!(value.parse::<ByteSize>().unwrap(), two_gib, "{value}");
+        }
+        assert_eq!("512".parse::<ByteSize>().unwrap().to_string(), "512B");
+        assert_eq!("1536M".parse::<ByteSize>().unwrap().to_string(), "1536M");
+        assert_eq!("1024K".parse::<ByteSize>().unwrap().to_string(), "1M");
+        assert_eq!(ByteSize::default().to_string(), "0B");
+    }
+
+    #[test]
+    fn test_invalid_byte_sizes() {
+        for value in ["", "M", "512X", "1.5G", "512 M", "2GiBB", "2BG", "-1M", "99999999999T"] {
+            assert!(value.parse::<ByteSize>().is_err(), "{value:?} should be rejected");
+        }
+    }
+
+    #[test]
+    fn test_serde_uses_display_form() {
+        let yaml = serde_yaml::to_string(&(Duration::from_secs(90), ByteSize::from_bytes(512 << 20))).unwrap();
+        assert_eq!(yaml, "- 1m30s\n- 512M\n");
+        let back: (Duration, ByteSize) = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(back, (Duration::from_secs(90), ByteSize::from_bytes(512 << 20)));
+    }
+
+    proptest! {
+        #[test]
+        fn test_duration_display_round_trips(millis in any::<u64>()) {
+            let duration = Duration::from_millis(millis);
+            prop_assert_eq!(duration.to_string().parse::<Duration>(), Ok(duration));
+        }
+
+        #[test]
+        fn test_duration_parse_display_is_stable(value in "([0-9]{1,6}(h|m|s|ms)){1,4}") {
+            let duration: Duration = value.parse().unwrap();
+            let canonical = duration.to_string();
+            prop_assert_eq!(canonical.parse::<Duration>(), Ok(duration));
+        }
+
+        #[test]
+        fn test_byte_size_display_round_trips(bytes in any::<u64>()) {
+            let size = ByteSize::from_bytes(bytes);
+            prop_assert_eq!(size.to_string().parse::<ByteSize>(), Ok(size));
+        }
+
+        #[test]
+        fn test_byte_size_parse_display_is_stable(value in "[0-9]{1,9}([kKmMgG](i?[bB]?)?|[bB])?") {
+            let size: ByteSize = value.parse().unwrap();
+            prop_assert_eq!(size.to_string().parse::<ByteSize>(), Ok(size));
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Curated service definitions expanded by `PRESET <name>` inside a SERVICE block.
+//!
+//! Anything the block sets itself wins: scalars replace the preset value, and
+//! ports, volumes and environment entries replace the preset entry for the
+//! same container port, container path or variable name.
+
+use crate::athena::parser::ast::{
+    AthenaFile, EnvironmentSection, EnvironmentVariable, PortMapping, Protocol, RestartPolicy, Service,
+    VolumeDefinition, VolumeMapping,
+};
+
+/// Placeholder replaced by the service name in preset environment values
+const SERVICE_PLACEHOLDER: &str = "{service}";
+
+#[derive(Debug)]
+pub struct Preset {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub image: &'static str,
+    pub default_version: &'static str,
+    /// Appended to the version to form the tag, e.g. `-management`
+    pub tag_suffix: &'static str,
+    pub ports: &'static [u16],
+    /// Container path of the data volume, named `<service>_data`
+    pub data_path: Option<&'static str>,
+    /// `KEY=value` literals, or bare names emitted as `{{KEY}}` templates
+    pub environment: &'static [&'static str],
+    pub health_check: &'static str,
+    pub restart: RestartPolicy,
+}
+
+pub const PRESETS: &[Preset] = &[
+    Preset {
+        name: "kafka",
+        description: "Single-node Kafka broker in KRaft mode (no ZooKeeper)",
+        image: "bitnami/kafka",
+        default_version: "3.7",
+        tag_suffix: "",
+        ports: &[9092],
+        data_path: Some("/bitnami/kafka"),
+        environment: &[
+            "KAFKA_CFG_NODE_ID=0",
+            "KAFKA_CFG_PROCESS_ROLES=controller,broker",
+            "KAFKA_CFG_LISTENERS=PLAINTEXT://:9092,CONTROLLER://:9093",
+            "KAFKA_CFG_ADVERTISED_LISTENERS=PLAINTEXT://{service}:9092",
+            "KAFKA_CFG_LISTENER_SECURITY_PROTOCOL_MAP=CONTROLLER:PLAINTEXT,PLAINTEXT:PLAINTEXT",
+            "KAFKA_CFG_CONTROLLER_QUORUM_VOTERS=0@localhost:9093",
+            "KAFKA_CFG_CONTROLLER_LISTENER_NAMES=CONTROLLER",
+        ],
+        health_check: "kafka-topics.sh --bootstrap-server localhost:9092 --list",
+        restart: RestartPolicy::Always,
+    },
+    Preset {
+        name: "postgres",
+        description: "PostgreSQL with a persistent data volume",
+        image: "postgres",
+        default_version: "16",
+        tag_suffix: "-alpine",
+        ports: &[5432],
+        data_path: Some("/var/lib/postgresql/data"),
+        environment: &["POSTGRES_USER", "POSTGRES_PASSWORD", "POSTGRES_DB"],
+        health_check: "pg_isready",
+        restart: RestartPolicy::Always,
+    },
+    Preset {
+        name: "rabbitmq",
+        description: "RabbitMQ with the management UI on port 15672",
+        image: "rabbitmq",
+        default_version: "3.13",
+        tag_suffix: "-management",
+        ports: &[5672, 15672],
+        data_path: Some("/var/lib/rabbitmq"),
+        environment: &["RABBITMQ_DEFAULT_USER", "RABBITMQ_DEFAULT_PASS"],
+        health_check: "rabbitmq-diagnostics -q ping",
+        restart: RestartPolicy::Always,
+    },
+    Preset {
+        name: "redis",
+        description: "Redis with a persistent data volume",
+        image: "redis",
+        default_version: "7",
+        tag_suffix: "-alpine",
+        ports: &[6379],
+        data_path: Some("/data"),
+        environment: &[],
+        health_check: "redis-cli ping",
+        restart: RestartPolicy::Always,
+    },
+];
+
+pub fn find_preset(name: &str) -> Option<&'static Preset> {
+    PRESETS.iter().find(|preset| preset.name == name)
+}
+
+pub fn preset_names() -> Vec<&'static str> {
+    PRESETS.iter().map(|preset| preset.name).collect()
+}
+
+impl Preset {
+    pub fn image_for(&self, version: Option<&str>) -> String {
+        format!(
+            "{}:{}{}",
+            self.image,
+            version.unwrap_or(self.default_version),
+            self.tag_suffix
+        )
+    }
+
+    /// Every environment entry the preset gives a service called `service_name`
+    pub fn environment_for(&self, service_name: &str) -> Vec<EnvironmentVariable> {
+        self.environment
+            .iter()
+            .map(|entry| self.env_entry(entry, service_name))
+            .collect()
+    }
+
+    fn env_entry(&self, entry: &str, service_name: &str) -> EnvironmentVariable {
+        if entry.contains('=') {
+            EnvironmentVariable::Literal(entry.replace(SERVICE_PLACEHOLDER, service_name))
+        } else {
+            EnvironmentVariable::Template(entry.to_string())
+        }
+    }
+
+    /// Fill in everything the service block did not set itself, returning
+    /// the data volume to declare if the preset added one
+    fn apply(&self, service: &mut Service, version: Option<&str>) -> Option<String> {
+        if service.image.is_none() && service.build_args.is_none() {
+            service.image = Some(self.image_for(version));
+        }
+        if service.health_check.is_none() {
+            service.health_check = Some(self.health_check.to_string());
+        }
+        if service.restart.is_none() {
+            service.restart = Some(self.restart.clone());
+        }
+
+        for &port in self.ports {
+            if !service.ports.iter().any(|p| p.container_port == port) {
+                service.ports.push(PortMapping {
+                    host_port: port,
+                    host_port_end: None,
+                    container_port: port,
+                    protocol: Protocol::Tcp,
+                });
+            }
+        }
+
+        let data_volume = self
+            .data_path
+            .filter(|path| !service.volumes.iter().any(|v| v.container_path == *path))
+            .map(|path| {
+                let name = data_volume_name(&service.name);
+                service.volumes.push(VolumeMapping {
+                    host_path: name.clone(),
+                    container_path: path.to_string(),
+                    options: Vec::new(),
+                });
+                name
+            });
+
+        let mut preset_env: Vec<EnvironmentVariable> = self
+            .environment_for(&service.name)
+            .into_iter()
+            .filter(|entry| {
+                !service
+                    .environment
+                    .iter()
+                    .any(|own| own.key() == entry.key())
+            })
+            .collect();
+        preset_env.append(&mut service.environment);
+        service.environment = preset_env;
+
+        data_volume
+    }
+}
+
+fn data_volume_name(service_name: &str) -> String {
+    format!("{}_data", service_name.replace('.', "_"))
+}
+
+/// Expand every `PRESET` in the file and declare the data volumes it needs
+pub fn expand_presets(athena_file: &mut AthenaFile) {
+    let mut volumes = Vec::new();
+
+    for service in &mut athena_file.services.services {
+        let Some(reference) = service.preset.clone() else {
+            continue;
+        };
+        let Some(preset) = find_preset(&reference.name) else {
+            continue;
+        };
+
+        // Attributed to the SERVICE line that asked for the preset
+        let location = service.location.clone();
+        volumes.extend(
+            preset
+                .apply(service, reference.version.as_deref())
+                .map(|name| (name, location)),
+        );
+    }
+
+    if volumes.is_empty() {
+        return;
+    }
+
+    let environment = athena_file.environment.get_or_insert_with(|| EnvironmentSection {
+        networks: Vec::new(),
+        volumes: Vec::new(),
+        secrets: Default::default(),
+        dns: Vec::new(),
+        externals: Vec::new(),
+        default_labels: None,
+    });
+    for (name, location) in volumes {
+        let used = environment.volumes.iter().any(|v| v.name == name);
+        if !used {
+            environment.volumes.push(VolumeDefinition {
+                name,
+                options: Vec::new(),
+                location,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::parse_athena_file;
+
+    #[test]
+    fn test_preset_fields_can_be_overridden() {
+        let input = r#"
+            SERVICES SECTION
+            SERVICE queue
+            PRESET rabbitmq VERSION "3.12"
+            PORT-MAPPING 25672 TO 15672
+            ENV-VARIABLE "RABBITMQ_DEFAULT_USER=admin"
+            END SERVICE
+        "#;
+        let athena_file = parse_athena_file(input).unwrap();
+        let service = &athena_file.services.services[0];
+
+        assert_eq!(service.image.as_deref(), Some("rabbitmq:3.12-management"));
+        assert_eq!(service.ports.len(), 2);
+        assert!(service.ports.iter().any(|p| p.host_port == 25672 && p.container_port == 15672));
+        assert!(service.ports.iter().any(|p| p.host_port == 5672));
+
+        let keys: Vec<&str> = service.environment.iter().map(EnvironmentVariable::key).collect();
+        assert_eq!(keys, vec!["RABBITMQ_DEFAULT_PASS", "RABBITMQ_DEFAULT_USER"]);
+
+        let volumes = &athena_file.environment.as_ref().unwrap().volumes;
+        assert_eq!(volumes[0].name, "queue_data");
+    }
+
+    #[test]
+    fn test_kafka_advertises_service_name() {
+        let input = "SERVICES SECTION\nSERVICE events\nPRESET kafka\nEND SERVICE";
+        let athena_file = parse_athena_file(input).unwrap();
+        let service = &athena_file.services.services[0];
+
+        assert_eq!(service.image.as_deref(), Some("bitnami/kafka:3.7"));
+        assert!(service.environment.iter().any(|e| matches!(
+            e,
+            EnvironmentVariable::Literal(v) if v == "KAFKA_CFG_ADVERTISED_LISTENERS=PLAINTEXT://events:9092"
+        )));
+    }
+
+    #[test]
+    fn test_unknown_preset_suggests_closest() {
+        let input = "SERVICES SECTION\nSERVICE db\nPRESET postgress\nEND SERVICE";
+        let err = parse_athena_file(input).unwrap_err().to_string();
+        assert!(err.contains("Invalid preset 'postgress'"));
+        assert!(err.contains("Did you mean 'postgres'?"));
+    }
+}
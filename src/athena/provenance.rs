@@ -0,0 +1,164 @@
+use sha2::{Digest, Sha256};
+
+const CHECKSUM_PREFIX: &str = "# Output-SHA256: ";
+const SOURCE_PREFIX: &str = "# Source: ";
+const SOURCE_HASH_MARKER: &str = " (sha256:";
+
+/// Starts the `--annotate-sources` comment above a service, volume or network.
+/// Annotations are left out of the output checksum, so annotated and plain
+/// output of the same source carry the same Output-SHA256.
+pub const ANNOTATION_PREFIX: &str = "  # from ";
+
+/// Joins the source paths of a file generated from several .ath files
+pub const SOURCE_PATH_SEPARATOR: &str = ", ";
+
+/// Provenance recorded in the header of a generated file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stamp {
+    pub source_path: String,
+    pub source_hash: String,
+    pub output_hash: String,
+}
+
+/// Result of comparing a generated file against its stamp and source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyStatus {
+    UpToDate,
+    /// The file was edited after generation
+    Modified,
+    /// The .ath source changed since the file was generated
+    Stale,
+}
+
+pub fn sha256_hex(content: &str) -> String {
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Content hashed as "the source" of a build. A single file is used as is;
+/// several are length-prefixed so moving text between files changes the hash.
+pub fn combine_sources(contents: &[String]) -> String {
+    match contents {
+        [single] => single.clone(),
+        _ => contents
+            .iter()
+            .map(|content| format!("{}:{content}", content.len()))
+            .collect(),
+    }
+}
+
+/// Insert the source and output checksum lines after the first header line.
+///
+/// The output checksum covers the whole file except its own line and any
+/// source annotations, so it can be recomputed from the stamped file alone.
+pub fn stamp_output(output: &str, source_path: &str, source_content: &str) -> String {
+    let (first_line, rest) = output.split_once('\n').unwrap_or((output, ""));
+    let source_line = format!(
+        "{SOURCE_PREFIX}{source_path}{SOURCE_HASH_MARKER}{})",
+        sha256_hex(source_content)
+    );
+
+    let body = format!("{first_line}\n{source_line}\n{rest}");
+    let checksum = sha256_hex(&strip_annotations(&body));
+
+    format!("{first_line}\n{CHECKSUM_PREFIX}{checksum}\n{source_line}\n{rest}")
+}
+
+/// Read the stamp from a generated file, if it has one
+pub fn read_stamp(output: &str) -> Option<Stamp> {
+    let output_hash = output
+        .lines()
+        .find_map(|line| line.strip_prefix(CHECKSUM_PREFIX))?;
+
+    let (source_path, source_hash) = output
+        .lines()
+        .find_map(|line| line.strip_prefix(SOURCE_PREFIX))
+        .and_then(|line| line.rsplit_once(SOURCE_HASH_MARKER))?;
+
+    Some(Stamp {
+        source_path: source_path.to_string(),
+        source_hash: source_hash.trim_end_matches(')').to_string(),
+        output_hash: output_hash.trim().to_string(),
+    })
+}
+
+/// Check a stamped file against its own checksum and the current source.
+/// Returns `None` when the file carries no stamp.
+pub fn verify_output(output: &str, source_content: &str) -> Option<VerifyStatus> {
+    let stamp = read_stamp(output)?;
+
+    if sha256_hex(&strip_annotations(&strip_checksum_line(output))) != stamp.output_hash {
+        return Some(VerifyStatus::Modified);
+    }
+
+    if sha256_hex(source_content) != stamp.source_hash {
+        return Some(VerifyStatus::Stale);
+    }
+
+    Some(VerifyStatus::UpToDate)
+}
+
+fn strip_checksum_line(output: &str) -> String {
+    output
+        .split_inclusive('\n')
+        .filter(|line| !line.starts_with(CHECKSUM_PREFIX))
+        .collect()
+}
+
+fn strip_annotations(output: &str) -> String {
+    output
+        .split_inclusive('\n')
+        .filter(|line| !line.starts_with(ANNOTATION_PREFIX))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = "# Generated by Athena\nservices:\n  web:\n    image: nginx\n";
+    const SOURCE: &str = "SERVICES SECTION\nSERVICE web\nIMAGE-ID nginx\nEND SERVICE";
+
+    #[test]
+    fn test_stamp_roundtrip() {
+        let stamped = stamp_output(OUTPUT, "stack.ath", SOURCE);
+        let stamp = read_stamp(&stamped).unwrap();
+
+        assert_eq!(stamp.source_path, "stack.ath");
+        assert_eq!(stamp.source_hash, sha256_hex(SOURCE));
+        assert!(stamped.starts_with("# Generated by Athena\n# Output-SHA256: "));
+        assert_eq!(verify_output(&stamped, SOURCE), Some(VerifyStatus::UpToDate));
+    }
+
+    #[test]
+    fn test_detects_modified_output() {
+        let stamped = stamp_output(OUTPUT, "stack.ath", SOURCE);
+        let edited = stamped.replace("image: nginx", "image: nginx:latest");
+
+        assert_eq!(verify_output(&edited, SOURCE), Some(VerifyStatus::Modified));
+    }
+
+    #[test]
+    fn test_detects_stale_output() {
+        let stamped = stamp_output(OUTPUT, "stack.ath", SOURCE);
+        let new_source = SOURCE.replace("nginx", "caddy");
+
+        assert_eq!(verify_output(&stamped, &new_source), Some(VerifyStatus::Stale));
+    }
+
+    #[test]
+    fn test_annotations_do_not_change_the_checksum() {
+        let annotated = OUTPUT.replace("  web:", "  # from stack.ath:2 (SERVICE web)\n  web:");
+        let plain = read_stamp(&stamp_output(OUTPUT, "stack.ath", SOURCE)).unwrap();
+        let stamped = stamp_output(&annotated, "stack.ath", SOURCE);
+
+        assert_eq!(read_stamp(&stamped).unwrap().output_hash, plain.output_hash);
+        assert_eq!(verify_output(&stamped, SOURCE), Some(VerifyStatus::UpToDate));
+        let edited = stamped.replace("image: nginx", "image: caddy");
+        assert_eq!(verify_output(&edited, SOURCE), Some(VerifyStatus::Modified));
+    }
+
+    #[test]
+    fn test_unstamped_output() {
+        assert_eq!(verify_output(OUTPUT, SOURCE), None);
+    }
+}
@@ -0,0 +1,318 @@
+//! `athena report`: an inventory of what a stack exposes, for security
+//! reviews and change tickets.
+//!
+//! Every section is sorted and the output carries no timestamp, so a report
+//! checked into the repository only changes when the stack does. JSON field
+//! names follow the AST (`image`, `host_port`, `host_path`, ...).
+
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::athena::error::{AthenaError, AthenaResult};
+use crate::athena::generator::defaults::DefaultsEngine;
+use crate::athena::mounts::{is_bind_mount, is_runtime_socket};
+use crate::athena::parser::ast::{AthenaFile, IpcMode, NetworkMode, PidMode, PortMapping, Protocol, VolumeMapping};
+
+/// Serialization of the inventory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Json,
+    /// Tables, one section per kind of entry
+    Markdown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Inventory {
+    pub project: String,
+    pub images: Vec<ImageEntry>,
+    pub ports: Vec<PortEntry>,
+    pub volumes: Vec<MountEntry>,
+    pub privileged: Vec<PrivilegedEntry>,
+}
+
+/// What a service runs
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageEntry {
+    pub service: String,
+    /// Reference as written in IMAGE-ID; None when the image is built
+    pub image: Option<String>,
+    /// None when the reference has no tag, so Docker pulls `latest`
+    pub tag: Option<String>,
+    pub digest: Option<String>,
+    /// Built from ./Dockerfile
+    pub build: bool,
+}
+
+/// A published host port
+#[derive(Debug, Clone, Serialize)]
+pub struct PortEntry {
+    pub service: String,
+    #[serde(flatten)]
+    pub port: PortMapping,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum MountType {
+    Bind,
+    Volume,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MountEntry {
+    pub service: String,
+    #[serde(rename = "type")]
+    pub mount_type: MountType,
+    #[serde(flatten)]
+    pub mount: VolumeMapping,
+}
+
+/// A setting giving the container host-level access. The DSL has no
+/// PRIVILEGED directive; these are what comes closest.
+#[derive(Debug, Clone, Serialize)]
+pub struct PrivilegedEntry {
+    pub service: String,
+    /// e.g. `network_mode: host` or `mounts /var/run/docker.sock`
+    pub setting: String,
+}
+
+impl Inventory {
+    /// The inventory of an already validated file
+    pub fn from_file(athena_file: &AthenaFile) -> Self {
+        let project = athena_file.get_project_name();
+        let mut inventory = Inventory {
+            project: project.clone(),
+            images: Vec::new(),
+            ports: Vec::new(),
+            volumes: Vec::new(),
+            privileged: Vec::new(),
+        };
+
+        for service in &athena_file.services.services {
+            let build = DefaultsEngine::create_build_config(service, &project).is_some();
+            // BUILD-ARGS make Compose build even when IMAGE-ID is set
+            let image = service.image.clone().filter(|_| service.build_args.is_none());
+            let (tag, digest) = image.as_deref().map(split_reference).unwrap_or_default();
+            inventory.images.push(ImageEntry {
+                service: service.name.clone(),
+                tag: tag.map(str::to_string),
+                digest: digest.map(str::to_string),
+                image,
+                build,
+            });
+
+            inventory.ports.extend(service.ports.iter().map(|port| PortEntry {
+                service: service.name.clone(),
+                port: port.clone(),
+            }));
+
+            for mount in &service.volumes {
+                let mount_type = if is_bind_mount(&mount.host_path) { MountType::Bind } else { MountType::Volume };
+                inventory.volumes.push(MountEntry {
+                    service: service.name.clone(),
+                    mount_type,
+                    mount: mount.clone(),
+                });
+            }
+
+            let mut settings: Vec<String> = service
+                .volumes
+                .iter()
+                .filter(|mount| is_runtime_socket(&mount.host_path))
+                .map(|mount| format!("mounts {}", mount.host_path))
+                .collect();
+            if service.network_mode == Some(NetworkMode::Host) {
+                settings.push("network_mode: host".to_string());
+            }
+            if service.pid == Some(PidMode::Host) {
+                settings.push("pid: host".to_string());
+            }
+            if service.ipc == Some(IpcMode::Host) {
+                settings.push("ipc: host".to_string());
+            }
+            inventory.privileged.extend(settings.into_iter().map(|setting| PrivilegedEntry {
+                service: service.name.clone(),
+                setting,
+            }));
+        }
+
+        inventory.images.sort_by(|a, b| a.service.cmp(&b.service));
+        inventory.ports.sort_by(|a, b| {
+            (a.port.host_port, a.port.container_port, &a.service)
+                .cmp(&(b.port.host_port, b.port.container_port, &b.service))
+        });
+        inventory.volumes.sort_by(|a, b| {
+            (a.mount_type, &a.mount.host_path, &a.service, &a.mount.container_path)
+                .cmp(&(b.mount_type, &b.mount.host_path, &b.service, &b.mount.container_path))
+        });
+        inventory
+            .privileged
+            .sort_by(|a, b| (&a.service, &a.setting).cmp(&(&b.service, &b.setting)));
+        inventory
+    }
+
+    pub fn render(&self, format: ReportFormat) -> AthenaResult<String> {
+        match format {
+            ReportFormat::Json => serde_json::to_string_pretty(self)
+                .map(|json| json + "\n")
+                .map_err(|e| AthenaError::config_error(format!("Failed to serialize report: {e}"))),
+            ReportFormat::Markdown => Ok(self.to_markdown()),
+        }
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut markdown = format!("# Stack inventory: {}\n", self.project);
+
+        section(&mut markdown, "Images", &["Service", "Image", "Tag", "Digest"], self.images.iter().map(|entry| {
+            let (image, tag) = match &entry.image {
+                Some(image) => (
+                    image.clone(),
+                    entry.tag.clone().unwrap_or_else(|| "latest (implicit)".to_string()),
+                ),
+                None => ("built from ./Dockerfile".to_string(), String::new()),
+            };
+            vec![entry.service.clone(), image, tag, entry.digest.clone().unwrap_or_default()]
+        }));
+
+        section(&mut markdown, "Host ports", &["Host port", "Container port", "Protocol", "Service"], self.ports.iter().map(|entry| {
+            let protocol = match entry.port.protocol {
+                Protocol::Tcp => "tcp",
+                Protocol::Udp => "udp",
+            };
+            vec![
+                entry.port.host_ports(),
+                entry.port.container_port.to_string(),
+                protocol.to_string(),
+                entry.service.clone(),
+            ]
+        }));
+
+        for (title, mount_type) in [("Bind mounts", MountType::Bind), ("Named volumes", MountType::Volume)] {
+            let mounts = self.volumes.iter().filter(|entry| entry.mount_type == mount_type);
+            section(&mut markdown, title, &["Host path", "Container path", "Options", "Service"], mounts.map(|entry| {
+                vec![
+                    entry.mount.host_path.clone(),
+                    entry.mount.container_path.clone(),
+                    entry.mount.options.join(","),
+                    entry.service.clone(),
+                ]
+            }));
+        }
+
+        section(&mut markdown, "Privileged access", &["Service", "Setting"], self.privileged.iter().map(|entry| {
+            vec![entry.service.clone(), entry.setting.clone()]
+        }));
+        markdown
+    }
+}
+
+/// `## title` and a table, or `None.` when there are no rows
+fn section(markdown: &mut String, title: &str, headers: &[&str], rows: impl Iterator<Item = Vec<String>>) {
+    let _ = write!(markdown, "\n## {title}\n\n");
+    let mut rows = rows.peekable();
+    if rows.peek().is_none() {
+        markdown.push_str("None.\n");
+        return;
+    }
+    let _ = writeln!(markdown, "| {} |", headers.join(" | "));
+    let _ = writeln!(markdown, "|{}", "---|".repeat(headers.len()));
+    for row in rows {
+        let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+        let _ = writeln!(markdown, "| {} |", cells.join(" | "));
+    }
+}
+
+/// Tag and digest of an image reference. A `:` before the last `/` belongs
+/// to a registry port, not a tag.
+fn split_reference(reference: &str) -> (Option<&str>, Option<&str>) {
+    let (name, digest) = match reference.split_once('@') {
+        Some((name, digest)) => (name, Some(digest)),
+        None => (reference, None),
+    };
+    let last_component = name.rfind('/').map_or(name, |slash| &name[slash + 1..]);
+    let tag = last_component.rsplit_once(':').map(|(_, tag)| tag);
+    (tag, digest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::parse_athena_file;
+
+    const STACK: &str = r#"DEPLOYMENT-ID shop
+ENVIRONMENT SECTION
+VOLUME pg_data
+
+SERVICES SECTION
+SERVICE web
+IMAGE-ID "nginx:1.27"
+PORT-MAPPING 443 TO 443
+PORT-MAPPING 80 TO 80
+VOLUME-MAPPING "./nginx.conf" TO "/etc/nginx/nginx.conf" (ro)
+END SERVICE
+
+SERVICE db
+IMAGE-ID "registry.local:5000/postgres"
+VOLUME-MAPPING "pg_data" TO "/var/lib/postgresql/data"
+END SERVICE
+
+SERVICE agent
+IMAGE-ID "datadog/agent@sha256:abc123"
+VOLUME-MAPPING "/var/run/docker.sock" TO "/var/run/docker.sock" (ro)
+ALLOW DOCKER SOCKET
+PID host
+END SERVICE
+
+SERVICE api
+PORT-MAPPING 8080 TO 8000
+END SERVICE
+"#;
+
+    fn inventory() -> Inventory {
+        Inventory::from_file(&parse_athena_file(STACK).unwrap())
+    }
+
+    #[test]
+    fn test_inventory_is_sorted() {
+        let inventory = inventory();
+        let services: Vec<&str> = inventory.images.iter().map(|e| e.service.as_str()).collect();
+        assert_eq!(services, ["agent", "api", "db", "web"]);
+        let ports: Vec<u16> = inventory.ports.iter().map(|e| e.port.host_port).collect();
+        assert_eq!(ports, [80, 443, 8080]);
+        let mounts: Vec<&str> = inventory.volumes.iter().map(|e| e.mount.host_path.as_str()).collect();
+        assert_eq!(mounts, ["./nginx.conf", "/var/run/docker.sock", "pg_data"]);
+        assert_eq!(inventory.privileged.len(), 2);
+    }
+
+    #[test]
+    fn test_image_references_are_split() {
+        let inventory = inventory();
+        let image = |service: &str| inventory.images.iter().find(|e| e.service == service).unwrap();
+        assert_eq!(image("web").tag.as_deref(), Some("1.27"));
+        assert_eq!(image("db").tag, None);
+        assert_eq!(image("agent").digest.as_deref(), Some("sha256:abc123"));
+        assert!(image("api").build && image("api").image.is_none());
+    }
+
+    #[test]
+    fn test_json_uses_ast_field_names() {
+        let json: serde_json::Value = serde_json::from_str(&inventory().render(ReportFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["ports"][0]["host_port"], 80);
+        assert_eq!(json["ports"][0]["container_port"], 80);
+        assert_eq!(json["volumes"][0]["type"], "bind");
+        assert_eq!(json["volumes"][0]["container_path"], "/etc/nginx/nginx.conf");
+        assert_eq!(json["images"][3]["image"], "nginx:1.27");
+    }
+
+    #[test]
+    fn test_markdown_sections() {
+        let markdown = inventory().render(ReportFormat::Markdown).unwrap();
+        assert!(markdown.starts_with("# Stack inventory: shop\n"), "{markdown}");
+        assert!(markdown.contains("| db | registry.local:5000/postgres | latest (implicit) |  |\n"), "{markdown}");
+        assert!(markdown.contains("| api | built from ./Dockerfile |  |  |\n"), "{markdown}");
+        assert!(markdown.contains("| 80 | 80 | tcp | web |\n| 443 | 443 | tcp | web |\n"), "{markdown}");
+        assert!(markdown.contains("| agent | mounts /var/run/docker.sock |\n| agent | pid: host |\n"), "{markdown}");
+        assert_eq!(markdown, inventory().render(ReportFormat::Markdown).unwrap());
+    }
+}
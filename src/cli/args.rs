@@ -1,6 +1,12 @@
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
+use crate::athena::generator::{parse_stack_prefix, ComposeFormat, OutputTarget};
+use crate::athena::report::ReportFormat;
+use crate::cli::cache::DEFAULT_CACHE_DIR;
+use crate::cli::output::ColorChoice;
+use crate::cli::suite::SuiteReport;
+
 #[derive(Parser, Debug)]
 #[command(
     name = "athena",
@@ -15,6 +21,15 @@ pub struct Cli {
     /// Enable verbose output
     #[arg(short, long)]
     pub verbose: bool,
+
+    /// Run as if started in DIR. Paths on the command line are relative to it;
+    /// paths inside a .ath file are always relative to that file
+    #[arg(short = 'C', long = "chdir", value_name = "DIR", global = true)]
+    pub chdir: Option<PathBuf>,
+
+    /// When to color output (NO_COLOR is honoured in auto mode)
+    #[arg(long, value_enum, global = true, default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
 }
 
 #[derive(Subcommand, Debug)]
@@ -22,14 +37,58 @@ pub enum Commands {
     /// Build docker-compose.yml from Athena DSL file
     #[command(alias = "b")]
     Build {
-        /// Input .ath file path (auto-detects if not specified)
+        /// Input .ath files, later ones overriding earlier ones, or a directory to build
+        /// each .ath file in it separately (auto-detects if not specified)
         #[arg(value_name = "FILE")]
-        input: Option<PathBuf>,
+        input: Vec<PathBuf>,
 
-        /// Output file path (defaults to docker-compose.yml)
+        /// Output file path (defaults to docker-compose.yml, or main.tf.json for terraform-docker);
+        /// with --all-envs, the directory holding one subdirectory per environment; with a
+        /// directory input, the directory receiving <stem>.yml per file
         #[arg(short, long, value_name = "FILE")]
         output: Option<PathBuf>,
 
+        /// With a directory input, also build the .ath files in its subdirectories
+        #[arg(long)]
+        recursive: bool,
+
+        /// With a directory input, build at most N files at once (defaults to the CPU count)
+        #[arg(short, long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        jobs: Option<u32>,
+
+        /// Apply the overrides of ENVIRONMENT "NAME" before generating
+        #[arg(long, value_name = "NAME")]
+        env: Option<String>,
+
+        /// Build every ENVIRONMENT into <output>/<name>/
+        #[arg(long, conflicts_with = "env")]
+        all_envs: bool,
+
+        /// Output format to generate
+        #[arg(long, value_enum, default_value_t = BuildTarget::Compose)]
+        target: BuildTarget,
+
+        /// Serialization of Compose output (ignored by terraform-docker, which is always JSON)
+        #[arg(long, value_enum, default_value_t = BuildFormat::Yaml)]
+        output_format: BuildFormat,
+
+        /// SELinux label appended to bind mounts with --target podman (z = shared, Z = private)
+        #[arg(long, value_name = "LABEL", value_parser = ["z", "Z"])]
+        selinux_label: Option<String>,
+
+        /// Prepend a note on running the stack under systemd with --target podman
+        #[arg(long)]
+        systemd_note: bool,
+
+        /// Omit the generation timestamp for byte-stable output
+        #[arg(long)]
+        reproducible: bool,
+
+        /// Leave out keys set to Compose's defaults, and the project network
+        /// when Compose's default network is equivalent
+        #[arg(long)]
+        minimal: bool,
+
         /// Validate syntax only, don't generate output
         #[arg(long)]
         validate_only: bool,
@@ -37,6 +96,82 @@ pub enum Commands {
         /// Quiet mode (disable verbose output)
         #[arg(short, long)]
         quiet: bool,
+
+        /// Warn about bind mount sources that don't exist on disk
+        #[arg(long)]
+        check_bind_mounts: bool,
+
+        /// Print the order services start in, grouping those that start in parallel
+        #[arg(long)]
+        show_start_order: bool,
+
+        /// Override a guardrail, e.g. services=1000 (keys: services, env_vars, output_bytes)
+        #[arg(long, value_name = "KEY=VALUE", value_delimiter = ',')]
+        limit: Vec<String>,
+
+        /// Skip the service count, environment size and output size guardrails
+        #[arg(long, conflicts_with = "limit")]
+        no_limits: bool,
+
+        /// Treat warnings as errors (exit code 4)
+        #[arg(long)]
+        fail_on_warnings: bool,
+
+        /// List the warnings silenced by `// athena:ignore` comments
+        #[arg(long)]
+        report_suppressed: bool,
+
+        /// Reuse output from previous builds of unchanged input, stored in DIR
+        #[arg(long, value_name = "DIR")]
+        cache_dir: Option<PathBuf>,
+
+        /// Print phase timings, output size and per-service counts after building
+        #[arg(long)]
+        stats: bool,
+
+        /// Format of the --stats report
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text, requires = "stats")]
+        format: OutputFormat,
+
+        /// Fail if an image the output pulls is missing from the local Docker daemon
+        #[arg(long)]
+        require_images_exist: bool,
+
+        /// Platform the images must exist for with --require-images-exist, unless a
+        /// service sets PLATFORM (e.g. linux/arm64)
+        #[arg(long, value_name = "OS/ARCH", requires = "require_images_exist")]
+        platform: Option<String>,
+
+        /// Comment each service, volume and network with the .ath file and line
+        /// declaring it (YAML output only)
+        #[arg(long, overrides_with = "no_annotations")]
+        annotate_sources: bool,
+
+        /// Leave out source annotations, overriding an earlier --annotate-sources
+        #[arg(long, overrides_with = "annotate_sources")]
+        no_annotations: bool,
+
+        /// Generate only these services, plus the services they depend on
+        #[arg(long, value_name = "SERVICES", value_delimiter = ',')]
+        only: Vec<String>,
+
+        /// With --only, leave out dependencies and drop DEPENDS-ON entries pointing at them
+        #[arg(long, requires = "only")]
+        no_deps: bool,
+
+        /// Pipe the output through CMD before writing it; CMD reads the Compose file on stdin
+        /// and prints the new one (repeatable, runs after the [[postprocess]] hooks of athena.toml)
+        #[arg(long, value_name = "CMD")]
+        post_process: Vec<String>,
+
+        /// Skip the [[postprocess]] hooks of athena.toml
+        #[arg(long)]
+        no_post_process: bool,
+
+        /// Prefix the project name, networks, named volumes, secrets and CONTAINER-NAMEs with
+        /// PREFIX-, so stacks from several branches can share a host; also the value of {{stack_prefix}}
+        #[arg(long, value_name = "PREFIX", value_parser = parse_stack_prefix)]
+        stack_prefix: Option<String>,
     },
 
     /// Validate Athena DSL file syntax
@@ -45,6 +180,199 @@ pub enum Commands {
         /// Input .ath file path (auto-detects if not specified)
         #[arg(value_name = "FILE")]
         input: Option<PathBuf>,
+
+        /// Warn about bind mount sources that don't exist on disk
+        #[arg(long)]
+        check_bind_mounts: bool,
+
+        /// Print the order services start in, grouping those that start in parallel
+        #[arg(long)]
+        show_start_order: bool,
+
+        /// Override a guardrail, e.g. services=1000 (keys: services, env_vars, output_bytes)
+        #[arg(long, value_name = "KEY=VALUE", value_delimiter = ',')]
+        limit: Vec<String>,
+
+        /// Skip the service count, environment size and output size guardrails
+        #[arg(long, conflicts_with = "limit")]
+        no_limits: bool,
+
+        /// Treat warnings as errors (exit code 4)
+        #[arg(long)]
+        fail_on_warnings: bool,
+
+        /// List the warnings silenced by `// athena:ignore` comments
+        #[arg(long)]
+        report_suppressed: bool,
+    },
+
+    /// Parse, validate and lint every .ath file under the given paths, as a CI suite
+    Test {
+        /// Directories to search and files to check (defaults to the current directory);
+        /// .gitignore'd and hidden paths are skipped
+        #[arg(value_name = "PATH")]
+        paths: Vec<PathBuf>,
+
+        /// Also write a report, as FORMAT:PATH (e.g. junit:athena.xml)
+        #[arg(long, value_name = "FORMAT:PATH")]
+        report: Option<SuiteReport>,
+
+        /// Fail files that have warnings, not only errors
+        #[arg(long)]
+        strict: bool,
+
+        /// Check at most N files at once (defaults to the CPU count)
+        #[arg(short, long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+        jobs: Option<u32>,
+
+        /// Override a guardrail, e.g. services=1000 (keys: services, env_vars, output_bytes)
+        #[arg(long, value_name = "KEY=VALUE", value_delimiter = ',')]
+        limit: Vec<String>,
+
+        /// Skip the service count, environment size and output size guardrails
+        #[arg(long, conflicts_with = "limit")]
+        no_limits: bool,
+    },
+
+    /// Manage the build output cache
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
+    /// Check whether a generated file was hand-edited or is stale relative to its .ath source
+    Verify {
+        /// Generated file to check, e.g. docker-compose.yml
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Source .ath file (defaults to the path recorded in the file header)
+        #[arg(long, value_name = "FILE")]
+        against: Option<PathBuf>,
+    },
+
+    /// Append a SERVICE block to an .ath file, leaving existing content and comments untouched
+    AddService {
+        /// .ath file to edit in place, or - to read stdin and print the result
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+
+        /// Name of the new service; it must not exist yet
+        #[arg(long)]
+        name: String,
+
+        /// IMAGE-ID of the service (built from ./Dockerfile if omitted)
+        #[arg(long)]
+        image: Option<String>,
+
+        /// ENV-VARIABLE: KEY=value for a literal, KEY for a {{KEY}} template (repeatable)
+        #[arg(long, value_name = "KEY[=VALUE]")]
+        env: Vec<String>,
+
+        /// PORT-MAPPING as HOST:CONTAINER, with an optional host range and /udp (repeatable)
+        #[arg(long, value_name = "HOST:CONTAINER")]
+        port: Vec<String>,
+
+        /// DEPENDS-ON an existing service (repeatable)
+        #[arg(long, value_name = "SERVICE")]
+        depends_on: Vec<String>,
+    },
+
+    /// Print the environment a service gets, one KEY=value per line
+    Env {
+        /// Input .ath files, later ones overriding earlier ones (auto-detects if not specified)
+        #[arg(value_name = "FILE")]
+        input: Vec<PathBuf>,
+
+        /// Service to show
+        #[arg(long, value_name = "NAME")]
+        service: String,
+
+        /// Apply the overrides of ENVIRONMENT "NAME" first
+        #[arg(long, value_name = "NAME")]
+        env: Option<String>,
+
+        /// Show every layer that sets KEY, with its file and line, and which one wins
+        #[arg(long, value_name = "KEY")]
+        explain: Option<String>,
+    },
+
+    /// Inventory the images, host ports, mounts and privileged settings of a stack
+    Report {
+        /// Input .ath files, later ones overriding earlier ones (auto-detects if not specified)
+        #[arg(value_name = "FILE")]
+        input: Vec<PathBuf>,
+
+        /// Apply the overrides of ENVIRONMENT "NAME" first
+        #[arg(long, value_name = "NAME")]
+        env: Option<String>,
+
+        /// Report format
+        #[arg(long, value_enum, default_value_t = InventoryFormat::Markdown)]
+        format: InventoryFormat,
+
+        /// Write the report to FILE instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Generate an nginx reverse-proxy config from EXPOSE-VIA directives
+    Nginx {
+        /// Input .ath file path (auto-detects if not specified)
+        #[arg(value_name = "FILE")]
+        input: Option<PathBuf>,
+
+        /// Output file path (defaults to nginx.conf)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+
+    /// Generate backup.sh and restore.sh for the stack's named volumes
+    Scripts {
+        /// Input .ath file path (auto-detects if not specified)
+        #[arg(value_name = "FILE")]
+        input: Option<PathBuf>,
+
+        /// Directory to write the scripts to
+        #[arg(short, long, value_name = "DIR", default_value = "scripts")]
+        output: PathBuf,
+    },
+
+    /// Generate a docker buildx bake file for services built from a Dockerfile
+    Bake {
+        /// Input .ath file path (auto-detects if not specified)
+        #[arg(value_name = "FILE")]
+        input: Option<PathBuf>,
+
+        /// Output file path (defaults to docker-bake.hcl, JSON if it ends in .json)
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+
+        /// Registry prefix for image tags, e.g. ghcr.io/acme
+        #[arg(long, value_name = "REGISTRY")]
+        registry: Option<String>,
+
+        /// Platforms to build for, e.g. linux/amd64,linux/arm64 (services with PLATFORM keep theirs)
+        #[arg(long, value_name = "PLATFORMS", value_delimiter = ',')]
+        platform: Vec<String>,
+    },
+
+    /// Print version, build and platform details for bug reports
+    SelfInfo {
+        /// Also check GitHub for a newer release (set ATHENA_NO_UPDATE_CHECK=1 to disable)
+        #[arg(long)]
+        check_updates: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+    },
+
+    /// Explain a warning code such as ATH005, with an example and its fix
+    Explain {
+        /// Warning code, e.g. ATH005
+        #[arg(value_name = "CODE")]
+        code: String,
     },
 
     /// Show information about Athena DSL syntax
@@ -56,5 +384,95 @@ pub enum Commands {
         /// Show supported directives
         #[arg(long)]
         directives: bool,
+
+        /// Show built-in service presets and their defaults
+        #[arg(long)]
+        presets: bool,
+
+        /// Show the exit code returned for each kind of failure
+        #[arg(long)]
+        exit_codes: bool,
+
+        /// List the ENVIRONMENT blocks of an .ath file (auto-detects if not specified)
+        #[arg(long)]
+        environments: bool,
+
+        /// Input .ath file for --environments
+        #[arg(value_name = "FILE", requires = "environments")]
+        input: Option<PathBuf>,
     },
-}
\ No newline at end of file
+}
+
+#[derive(Subcommand, Debug)]
+pub enum CacheAction {
+    /// Remove all cached build output
+    Clear {
+        /// Cache directory used with `athena build --cache-dir`
+        #[arg(long, value_name = "DIR", default_value = DEFAULT_CACHE_DIR)]
+        cache_dir: PathBuf,
+    },
+}
+
+/// Output formats supported by `athena build`
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BuildTarget {
+    /// Docker Compose YAML
+    #[default]
+    Compose,
+    /// Compose YAML adjusted for rootless podman-compose
+    Podman,
+    /// Terraform JSON for the kreuzwerker/docker provider
+    TerraformDocker,
+}
+
+impl From<BuildTarget> for OutputTarget {
+    fn from(target: BuildTarget) -> Self {
+        match target {
+            BuildTarget::Compose => OutputTarget::Compose,
+            BuildTarget::Podman => OutputTarget::Podman,
+            BuildTarget::TerraformDocker => OutputTarget::TerraformDocker,
+        }
+    }
+}
+
+/// Compose serializations supported by `athena build`
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BuildFormat {
+    #[default]
+    Yaml,
+    Json,
+}
+
+impl From<BuildFormat> for ComposeFormat {
+    fn from(format: BuildFormat) -> Self {
+        match format {
+            BuildFormat::Yaml => ComposeFormat::Yaml,
+            BuildFormat::Json => ComposeFormat::Json,
+        }
+    }
+}
+
+/// Formats of `athena report`
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InventoryFormat {
+    #[default]
+    Markdown,
+    Json,
+}
+
+impl From<InventoryFormat> for ReportFormat {
+    fn from(format: InventoryFormat) -> Self {
+        match format {
+            InventoryFormat::Markdown => ReportFormat::Markdown,
+            InventoryFormat::Json => ReportFormat::Json,
+        }
+    }
+}
+
+/// Human or machine-readable output for reporting commands
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+}
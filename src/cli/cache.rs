@@ -0,0 +1,133 @@
+//! Opt-in cache of generated output, keyed on everything that affects it.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::athena::provenance::sha256_hex;
+use crate::athena::{AthenaError, AthenaResult, GeneratorOptions};
+use crate::cli::utils::write_atomic;
+
+pub const DEFAULT_CACHE_DIR: &str = ".athena-cache";
+
+const ENTRY_EXTENSION: &str = "json";
+
+/// Generated content plus the warnings printed when it was first built
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedOutput {
+    pub content: String,
+    pub warnings: Vec<String>,
+}
+
+pub struct OutputCache {
+    dir: PathBuf,
+}
+
+impl OutputCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// Hash of the Athena build, the generator options and every input file
+    /// that generation reads (the .ath source first)
+    pub fn key(options: &GeneratorOptions, inputs: &[&str]) -> String {
+        let mut material = format!(
+            "{}\n{}\n{}\n",
+            env!("CARGO_PKG_VERSION"),
+            env!("ATHENA_GIT_COMMIT"),
+            options.fingerprint()
+        );
+        for input in inputs {
+            // Length prefixes keep ["ab", "c"] and ["a", "bc"] apart
+            material.push_str(&format!("{}:{input}", input.len()));
+        }
+        sha256_hex(&material)
+    }
+
+    /// A missing or unreadable entry is a miss, never an error
+    pub fn load(&self, key: &str) -> Option<CachedOutput> {
+        let raw = fs::read_to_string(self.entry_path(key)).ok()?;
+        serde_json::from_str(&raw).ok()
+    }
+
+    pub fn store(&self, key: &str, output: &CachedOutput) -> AthenaResult<()> {
+        fs::create_dir_all(&self.dir).map_err(AthenaError::IoError)?;
+        let raw = serde_json::to_string(output)
+            .map_err(|e| AthenaError::config_error(format!("Failed to serialize cache entry: {e}")))?;
+        write_atomic(&self.entry_path(key), &raw)
+    }
+
+    /// Remove cache entries, leaving any other files in the directory alone.
+    /// Returns the number of entries removed.
+    pub fn clear(&self) -> AthenaResult<usize> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(e) => return Err(AthenaError::IoError(e)),
+        };
+
+        let mut removed = 0;
+        for entry in entries {
+            let path = entry.map_err(AthenaError::IoError)?.path();
+            if is_cache_entry(&path) {
+                fs::remove_file(&path).map_err(AthenaError::IoError)?;
+                removed += 1;
+            }
+        }
+
+        Ok(removed)
+    }
+
+    fn entry_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.{ENTRY_EXTENSION}"))
+    }
+}
+
+fn is_cache_entry(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == ENTRY_EXTENSION)
+        && path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .is_some_and(|stem| stem.len() == 64 && stem.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::generator::OutputTarget;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_key_depends_on_source_and_options() {
+        let options = GeneratorOptions::new();
+        let key = OutputCache::key(&options, &["SERVICES SECTION"]);
+
+        assert_eq!(key, OutputCache::key(&options, &["SERVICES SECTION"]));
+        assert_ne!(key, OutputCache::key(&options, &["SERVICES SECTION\n"]));
+        assert_ne!(key, OutputCache::key(&options, &["SERVICES SECTION", "FROM alpine"]));
+        assert_ne!(
+            key,
+            OutputCache::key(&options.clone().with_target(OutputTarget::Podman), &["SERVICES SECTION"])
+        );
+    }
+
+    #[test]
+    fn test_store_load_and_clear() {
+        let dir = TempDir::new().unwrap();
+        let cache = OutputCache::new(dir.path().join("cache"));
+        let key = OutputCache::key(&GeneratorOptions::new(), &["source"]);
+        let output = CachedOutput {
+            content: "services: {}\n".to_string(),
+            warnings: vec!["careful".to_string()],
+        };
+
+        assert_eq!(cache.load(&key), None);
+        cache.store(&key, &output).unwrap();
+        assert_eq!(cache.load(&key), Some(output));
+
+        fs::write(dir.path().join("cache/notes.json"), "{}").unwrap();
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert_eq!(cache.load(&key), None);
+        assert!(dir.path().join("cache/notes.json").exists());
+    }
+}
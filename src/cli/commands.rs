@@ -1,121 +1,942 @@
+use std::collections::BTreeMap;
 use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 
-use crate::athena::{generate_docker_compose, parse_athena_file, AthenaError, AthenaResult};
-use crate::cli::args::Commands;
-use crate::cli::utils::{auto_detect_ath_file, should_be_verbose};
+use rayon::prelude::*;
+
+use crate::athena::dependencies::start_order;
+use crate::athena::edit::{append_service, NewService};
+use crate::athena::env_trace::{trace_service_env, EnvValue};
+use crate::athena::labels::{default_labels_path, load_default_labels};
+use crate::athena::limits::{Limits, CONFIG_FILE};
+use crate::athena::merge::{apply_environment, parse_and_merge, SourceFile};
+use crate::athena::mounts::check_bind_mounts;
+use crate::athena::parser::{check_platform, dsl_keywords};
+use crate::athena::warnings::{collect_warnings, find_code, Diagnostics, Warning, WARNING_CODES};
+use crate::athena::parser::ast::AthenaFile;
+use crate::athena::error::{EnhancedValidationError, EXIT_CODES};
+use crate::athena::generator::{
+    generate_bake_file, generate_nginx_config, generate_volume_scripts, select_services, BakeFormat,
+    OutputTarget, SelinuxLabel, ServiceSelection,
+};
+use crate::athena::presets::PRESETS;
+use crate::athena::provenance::{
+    combine_sources, read_stamp, stamp_output, verify_output, VerifyStatus, SOURCE_PATH_SEPARATOR,
+};
+use crate::athena::report::{Inventory, ReportFormat};
+use crate::athena::{generate, parse_athena_file, AthenaError, AthenaResult, GeneratorOptions};
+use crate::cli::args::{CacheAction, Commands, OutputFormat};
+use crate::cli::cache::{CachedOutput, OutputCache};
+use crate::cli::junit;
+use crate::cli::messages::{message, text, Message};
+use crate::cli::output;
+use crate::cli::postprocess::{load_hooks, run_hooks, PostProcessHook};
+use crate::cli::preflight;
+use crate::cli::stats::{millis, BuildStats, PhaseTimings};
+use crate::cli::suite::{check_file, discover, FileResult, SuiteReport};
+use crate::cli::table::{Cell, Table};
+use crate::cli::update::{check_for_updates, UpdateStatus};
+use crate::cli::utils::{auto_detect_ath_file, find_ath_files, should_be_verbose, write_atomic};
 
 pub fn execute_command(command: Option<Commands>, verbose: bool) -> AthenaResult<()> {
     match command {
         // Magic command - no argument, auto-detect and build
         None => {
             if verbose {
-                println!("Magic mode: Auto-detecting and building...");
+                println!("{}", text(Message::MagicMode));
             }
-            execute_build(None, None, false, true)
+            let settings = BuildSettings {
+                verbose: true,
+                limits: resolve_limits(&[], false)?,
+                post_process: resolve_post_process(Vec::new(), false)?,
+                ..BuildSettings::default()
+            };
+            execute_build(Vec::new(), None, GeneratorOptions::default(), settings)
         }
         Some(Commands::Build {
             input,
             output,
+            recursive,
+            jobs,
+            env,
+            all_envs,
+            target,
+            output_format,
+            selinux_label,
+            systemd_note,
+            reproducible,
+            minimal,
             validate_only,
             quiet,
+            check_bind_mounts,
+            show_start_order,
+            limit,
+            no_limits,
+            fail_on_warnings,
+            report_suppressed,
+            cache_dir,
+            stats,
+            format,
+            require_images_exist,
+            platform,
+            annotate_sources,
+            no_annotations,
+            only,
+            no_deps,
+            post_process,
+            no_post_process,
+            stack_prefix,
+        }) => {
+            if let Some(platform) = &platform {
+                check_platform_flag(platform)?;
+            }
+            let annotate_sources = annotate_sources && !no_annotations;
+            // Progress lines would break the JSON report on stdout
+            let verbose = should_be_verbose(quiet) && !(stats && format == OutputFormat::Json);
+            let options = GeneratorOptions::new()
+                .with_target(target.into())
+                .with_compose_format(output_format.into())
+                .with_reproducible(reproducible)
+                .with_selinux_label(selinux_label.map(|label| match label.as_str() {
+                    "z" => SelinuxLabel::Shared,
+                    _ => SelinuxLabel::Private,
+                }))
+                .with_systemd_note(systemd_note)
+                .with_annotate_sources(annotate_sources)
+                .with_minimal(minimal)
+                .with_stack_prefix(stack_prefix);
+            if annotate_sources && !options.supports_comments() {
+                return Err(AthenaError::incompatible_error(message!(
+                    AnnotateNeedsYaml,
+                    what = options.description()
+                )));
+            }
+            if minimal && options.target() == OutputTarget::TerraformDocker {
+                return Err(AthenaError::incompatible_error(message!(
+                    MinimalNeedsCompose,
+                    what = options.description()
+                )));
+            }
+            let post_process = resolve_post_process(post_process, no_post_process)?;
+            if !post_process.is_empty() && options.target() == OutputTarget::TerraformDocker {
+                return Err(AthenaError::incompatible_error(message!(
+                    PostProcessNeedsCompose,
+                    what = options.description()
+                )));
+            }
+            let settings = BuildSettings {
+                validate_only,
+                verbose,
+                check_mounts: check_bind_mounts,
+                show_start_order,
+                env,
+                limits: resolve_limits(&limit, no_limits)?,
+                warning_policy: WarningPolicy {
+                    fail: fail_on_warnings,
+                    report_suppressed,
+                },
+                cache_dir,
+                stats: stats.then_some(format),
+                require_images: require_images_exist.then_some(platform),
+                only: (!only.is_empty()).then_some(ServiceSelection {
+                    services: only,
+                    with_dependencies: !no_deps,
+                }),
+                post_process,
+                batch: false,
+            };
+            if let Some(dir) = input.iter().find(|path| path.is_dir()) {
+                if input.len() > 1 {
+                    return Err(AthenaError::usage_error(message!(
+                        DirectoryWithOtherInputs,
+                        dir = dir.display()
+                    )));
+                }
+                let single_stack_flag = [
+                    (all_envs, "--all-envs"),
+                    (stats, "--stats"),
+                    (show_start_order, "--show-start-order"),
+                ]
+                .into_iter()
+                .find_map(|(set, flag)| set.then_some(flag));
+                if let Some(flag) = single_stack_flag {
+                    return Err(AthenaError::usage_error(message!(SingleStackFlag, flag = flag)));
+                }
+                let batch = BatchSettings { recursive, jobs };
+                return execute_build_directory(dir, output, batch, options, settings);
+            }
+            if recursive || jobs.is_some() {
+                return Err(AthenaError::usage_error(text(Message::DirectoryOnlyFlags).to_string()));
+            }
+            if all_envs {
+                execute_build_all_envs(input, output, options, settings)
+            } else {
+                execute_build(input, output, options, settings)
+            }
+        }
+
+        Some(Commands::Cache {
+            action: CacheAction::Clear { cache_dir },
+        }) => execute_cache_clear(&cache_dir),
+
+        Some(Commands::Validate {
+            input,
+            check_bind_mounts,
+            show_start_order,
+            limit,
+            no_limits,
+            fail_on_warnings,
+            report_suppressed,
+        }) => {
+            let limits = resolve_limits(&limit, no_limits)?;
+            let warning_policy = WarningPolicy {
+                fail: fail_on_warnings,
+                report_suppressed,
+            };
+            execute_validate(input, verbose, check_bind_mounts, show_start_order, limits, warning_policy)
+        }
+
+        Some(Commands::Test {
+            paths,
+            report,
+            strict,
+            jobs,
+            limit,
+            no_limits,
         }) => {
-            let verbose = should_be_verbose(quiet);
-            execute_build(input, output, validate_only, verbose)
+            let limits = resolve_limits(&limit, no_limits)?;
+            execute_test(paths, report, strict, jobs, limits)
         }
 
-        Some(Commands::Validate { input }) => execute_validate(input, verbose),
+        Some(Commands::Verify { file, against }) => execute_verify(file, against),
+
+        Some(Commands::AddService {
+            file,
+            name,
+            image,
+            env,
+            port,
+            depends_on,
+        }) => execute_add_service(
+            file,
+            NewService {
+                name,
+                image,
+                env,
+                ports: port,
+                depends_on,
+            },
+        ),
+
+        Some(Commands::Env {
+            input,
+            service,
+            env,
+            explain,
+        }) => execute_env(input, &service, env.as_deref(), explain.as_deref(), verbose),
+
+        Some(Commands::Report {
+            input,
+            env,
+            format,
+            output,
+        }) => execute_report(input, env.as_deref(), format.into(), output, verbose),
+
+        Some(Commands::Nginx { input, output }) => execute_nginx(input, output, verbose),
+
+        Some(Commands::Scripts { input, output }) => execute_scripts(input, output, verbose),
+
+        Some(Commands::Bake {
+            input,
+            output,
+            registry,
+            platform,
+        }) => execute_bake(input, output, registry, platform, verbose),
+
+        Some(Commands::SelfInfo {
+            check_updates,
+            format,
+        }) => {
+            execute_self_info(check_updates, format);
+            Ok(())
+        }
+
+        Some(Commands::Explain { code }) => execute_explain(&code),
 
         Some(Commands::Info {
             examples,
             directives,
+            presets,
+            exit_codes,
+            environments,
+            input,
         }) => {
-            execute_info(examples, directives);
+            if environments {
+                return execute_info_environments(input);
+            }
+            execute_info(examples, directives, presets, exit_codes);
             Ok(())
         }
     }
 }
 
-fn execute_build(
-    input: Option<std::path::PathBuf>,
-    output: Option<std::path::PathBuf>,
+/// How `build` and `validate` treat warnings
+#[derive(Debug, Default, Clone, Copy)]
+struct WarningPolicy {
+    /// `--fail-on-warnings`
+    fail: bool,
+    /// `--report-suppressed`
+    report_suppressed: bool,
+}
+
+impl WarningPolicy {
+    /// Print the warnings, and the suppressed ones when asked; returns the printed warnings
+    fn report(self, diagnostics: &Diagnostics) -> &[Warning] {
+        for warning in &diagnostics.warnings {
+            output::warning(warning);
+        }
+        if let Some(warning) = diagnostics.warnings.first() {
+            output::info(message!(ExplainHint, code = warning.code));
+        }
+        if self.report_suppressed {
+            for (warning, suppression) in &diagnostics.suppressed {
+                let location = match &suppression.file {
+                    Some(file) => format!("{file}:{}", suppression.line),
+                    None => suppression.line.to_string(),
+                };
+                output::info(message!(SuppressedAt, location = location, warning = warning));
+            }
+        }
+        &diagnostics.warnings
+    }
+
+    /// Table of warning counts per code, printed by `athena validate`
+    fn summarize(self, warnings: &[Warning], mount_warnings: usize) {
+        let mut counts: Vec<(&str, usize)> = Vec::new();
+        for warning in warnings {
+            match counts.iter_mut().find(|(code, _)| *code == warning.code) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((warning.code, 1)),
+            }
+        }
+        if counts.is_empty() && mount_warnings == 0 {
+            return;
+        }
+        counts.sort_unstable();
+
+        let severity = || {
+            if self.fail {
+                Cell::colored("error", output::RED)
+            } else {
+                Cell::colored("warning", output::YELLOW)
+            }
+        };
+        let mut table = Table::new(&["Rule", "Severity", "Count", "Summary"]);
+        for (code, count) in counts {
+            let summary = find_code(code).map_or("", |entry| entry.summary);
+            table.add_row(vec![code.into(), severity(), count.to_string().into(), summary.into()]);
+        }
+        if mount_warnings > 0 {
+            table.add_row(vec![
+                "mounts".into(),
+                severity(),
+                mount_warnings.to_string().into(),
+                "bind mount source is missing or the wrong kind".into(),
+            ]);
+        }
+        table.print();
+    }
+
+    /// Fail the run under `--fail-on-warnings` once any warning was printed
+    fn enforce(self, warnings: usize) -> AthenaResult<()> {
+        if !self.fail || warnings == 0 {
+            return Ok(());
+        }
+
+        Err(AthenaError::validation_error_enhanced(
+            EnhancedValidationError::new(message!(WarningsAsErrors, count = warnings))
+                .with_suggestion(text(Message::WarningsAsErrorsFix).to_string()),
+        ))
+    }
+}
+
+/// `athena build` flags that shape the run rather than the generated output
+#[derive(Debug, Default, Clone)]
+struct BuildSettings {
     validate_only: bool,
     verbose: bool,
+    check_mounts: bool,
+    show_start_order: bool,
+    /// `--env`: the ENVIRONMENT block applied after merging
+    env: Option<String>,
+    /// None with `--no-limits`
+    limits: Option<Limits>,
+    warning_policy: WarningPolicy,
+    cache_dir: Option<std::path::PathBuf>,
+    /// Report format for `--stats`
+    stats: Option<OutputFormat>,
+    /// `--require-images-exist`, with the `--platform` the images must exist for
+    require_images: Option<Option<String>>,
+    /// `--only`: the services to generate
+    only: Option<ServiceSelection>,
+    /// `[[postprocess]]` hooks of athena.toml, then `--post-process` ones
+    post_process: Vec<PostProcessHook>,
+    /// Part of a directory build, whose summary table replaces the per-file notices
+    batch: bool,
+}
+
+fn execute_build(
+    inputs: Vec<std::path::PathBuf>,
+    output: Option<std::path::PathBuf>,
+    options: GeneratorOptions,
+    settings: BuildSettings,
 ) -> AthenaResult<()> {
-    let input = auto_detect_ath_file(input)?;
-    if verbose {
-        println!("Reading Athena file: {}", input.display());
-    }
+    let BuildSettings {
+        validate_only,
+        verbose,
+        check_mounts,
+        show_start_order,
+        env,
+        limits,
+        warning_policy,
+        cache_dir,
+        stats,
+        require_images,
+        only,
+        post_process,
+        batch,
+    } = settings;
+    let (inputs, contents) = read_inputs(inputs, verbose)?;
+    let paths: Vec<String> = inputs.iter().map(|p| p.display().to_string()).collect();
+    let sources: Vec<SourceFile> = paths
+        .iter()
+        .zip(&contents)
+        .map(|(path, content)| SourceFile { path, content })
+        .collect();
+    // Relative paths resolve against the first file, as with compose -f
+    let input = &inputs[0];
+    let output_path = output.unwrap_or_else(|| options.default_file_name().into());
+    // Compose resolves bind mounts and build contexts against the output file
+    let options = options.with_base_dir(input_dir(input)).with_output_dir(input_dir(&output_path));
+    warn_on_extension_mismatch(&output_path, &options);
+    let source_label = paths.join(SOURCE_PATH_SEPARATOR);
+    let source_content = combine_sources(&contents);
 
-    let content = fs::read_to_string(&input).map_err(AthenaError::IoError)?;
+    // BUILD-ARGS are checked against the Dockerfile next to the input, so it is part of the key
+    let dockerfile = fs::read_to_string(options.base_dir().join("Dockerfile")).unwrap_or_default();
+    // So is a DEFAULT LABELS FROM file, found with an extra parse; a broken
+    // input fails the real run below
+    let default_labels = match &cache_dir {
+        Some(_) => parse_and_merge(&sources)
+            .ok()
+            .and_then(|file| default_labels_path(&file, input_dir(input)))
+            .and_then(|path| fs::read_to_string(path).ok())
+            .unwrap_or_default(),
+        None => String::new(),
+    };
+    // Cached output only passed the limits it was generated under
+    let limits_key = format!("{limits:?}");
+    let only_key = format!("{only:?}");
+    let cache = cache_dir.map(|dir| {
+        let mut key_inputs: Vec<&str> = contents.iter().map(String::as_str).collect();
+        key_inputs.push(&dockerfile);
+        key_inputs.push(&default_labels);
+        key_inputs.push(&limits_key);
+        key_inputs.push(env.as_deref().unwrap_or_default());
+        key_inputs.push(&only_key);
+        // Annotations name the input files
+        if options.annotate_sources() {
+            key_inputs.push(&source_label);
+        }
+        (OutputCache::new(dir), OutputCache::key(&options, &key_inputs))
+    });
+
+    // Bind mount and image checks look outside the input, the start order and
+    // suppressed warnings need the parsed file and stats time each phase, so
+    // all of them need a real run
+    let needs_parse =
+        show_start_order || warning_policy.report_suppressed || require_images.is_some();
+    if !validate_only && !check_mounts && !needs_parse && stats.is_none() {
+        if let Some(cached) = cache.as_ref().and_then(|(cache, key)| cache.load(key)) {
+            for warning in &cached.warnings {
+                output::warning(warning);
+            }
+            warning_policy.enforce(cached.warnings.len())?;
+            let content = run_hooks(&post_process, cached.content, verbose)?;
+            if let Some(limits) = &limits {
+                limits.check_output_size(content.len())?;
+            }
+            write_generated(&output_path, &options, &content, &source_label, &source_content)?;
+            if !batch {
+                announce_generated(&options, &output_path, None);
+            }
+            if verbose {
+                println!("{}", text(Message::ReusedCache));
+            }
+            return Ok(());
+        }
+    }
 
     if verbose {
-        println!("Validating syntax...");
+        println!("{}", text(Message::ValidatingSyntax));
     }
 
-    let athena_file = parse_athena_file(&content)?;
+    let started = Instant::now();
+    let mut athena_file = parse_and_merge(&sources)?;
+    if let Some(env) = &env {
+        if verbose {
+            println!("{}", message!(ApplyingEnvironment, env = env));
+        }
+        apply_environment(&mut athena_file, env)?;
+    }
+    load_default_labels(&mut athena_file, input_dir(input))?;
+    let selection_notes = match &only {
+        Some(selection) => select_services(&mut athena_file, selection)?,
+        None => Vec::new(),
+    };
+    let mut timings = PhaseTimings {
+        parse_ms: millis(started.elapsed()),
+        ..PhaseTimings::default()
+    };
 
     if verbose {
         println!(
-            "Successfully parsed Athena file with {} services",
-            athena_file.services.services.len()
+            "{}",
+            message!(ParsedServices, count = athena_file.services.services.len())
         );
     }
 
+    let started = Instant::now();
+    let mut warnings: Vec<String> = warning_policy
+        .report(&collect_warnings(&athena_file))
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    for note in &selection_notes {
+        output::warning(note);
+    }
+    warnings.extend(selection_notes);
+
+    let mut mount_warnings = 0;
+    if check_mounts {
+        mount_warnings = report_bind_mounts(&athena_file, input);
+    }
+    if let Some(limits) = &limits {
+        limits.check(&athena_file)?;
+    }
+    timings.validation_ms = millis(started.elapsed());
+
+    if show_start_order {
+        print_start_order(&athena_file)?;
+    }
+
     if validate_only {
-        println!("Athena file is valid");
+        warning_policy.enforce(warnings.len() + mount_warnings)?;
+        if !batch {
+            println!("{}", text(Message::FileIsValid));
+        }
         return Ok(());
     }
 
-    let compose_yaml = generate_docker_compose(&athena_file)?;
+    let started = Instant::now();
+    let generated = generate(&athena_file, &options)?;
+    timings.generation_ms = millis(started.elapsed());
+    // The cache keeps the generated file; hooks run on every build
+    let content = run_hooks(&post_process, generated.content.clone(), verbose)?;
+    if let Some(limits) = &limits {
+        limits.check_output_size(content.len())?;
+    }
+    for warning in &generated.warnings {
+        output::warning(warning);
+    }
+    warnings.extend(generated.warnings);
+    warning_policy.enforce(warnings.len() + mount_warnings)?;
 
-    let output_path = output.unwrap_or_else(|| "docker-compose.yml".into());
+    if let Some(platform) = &require_images {
+        let checked = preflight::require_images(&athena_file, platform.as_deref())?;
+        if verbose {
+            println!("{}", message!(ImagesFound, count = checked));
+        }
+    }
 
-    fs::write(&output_path, &compose_yaml).map_err(AthenaError::IoError)?;
+    let output_bytes = write_generated(
+        &output_path,
+        &options,
+        &content,
+        &source_label,
+        &source_content,
+    )?;
+    if !batch {
+        announce_generated(&options, &output_path, stats);
+    }
 
-    println!("Generated docker-compose.yml at: {}", output_path.display());
+    if let Some((cache, key)) = &cache {
+        let cached = CachedOutput {
+            content: generated.content,
+            warnings,
+        };
+        if let Err(e) = cache.store(key, &cached) {
+            output::warning(message!(CacheWriteFailed, error = e));
+        }
+    }
 
     if verbose {
-        println!("Project details:");
-        println!("   - Project name: {}", athena_file.get_project_name());
-        println!("   - Network name: {}", athena_file.get_network_name());
+        println!("{}", text(Message::ProjectDetails));
+        println!("{}", message!(DetailProjectName, name = athena_file.get_project_name()));
+        println!("{}", message!(DetailNetworkName, name = athena_file.get_network_name()));
         println!(
-            "   - Services: {}",
-            athena_file.services.services.len()
+            "{}",
+            message!(DetailServices, count = athena_file.services.services.len())
         );
 
         for service in &athena_file.services.services {
             println!(
-                "     - {} ({})",
-                service.name,
-                service.image.as_deref().unwrap_or("no image")
+                "{}",
+                message!(
+                    DetailService,
+                    name = service.name,
+                    image = service.image.as_deref().unwrap_or(text(Message::NoImage))
+                )
             );
         }
     }
 
+    match stats {
+        Some(OutputFormat::Text) => BuildStats::new(&athena_file, timings, output_bytes).print_text(),
+        Some(OutputFormat::Json) => BuildStats::new(&athena_file, timings, output_bytes).print_json(),
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// The given .ath files, or the auto-detected one, with their contents
+fn read_inputs(
+    inputs: Vec<std::path::PathBuf>,
+    verbose: bool,
+) -> AthenaResult<(Vec<std::path::PathBuf>, Vec<String>)> {
+    let inputs = if inputs.is_empty() {
+        vec![auto_detect_ath_file(None)?]
+    } else {
+        inputs
+    };
+
+    let mut contents = Vec::with_capacity(inputs.len());
+    for input in &inputs {
+        if verbose {
+            println!("{}", message!(ReadingFile, file = input.display()));
+        }
+        contents.push(fs::read_to_string(input).map_err(AthenaError::IoError)?);
+    }
+    Ok((inputs, contents))
+}
+
+fn environment_names(inputs: &[std::path::PathBuf], contents: &[String]) -> AthenaResult<Vec<String>> {
+    let paths: Vec<String> = inputs.iter().map(|p| p.display().to_string()).collect();
+    let sources: Vec<SourceFile> = paths
+        .iter()
+        .zip(contents)
+        .map(|(path, content)| SourceFile { path, content })
+        .collect();
+    let athena_file = parse_and_merge(&sources)?;
+    Ok(athena_file.environments.into_iter().map(|e| e.name).collect())
+}
+
+/// `--all-envs`: one full build per ENVIRONMENT, written to `<dir>/<name>/`
+fn execute_build_all_envs(
+    inputs: Vec<std::path::PathBuf>,
+    output: Option<std::path::PathBuf>,
+    options: GeneratorOptions,
+    settings: BuildSettings,
+) -> AthenaResult<()> {
+    let (inputs, contents) = read_inputs(inputs, false)?;
+    let names = environment_names(&inputs, &contents)?;
+    if names.is_empty() {
+        return Err(AthenaError::validation_error_enhanced(
+            EnhancedValidationError::new(text(Message::NoEnvironmentBlocks).to_string())
+                .with_suggestion(text(Message::NoEnvironmentBlocksFix).to_string()),
+        ));
+    }
+
+    let dir = output.unwrap_or_else(|| ".".into());
+    for name in names {
+        let env_dir = dir.join(&name);
+        fs::create_dir_all(&env_dir).map_err(AthenaError::IoError)?;
+        let settings = BuildSettings {
+            env: Some(name),
+            ..settings.clone()
+        };
+        execute_build(
+            inputs.clone(),
+            Some(env_dir.join(options.default_file_name())),
+            options.clone(),
+            settings,
+        )?;
+    }
+    Ok(())
+}
+
+/// `athena build <dir>` flags
+#[derive(Debug, Clone, Copy)]
+struct BatchSettings {
+    /// `--recursive`
+    recursive: bool,
+    /// `--jobs`, or None for one thread per CPU
+    jobs: Option<u32>,
+}
+
+/// `athena build <dir>`: build every .ath file into `<output>/<stem>.yml`
+/// in parallel, then print a table of the results. A failed file doesn't
+/// stop the others, but fails the run once all of them are done.
+fn execute_build_directory(
+    dir: &Path,
+    output: Option<PathBuf>,
+    batch: BatchSettings,
+    options: GeneratorOptions,
+    settings: BuildSettings,
+) -> AthenaResult<()> {
+    let inputs = find_ath_files(dir, batch.recursive)?;
+    if inputs.is_empty() {
+        let message = if batch.recursive {
+            message!(NoAthFilesIn, dir = dir.display())
+        } else {
+            message!(NoAthFilesDirectlyIn, dir = dir.display())
+        };
+        return Err(AthenaError::config_error(message));
+    }
+
+    let output_dir = output.unwrap_or_else(|| ".".into());
+    let outputs = directory_outputs(&inputs, &output_dir, &options)?;
+    fs::create_dir_all(&output_dir).map_err(AthenaError::IoError)?;
+
+    // Progress lines from parallel builds would interleave
+    let settings = BuildSettings {
+        verbose: false,
+        batch: true,
+        ..settings
+    };
+    let mut pool = rayon::ThreadPoolBuilder::new();
+    if let Some(jobs) = batch.jobs {
+        pool = pool.num_threads(jobs as usize);
+    }
+    let pool = pool
+        .build()
+        .map_err(|e| AthenaError::config_error(message!(ThreadPoolFailed, error = e)))?;
+    let results: Vec<AthenaResult<()>> = pool.install(|| {
+        inputs
+            .par_iter()
+            .zip(outputs.par_iter())
+            .map(|(input, output_path)| {
+                execute_build(
+                    vec![input.clone()],
+                    Some(output_path.clone()),
+                    options.clone(),
+                    settings.clone(),
+                )
+            })
+            .collect()
+    });
+
+    let mut table = Table::new(&["Input", "Output", "Status", "Details"]);
+    let mut failed = 0;
+    for ((input, output_path), result) in inputs.iter().zip(&outputs).zip(&results) {
+        let (status, details) = match result {
+            Ok(()) => (Cell::from("ok"), String::new()),
+            Err(e) => {
+                failed += 1;
+                let message = e.to_string();
+                let first_line = message.lines().next().unwrap_or_default().trim().to_string();
+                (Cell::colored("failed", output::RED), first_line)
+            }
+        };
+        table.add_row(vec![
+            input.display().to_string().into(),
+            output_path.display().to_string().into(),
+            status,
+            details.into(),
+        ]);
+    }
+    table.print();
+
+    if failed > 0 {
+        return Err(AthenaError::config_error(message!(
+            FilesFailed,
+            failed = failed,
+            total = inputs.len()
+        )));
+    }
+    Ok(())
+}
+
+/// `<output_dir>/<stem>.<ext>` for each input, failing before anything is
+/// built when two inputs would share an output
+fn directory_outputs(
+    inputs: &[PathBuf],
+    output_dir: &Path,
+    options: &GeneratorOptions,
+) -> AthenaResult<Vec<PathBuf>> {
+    // docker-compose.yml -> yml, main.tf.json -> tf.json
+    let extension = options
+        .default_file_name()
+        .split_once('.')
+        .map_or("yml", |(_, extension)| extension);
+
+    let outputs: Vec<PathBuf> = inputs
+        .iter()
+        .map(|input| {
+            let stem = input.file_stem().unwrap_or_default().to_string_lossy();
+            output_dir.join(format!("{stem}.{extension}"))
+        })
+        .collect();
+
+    let mut by_output: BTreeMap<&Path, Vec<String>> = BTreeMap::new();
+    for (input, output_path) in inputs.iter().zip(&outputs) {
+        by_output.entry(output_path).or_default().push(input.display().to_string());
+    }
+    let collisions: Vec<String> = by_output
+        .into_iter()
+        .filter(|(_, inputs)| inputs.len() > 1)
+        .map(|(output_path, inputs)| format!("  {}: {}", output_path.display(), inputs.join(", ")))
+        .collect();
+    if !collisions.is_empty() {
+        return Err(AthenaError::config_error(message!(
+            OutputCollisions,
+            collisions = collisions.join("\n")
+        )));
+    }
+    Ok(outputs)
+}
+
+fn execute_info_environments(input: Option<std::path::PathBuf>) -> AthenaResult<()> {
+    let input = auto_detect_ath_file(input)?;
+    let content = fs::read_to_string(&input).map_err(AthenaError::IoError)?;
+    let athena_file = parse_athena_file(&content)?;
+
+    if athena_file.environments.is_empty() {
+        println!("{}", message!(NoEnvironments, file = input.display()));
+        return Ok(());
+    }
+
+    println!("{}", message!(EnvironmentsIn, file = input.display()));
+    for environment in &athena_file.environments {
+        let services: Vec<&str> = environment.services.iter().map(|s| s.name.as_str()).collect();
+        println!(
+            "{}",
+            message!(EnvironmentOverrides, name = environment.name, services = services.join(", "))
+        );
+    }
     Ok(())
 }
 
-fn execute_validate(input: Option<std::path::PathBuf>, verbose: bool) -> AthenaResult<()> {
+/// Stamp (when the format allows comments) and write generated output,
+/// returning the number of bytes written
+fn write_generated(
+    output_path: &Path,
+    options: &GeneratorOptions,
+    generated: &str,
+    source_label: &str,
+    source: &str,
+) -> AthenaResult<usize> {
+    let contents = if options.supports_comments() {
+        stamp_output(generated, source_label, source)
+    } else {
+        generated.to_string()
+    };
+
+    write_atomic(output_path, &contents)?;
+    Ok(contents.len())
+}
+
+/// Catch JSON written to a .yml path and YAML written to a .json path
+fn warn_on_extension_mismatch(output_path: &Path, options: &GeneratorOptions) {
+    let extension = output_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(str::to_ascii_lowercase);
+    let expected = if options.writes_json() { "JSON" } else { "YAML" };
+
+    let mismatch = match extension.as_deref() {
+        Some("yml" | "yaml") => options.writes_json(),
+        Some("json") => !options.writes_json(),
+        _ => false,
+    };
+    if mismatch {
+        output::warning(message!(
+            ExtensionMismatch,
+            format = expected,
+            path = output_path.display()
+        ));
+    }
+}
+
+/// JSON stats own stdout, so the notice moves to stderr to keep it parseable
+fn announce_generated(options: &GeneratorOptions, output_path: &Path, stats: Option<OutputFormat>) {
+    let message = message!(GeneratedAt, what = options.description(), path = output_path.display());
+    if stats == Some(OutputFormat::Json) {
+        eprintln!("{message}");
+    } else {
+        println!("{message}");
+    }
+}
+
+fn execute_cache_clear(cache_dir: &Path) -> AthenaResult<()> {
+    let removed = OutputCache::new(cache_dir).clear()?;
+    println!("{}", message!(CacheCleared, count = removed, dir = cache_dir.display()));
+    Ok(())
+}
+
+fn execute_validate(
+    input: Option<std::path::PathBuf>,
+    verbose: bool,
+    check_mounts: bool,
+    show_start_order: bool,
+    limits: Option<Limits>,
+    warning_policy: WarningPolicy,
+) -> AthenaResult<()> {
     let input = auto_detect_ath_file(input)?;
     if verbose {
-        println!("Validating Athena file: {}", input.display());
+        println!("{}", message!(ValidatingFile, file = input.display()));
     }
 
     let content = fs::read_to_string(&input).map_err(AthenaError::IoError)?;
 
-    let athena_file = parse_athena_file(&content)?;
+    let mut athena_file = parse_athena_file(&content)?;
+    load_default_labels(&mut athena_file, input_dir(&input))?;
+
+    let diagnostics = collect_warnings(&athena_file);
+    let warnings = warning_policy.report(&diagnostics);
+    let mount_warnings = if check_mounts {
+        report_bind_mounts(&athena_file, &input)
+    } else {
+        0
+    };
+    warning_policy.summarize(warnings, mount_warnings);
+
+    if let Some(limits) = &limits {
+        limits.check(&athena_file)?;
+    }
+
+    if show_start_order {
+        print_start_order(&athena_file)?;
+    }
 
-    println!("Athena file is valid");
+    warning_policy.enforce(warnings.len() + mount_warnings)?;
+    println!("{}", text(Message::FileIsValid));
 
     if verbose {
-        println!("Project name: {}", athena_file.get_project_name());
-        println!("Services found: {}", athena_file.services.services.len());
+        println!("{}", message!(ProjectName, name = athena_file.get_project_name()));
+        println!("{}", message!(ServicesFound, count = athena_file.services.services.len()));
 
         for service in &athena_file.services.services {
             println!(
-                "  - {}: {}",
-                service.name,
-                service.image.as_deref().unwrap_or("no image")
+                "{}",
+                message!(
+                    ServiceImage,
+                    name = service.name,
+                    image = service.image.as_deref().unwrap_or(text(Message::NoImage))
+                )
             );
         }
     }
@@ -123,11 +944,482 @@ fn execute_validate(input: Option<std::path::PathBuf>, verbose: bool) -> AthenaR
     Ok(())
 }
 
-fn execute_info(examples: bool, directives: bool) {
+/// Hooks from athena.toml, unless `--no-post-process`, then those given on the command line
+fn resolve_post_process(commands: Vec<String>, no_config: bool) -> AthenaResult<Vec<PostProcessHook>> {
+    let mut hooks = if no_config {
+        Vec::new()
+    } else {
+        load_hooks(Path::new(CONFIG_FILE))?
+    };
+    hooks.extend(commands.into_iter().map(|command| PostProcessHook { command }));
+    Ok(hooks)
+}
+
+/// Limits from athena.toml with `--limit` overrides applied, or None with `--no-limits`
+fn resolve_limits(overrides: &[String], no_limits: bool) -> AthenaResult<Option<Limits>> {
+    if no_limits {
+        return Ok(None);
+    }
+
+    let mut limits = Limits::load(Path::new(CONFIG_FILE))?;
+    for spec in overrides {
+        limits.apply_override(spec)?;
+    }
+    Ok(Some(limits))
+}
+
+/// `athena add-service`; `-` edits stdin and prints the result instead
+fn execute_add_service(file: std::path::PathBuf, service: NewService) -> AthenaResult<()> {
+    if file.as_os_str() == "-" {
+        let mut source = String::new();
+        std::io::stdin()
+            .read_to_string(&mut source)
+            .map_err(AthenaError::IoError)?;
+        print!("{}", append_service(&source, &service)?);
+        return Ok(());
+    }
+
+    let source = fs::read_to_string(&file).map_err(AthenaError::IoError)?;
+    let edited = append_service(&source, &service)?;
+    write_atomic(&file, &edited)?;
+    println!("{}", message!(ServiceAdded, name = service.name, file = file.display()));
+    Ok(())
+}
+
+fn execute_verify(
+    file: std::path::PathBuf,
+    against: Option<std::path::PathBuf>,
+) -> AthenaResult<()> {
+    let generated = fs::read_to_string(&file).map_err(AthenaError::IoError)?;
+
+    let Some(stamp) = read_stamp(&generated) else {
+        return Err(AthenaError::config_error(message!(NoChecksumHeader, file = file.display())));
+    };
+
+    // Builds from several files record all of their paths
+    let sources: Vec<std::path::PathBuf> = match against {
+        Some(path) => vec![path],
+        None => stamp.source_path.split(SOURCE_PATH_SEPARATOR).map(Into::into).collect(),
+    };
+    let mut contents = Vec::with_capacity(sources.len());
+    for path in &sources {
+        contents.push(fs::read_to_string(path).map_err(AthenaError::IoError)?);
+    }
+    let source_content = combine_sources(&contents);
+    let source_paths: Vec<String> = sources.iter().map(|p| p.display().to_string()).collect();
+    let source = source_paths.join(SOURCE_PATH_SEPARATOR);
+
+    match verify_output(&generated, &source_content) {
+        Some(VerifyStatus::UpToDate) | None => {
+            println!("{}", message!(UpToDate, file = file.display(), source = source));
+            Ok(())
+        }
+        Some(VerifyStatus::Modified) => Err(AthenaError::validation_error_enhanced(
+            EnhancedValidationError::new(message!(OutputModified, file = file.display()))
+                .with_suggestion(message!(OutputModifiedFix, source = source)),
+        )),
+        Some(VerifyStatus::Stale) => Err(AthenaError::validation_error_enhanced(
+            EnhancedValidationError::new(message!(OutputStale, file = file.display(), source = source))
+                .with_suggestion(message!(OutputStaleFix, sources = source_paths.join(" "))),
+        )),
+    }
+}
+
+/// `athena env`: the resolved environment of one service, or with
+/// `--explain` the layers setting one variable
+fn execute_env(
+    inputs: Vec<PathBuf>,
+    service: &str,
+    env: Option<&str>,
+    explain: Option<&str>,
+    verbose: bool,
+) -> AthenaResult<()> {
+    let (inputs, contents) = read_inputs(inputs, verbose)?;
+    let paths: Vec<String> = inputs.iter().map(|p| p.display().to_string()).collect();
+    let sources: Vec<SourceFile> = paths
+        .iter()
+        .zip(&contents)
+        .map(|(path, content)| SourceFile { path, content })
+        .collect();
+    let traces = trace_service_env(&sources, service, env)?;
+
+    let Some(key) = explain else {
+        if traces.is_empty() {
+            output::info(message!(NoEnvVariables, service = service));
+        }
+        for trace in &traces {
+            println!("{}={}", trace.key, trace.value);
+        }
+        return Ok(());
+    };
+
+    let trace = traces.iter().find(|t| t.key == key).ok_or_else(|| {
+        let keys: Vec<&str> = traces.iter().map(|t| t.key.as_str()).collect();
+        AthenaError::validation_error_enhanced(
+            EnhancedValidationError::new(message!(UnknownEnvKey, service = service, key = key))
+                .with_suggestion(message!(UnknownEnvKeyFix, service = service, keys = keys.join(", "))),
+        )
+    })?;
+
+    println!("{}", message!(EnvLayers, key = key, service = service));
+    let mut table = Table::new(&["Layer", "Location", "Value", "Status"]);
+    for (index, setting) in trace.settings.iter().enumerate() {
+        let status = if Some(index) == trace.winner() {
+            Cell::from(text(Message::EnvWins))
+        } else {
+            Cell::colored(text(Message::EnvOverridden), output::YELLOW)
+        };
+        let location = setting.location.as_ref().map(ToString::to_string).unwrap_or_default();
+        table.add_row(vec![
+            setting.source.to_string().into(),
+            location.into(),
+            setting.value.to_string().into(),
+            status,
+        ]);
+    }
+    table.print();
+    if let EnvValue::Template(_) = trace.value {
+        println!("{}", message!(EnvTemplateNote, value = trace.value));
+    }
+    Ok(())
+}
+
+fn execute_report(
+    inputs: Vec<PathBuf>,
+    env: Option<&str>,
+    format: ReportFormat,
+    output_path: Option<PathBuf>,
+    verbose: bool,
+) -> AthenaResult<()> {
+    let (inputs, contents) = read_inputs(inputs, verbose)?;
+    let paths: Vec<String> = inputs.iter().map(|p| p.display().to_string()).collect();
+    let sources: Vec<SourceFile> = paths
+        .iter()
+        .zip(&contents)
+        .map(|(path, content)| SourceFile { path, content })
+        .collect();
+    let mut athena_file = parse_and_merge(&sources)?;
+    if let Some(name) = env {
+        apply_environment(&mut athena_file, name)?;
+    }
+    // Only report on a stack `athena build` would accept
+    let options = GeneratorOptions::new().with_reproducible(true).with_base_dir(input_dir(&inputs[0]));
+    generate(&athena_file, &options)?;
+
+    let report = Inventory::from_file(&athena_file).render(format)?;
+    let Some(output_path) = output_path else {
+        print!("{report}");
+        return Ok(());
+    };
+    write_atomic(&output_path, &report)?;
+    let format = match format {
+        ReportFormat::Json => "JSON",
+        ReportFormat::Markdown => "Markdown",
+    };
+    println!("{}", message!(ReportWritten, format = format, path = output_path.display()));
+    Ok(())
+}
+
+fn execute_nginx(
+    input: Option<std::path::PathBuf>,
+    output: Option<std::path::PathBuf>,
+    verbose: bool,
+) -> AthenaResult<()> {
+    let input = auto_detect_ath_file(input)?;
+    if verbose {
+        println!("{}", message!(ReadingFile, file = input.display()));
+    }
+
+    let content = fs::read_to_string(&input).map_err(AthenaError::IoError)?;
+    let athena_file = parse_athena_file(&content)?;
+
+    let nginx_config = generate_nginx_config(&athena_file)?;
+
+    let output_path = output.unwrap_or_else(|| "nginx.conf".into());
+    if let Some(parent) = output_path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(AthenaError::IoError)?;
+    }
+
+    write_atomic(&output_path, &nginx_config)?;
+
+    println!("{}", message!(GeneratedNginx, path = output_path.display()));
+
+    Ok(())
+}
+
+fn execute_scripts(
+    input: Option<std::path::PathBuf>,
+    output: std::path::PathBuf,
+    verbose: bool,
+) -> AthenaResult<()> {
+    let input = auto_detect_ath_file(input)?;
+    if verbose {
+        println!("{}", message!(ReadingFile, file = input.display()));
+    }
+
+    let content = fs::read_to_string(&input).map_err(AthenaError::IoError)?;
+    let athena_file = parse_athena_file(&content)?;
+    let scripts = generate_volume_scripts(&athena_file)?;
+
+    fs::create_dir_all(&output).map_err(AthenaError::IoError)?;
+    for (name, script) in [("backup.sh", &scripts.backup), ("restore.sh", &scripts.restore)] {
+        let path = output.join(name);
+        write_atomic(&path, script)?;
+        make_executable(&path)?;
+        println!("{}", message!(GeneratedFile, path = path.display()));
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn make_executable(path: &Path) -> AthenaResult<()> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755)).map_err(AthenaError::IoError)
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> AthenaResult<()> {
+    Ok(())
+}
+
+fn check_platform_flag(platform: &str) -> AthenaResult<()> {
+    check_platform(platform).map_err(|(message, suggestion)| {
+        AthenaError::validation_error_enhanced(
+            EnhancedValidationError::new(message!(PlatformFlag, error = message)).with_suggestion(suggestion),
+        )
+    })
+}
+
+fn execute_bake(
+    input: Option<std::path::PathBuf>,
+    output: Option<std::path::PathBuf>,
+    registry: Option<String>,
+    platforms: Vec<String>,
+    verbose: bool,
+) -> AthenaResult<()> {
+    for platform in &platforms {
+        check_platform_flag(platform)?;
+    }
+
+    let input = auto_detect_ath_file(input)?;
+    if verbose {
+        println!("{}", message!(ReadingFile, file = input.display()));
+    }
+
+    let content = fs::read_to_string(&input).map_err(AthenaError::IoError)?;
+    let athena_file = parse_athena_file(&content)?;
+
+    let output_path = output.unwrap_or_else(|| "docker-bake.hcl".into());
+    let format = if output_path.extension().is_some_and(|ext| ext == "json") {
+        BakeFormat::Json
+    } else {
+        BakeFormat::Hcl
+    };
+
+    let bake = generate_bake_file(&athena_file, registry.as_deref(), &platforms, format)?;
+    for skipped in &bake.skipped {
+        output::info(skipped);
+    }
+
+    write_atomic(&output_path, &bake.content)?;
+
+    println!("{}", message!(GeneratedBake, path = output_path.display()));
+
+    Ok(())
+}
+
+/// Print DEPENDS-ON layers; fails with the cycle path if there is one
+fn print_start_order(athena_file: &AthenaFile) -> AthenaResult<()> {
+    let layers = start_order(&athena_file.services.services)?;
+
+    println!("{}", text(Message::StartOrder));
+    for (index, layer) in layers.iter().enumerate() {
+        println!("  {}. {}", index + 1, layer.join(", "));
+    }
+    Ok(())
+}
+
+/// `athena test`: check every discovered file in parallel, print the
+/// diagnostics grouped by file and a summary table, and write the report
+fn execute_test(
+    paths: Vec<PathBuf>,
+    report: Option<SuiteReport>,
+    strict: bool,
+    jobs: Option<u32>,
+    limits: Option<Limits>,
+) -> AthenaResult<()> {
+    let paths = if paths.is_empty() { vec![PathBuf::from(".")] } else { paths };
+    let files = discover(&paths)?;
+    if files.is_empty() {
+        let searched: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+        return Err(AthenaError::config_error(message!(NoAthFilesUnder, paths = searched.join(", "))));
+    }
+
+    let mut pool = rayon::ThreadPoolBuilder::new();
+    if let Some(jobs) = jobs {
+        pool = pool.num_threads(jobs as usize);
+    }
+    let pool = pool
+        .build()
+        .map_err(|e| AthenaError::config_error(message!(ThreadPoolFailed, error = e)))?;
+    let results: Vec<FileResult> =
+        pool.install(|| files.par_iter().map(|file| check_file(file, limits.as_ref())).collect());
+
+    for result in results.iter().filter(|r| !r.errors.is_empty() || !r.warnings.is_empty()) {
+        println!("{}", result.path.display());
+        for error in &result.errors {
+            println!("{}", message!(SuiteError, error = error.lines().next().unwrap_or_default()));
+        }
+        for warning in &result.warnings {
+            println!("{}", message!(SuiteWarning, warning = warning));
+        }
+        println!();
+    }
+
+    let mut table = Table::new(&[
+        text(Message::SuiteColumnFile),
+        text(Message::SuiteColumnStatus),
+        text(Message::SuiteColumnErrors),
+        text(Message::SuiteColumnWarnings),
+    ]);
+    for result in &results {
+        let status = if result.failed(strict) {
+            Cell::colored(text(Message::SuiteStatusFailed), output::RED)
+        } else if !result.warnings.is_empty() {
+            Cell::colored(text(Message::SuiteStatusWarnings), output::YELLOW)
+        } else {
+            Cell::from(text(Message::SuiteStatusOk))
+        };
+        table.add_row(vec![
+            result.path.display().to_string().into(),
+            status,
+            result.errors.len().to_string().into(),
+            result.warnings.len().to_string().into(),
+        ]);
+    }
+    table.print();
+
+    let failed = results.iter().filter(|r| r.failed(strict)).count();
+    let warned = results.iter().filter(|r| !r.warnings.is_empty()).count();
+    println!("{}", message!(SuiteChecked, count = results.len(), failed = failed, warned = warned));
+
+    if let Some(SuiteReport::Junit(path)) = &report {
+        let cases: Vec<_> = results.iter().map(|r| r.test_case(strict)).collect();
+        write_atomic(path, &junit::render("athena", &cases))?;
+        println!("{}", message!(ReportWritten, format = "JUnit", path = path.display()));
+    }
+
+    if failed > 0 {
+        let message = if strict {
+            message!(SuiteFailedStrict, failed = failed, total = results.len())
+        } else {
+            message!(SuiteFailed, failed = failed, total = results.len())
+        };
+        return Err(AthenaError::validation_error_enhanced(EnhancedValidationError::new(message)));
+    }
+    Ok(())
+}
+
+/// Directory that relative paths in `input` resolve against; also the
+/// directory a file is written to
+fn input_dir(input: &Path) -> &Path {
+    input
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+}
+
+/// Print missing bind mount sources, relative to the .ath file, returning how many there were
+fn report_bind_mounts(athena_file: &AthenaFile, input: &Path) -> usize {
+    let warnings = check_bind_mounts(athena_file, input_dir(input));
+    for warning in &warnings {
+        output::warning(warning);
+    }
+    warnings.len()
+}
+
+/// Cargo features compiled into this binary
+const ENABLED_FEATURES: &[(&str, bool)] = &[("update-check", cfg!(feature = "update-check"))];
+
+fn execute_self_info(check_updates: bool, format: OutputFormat) {
+    let version = env!("CARGO_PKG_VERSION");
+    let commit = env!("ATHENA_GIT_COMMIT");
+    let features: Vec<&str> = ENABLED_FEATURES
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect();
+    let keywords = dsl_keywords();
+    let update = check_updates.then(|| check_for_updates(version));
+
+    match format {
+        OutputFormat::Json => {
+            let mut info = serde_json::json!({
+                "version": version,
+                "commit": commit,
+                "os": std::env::consts::OS,
+                "arch": std::env::consts::ARCH,
+                "features": features,
+                "dsl_keywords": keywords,
+            });
+            if let Some(update) = update {
+                info["update"] = match update {
+                    UpdateStatus::UpToDate { latest } => {
+                        serde_json::json!({ "status": "up-to-date", "latest": latest })
+                    }
+                    UpdateStatus::Available { latest } => {
+                        serde_json::json!({ "status": "available", "latest": latest })
+                    }
+                    UpdateStatus::Disabled => serde_json::json!({ "status": "disabled" }),
+                    UpdateStatus::Unavailable(reason) => {
+                        serde_json::json!({ "status": "unavailable", "reason": reason })
+                    }
+                };
+            }
+            println!("{info:#}");
+        }
+        OutputFormat::Text => {
+            println!("{}", message!(SelfVersion, version = version, commit = commit));
+            println!(
+                "{}",
+                message!(SelfPlatform, os = std::env::consts::OS, arch = std::env::consts::ARCH)
+            );
+            if features.is_empty() {
+                println!("{}", text(Message::SelfNoFeatures));
+            } else {
+                println!("{}", message!(SelfFeatures, features = features.join(", ")));
+            }
+            println!("{}", message!(SelfKeywords, keywords = keywords.join(", ")));
+
+            match update {
+                Some(UpdateStatus::UpToDate { latest }) => {
+                    println!("{}", message!(UpdatesUpToDate, latest = latest));
+                }
+                Some(UpdateStatus::Available { latest }) => {
+                    println!("{}", message!(UpdatesAvailable, latest = latest));
+                }
+                Some(UpdateStatus::Disabled) => {
+                    println!("{}", message!(UpdatesDisabled, var = crate::cli::update::DISABLE_ENV_VAR));
+                }
+                Some(UpdateStatus::Unavailable(reason)) => {
+                    output::warning(message!(UpdateCheckFailed, reason = reason));
+                }
+                None => {}
+            }
+        }
+    }
+}
+
+fn execute_info(examples: bool, directives: bool, presets: bool, exit_codes: bool) {
     if examples {
         show_examples();
     } else if directives {
         show_directives();
+    } else if presets {
+        show_presets();
+    } else if exit_codes {
+        show_exit_codes();
     } else {
         show_general_info();
     }
@@ -226,6 +1518,71 @@ END SERVICE
     );
 }
 
+fn execute_explain(code: &str) -> AthenaResult<()> {
+    let entry = find_code(code).ok_or_else(|| {
+        let codes: Vec<&str> = WARNING_CODES.iter().map(|entry| entry.code).collect();
+        AthenaError::validation_error_enhanced(
+            EnhancedValidationError::new(message!(UnknownWarningCode, code = code))
+                .with_suggestion(message!(UnknownWarningCodeFix, codes = codes.join(", "))),
+        )
+    })?;
+
+    println!("{}: {}", entry.code, entry.summary);
+    println!();
+    println!("{}", entry.explanation);
+    println!();
+    println!("{}", text(Message::ExplainExample));
+    for line in entry.example.lines() {
+        println!("  {line}");
+    }
+    println!();
+    println!("{}", text(Message::ExplainFixed));
+    for line in entry.fixed.lines() {
+        println!("  {line}");
+    }
+    println!();
+    println!("{}", message!(ExplainIgnore, code = entry.code));
+    Ok(())
+}
+
+fn show_exit_codes() {
+    let title = text(Message::ExitCodesTitle);
+    println!("{title}");
+    println!("{}", "=".repeat(title.chars().count()));
+    println!();
+    for (code, meaning) in EXIT_CODES {
+        println!("{}", message!(ExitCode, code = code, meaning = meaning));
+    }
+}
+
+fn show_presets() {
+    println!("Athena Service Presets");
+    println!("======================");
+    println!();
+    println!("Use inside a service block; any directive in the block overrides the preset:");
+    println!("  SERVICE queue");
+    println!("  PRESET rabbitmq VERSION \"3.13\"");
+    println!("  END SERVICE");
+
+    for preset in PRESETS {
+        println!();
+        println!("{} - {}", preset.name, preset.description);
+        println!("  Image:        {}", preset.image_for(None));
+        let ports: Vec<String> = preset.ports.iter().map(u16::to_string).collect();
+        println!("  Ports:        {}", ports.join(", "));
+        if let Some(path) = preset.data_path {
+            println!("  Data volume:  <service>_data -> {path}");
+        }
+        if !preset.environment.is_empty() {
+            println!("  Environment:");
+            for entry in preset.environment {
+                println!("    {entry}");
+            }
+        }
+        println!("  Health check: {}", preset.health_check);
+    }
+}
+
 fn show_directives() {
     println!("Athena DSL Directives Reference");
     println!("==============================");
@@ -237,9 +1594,12 @@ fn show_directives() {
     println!();
 
     println!("ENVIRONMENT SECTION");
-    println!("  NETWORK-NAME <name>      - Docker network name");
+    println!("  NETWORK-NAME <name> [SUBNET <cidr>] - Docker network (the first is the project network)");
     println!("  VOLUME <name>            - Define named volume");
     println!("  SECRET <name> <value>    - Define secret value");
+    println!("  DNS <ip>                 - Default DNS server for all services");
+    println!("  DEFAULT LABELS FROM <file> - YAML mapping of labels for every service");
+    println!("  EXTERNAL SERVICE <name> HOST <host> [PORT <port>] - Service outside the stack");
     println!();
 
     println!("SERVICE DIRECTIVES");
@@ -247,12 +1607,42 @@ fn show_directives() {
     println!("  IMAGE-ID <image:tag>            - Docker image");
     println!("  PORT-MAPPING <host> TO <container> [(tcp|udp)] - Port mapping");
     println!("  ENV-VARIABLE {{VAR_NAME}}       - Environment variable template");
-    println!("  COMMAND <command>               - Override container command");
-    println!("  VOLUME-MAPPING <host> TO <container> [(ro|rw)] - Volume mount");
-    println!("  DEPENDS-ON <service>            - Service dependency");
+    println!("  COMMAND <command>               - Override container command (\"shell form\" or [\"exec\", \"form\"])");
+    println!("  ENTRYPOINT <command>            - Override container entrypoint");
+    println!("  WORKDIR <path>                  - Working directory inside the container");
+    println!("  USER <user[:group]>             - User the container runs as");
+    println!("  VOLUME-MAPPING <host> TO <container> [(options)] - Volume mount (ro, rw, z, Z, cached, rshared, nocopy, ...)");
+    println!("  DEPENDS-ON <service> [STARTED|HEALTHY|COMPLETED] - Service dependency");
+    println!("  KIND (service|job)              - Jobs run once: restart \"no\", no default healthcheck");
+    println!("  PROFILE <name>                  - Only start with docker compose --profile <name>");
     println!("  HEALTH-CHECK <command>          - Health check command");
     println!("  RESTART-POLICY (always|unless-stopped|on-failure|no)");
     println!("  RESOURCE-LIMITS CPU <limit> MEMORY <limit> - Resource constraints");
+    println!("  RESERVE [MEMORY <size>] [CPUS <count>] - Guaranteed resources, within the limits");
+    println!("  REPLICAS <count>                - Swarm replica count (at least 1)");
+    println!("  DEPLOY-MODE (global|replicated) - Swarm scheduling mode");
+    println!("  INIT (TRUE|FALSE)               - Run an init process as PID 1");
+    println!("  TTY (TRUE|FALSE)                - Allocate a pseudo-TTY");
+    println!("  STDIN-OPEN (TRUE|FALSE)         - Keep stdin open");
+    println!("  STOP-GRACE-PERIOD <duration>    - Time to wait before SIGKILL, e.g. 1m30s");
+    println!("  STOP-SIGNAL <signal>            - Signal docker stop sends first, e.g. SIGQUIT");
+    println!("  EXPOSE-VIA nginx PATH <path>    - Route a path to this service ('athena nginx')");
+    println!("  WATCH (SYNC|SYNC+RESTART) <path> TO <target> | WATCH REBUILD <path> - docker compose watch");
+    println!("  DNS <ip>                        - Custom DNS server");
+    println!("  EXTRA-HOST <hostname> <ip>      - Add an /etc/hosts entry");
+    println!("  HOSTNAME <name>                 - Container hostname");
+    println!("  LABEL <key>=<value>...          - Container labels, over DEFAULT LABELS FROM");
+    println!("  CONTAINER-NAME <name>           - Fixed container name (single replica only)");
+    println!("  PULL-POLICY (always|missing|never) - When to pull the image");
+    println!("  PLATFORM <os/arch[/variant]>    - Pin the image platform, e.g. linux/amd64");
+    println!("  PID host                        - Share the host's PID namespace");
+    println!("  IPC (host|shareable)            - IPC namespace mode");
+    println!("  NETWORK-MODE (host|none|service:<name>) - Replace the project network");
+    println!("  ON NETWORK <name> [ALIAS <dns>]... [IPV4 <ip>] - Join another network, with aliases or a static address");
+    println!("  PRESET <name> [VERSION <tag>]   - Start from a built-in service ('athena info --presets')");
+    println!("  ALLOW UNRESOLVED HOSTS          - Don't warn about env URLs pointing at undeclared services");
+    println!("  ALLOW DOCKER SOCKET             - Permit mounting the Docker socket (reported as ATH010)");
+    println!("  USES <external>                 - Reach an EXTERNAL SERVICE by name (adds an extra_hosts entry)");
     println!();
 
     println!("EXAMPLES");
@@ -0,0 +1,126 @@
+//! JUnit XML reports, which most CI systems display test case by test case.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+/// One test case; it fails when it has failures
+#[derive(Debug, Clone, Default)]
+pub struct TestCase {
+    pub name: String,
+    pub time: Duration,
+    pub failures: Vec<Failure>,
+    /// Diagnostics that don't fail the case, shown as its `system-out`
+    pub output: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Failure {
+    /// `type` attribute, e.g. `error` or `warning`
+    pub kind: &'static str,
+    /// Full text; its first line is also the `message` attribute
+    pub text: String,
+}
+
+/// A `<testsuites>` document holding one suite named `suite`
+pub fn render(suite: &str, cases: &[TestCase]) -> String {
+    let failed = cases.iter().filter(|case| !case.failures.is_empty()).count();
+    let time: Duration = cases.iter().map(|case| case.time).sum();
+    let totals = format!(
+        "name=\"{}\" tests=\"{}\" failures=\"{failed}\" errors=\"0\" time=\"{:.3}\"",
+        escape(suite),
+        cases.len(),
+        time.as_secs_f64()
+    );
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(xml, "<testsuites {totals}>");
+    let _ = writeln!(xml, "  <testsuite {totals}>");
+    for case in cases {
+        let _ = write!(
+            xml,
+            "    <testcase classname=\"{}\" name=\"{}\" time=\"{:.3}\"",
+            escape(suite),
+            escape(&case.name),
+            case.time.as_secs_f64()
+        );
+        if case.failures.is_empty() && case.output.is_empty() {
+            xml.push_str("/>\n");
+            continue;
+        }
+        xml.push_str(">\n");
+        for failure in &case.failures {
+            let message = failure.text.lines().next().unwrap_or_default();
+            let _ = writeln!(
+                xml,
+                "      <failure type=\"{}\" message=\"{}\">{}</failure>",
+                failure.kind,
+                escape(message),
+                escape(&failure.text)
+            );
+        }
+        if !case.output.is_empty() {
+            let _ = writeln!(xml, "      <system-out>{}</system-out>", escape(&case.output.join("\n")));
+        }
+        xml.push_str("    </testcase>\n");
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+/// Text safe in attributes and element content. Control characters XML 1.0
+/// can't represent at all, such as ANSI escapes, are dropped.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\t' | '\r' => escaped.push(c),
+            c if c.is_control() && (c as u32) < 0x20 => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_counts_failed_cases() {
+        let cases = vec![
+            TestCase {
+                name: "stacks/api.ath".to_string(),
+                time: Duration::from_millis(12),
+                ..TestCase::default()
+            },
+            TestCase {
+                name: "stacks/db.ath".to_string(),
+                time: Duration::from_millis(3),
+                failures: vec![Failure {
+                    kind: "error",
+                    text: "Validation error: Service 'db' <broken>\n\nSuggestion: fix it".to_string(),
+                }],
+                output: vec!["[ATH001] \"Api\" is uppercase".to_string()],
+            },
+        ];
+
+        let xml = render("athena", &cases);
+        assert!(xml.contains(r#"<testsuites name="athena" tests="2" failures="1" errors="0" time="0.015">"#), "{xml}");
+        assert!(xml.contains(r#"<testcase classname="athena" name="stacks/api.ath" time="0.012"/>"#), "{xml}");
+        assert!(xml.contains(r#"message="Validation error: Service &apos;db&apos; &lt;broken&gt;">"#), "{xml}");
+        assert!(xml.contains("&lt;broken&gt;&#10;&#10;Suggestion: fix it</failure>"), "{xml}");
+        assert!(xml.contains("<system-out>[ATH001] &quot;Api&quot; is uppercase</system-out>"), "{xml}");
+    }
+
+    #[test]
+    fn test_escape_drops_characters_xml_cannot_hold() {
+        assert_eq!(escape("a\u{1b}[31mb\tc"), "a[31mb\tc");
+        assert_eq!(escape("x & y"), "x &amp; y");
+    }
+}
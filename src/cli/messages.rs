@@ -0,0 +1,323 @@
+//! User-facing CLI messages, looked up by key.
+//!
+//! Progress lines, results, hints and CLI errors are declared once here with
+//! their English text, so a translation is one more table and tests can match
+//! output by key instead of by copy. Templates name their arguments in braces,
+//! e.g. `Reading Athena file: {file}`; use [`message!`] to render one.
+//!
+//! The longer reference pages (`athena info --examples`, `--directives` and
+//! `--presets`) and messages raised by the compiler itself are not covered yet.
+
+use std::fmt::{Display, Write};
+use std::sync::OnceLock;
+
+use crate::cli::output;
+
+/// Render a message by key: `message!(ReadingFile, file = path.display())`
+macro_rules! message {
+    ($key:ident $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::cli::messages::render(
+            $crate::cli::messages::Message::$key,
+            &[$((stringify!($name), &$value as &dyn std::fmt::Display)),*],
+        )
+    };
+}
+pub(crate) use message;
+
+/// Picks the language of CLI messages, e.g. `ATHENA_LANG=en`
+pub const LANG_ENV_VAR: &str = "ATHENA_LANG";
+
+/// Languages with a message table
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Locale {
+    #[default]
+    En,
+}
+
+impl Locale {
+    /// Codes accepted by [`LANG_ENV_VAR`]
+    pub const SUPPORTED: &'static [&'static str] = &["en"];
+
+    /// Read a language code; a region or encoding suffix is ignored, so
+    /// `en`, `EN` and `en_US.UTF-8` are all English
+    pub fn parse(value: &str) -> Option<Self> {
+        let language = value.split(['_', '-', '.']).next().unwrap_or_default();
+        match language.to_ascii_lowercase().as_str() {
+            "en" => Some(Self::En),
+            _ => None,
+        }
+    }
+
+    pub fn text(self, message: Message) -> &'static str {
+        match self {
+            Self::En => english(message),
+        }
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// Resolve the locale once at startup, warning when `ATHENA_LANG` names a
+/// language without a table
+pub fn init() {
+    let locale = match std::env::var(LANG_ENV_VAR) {
+        Ok(value) if !value.is_empty() => Locale::parse(&value).unwrap_or_else(|| {
+            output::warning(message!(
+                UnsupportedLanguage,
+                var = LANG_ENV_VAR,
+                value = value,
+                supported = Locale::SUPPORTED.join(", ")
+            ));
+            Locale::default()
+        }),
+        _ => Locale::default(),
+    };
+    let _ = LOCALE.set(locale);
+}
+
+pub fn locale() -> Locale {
+    LOCALE.get().copied().unwrap_or_default()
+}
+
+/// The template of `message` in the current locale
+pub fn text(message: Message) -> &'static str {
+    locale().text(message)
+}
+
+/// Fill the `{name}` placeholders of `message`; unknown ones are kept as is
+pub fn render(message: Message, args: &[(&str, &dyn Display)]) -> String {
+    fill(text(message), args)
+}
+
+fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        let name = &rest[start + 1..end];
+        match args.iter().find(|(arg, _)| *arg == name) {
+            Some((_, value)) => {
+                let _ = write!(rendered, "{value}");
+            }
+            None => rendered.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+macro_rules! messages {
+    ($($(#[$doc:meta])* $key:ident => $english:literal,)+) => {
+        /// Key of a user-facing CLI message
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Message {
+            $($(#[$doc])* $key,)+
+        }
+
+        impl Message {
+            pub const ALL: &'static [Message] = &[$(Message::$key),+];
+        }
+
+        fn english(message: Message) -> &'static str {
+            match message {
+                $(Message::$key => $english,)+
+            }
+        }
+    };
+}
+
+messages! {
+    // Progress
+    MagicMode => "Magic mode: Auto-detecting and building...",
+    AutoDetected => "Auto-detected: {file}",
+    ReadingFile => "Reading Athena file: {file}",
+    ValidatingFile => "Validating Athena file: {file}",
+    ValidatingSyntax => "Validating syntax...",
+    ApplyingEnvironment => "Applying ENVIRONMENT \"{env}\"",
+    ParsedServices => "Successfully parsed Athena file with {count} services",
+    ReusedCache => "Reused cached output for unchanged input",
+    RunningHook => "Running post-process hook: {command}",
+    ImagesFound => "Found all {count} image(s) in the local Docker daemon",
+
+    // Results
+    FileIsValid => "Athena file is valid",
+    GeneratedAt => "Generated {what} at: {path}",
+    GeneratedNginx => "Generated nginx config at: {path}",
+    GeneratedBake => "Generated bake file at: {path}",
+    GeneratedFile => "Generated {path}",
+    ServiceAdded => "Added service '{name}' to {file}",
+    UpToDate => "{file} is up to date with {source}",
+    SuiteChecked => "Checked {count} file(s): {failed} failed, {warned} with warnings",
+    SuiteError => "  error: {error}",
+    SuiteWarning => "  warning: {warning}",
+    SuiteColumnFile => "File",
+    SuiteColumnStatus => "Status",
+    SuiteColumnErrors => "Errors",
+    SuiteColumnWarnings => "Warnings",
+    SuiteStatusFailed => "failed",
+    SuiteStatusWarnings => "warnings",
+    SuiteStatusOk => "ok",
+    ReportWritten => "Wrote {format} report to {path}",
+    CacheCleared => "Removed {count} cached build(s) from {dir}",
+    NoEnvironments => "{file} declares no environments",
+    EnvironmentsIn => "Environments in {file}:",
+    EnvironmentOverrides => "  {name} (overrides: {services})",
+    StartOrder => "Start order:",
+    NoEnvVariables => "Service '{service}' sets no environment variables",
+    EnvLayers => "{key} of service '{service}', lowest precedence first:",
+    EnvWins => "wins",
+    EnvOverridden => "overridden",
+    ProjectDetails => "Project details:",
+    DetailProjectName => "   - Project name: {name}",
+    DetailNetworkName => "   - Network name: {name}",
+    DetailServices => "   - Services: {count}",
+    DetailService => "     - {name} ({image})",
+    ProjectName => "Project name: {name}",
+    ServicesFound => "Services found: {count}",
+    ServiceImage => "  - {name}: {image}",
+    NoImage => "no image",
+    UpdatesUpToDate => "Updates: up to date (latest release {latest})",
+    UpdatesAvailable => "Updates: {latest} is available (https://github.com/Jeck0v/Athena/releases)",
+    UpdatesDisabled => "Updates: check disabled by {var}",
+    SelfVersion => "Athena {version} (commit {commit})",
+    SelfPlatform => "Platform: {os}/{arch}",
+    SelfFeatures => "Features: {features}",
+    SelfNoFeatures => "Features: none",
+    SelfKeywords => "DSL keywords: {keywords}",
+
+    // Reference pages
+    ExplainExample => "Example:",
+    ExplainFixed => "Fixed:",
+    ExplainIgnore => "To silence it instead, add `// athena:ignore {code}` on the line above the SERVICE or directive.",
+    ExitCodesTitle => "Athena Exit Codes",
+    ExitCode => "  {code}  {meaning}",
+
+    // Hints and next steps
+    EnvTemplateNote => "{value} is left to Compose, which substitutes it from the shell or .env when the stack starts",
+    ExplainHint => "For more info on a warning, run `athena explain {code}`",
+    SuppressedAt => "Suppressed at {location}: {warning}",
+    NotFoundHint => "Make sure the file path is correct and the file exists.",
+    PermissionHint => "Check file permissions and try running with appropriate privileges.",
+    ParseHint => "Check the syntax of your .ath file. Use 'athena info --examples' for syntax examples.",
+    ParseCommonIssues => "Common issues: missing END SERVICE, incorrect keywords, or malformed strings.",
+    ValidationHint => "Fix the validation issues in your configuration.",
+    CycleHint => "Review your service dependencies to avoid circular references.",
+
+    // Warnings
+    UnsupportedLanguage => "{var}={value} is not supported (available: {supported}); using en",
+    ExtensionMismatch => "Writing {format} to {path}; the file extension suggests a different format",
+    CacheWriteFailed => "Could not write build cache: {error}",
+    UpdateCheckFailed => "Could not check for updates: {reason}",
+
+    // Errors, and the suggestions that go with them
+    WarningsAsErrors => "{count} warning(s) treated as errors (--fail-on-warnings)",
+    WarningsAsErrorsFix => "Fix them, or silence a code with `// athena:ignore <CODE>` on the line above the SERVICE or directive",
+    AnnotateNeedsYaml => "--annotate-sources needs YAML output, but {what} has no comments",
+    MinimalNeedsCompose => "--minimal only applies to Compose output, not to {what}",
+    PostProcessNeedsCompose => "Post-process hooks only apply to Compose output, not to {what}; pass --no-post-process to skip those in athena.toml",
+    HookFailed => "Post-process hook `{command}` failed: {error}",
+    HookNotUtf8 => "its output is not UTF-8",
+    HookInvalidOutput => "Post-process hook `{command}` produced an invalid Compose file:\n  - {problems}",
+    HookInvalidOutputFix => "The hook must print the whole Compose file on stdout; write diagnostics to stderr",
+    NoEnvironmentBlocks => "--all-envs found no ENVIRONMENT blocks",
+    NoEnvironmentBlocksFix => "Declare one with ENVIRONMENT \"name\" ... END ENVIRONMENT after the SERVICES SECTION",
+    NoChecksumHeader => "{file} has no Athena checksum header; regenerate it with 'athena build'",
+    OutputModified => "{file} was modified after it was generated",
+    OutputModifiedFix => "Move the manual changes into {source} and run 'athena build' again",
+    OutputStale => "{file} is stale: {source} changed since it was generated",
+    OutputStaleFix => "Run 'athena build {sources}' to regenerate it",
+    UnknownEnvKey => "Service '{service}' has no environment variable {key}",
+    UnknownEnvKeyFix => "Variables of '{service}': {keys}",
+    PlatformFlag => "--platform: {error}",
+    NoAthFile => "No .ath file found in current directory. Please specify a file or create one.",
+    MultipleAthFiles => "Multiple .ath files found. Please specify which one to use:",
+    MultipleAthFilesUsage => "Usage: athena build <FILE>",
+    ImagesMissing => "{count} image(s) missing from the local Docker daemon:\n{images}",
+    ImagesMissingFix => "Pull or load them (docker pull / docker load) before deploying",
+    DirectoryWithOtherInputs => "{dir} is a directory; build it on its own, without other inputs",
+    DirectoryOnlyFlags => "--recursive and --jobs only apply to a directory input",
+    SingleStackFlag => "{flag} builds a single stack and can't be used with a directory input",
+    NoAthFilesIn => "No .ath files found in {dir}",
+    NoAthFilesDirectlyIn => "No .ath files found directly in {dir}; pass --recursive to search its subdirectories",
+    OutputCollisions => "Several inputs would be written to the same output:\n{collisions}\nRename them so every file name is unique",
+    NoAthFilesUnder => "No .ath files found under {paths}",
+    SuiteFailed => "{failed} of {total} file(s) failed the checks",
+    SuiteFailedStrict => "{failed} of {total} file(s) failed the checks (--strict counts warnings)",
+    ThreadPoolFailed => "Could not start build threads: {error}",
+    FilesFailed => "{failed} of {total} file(s) failed to build",
+    ChdirFailed => "Cannot change to directory {dir}: {error}",
+    UnknownWarningCode => "Unknown warning code '{code}'",
+    UnknownWarningCodeFix => "Known codes: {codes}",
+    DaemonUnreachable => "--require-images-exist could not reach the Docker daemon: {reason}. Start Docker, point DOCKER_HOST at a running daemon, or set {var} to wait longer",
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Placeholder names of a template, in order
+    fn placeholders(template: &str) -> Vec<&str> {
+        template
+            .split('{')
+            .skip(1)
+            .filter_map(|part| part.split_once('}').map(|(name, _)| name))
+            .collect()
+    }
+
+    #[test]
+    fn test_fill_substitutes_named_arguments() {
+        let file = "app.ath";
+        assert_eq!(
+            fill("{file} is up to date with {source}", &[("file", &file), ("source", &"b.ath")]),
+            "app.ath is up to date with b.ath"
+        );
+        assert_eq!(fill("count: {count}", &[("count", &3)]), "count: 3");
+        assert_eq!(fill("kept {missing} as is", &[]), "kept {missing} as is");
+        assert_eq!(fill("unclosed {brace", &[]), "unclosed {brace");
+    }
+
+    #[test]
+    fn test_message_macro_renders_by_key() {
+        assert_eq!(
+            message!(ServiceAdded, name = "api", file = "app.ath"),
+            "Added service 'api' to app.ath"
+        );
+        assert_eq!(message!(FileIsValid), "Athena file is valid");
+    }
+
+    #[test]
+    fn test_every_template_is_well_formed() {
+        for &message in Message::ALL {
+            let template = english(message);
+            assert!(!template.is_empty(), "{message:?} has no text");
+            assert_eq!(
+                template.matches('{').count(),
+                template.matches('}').count(),
+                "{message:?} has unbalanced braces"
+            );
+            for name in placeholders(template) {
+                assert!(
+                    !name.is_empty() && name.chars().all(|c| c.is_ascii_lowercase() || c == '_'),
+                    "{message:?} has placeholder {{{name}}}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_locale_parse() {
+        for value in ["en", "EN", "en_US.UTF-8", "en-GB"] {
+            assert_eq!(Locale::parse(value), Some(Locale::En), "{value}");
+        }
+        for value in ["fr", "de_DE", "english", ""] {
+            assert_eq!(Locale::parse(value), None, "{value}");
+        }
+        for code in Locale::SUPPORTED {
+            assert!(Locale::parse(code).is_some(), "{code}");
+        }
+    }
+}
@@ -1,5 +1,15 @@
 pub mod args;
+pub mod cache;
 pub mod commands;
+pub mod junit;
+pub mod messages;
+pub mod output;
+pub mod postprocess;
+pub mod preflight;
+pub mod stats;
+pub mod suite;
+pub mod table;
+pub mod update;
 pub mod utils;
 
 pub use args::Cli;
@@ -0,0 +1,105 @@
+//! Decorated terminal output.
+//!
+//! Every labelled message (errors, warnings, info) goes through this module so
+//! that `--color` and `NO_COLOR` are honoured in one place.
+
+use clap::ValueEnum;
+use std::fmt::Display;
+use std::io::IsTerminal;
+use std::sync::OnceLock;
+
+/// When to use ANSI colors in terminal output
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Color when stderr is a terminal and NO_COLOR is not set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// How [`crate::cli::table::Table`] is drawn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TableStyle {
+    /// Box characters and colors
+    Styled,
+    /// ASCII borders, no escape codes
+    Plain,
+}
+
+static COLOR_ENABLED: OnceLock<bool> = OnceLock::new();
+/// Tables go to stdout, so they are styled by stdout's terminal rather than stderr's
+static TABLES_STYLED: OnceLock<bool> = OnceLock::new();
+
+pub const RED: &str = "1;31";
+pub const YELLOW: &str = "1;33";
+const CYAN: &str = "1;36";
+
+/// Width used for tables when `COLUMNS` is unset
+const DEFAULT_WIDTH: usize = 100;
+
+/// Resolve the color choice once at startup
+pub fn init(choice: ColorChoice) {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    let _ = COLOR_ENABLED.set(should_color(choice, no_color, std::io::stderr().is_terminal()));
+    let _ = TABLES_STYLED.set(should_color(choice, no_color, std::io::stdout().is_terminal()));
+}
+
+pub fn table_style() -> TableStyle {
+    if TABLES_STYLED.get().copied().unwrap_or(false) {
+        TableStyle::Styled
+    } else {
+        TableStyle::Plain
+    }
+}
+
+/// Terminal width from `COLUMNS`, as exported by most shells
+pub fn terminal_width() -> usize {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .filter(|&columns| columns > 0)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+fn should_color(choice: ColorChoice, no_color: bool, is_terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => !no_color && is_terminal,
+    }
+}
+
+fn paint(label: &str, code: &str) -> String {
+    if COLOR_ENABLED.get().copied().unwrap_or(false) {
+        format!("\x1b[{code}m{label}\x1b[0m")
+    } else {
+        label.to_string()
+    }
+}
+
+pub fn error(message: impl Display) {
+    eprintln!("{} {message}", paint("Error:", RED));
+}
+
+pub fn warning(message: impl Display) {
+    eprintln!("{} {message}", paint("Warning:", YELLOW));
+}
+
+pub fn info(message: impl Display) {
+    eprintln!("{} {message}", paint("Info:", CYAN));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_color() {
+        assert!(should_color(ColorChoice::Always, true, false));
+        assert!(!should_color(ColorChoice::Never, false, true));
+        assert!(should_color(ColorChoice::Auto, false, true));
+        assert!(!should_color(ColorChoice::Auto, true, true));
+        assert!(!should_color(ColorChoice::Auto, false, false));
+    }
+}
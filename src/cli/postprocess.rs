@@ -0,0 +1,177 @@
+//! Post-process hooks: commands that rewrite the generated Compose file
+//! before it is written, from `--post-process` or `[[postprocess]]` entries
+//! in athena.toml.
+//!
+//! A hook reads the file on stdin and prints the new file on stdout. Its
+//! output goes through the Compose self-check before the next hook or the
+//! write sees it, so a broken transform fails the build instead of leaving a
+//! corrupt file behind. Only problems the hook introduced count: anything
+//! already in its input, such as a named volume Athena mounts without a
+//! VOLUME declaring it, is not the hook's doing.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use serde::Deserialize;
+
+use crate::athena::error::{AthenaError, AthenaResult, EnhancedValidationError};
+use crate::athena::generator::check_compose_schema;
+use crate::cli::messages::{message, text, Message};
+
+/// One command, run through the shell from the current directory
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PostProcessHook {
+    pub command: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    postprocess: Vec<PostProcessHook>,
+}
+
+/// The `[[postprocess]]` hooks of `config`; none when the file doesn't exist
+pub fn load_hooks(config: &Path) -> AthenaResult<Vec<PostProcessHook>> {
+    let content = match fs::read_to_string(config) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(AthenaError::IoError(e)),
+    };
+
+    toml::from_str::<ConfigFile>(&content)
+        .map(|config| config.postprocess)
+        .map_err(|e| AthenaError::config_error(format!("Invalid {}: {}", config.display(), e.message())))
+}
+
+/// Pipe `content` through every hook in order
+pub fn run_hooks(hooks: &[PostProcessHook], content: String, verbose: bool) -> AthenaResult<String> {
+    hooks.iter().try_fold(content, |content, hook| {
+        if verbose {
+            println!("{}", message!(RunningHook, command = hook.command));
+        }
+        let existing = check_compose_schema(&content);
+        let processed = run_hook(hook, &content)?;
+        let problems: Vec<String> = check_compose_schema(&processed)
+            .into_iter()
+            .filter(|problem| !existing.contains(problem))
+            .collect();
+        if !problems.is_empty() {
+            return Err(AthenaError::validation_error_enhanced(
+                EnhancedValidationError::new(message!(
+                    HookInvalidOutput,
+                    command = hook.command,
+                    problems = problems.join("\n  - ")
+                ))
+                .with_suggestion(text(Message::HookInvalidOutputFix).to_string()),
+            ));
+        }
+        Ok(processed)
+    })
+}
+
+fn run_hook(hook: &PostProcessHook, input: &str) -> AthenaResult<String> {
+    let failed = |error: String| AthenaError::config_error(message!(HookFailed, command = hook.command, error = error));
+
+    let mut child = shell(&hook.command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| failed(e.to_string()))?;
+
+    // Written from another thread so a hook that prints before it has read
+    // everything can't deadlock on a full pipe. A hook that ignores its input
+    // closes the pipe early, which is its business.
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || {
+        let _ = stdin.write_all(input.as_bytes());
+    });
+    let output = child.wait_with_output().map_err(|e| failed(e.to_string()))?;
+    let _ = writer.join();
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(failed(format!("{}\n{}", output.status, stderr.trim_end())));
+    }
+    String::from_utf8(output.stdout).map_err(|_| failed(text(Message::HookNotUtf8).to_string()))
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const COMPOSE: &str = "services:\n  api:\n    image: api:1.0\n";
+
+    fn hook(command: &str) -> PostProcessHook {
+        PostProcessHook {
+            command: command.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_hooks_run_in_order() {
+        let hooks = [hook("sed 's/api:1.0/mirror.local\\/api:1.0/'"), hook("cat; echo '    init: true'")];
+        let processed = run_hooks(&hooks, COMPOSE.to_string(), false).unwrap();
+        assert_eq!(processed, "services:\n  api:\n    image: mirror.local/api:1.0\n    init: true\n");
+    }
+
+    #[test]
+    fn test_failing_hook_reports_its_stderr() {
+        let error = run_hooks(&[hook("echo 'registry unreachable' >&2; exit 3")], COMPOSE.to_string(), false)
+            .unwrap_err();
+        assert_eq!(error.exit_code(), 1);
+        let error = error.to_string();
+        assert!(error.contains("exit status: 3"), "{error}");
+        assert!(error.contains("registry unreachable"), "{error}");
+    }
+
+    #[test]
+    fn test_invalid_output_is_rejected() {
+        let error = run_hooks(&[hook("cat; echo '    depends_on: [db]'")], COMPOSE.to_string(), false).unwrap_err();
+        assert_eq!(error.exit_code(), 4);
+        assert!(error.to_string().contains("service 'api': depends on undefined service 'db'"), "{error}");
+    }
+
+    #[test]
+    fn test_only_problems_the_hook_introduced_count() {
+        let undeclared = "services:\n  db:\n    image: postgres:16\n    volumes: [\"pgdata:/var/lib/postgresql/data\"]\n";
+        assert_eq!(run_hooks(&[hook("cat")], undeclared.to_string(), false).unwrap(), undeclared);
+
+        let error = run_hooks(&[hook("cat; echo '    depends_on: [cache]'")], undeclared.to_string(), false)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("depends on undefined service 'cache'"), "{error}");
+        assert!(!error.contains("pgdata"), "{error}");
+    }
+
+    #[test]
+    fn test_hooks_from_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = temp_dir.path().join("athena.toml");
+        assert!(load_hooks(&config).unwrap().is_empty());
+
+        fs::write(&config, "[limits]\nservices = 5\n\n[[postprocess]]\ncommand = \"./mirror.sh\"\n\n[[postprocess]]\ncommand = \"./sidecar.sh\"\n").unwrap();
+        assert_eq!(load_hooks(&config).unwrap(), [hook("./mirror.sh"), hook("./sidecar.sh")]);
+
+        fs::write(&config, "[[postprocess]]\nscript = \"./mirror.sh\"\n").unwrap();
+        assert!(load_hooks(&config).is_err());
+    }
+}
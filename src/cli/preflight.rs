@@ -0,0 +1,264 @@
+//! `athena build --require-images-exist`: check that every image the output
+//! pulls is already in the local Docker daemon, e.g. before an offline deploy.
+//!
+//! Shells out to the docker CLI rather than talking to the daemon socket, so
+//! it follows the same DOCKER_HOST and context settings as the deploy itself.
+
+use std::ffi::OsString;
+use std::process::{Command, Output, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::athena::error::EnhancedValidationError;
+use crate::athena::parser::ast::AthenaFile;
+use crate::athena::{AthenaError, AthenaResult};
+use crate::cli::messages::{message, text, Message};
+
+/// Docker binary to run instead of the one on PATH
+pub const DOCKER_ENV_VAR: &str = "ATHENA_DOCKER";
+
+/// Seconds to wait for each docker command before giving up
+pub const TIMEOUT_ENV_VAR: &str = "ATHENA_DOCKER_TIMEOUT";
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// An image pulled by the output, with the services that use it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImageReference {
+    pub image: String,
+    /// os/arch[/variant] the image must be available for, if pinned
+    pub platform: Option<String>,
+    pub services: Vec<String>,
+}
+
+/// Images the generated output pulls. Services with a build section are
+/// skipped, and a service's PLATFORM takes precedence over `platform`.
+pub fn image_references(athena_file: &AthenaFile, platform: Option<&str>) -> Vec<ImageReference> {
+    let mut references: Vec<ImageReference> = Vec::new();
+    for service in &athena_file.services.services {
+        let Some(image) = &service.image else {
+            continue;
+        };
+        if service.build_args.is_some() {
+            continue;
+        }
+
+        let platform = service.platform.as_deref().or(platform).map(str::to_string);
+        match references
+            .iter_mut()
+            .find(|r| &r.image == image && r.platform == platform)
+        {
+            Some(reference) => reference.services.push(service.name.clone()),
+            None => references.push(ImageReference {
+                image: image.clone(),
+                platform,
+                services: vec![service.name.clone()],
+            }),
+        }
+    }
+    references
+}
+
+/// Fail with every image missing from the local daemon; returns how many
+/// images were checked
+pub fn require_images(athena_file: &AthenaFile, platform: Option<&str>) -> AthenaResult<usize> {
+    let references = image_references(athena_file, platform);
+    if references.is_empty() {
+        return Ok(0);
+    }
+
+    let docker = Docker::from_env();
+    docker.ping()?;
+
+    let mut missing: Vec<String> = Vec::new();
+    for reference in &references {
+        let found = docker.image_platform(&reference.image)?;
+        let problem = match (&found, &reference.platform) {
+            (None, _) => Some(String::new()),
+            (Some(found), Some(wanted)) if !platform_matches(found, wanted) => {
+                Some(format!(" for {wanted} (found {found})"))
+            }
+            _ => None,
+        };
+        if let Some(problem) = problem {
+            missing.push(format!(
+                "  {}{problem} (services: {})",
+                reference.image,
+                reference.services.join(", ")
+            ));
+        }
+    }
+
+    if missing.is_empty() {
+        return Ok(references.len());
+    }
+    Err(AthenaError::validation_error_enhanced(
+        EnhancedValidationError::new(message!(
+            ImagesMissing,
+            count = missing.len(),
+            images = missing.join("\n")
+        ))
+        .with_suggestion(text(Message::ImagesMissingFix).to_string()),
+    ))
+}
+
+/// Whether an image built for `found` satisfies `wanted`; a platform without
+/// a variant accepts any variant
+fn platform_matches(found: &str, wanted: &str) -> bool {
+    let normalize = |platform: &str| -> Vec<String> {
+        platform
+            .split('/')
+            .map(|part| match part {
+                "x86_64" | "x86-64" => "amd64".to_string(),
+                "aarch64" => "arm64".to_string(),
+                other => other.to_lowercase(),
+            })
+            .collect()
+    };
+    let (found, wanted) = (normalize(found), normalize(wanted));
+    match wanted.len() {
+        3 => found == wanted,
+        _ => found.iter().take(2).eq(wanted.iter().take(2)),
+    }
+}
+
+struct Docker {
+    binary: OsString,
+    timeout: Duration,
+}
+
+impl Docker {
+    fn from_env() -> Self {
+        let timeout = std::env::var(TIMEOUT_ENV_VAR)
+            .ok()
+            .and_then(|seconds| seconds.parse::<f64>().ok())
+            .filter(|seconds| seconds.is_finite() && *seconds > 0.0)
+            .map_or(DEFAULT_TIMEOUT, Duration::from_secs_f64);
+        Self {
+            binary: std::env::var_os(DOCKER_ENV_VAR)
+                .filter(|binary| !binary.is_empty())
+                .unwrap_or_else(|| "docker".into()),
+            timeout,
+        }
+    }
+
+    /// Make sure the daemon answers before checking images one by one
+    fn ping(&self) -> AthenaResult<()> {
+        let output = self
+            .run(&["version", "--format", "{{.Server.Version}}"])
+            .map_err(unreachable_daemon)?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(unreachable_daemon(stderr_line(&output)))
+        }
+    }
+
+    /// os/arch[/variant] of a local image, or None when it isn't present
+    fn image_platform(&self, image: &str) -> AthenaResult<Option<String>> {
+        let output = self
+            .run(&[
+                "image",
+                "inspect",
+                "--format",
+                "{{.Os}}/{{.Architecture}}{{if .Variant}}/{{.Variant}}{{end}}",
+                image,
+            ])
+            .map_err(unreachable_daemon)?;
+        Ok(output
+            .status
+            .success()
+            .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string()))
+    }
+
+    /// Run docker, killing it if it doesn't exit within the timeout. Output
+    /// is a line or two, so it fits in the pipe buffers while we wait.
+    fn run(&self, args: &[&str]) -> Result<Output, String> {
+        let mut child = Command::new(&self.binary)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("could not run '{}': {e}", self.binary.to_string_lossy()))?;
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            match child.try_wait() {
+                Ok(Some(_)) => return child.wait_with_output().map_err(|e| e.to_string()),
+                Ok(None) if Instant::now() >= deadline => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!(
+                        "'{} {}' did not answer within {:.1}s",
+                        self.binary.to_string_lossy(),
+                        args[0],
+                        self.timeout.as_secs_f64()
+                    ));
+                }
+                Ok(None) => thread::sleep(Duration::from_millis(20)),
+                Err(e) => return Err(e.to_string()),
+            }
+        }
+    }
+}
+
+fn stderr_line(output: &Output) -> String {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    stderr
+        .lines()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or("docker exited with an error")
+        .trim()
+        .to_string()
+}
+
+fn unreachable_daemon(reason: String) -> AthenaError {
+    AthenaError::config_error(message!(DaemonUnreachable, reason = reason, var = TIMEOUT_ENV_VAR))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::parse_athena_file;
+
+    #[test]
+    fn test_image_references_group_services_and_skip_builds() {
+        let athena_file = parse_athena_file(
+            "SERVICES SECTION\n\
+             SERVICE web\nIMAGE-ID nginx:alpine\nEND SERVICE\n\
+             SERVICE admin\nIMAGE-ID nginx:alpine\nEND SERVICE\n\
+             SERVICE arm\nIMAGE-ID nginx:alpine\nPLATFORM \"linux/arm64\"\nEND SERVICE\n\
+             SERVICE api\nIMAGE-ID python:3.12\nBUILD-ARGS PYTHON_VERSION=\"3.12\"\nEND SERVICE\n\
+             SERVICE worker\nEND SERVICE",
+        )
+        .unwrap();
+
+        let references = image_references(&athena_file, Some("linux/amd64"));
+        assert_eq!(
+            references,
+            vec![
+                ImageReference {
+                    image: "nginx:alpine".to_string(),
+                    platform: Some("linux/amd64".to_string()),
+                    services: vec!["web".to_string(), "admin".to_string()],
+                },
+                ImageReference {
+                    image: "nginx:alpine".to_string(),
+                    platform: Some("linux/arm64".to_string()),
+                    services: vec!["arm".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_platform_matches() {
+        assert!(platform_matches("linux/amd64", "linux/amd64"));
+        assert!(platform_matches("linux/arm64/v8", "linux/arm64"));
+        assert!(platform_matches("linux/arm64", "linux/aarch64"));
+        assert!(platform_matches("linux/arm/v7", "linux/arm/v7"));
+        assert!(!platform_matches("linux/arm/v6", "linux/arm/v7"));
+        assert!(!platform_matches("linux/amd64", "linux/arm64"));
+    }
+}
@@ -0,0 +1,127 @@
+//! Timing and size report printed by `athena build --stats`.
+
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::athena::parser::ast::AthenaFile;
+use crate::cli::table::Table;
+
+/// Wall-clock time of each build phase, in milliseconds
+#[derive(Debug, Default, Serialize)]
+pub struct PhaseTimings {
+    pub parse_ms: f64,
+    /// Warnings and bind mount checks run before generation
+    pub validation_ms: f64,
+    /// Includes the consistency checks the generator runs on its own output
+    pub generation_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ServiceStats {
+    pub name: String,
+    pub ports: usize,
+    pub env_vars: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BuildStats {
+    pub timings: PhaseTimings,
+    pub services: usize,
+    pub networks: usize,
+    pub volumes: usize,
+    pub output_bytes: usize,
+    pub per_service: Vec<ServiceStats>,
+}
+
+pub fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl BuildStats {
+    pub fn new(athena_file: &AthenaFile, timings: PhaseTimings, output_bytes: usize) -> Self {
+        let (networks, volumes) = athena_file
+            .environment
+            .as_ref()
+            .map_or((0, 0), |env| (env.networks.len(), env.volumes.len()));
+
+        Self {
+            timings,
+            services: athena_file.services.services.len(),
+            // Without a NETWORK declaration the project network is still created
+            networks: networks.max(1),
+            volumes,
+            output_bytes,
+            per_service: athena_file
+                .services
+                .services
+                .iter()
+                .map(|service| ServiceStats {
+                    name: service.name.clone(),
+                    ports: service.ports.len(),
+                    env_vars: service.environment.len(),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn print_text(&self) {
+        println!("Build stats:");
+        println!("   - Parse:      {:.2} ms", self.timings.parse_ms);
+        println!("   - Validation: {:.2} ms", self.timings.validation_ms);
+        println!("   - Generation: {:.2} ms", self.timings.generation_ms);
+        println!(
+            "   - Services: {}, networks: {}, volumes: {}",
+            self.services, self.networks, self.volumes
+        );
+        println!("   - Output size: {} bytes", self.output_bytes);
+        self.service_table().print();
+    }
+
+    fn service_table(&self) -> Table {
+        let mut table = Table::new(&["Service", "Ports", "Env vars"]);
+        for service in &self.per_service {
+            table.add_row(vec![
+                service.name.as_str().into(),
+                service.ports.to_string().into(),
+                service.env_vars.to_string().into(),
+            ]);
+        }
+        table
+    }
+
+    pub fn print_json(&self) {
+        // Serializing plain numbers and strings cannot fail
+        println!("{:#}", serde_json::to_value(self).unwrap_or_default());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::athena::parser::parse_athena_file;
+    use crate::cli::output::TableStyle;
+
+    #[test]
+    fn test_counts_services_and_defaults_network() {
+        let athena_file = parse_athena_file(
+            "SERVICES SECTION\nSERVICE api\nIMAGE-ID \"node\"\nPORT-MAPPING 80 TO 80\nENV-VARIABLE {{TOKEN}}\nEND SERVICE",
+        )
+        .unwrap();
+
+        let stats = BuildStats::new(&athena_file, PhaseTimings::default(), 42);
+        assert_eq!(stats.services, 1);
+        assert_eq!(stats.networks, 1);
+        assert_eq!(stats.volumes, 0);
+        assert_eq!(stats.per_service[0].ports, 1);
+        assert_eq!(stats.per_service[0].env_vars, 1);
+
+        assert_eq!(
+            stats.service_table().render(TableStyle::Plain, 80),
+            "+---------+-------+----------+\n| Service | Ports | Env vars |\n+---------+-------+----------+\n| api     | 1     | 1        |\n+---------+-------+----------+\n"
+        );
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["output_bytes"], 42);
+        assert_eq!(json["per_service"][0]["name"], "api");
+    }
+}
@@ -0,0 +1,227 @@
+//! `athena test`: parse, validate and lint every .ath file of a project as
+//! one suite, for CI.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use ignore::WalkBuilder;
+
+use crate::athena::labels::load_default_labels;
+use crate::athena::limits::Limits;
+use crate::athena::merge::{apply_environment, parse_and_merge, SourceFile};
+use crate::athena::parser::ast::AthenaFile;
+use crate::athena::warnings::collect_warnings;
+use crate::athena::{generate, AthenaError, AthenaResult, GeneratorOptions};
+use crate::cli::junit::{Failure, TestCase};
+
+/// `--report FORMAT:PATH`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SuiteReport {
+    Junit(PathBuf),
+}
+
+impl FromStr for SuiteReport {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        match spec.split_once(':') {
+            Some(("junit", path)) if !path.is_empty() => Ok(SuiteReport::Junit(path.into())),
+            _ => Err(format!("expected junit:<path>, e.g. junit:athena.xml, not '{spec}'")),
+        }
+    }
+}
+
+/// The .ath files under `paths`, sorted. Directories are walked the way git
+/// sees them: .gitignore, .ignore and hidden entries are skipped, whether or
+/// not the directory is a repository. Files named directly are always kept.
+pub fn discover(paths: &[PathBuf]) -> AthenaResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if !path.is_dir() {
+            files.push(path.clone());
+            continue;
+        }
+        for entry in WalkBuilder::new(path).require_git(false).build() {
+            let entry = entry.map_err(|e| AthenaError::config_error(e.to_string()))?;
+            let is_file = entry.file_type().is_some_and(|t| t.is_file());
+            if is_file && entry.path().extension().is_some_and(|ext| ext == "ath") {
+                // `stacks/api.ath` rather than `./stacks/api.ath` when searching the default `.`
+                let found = entry.path().strip_prefix(".").unwrap_or(entry.path());
+                files.push(found.to_path_buf());
+            }
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// What checking one file found
+#[derive(Debug, Clone, Default)]
+pub struct FileResult {
+    pub path: PathBuf,
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+    pub time: Duration,
+}
+
+impl FileResult {
+    /// Errors fail a file, and so do warnings under `--strict`
+    pub fn failed(&self, strict: bool) -> bool {
+        !self.errors.is_empty() || (strict && !self.warnings.is_empty())
+    }
+
+    pub fn test_case(&self, strict: bool) -> TestCase {
+        let mut failures: Vec<Failure> = self
+            .errors
+            .iter()
+            .map(|error| Failure {
+                kind: "error",
+                text: error.clone(),
+            })
+            .collect();
+        let mut output = Vec::new();
+        if strict {
+            failures.extend(self.warnings.iter().map(|warning| Failure {
+                kind: "warning",
+                text: warning.clone(),
+            }));
+        } else {
+            output = self.warnings.clone();
+        }
+        TestCase {
+            name: self.path.display().to_string(),
+            time: self.time,
+            failures,
+            output,
+        }
+    }
+}
+
+/// Run everything `athena build` would on `path` without writing output:
+/// parsing, DEFAULT LABELS FROM, warnings, limits and the generator's own
+/// validation, for the file itself and with each of its ENVIRONMENT blocks
+pub fn check_file(path: &Path, limits: Option<&Limits>) -> FileResult {
+    let started = Instant::now();
+    let mut result = FileResult {
+        path: path.to_path_buf(),
+        ..FileResult::default()
+    };
+    if let Err(e) = run_checks(path, limits, &mut result) {
+        result.errors.insert(0, e.to_string());
+    }
+    result.time = started.elapsed();
+    result
+}
+
+fn run_checks(path: &Path, limits: Option<&Limits>, result: &mut FileResult) -> AthenaResult<()> {
+    let content = fs::read_to_string(path).map_err(AthenaError::IoError)?;
+    let display = path.display().to_string();
+    let mut athena_file = parse_and_merge(&[SourceFile {
+        path: &display,
+        content: &content,
+    }])?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+    load_default_labels(&mut athena_file, base_dir)?;
+    let options = GeneratorOptions::new().with_reproducible(true).with_base_dir(base_dir);
+
+    result.warnings = collect_warnings(&athena_file)
+        .warnings
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    result.warnings.extend(check_stack(&athena_file, &options, limits)?);
+
+    let names: Vec<String> = athena_file.environments.iter().map(|e| e.name.clone()).collect();
+    for name in names {
+        let mut staged = athena_file.clone();
+        let checked = apply_environment(&mut staged, &name).and_then(|()| check_stack(&staged, &options, limits));
+        if let Err(e) = checked {
+            result.errors.push(format!("ENVIRONMENT \"{name}\": {e}"));
+        }
+    }
+    Ok(())
+}
+
+/// Limits and generation; returns the generator's notes
+fn check_stack(
+    athena_file: &AthenaFile,
+    options: &GeneratorOptions,
+    limits: Option<&Limits>,
+) -> AthenaResult<Vec<String>> {
+    if let Some(limits) = limits {
+        limits.check(athena_file)?;
+    }
+    let generated = generate(athena_file, options)?;
+    if let Some(limits) = limits {
+        limits.check_output_size(generated.content.len())?;
+    }
+    Ok(generated.warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const VALID: &str = "SERVICES SECTION\nSERVICE api\nIMAGE-ID \"api:latest\"\nEND SERVICE\n";
+
+    #[test]
+    fn test_discover_respects_gitignore() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        for dir in ["stacks/nested", "build", ".hidden"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in ["a.ath", "stacks/b.ath", "stacks/nested/c.ath", "build/d.ath", ".hidden/e.ath", "notes.txt"] {
+            fs::write(root.join(file), VALID).unwrap();
+        }
+        fs::write(root.join(".gitignore"), "build/\n").unwrap();
+
+        let files = discover(&[root.to_path_buf(), root.join("build/d.ath")]).unwrap();
+        let relative: Vec<PathBuf> = files.iter().map(|f| f.strip_prefix(root).unwrap().to_path_buf()).collect();
+        let expected: Vec<PathBuf> = ["a.ath", "build/d.ath", "stacks/b.ath", "stacks/nested/c.ath"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        assert_eq!(relative, expected);
+    }
+
+    #[test]
+    fn test_check_file_reports_environment_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("stack.ath");
+        fs::write(
+            &path,
+            format!("{VALID}\nENVIRONMENT \"prod\"\nSERVICE api\nDEPENDS-ON ghost\nEND SERVICE\nEND ENVIRONMENT\n"),
+        )
+        .unwrap();
+
+        let result = check_file(&path, None);
+        assert_eq!(result.errors.len(), 1, "{:#?}", result.errors);
+        assert!(result.errors[0].starts_with("ENVIRONMENT \"prod\": "), "{}", result.errors[0]);
+        assert!(result.failed(false));
+    }
+
+    #[test]
+    fn test_warnings_fail_only_when_strict() {
+        let result = FileResult {
+            path: "stack.ath".into(),
+            warnings: vec!["[ATH001] uppercase".to_string()],
+            ..FileResult::default()
+        };
+        assert!(!result.failed(false));
+        assert!(result.failed(true));
+        assert_eq!(result.test_case(false).output.len(), 1);
+        assert_eq!(result.test_case(true).failures[0].kind, "warning");
+    }
+
+    #[test]
+    fn test_report_spec() {
+        assert_eq!("junit:out/athena.xml".parse(), Ok(SuiteReport::Junit("out/athena.xml".into())));
+        assert!("junit:".parse::<SuiteReport>().is_err());
+        assert!("tap:out.tap".parse::<SuiteReport>().is_err());
+    }
+}
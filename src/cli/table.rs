@@ -0,0 +1,272 @@
+//! Aligned tables for informational output.
+//!
+//! Tables are drawn with box characters and a colored header when stdout is a
+//! terminal and color is enabled, and with plain ASCII otherwise so piped
+//! output stays greppable. Cells wrap at spaces to fit the terminal; a single
+//! word longer than its column (e.g. a service name) widens the table instead
+//! of being split.
+
+use crate::cli::output::{self, TableStyle};
+
+const HEADER_COLOR: &str = "1";
+
+/// A table cell, optionally colored in the styled mode
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cell {
+    text: String,
+    color: Option<&'static str>,
+}
+
+impl Cell {
+    pub fn colored(text: impl Into<String>, color: &'static str) -> Self {
+        Self {
+            text: text.into(),
+            color: Some(color),
+        }
+    }
+}
+
+impl From<String> for Cell {
+    fn from(text: String) -> Self {
+        Self { text, color: None }
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Self {
+        text.to_string().into()
+    }
+}
+
+struct Borders {
+    top: [char; 3],
+    middle: [char; 3],
+    bottom: [char; 3],
+    horizontal: char,
+    vertical: char,
+}
+
+const BOX: Borders = Borders {
+    top: ['┌', '┬', '┐'],
+    middle: ['├', '┼', '┤'],
+    bottom: ['└', '┴', '┘'],
+    horizontal: '─',
+    vertical: '│',
+};
+
+const ASCII: Borders = Borders {
+    top: ['+', '+', '+'],
+    middle: ['+', '+', '+'],
+    bottom: ['+', '+', '+'],
+    horizontal: '-',
+    vertical: '|',
+};
+
+#[derive(Debug, Clone)]
+pub struct Table {
+    headers: Vec<Cell>,
+    rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    pub fn new(headers: &[&str]) -> Self {
+        Self {
+            headers: headers
+                .iter()
+                .map(|header| Cell::colored(*header, HEADER_COLOR))
+                .collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Add a row; it must have one cell per header
+    pub fn add_row(&mut self, row: Vec<Cell>) {
+        debug_assert_eq!(row.len(), self.headers.len(), "row width must match the headers");
+        self.rows.push(row);
+    }
+
+    /// Print to stdout in the style and width of the current terminal
+    pub fn print(&self) {
+        print!("{}", self.render(output::table_style(), output::terminal_width()));
+    }
+
+    /// The table as text, at most `max_width` columns wide unless a single
+    /// word doesn't fit
+    pub fn render(&self, style: TableStyle, max_width: usize) -> String {
+        let borders = match style {
+            TableStyle::Styled => &BOX,
+            TableStyle::Plain => &ASCII,
+        };
+        let widths = self.column_widths(max_width);
+
+        let mut out = rule(borders, borders.top, &widths);
+        out += &render_row(&self.headers, &widths, borders, style);
+        out += &rule(borders, borders.middle, &widths);
+        for row in &self.rows {
+            out += &render_row(row, &widths, borders, style);
+        }
+        out += &rule(borders, borders.bottom, &widths);
+        out
+    }
+
+    /// Natural widths, narrowing the widest wrappable column until the table
+    /// fits or every column is down to its longest word
+    fn column_widths(&self, max_width: usize) -> Vec<usize> {
+        let cells = || std::iter::once(&self.headers).chain(&self.rows);
+        let column_max = |measure: fn(&str) -> usize| -> Vec<usize> {
+            (0..self.headers.len())
+                .map(|column| {
+                    cells()
+                        .map(|row| measure(&row[column].text))
+                        .max()
+                        .unwrap_or(0)
+                })
+                .collect()
+        };
+
+        let mut widths = column_max(|text| text.chars().count());
+        let minimums = column_max(|text| {
+            text.split_whitespace()
+                .map(|word| word.chars().count())
+                .max()
+                .unwrap_or(0)
+        });
+
+        // "| " before each cell, " " after it, and the closing border
+        let borders = 3 * widths.len() + 1;
+        while widths.iter().sum::<usize>() + borders > max_width {
+            let Some(column) = (0..widths.len())
+                .filter(|&column| widths[column] > minimums[column])
+                .max_by_key(|&column| widths[column])
+            else {
+                break;
+            };
+            widths[column] -= 1;
+        }
+        widths
+    }
+}
+
+fn rule(borders: &Borders, [left, join, right]: [char; 3], widths: &[usize]) -> String {
+    let segments: Vec<String> = widths
+        .iter()
+        .map(|width| borders.horizontal.to_string().repeat(width + 2))
+        .collect();
+    format!("{left}{}{right}\n", segments.join(&join.to_string()))
+}
+
+fn render_row(row: &[Cell], widths: &[usize], borders: &Borders, style: TableStyle) -> String {
+    let wrapped: Vec<Vec<String>> = row
+        .iter()
+        .zip(widths)
+        .map(|(cell, width)| wrap(&cell.text, *width))
+        .collect();
+    let height = wrapped.iter().map(Vec::len).max().unwrap_or(1);
+
+    let mut out = String::new();
+    for line in 0..height {
+        for ((cell, lines), width) in row.iter().zip(&wrapped).zip(widths) {
+            let text = lines.get(line).map_or("", String::as_str);
+            let padded = format!("{text:<width$}");
+            let painted = match (style, cell.color) {
+                (TableStyle::Styled, Some(code)) => format!("\x1b[{code}m{padded}\x1b[0m"),
+                _ => padded,
+            };
+            out += &format!("{} {painted} ", borders.vertical);
+        }
+        out += &format!("{}\n", borders.vertical);
+    }
+    out
+}
+
+/// Greedy word wrap; never splits a word
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    let mut current = String::new();
+    for word in text.split_whitespace() {
+        if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        }
+        if !current.is_empty() {
+            current.push(' ');
+        }
+        current.push_str(word);
+    }
+    lines.push(current);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Table {
+        let mut table = Table::new(&["Rule", "Severity", "Count"]);
+        table.add_row(vec!["ATH001".into(), Cell::colored("warning", "1;33"), "2".into()]);
+        table.add_row(vec!["ATH004".into(), Cell::colored("error", "1;31"), "1".into()]);
+        table
+    }
+
+    #[test]
+    fn test_plain_table() {
+        assert_eq!(
+            sample().render(TableStyle::Plain, 80),
+            "\
++--------+----------+-------+
+| Rule   | Severity | Count |
++--------+----------+-------+
+| ATH001 | warning  | 2     |
+| ATH004 | error    | 1     |
++--------+----------+-------+
+"
+        );
+    }
+
+    #[test]
+    fn test_styled_table() {
+        assert_eq!(
+            sample().render(TableStyle::Styled, 80),
+            "\
+┌────────┬──────────┬───────┐
+│ \x1b[1mRule  \x1b[0m │ \x1b[1mSeverity\x1b[0m │ \x1b[1mCount\x1b[0m │
+├────────┼──────────┼───────┤
+│ ATH001 │ \x1b[1;33mwarning \x1b[0m │ 2     │
+│ ATH004 │ \x1b[1;31merror   \x1b[0m │ 1     │
+└────────┴──────────┴───────┘
+"
+        );
+    }
+
+    #[test]
+    fn test_long_cells_wrap_between_words() {
+        let mut table = Table::new(&["Service", "Summary"]);
+        table.add_row(vec![
+            "a_very_long_service_name_that_does_not_fit".into(),
+            "depends on a service that only runs under a profile".into(),
+        ]);
+
+        let rendered = table.render(TableStyle::Plain, 70);
+        assert_eq!(
+            rendered,
+            "\
++--------------------------------------------+-----------------------+
+| Service                                    | Summary               |
++--------------------------------------------+-----------------------+
+| a_very_long_service_name_that_does_not_fit | depends on a service  |
+|                                            | that only runs under  |
+|                                            | a profile             |
++--------------------------------------------+-----------------------+
+"
+        );
+        assert!(rendered.lines().all(|line| line.chars().count() <= 70));
+    }
+
+    #[test]
+    fn test_words_are_never_split() {
+        let mut table = Table::new(&["Service"]);
+        table.add_row(vec!["a_service_name_wider_than_the_terminal".into()]);
+
+        let rendered = table.render(TableStyle::Plain, 10);
+        assert!(rendered.contains("| a_service_name_wider_than_the_terminal |"));
+    }
+}
@@ -0,0 +1,86 @@
+//! Opt-in check for newer Athena releases on GitHub.
+
+/// Set to any non-empty value to turn the check into a no-op
+pub const DISABLE_ENV_VAR: &str = "ATHENA_NO_UPDATE_CHECK";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpdateStatus {
+    UpToDate { latest: String },
+    Available { latest: String },
+    Disabled,
+    /// The check could not complete; never fatal
+    Unavailable(String),
+}
+
+/// Ask GitHub for the latest release tag and compare it with this build
+pub fn check_for_updates(current: &str) -> UpdateStatus {
+    if std::env::var_os(DISABLE_ENV_VAR).is_some_and(|v| !v.is_empty()) {
+        return UpdateStatus::Disabled;
+    }
+
+    match fetch_latest_tag() {
+        Ok(latest) if is_newer(&latest, current) => UpdateStatus::Available { latest },
+        Ok(latest) => UpdateStatus::UpToDate { latest },
+        Err(reason) => UpdateStatus::Unavailable(reason),
+    }
+}
+
+#[cfg(feature = "update-check")]
+fn fetch_latest_tag() -> Result<String, String> {
+    const RELEASES_URL: &str = "https://api.github.com/repos/Jeck0v/Athena/releases/latest";
+
+    // Short timeout: a slow network must never hold up the command
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(std::time::Duration::from_secs(3)))
+        .build()
+        .into();
+
+    let body = agent
+        .get(RELEASES_URL)
+        .header("User-Agent", concat!("athena/", env!("CARGO_PKG_VERSION")))
+        .header("Accept", "application/vnd.github+json")
+        .call()
+        .and_then(|mut response| response.body_mut().read_to_string())
+        .map_err(|e| e.to_string())?;
+
+    let release: serde_json::Value = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+    release["tag_name"]
+        .as_str()
+        .map(str::to_string)
+        .ok_or_else(|| "release response has no tag_name".to_string())
+}
+
+#[cfg(not(feature = "update-check"))]
+fn fetch_latest_tag() -> Result<String, String> {
+    Err("built without the update-check feature".to_string())
+}
+
+/// Compare dotted versions such as `v0.2.0` and `0.1.0`; pre-release suffixes are ignored
+fn is_newer(candidate: &str, current: &str) -> bool {
+    parse_version(candidate) > parse_version(current)
+}
+
+fn parse_version(version: &str) -> Vec<u64> {
+    version
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("v0.2.0", "0.1.0"));
+        assert!(is_newer("0.10.0", "0.9.3"));
+        assert!(is_newer("v1.0.0-rc.1", "0.9.0"));
+        assert!(!is_newer("v0.1.0", "0.1.0"));
+        assert!(!is_newer("0.0.9", "0.1.0"));
+    }
+}
@@ -1,8 +1,10 @@
 use std::fmt::Write;
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
 
 use crate::athena::{AthenaError, AthenaResult};
+use crate::cli::messages::{message, text, Message};
 
 /// Auto-detect a .ath file in the current directory.
 ///
@@ -28,29 +30,157 @@ pub fn auto_detect_ath_file(input: Option<PathBuf>) -> AthenaResult<PathBuf> {
         .collect();
 
     match ath_files.len() {
-        0 => Err(AthenaError::ConfigError(
-            "No .ath file found in current directory. Please specify a file or create one."
-                .to_string(),
-        )),
+        0 => Err(AthenaError::ConfigError(text(Message::NoAthFile).to_string())),
         1 => {
             let file = &ath_files[0];
-            println!("Auto-detected: {}", file.display());
+            println!("{}", message!(AutoDetected, file = file.display()));
             Ok(file.clone())
         }
         _ => {
-            let mut error_msg =
-                "Multiple .ath files found. Please specify which one to use:\n".to_string();
+            let mut error_msg = text(Message::MultipleAthFiles).to_string();
             for file in &ath_files {
-                let _ = writeln!(error_msg, "  - {}", file.display());
+                let _ = write!(error_msg, "\n  - {}", file.display());
             }
-            error_msg.push_str("Usage: athena build <FILE>");
+            let _ = write!(error_msg, "\n{}", text(Message::MultipleAthFilesUsage));
             Err(AthenaError::ConfigError(error_msg))
         }
     }
 }
 
+/// The .ath files in `dir`, sorted; with `recursive`, those in its
+/// subdirectories too. Symlinked directories are not followed.
+pub fn find_ath_files(dir: &Path, recursive: bool) -> AthenaResult<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).map_err(AthenaError::IoError)? {
+            let entry = entry.map_err(AthenaError::IoError)?;
+            let path = entry.path();
+            let file_type = entry.file_type().map_err(AthenaError::IoError)?;
+            if file_type.is_dir() {
+                if recursive {
+                    pending.push(path);
+                }
+            } else if path.extension().is_some_and(|ext| ext == "ath") && path.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
 /// Determine if we should be in verbose mode (default yes, unless --quiet).
 #[must_use]
 pub fn should_be_verbose(quiet: bool) -> bool {
     !quiet
-}
\ No newline at end of file
+}
+
+/// Switch to the `-C` directory before any path is resolved, as git and make do
+pub fn change_directory(dir: &Path) -> AthenaResult<()> {
+    std::env::set_current_dir(dir).map_err(|e| {
+        let error = message!(ChdirFailed, dir = dir.display(), error = e);
+        AthenaError::IoError(std::io::Error::new(e.kind(), error))
+    })
+}
+
+/// Write `contents` to `path` atomically.
+///
+/// The data goes to a temporary file in the same directory, is synced to disk
+/// and then renamed over the target, so an interrupted run leaves either the
+/// old file or the new one, never a truncated mix.
+pub fn write_atomic(path: &Path, contents: &str) -> AthenaResult<()> {
+    let dir = path
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+
+    let mut temp = temp_file_builder(path).tempfile_in(dir).map_err(AthenaError::IoError)?;
+    temp.write_all(contents.as_bytes()).map_err(AthenaError::IoError)?;
+    temp.as_file().sync_all().map_err(AthenaError::IoError)?;
+
+    temp.persist(path).map_err(|e| AthenaError::IoError(e.error))?;
+    Ok(())
+}
+
+/// Temp files are created 0600. Replacing a file keeps its mode; a new file
+/// gets the mode `fs::write` would give it.
+fn temp_file_builder(path: &Path) -> tempfile::Builder<'static, 'static> {
+    let mut builder = tempfile::Builder::new();
+    if let Some(permissions) = fs::metadata(path).ok().map(|m| m.permissions()).or_else(new_file_permissions) {
+        builder.permissions(permissions);
+    }
+    builder
+}
+
+/// 0666, which creating the file reduces by the umask
+#[cfg(unix)]
+fn new_file_permissions() -> Option<fs::Permissions> {
+    use std::os::unix::fs::PermissionsExt;
+    Some(fs::Permissions::from_mode(0o666))
+}
+
+#[cfg(not(unix))]
+fn new_file_permissions() -> Option<fs::Permissions> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_atomic_replaces_file_without_leftovers() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("docker-compose.yml");
+
+        write_atomic(&target, "first").unwrap();
+        write_atomic(&target, "second").unwrap();
+
+        assert_eq!(fs::read_to_string(&target).unwrap(), "second");
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_atomic_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        // A new file honours the umask, like fs::write
+        let plain = temp_dir.path().join("plain.yml");
+        fs::write(&plain, "").unwrap();
+        let target = temp_dir.path().join("docker-compose.yml");
+        write_atomic(&target, "first").unwrap();
+        assert_eq!(mode(&target), mode(&plain));
+
+        // Rewriting keeps whatever mode the file had
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o640)).unwrap();
+        write_atomic(&target, "second").unwrap();
+        assert_eq!(mode(&target), 0o640);
+    }
+
+    #[test]
+    fn test_find_ath_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("team/nested")).unwrap();
+        for file in ["b.ath", "a.ath", "notes.md", "team/c.ath", "team/nested/d.ath"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+
+        assert_eq!(find_ath_files(root, false).unwrap(), vec![root.join("a.ath"), root.join("b.ath")]);
+        assert_eq!(
+            find_ath_files(root, true).unwrap(),
+            vec![
+                root.join("a.ath"),
+                root.join("b.ath"),
+                root.join("team/c.ath"),
+                root.join("team/nested/d.ath"),
+            ]
+        );
+    }
+}
@@ -1,45 +1,53 @@
 use clap::Parser;
 use std::process;
 
-mod cli;
-mod athena;
-
-use cli::{Cli, execute_command};
+use athena::cli::messages::{self, text, Message};
+use athena::cli::utils::change_directory;
+use athena::cli::{Cli, execute_command, output};
 
 fn main() {
     let cli = Cli::parse();
+    output::init(cli.color);
+    messages::init();
 
-    if let Err(e) = execute_command(cli.command, cli.verbose) {
-        eprintln!("Error: {e}");
+    let result = match &cli.chdir {
+        Some(dir) => change_directory(dir),
+        None => Ok(()),
+    };
+    if let Err(e) = result.and_then(|()| execute_command(cli.command, cli.verbose)) {
+        output::error(&e);
 
         // Print additional context for common errors
         match &e {
             athena::AthenaError::IoError(io_err) => {
                 match io_err.kind() {
                     std::io::ErrorKind::NotFound => {
-                        eprintln!("Make sure the file path is correct and the file exists.");
+                        eprintln!("{}", text(Message::NotFoundHint));
                     }
                     std::io::ErrorKind::PermissionDenied => {
-                        eprintln!("Check file permissions and try running with appropriate privileges.");
+                        eprintln!("{}", text(Message::PermissionHint));
                     }
                     _ => {}
                 }
             }
             athena::AthenaError::ParseError(msg) => {
-                eprintln!("Check the syntax of your .ath file. Use 'athena info --examples' for syntax examples.");
+                eprintln!("{}", text(Message::ParseHint));
                 if msg.message.contains("Parse error") {
-                    eprintln!("Common issues: missing END SERVICE, incorrect keywords, or malformed strings.");
+                    eprintln!("{}", text(Message::ParseCommonIssues));
                 }
             }
             athena::AthenaError::ValidationError(msg) => {
-                eprintln!("Fix the validation issues in your configuration.");
-                if msg.message.contains("circular") {
-                    eprintln!("Review your service dependencies to avoid circular references.");
+                eprintln!("{}", text(Message::ValidationHint));
+                if msg.message.starts_with("Circular dependency") {
+                    eprintln!("{}", text(Message::CycleHint));
                 }
             }
-            athena::AthenaError::YamlError(_) | athena::AthenaError::ConfigError(_) => {}
+            athena::AthenaError::YamlError(_)
+            | athena::AthenaError::ConfigError(_)
+            | athena::AthenaError::UsageError(_)
+            | athena::AthenaError::IncompatibleError(_) => {}
         }
 
-        process::exit(1);
+        process::exit(e.exit_code());
     }
-}
\ No newline at end of file
+}
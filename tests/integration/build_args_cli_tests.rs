@@ -59,15 +59,15 @@ fn test_build_args_basic_cli() {
     
     // Check service has build configuration with args
     let services = parsed["services"].as_mapping().unwrap();
-    let api_service = services.get(&Value::String("api".to_string())).unwrap();
+    let api_service = services.get(Value::String("api".to_string())).unwrap();
     
     let build_config = api_service.get("build").expect("Should have build config");
     let args = build_config.get("args").expect("Should have args");
     let args_map = args.as_mapping().unwrap();
     
-    assert_eq!(args_map.get(&Value::String("NODE_ENV".to_string())),
+    assert_eq!(args_map.get(Value::String("NODE_ENV".to_string())),
                Some(&Value::String("production".to_string())));
-    assert_eq!(args_map.get(&Value::String("PORT".to_string())),
+    assert_eq!(args_map.get(Value::String("PORT".to_string())),
                Some(&Value::String("3000".to_string())));
 }
 
@@ -184,17 +184,17 @@ fn test_build_args_multiple_services() {
     let services = parsed["services"].as_mapping().unwrap();
     
     // Check frontend service
-    let frontend = services.get(&Value::String("frontend".to_string())).unwrap();
+    let frontend = services.get(Value::String("frontend".to_string())).unwrap();
     let frontend_build = frontend.get("build").expect("Frontend should have build");
     assert!(frontend_build.get("args").is_some());
     
     // Check api service
-    let api = services.get(&Value::String("api".to_string())).unwrap(); 
+    let api = services.get(Value::String("api".to_string())).unwrap(); 
     let api_build = api.get("build").expect("API should have build");
     assert!(api_build.get("args").is_some());
     
     // Check database service (should use image)
-    let database = services.get(&Value::String("database".to_string())).unwrap();
+    let database = services.get(Value::String("database".to_string())).unwrap();
     assert!(database.get("build").is_none());
     assert_eq!(database.get("image").unwrap(), "postgres:15");
 }
@@ -219,7 +219,7 @@ fn test_build_args_with_image_precedence() {
     let parsed = parse_yaml(&yaml_content);
     
     let services = parsed["services"].as_mapping().unwrap();
-    let api = services.get(&Value::String("api".to_string())).unwrap();
+    let api = services.get(Value::String("api".to_string())).unwrap();
     
     // Should use build config, not image
     assert!(api.get("build").is_some());
@@ -227,7 +227,7 @@ fn test_build_args_with_image_precedence() {
     
     let build_config = api.get("build").unwrap();
     let args = build_config.get("args").unwrap();
-    assert!(args.as_mapping().unwrap().contains_key(&Value::String("NODE_ENV".to_string())));
+    assert!(args.as_mapping().unwrap().contains_key(Value::String("NODE_ENV".to_string())));
 }
 
 #[test]
@@ -253,17 +253,17 @@ fn test_build_args_complex_scenario() {
     assert_eq!(parsed["name"], "build-args-complex");
     
     let networks = parsed["networks"].as_mapping().unwrap();
-    assert!(networks.contains_key(&Value::String("custom_network".to_string())));
+    assert!(networks.contains_key(Value::String("custom_network".to_string())));
     
     // Check all services are present
     let services = parsed["services"].as_mapping().unwrap();
-    assert!(services.contains_key(&Value::String("web_server".to_string())));
-    assert!(services.contains_key(&Value::String("app".to_string())));
-    assert!(services.contains_key(&Value::String("redis".to_string())));
-    assert!(services.contains_key(&Value::String("cache".to_string())));
+    assert!(services.contains_key(Value::String("web_server".to_string())));
+    assert!(services.contains_key(Value::String("app".to_string())));
+    assert!(services.contains_key(Value::String("redis".to_string())));
+    assert!(services.contains_key(Value::String("cache".to_string())));
     
     // Check web_server has multiple build args
-    let web_server = services.get(&Value::String("web_server".to_string())).unwrap();
+    let web_server = services.get(Value::String("web_server".to_string())).unwrap();
     let web_build = web_server.get("build").unwrap();
     let web_args = web_build.get("args").unwrap().as_mapping().unwrap();
     assert_eq!(web_args.len(), 3);
@@ -342,7 +342,7 @@ BUILD-ARGS NODEJS_VERSION="20" NODE_ENVIRONMENT="dev" API_URL="http://test" DB_U
 PORT-MAPPING 3000 TO 3000
 END SERVICE
     "#;
-    let test_file = create_test_file(&temp_dir, "test.ath", &ath_content);
+    let test_file = create_test_file(&temp_dir, "test.ath", ath_content);
     
     // Should fail with intelligent suggestions
     let mut cmd = Command::cargo_bin("athena").unwrap();
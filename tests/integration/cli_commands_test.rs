@@ -1,4 +1,5 @@
 use assert_cmd::Command;
+use athena::cli::messages::{render, text, Message, LANG_ENV_VAR};
 use predicates::prelude::*;
 use std::fs;
 use tempfile::TempDir;
@@ -248,4 +249,1293 @@ fn test_cli_version() {
     cmd.assert()
         .success()
         .stdout(predicate::str::contains("athena 0.1.0"));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_cli_validate_check_bind_mounts() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    fs::create_dir(temp_dir.path().join("data")).expect("Failed to create directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "mounts.ath",
+        r#"DEPLOYMENT-ID MOUNTS_TEST
+SERVICES SECTION
+
+SERVICE web
+IMAGE-ID nginx:alpine
+VOLUME-MAPPING "./data" TO "/usr/share/nginx/html"
+VOLUME-MAPPING "./nginx.conf" TO "/etc/nginx/nginx.conf" (ro)
+VOLUME-MAPPING "web_cache" TO "/var/cache/nginx"
+END SERVICE"#,
+    );
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("validate")
+        .arg(&ath_file)
+        .arg("--check-bind-mounts");
+
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("file './nginx.conf' does not exist"))
+        .stderr(predicate::str::contains("./data").not())
+        .stderr(predicate::str::contains("web_cache").not());
+
+    // Without the flag, no filesystem checks are performed
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("validate").arg(&ath_file);
+
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("Warning").not());
+}
+
+#[test]
+fn test_cli_nginx_command() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "proxy.ath",
+        r#"DEPLOYMENT-ID PROXY_TEST
+SERVICES SECTION
+
+SERVICE api
+IMAGE-ID python:3.12-slim
+PORT-MAPPING 8000 TO 8000
+EXPOSE-VIA nginx PATH "/api/"
+END SERVICE
+
+SERVICE worker
+IMAGE-ID python:3.12-slim
+END SERVICE"#,
+    );
+
+    let output_file = temp_dir.path().join("nginx/conf.d/generated.conf");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("nginx")
+        .arg(&ath_file)
+        .arg("-o")
+        .arg(&output_file);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Generated nginx config"));
+
+    let config = fs::read_to_string(&output_file).expect("Failed to read nginx config");
+    assert!(config.contains("upstream api"), "Should contain api upstream");
+    assert!(config.contains("location /api/"), "Should contain api location");
+    assert!(!config.contains("worker"), "Services without EXPOSE-VIA should be skipped");
+}
+
+#[test]
+fn test_cli_build_terraform_docker_target() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "terraform.ath",
+        r#"DEPLOYMENT-ID TF_TEST
+SERVICES SECTION
+
+SERVICE web
+IMAGE-ID nginx:alpine
+PORT-MAPPING 8080 TO 80
+REPLICAS 2
+END SERVICE"#,
+    );
+
+    let output_file = temp_dir.path().join("main.tf.json");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build")
+        .arg(&ath_file)
+        .arg("--target")
+        .arg("terraform-docker")
+        .arg("-o")
+        .arg(&output_file);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Generated Terraform config"))
+        .stderr(predicate::str::contains("Swarm deploy settings"));
+
+    let config = fs::read_to_string(&output_file).expect("Failed to read Terraform config");
+    assert!(config.contains("\"docker_container\""), "Should contain container resources");
+    assert!(config.contains("kreuzwerker/docker"), "Should pin the docker provider");
+}
+
+#[test]
+fn test_cli_build_podman_target() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "podman.ath",
+        r#"DEPLOYMENT-ID PODMAN_TEST
+SERVICES SECTION
+
+SERVICE web
+IMAGE-ID nginx:alpine
+VOLUME-MAPPING "./html" TO "/usr/share/nginx/html"
+REPLICAS 2
+END SERVICE"#,
+    );
+
+    let output_file = temp_dir.path().join("podman-compose.yml");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build")
+        .arg(&ath_file)
+        .arg("--target")
+        .arg("podman")
+        .arg("--selinux-label")
+        .arg("z")
+        .arg("-o")
+        .arg(&output_file);
+
+    cmd.assert()
+        .success()
+        .stderr(predicate::str::contains("ignores REPLICAS"));
+
+    let yaml = fs::read_to_string(&output_file).expect("Failed to read podman compose file");
+    assert!(yaml.contains("./html:/usr/share/nginx/html:z"), "Bind mount should carry the SELinux label");
+    assert!(!yaml.contains("replicas"), "Swarm-only keys should be removed");
+}
+
+#[test]
+fn test_cli_bake_command() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "bake.ath",
+        r#"DEPLOYMENT-ID BAKE_TEST
+SERVICES SECTION
+
+SERVICE api
+BUILD-ARGS NODE_VERSION="20"
+END SERVICE
+
+SERVICE cache
+IMAGE-ID redis:7-alpine
+END SERVICE"#,
+    );
+
+    let output_file = temp_dir.path().join("docker-bake.json");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("bake")
+        .arg(&ath_file)
+        .arg("--registry")
+        .arg("ghcr.io/acme")
+        .arg("-o")
+        .arg(&output_file);
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Generated bake file"))
+        .stderr(predicate::str::contains("Info: Service 'cache'"));
+
+    let bake = fs::read_to_string(&output_file).expect("Failed to read bake file");
+    assert!(bake.contains("\"ghcr.io/acme/api\""), "Tag should include the registry");
+    assert!(!bake.contains("\"cache\""), "Prebuilt images should be skipped");
+}
+
+#[test]
+fn test_cli_build_reproducible_output_is_byte_stable() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "stable.ath",
+        r#"DEPLOYMENT-ID STABLE_TEST
+SERVICES SECTION
+
+SERVICE web
+IMAGE-ID nginx:alpine
+PORT-MAPPING 8080 TO 80
+END SERVICE"#,
+    );
+
+    let mut outputs = Vec::new();
+    for name in ["first.yml", "second.yml"] {
+        let output_file = temp_dir.path().join(name);
+        let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+        cmd.arg("build")
+            .arg(&ath_file)
+            .arg("--reproducible")
+            .arg("-o")
+            .arg(&output_file);
+        cmd.assert().success();
+        outputs.push(fs::read_to_string(&output_file).expect("Failed to read output file"));
+    }
+
+    assert_eq!(outputs[0], outputs[1], "Reproducible builds should be byte-identical");
+    assert!(!outputs[0].contains("# Generated:"), "Timestamp should be omitted");
+    assert!(outputs[0].contains("# Output-SHA256: "), "Checksum header should be present");
+}
+
+#[test]
+fn test_cli_verify_detects_modified_and_stale_output() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_content = r#"DEPLOYMENT-ID VERIFY_TEST
+SERVICES SECTION
+
+SERVICE web
+IMAGE-ID nginx:alpine
+PORT-MAPPING 8080 TO 80
+END SERVICE"#;
+    let ath_file = create_test_ath_file(&temp_dir, "verify.ath", ath_content);
+    let output_file = temp_dir.path().join("docker-compose.yml");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build").arg(&ath_file).arg("-o").arg(&output_file);
+    cmd.assert().success();
+
+    let verify = |expected_success: bool, message: &str| {
+        let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+        cmd.arg("verify")
+            .arg(&output_file)
+            .arg("--against")
+            .arg(&ath_file);
+        let assert = cmd.assert();
+        if expected_success {
+            assert.success().stdout(predicate::str::contains(message));
+        } else {
+            assert.failure().stderr(predicate::str::contains(message));
+        }
+    };
+
+    let file = output_file.display();
+    let source = &ath_file;
+    verify(true, &render(Message::UpToDate, &[("file", &file), ("source", &source)]));
+
+    let generated = fs::read_to_string(&output_file).expect("Failed to read output file");
+    fs::write(&output_file, generated.replace("restart:", "restart:  ")).expect("Failed to edit output");
+    verify(false, &render(Message::OutputModified, &[("file", &file)]));
+
+    fs::write(&output_file, &generated).expect("Failed to restore output");
+    fs::write(&ath_file, format!("{ath_content}\n")).expect("Failed to edit source");
+    verify(false, &render(Message::OutputStale, &[("file", &file), ("source", &source)]));
+}
+
+#[test]
+fn test_cli_athena_lang() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "lang.ath",
+        "SERVICES SECTION\nSERVICE web\nIMAGE-ID nginx:alpine\nEND SERVICE",
+    );
+
+    let validate = |lang: &str| {
+        let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+        cmd.arg("validate").arg(&ath_file).env(LANG_ENV_VAR, lang);
+        cmd.assert().success().stdout(predicate::str::contains(text(Message::FileIsValid)))
+    };
+
+    validate("en_US.UTF-8").stderr(predicate::str::contains(LANG_ENV_VAR).not());
+    // Unsupported languages fall back to English with a warning
+    validate("xx").stderr(predicate::str::contains(format!(
+        "Warning: {LANG_ENV_VAR}=xx is not supported (available: en); using en"
+    )));
+}
+
+#[test]
+fn test_cli_build_annotate_sources() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "stack.ath",
+        r#"DEPLOYMENT-ID ANNOTATED
+ENVIRONMENT SECTION
+VOLUME pg_data
+SERVICES SECTION
+
+SERVICE db
+IMAGE-ID postgres:16
+VOLUME-MAPPING "pg_data" TO "/var/lib/postgresql/data"
+END SERVICE
+
+SERVICE web
+IMAGE-ID nginx:alpine
+DEPENDS-ON db
+END SERVICE"#,
+    );
+
+    let build = |name: &str, flags: &[&str]| {
+        let output_file = temp_dir.path().join(name);
+        let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+        cmd.arg("build")
+            .arg(&ath_file)
+            .arg("--reproducible")
+            .args(flags)
+            .arg("-o")
+            .arg(&output_file);
+        cmd.assert().success();
+        output_file
+    };
+    let annotated_file = build("annotated.yml", &["--annotate-sources"]);
+    let plain_file = build("plain.yml", &["--annotate-sources", "--no-annotations"]);
+    let annotated = fs::read_to_string(&annotated_file).expect("Failed to read output file");
+    let plain = fs::read_to_string(&plain_file).expect("Failed to read output file");
+
+    assert!(annotated.contains(&format!("  # from {ath_file}:6 (SERVICE db)\n  db:\n")));
+    assert!(annotated.contains(&format!("  # from {ath_file}:11 (SERVICE web)\n  web:\n")));
+    assert!(annotated.contains(&format!("  # from {ath_file}:3 (VOLUME pg_data)\n  pg_data:\n")));
+    assert!(!plain.contains("# from "));
+
+    let stripped: String = annotated
+        .split_inclusive('\n')
+        .filter(|line| !line.starts_with("  # from "))
+        .collect();
+    assert_eq!(stripped, plain, "Annotations should be the only difference");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("verify").arg(&annotated_file).arg("--against").arg(&ath_file);
+    cmd.assert().success().stdout(predicate::str::contains("is up to date"));
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build")
+        .arg(&ath_file)
+        .arg("--annotate-sources")
+        .arg("--output-format")
+        .arg("json")
+        .arg("-o")
+        .arg(temp_dir.path().join("docker-compose.json"));
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("--annotate-sources needs YAML output"));
+}
+
+#[test]
+fn test_cli_self_info_json() {
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("self-info")
+        .arg("--check-updates")
+        .arg("--format")
+        .arg("json")
+        .env("ATHENA_NO_UPDATE_CHECK", "1");
+
+    let output = cmd.output().expect("Failed to run athena");
+    assert!(output.status.success());
+
+    let info: serde_json::Value =
+        serde_json::from_slice(&output.stdout).expect("self-info should print valid JSON");
+    assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+    assert!(info["commit"].is_string());
+    assert_eq!(info["os"], std::env::consts::OS);
+    assert_eq!(info["update"]["status"], "disabled");
+
+    let keywords = info["dsl_keywords"].as_array().expect("keywords should be an array");
+    assert!(keywords.iter().any(|k| k == "IMAGE-ID"));
+}
+
+#[test]
+fn test_cli_build_cache_hit_and_clear() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "cached.ath",
+        "DEPLOYMENT-ID CACHE_TEST\nSERVICES SECTION\nSERVICE web\nIMAGE-ID nginx:alpine\nEND SERVICE",
+    );
+    let output_file = temp_dir.path().join("docker-compose.yml");
+    let cache_dir = temp_dir.path().join(".athena-cache");
+
+    let build = || {
+        let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+        cmd.current_dir(temp_dir.path())
+            .arg("build")
+            .arg(&ath_file)
+            .arg("-o")
+            .arg(&output_file)
+            .arg("--cache-dir")
+            .arg(&cache_dir);
+        cmd
+    };
+
+    build()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Reused cached output").not());
+    let first = fs::read_to_string(&output_file).expect("Output should exist");
+
+    fs::remove_file(&output_file).unwrap();
+    build()
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Reused cached output"));
+    assert_eq!(fs::read_to_string(&output_file).unwrap(), first);
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("cache")
+        .arg("clear")
+        .arg("--cache-dir")
+        .arg(&cache_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed 1 cached build(s)"));
+}
+
+#[test]
+fn test_cli_build_stats_json() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "stats.ath",
+        "DEPLOYMENT-ID STATS_TEST\nSERVICES SECTION\nSERVICE web\nIMAGE-ID nginx:alpine\nPORT-MAPPING 8080 TO 80\nEND SERVICE",
+    );
+    let output_file = temp_dir.path().join("docker-compose.yml");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    let assert = cmd
+        .arg("build")
+        .arg(&ath_file)
+        .arg("-o")
+        .arg(&output_file)
+        .arg("--stats")
+        .arg("--format")
+        .arg("json")
+        .assert()
+        .success();
+
+    let stats: serde_json::Value =
+        serde_json::from_slice(&assert.get_output().stdout).expect("stdout should be only the JSON report");
+    assert_eq!(stats["services"], 1);
+    assert_eq!(stats["per_service"][0]["ports"], 1);
+    assert_eq!(
+        stats["output_bytes"].as_u64().unwrap(),
+        fs::metadata(&output_file).unwrap().len()
+    );
+    assert!(stats["timings"]["parse_ms"].is_number());
+}
+
+#[test]
+fn test_cli_build_merges_files_in_order() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let base = create_test_ath_file(
+        &temp_dir,
+        "base.ath",
+        "DEPLOYMENT-ID MERGE_TEST\nSERVICES SECTION\nSERVICE web\nIMAGE-ID nginx:alpine\nPORT-MAPPING 8080 TO 80\nEND SERVICE",
+    );
+    let prod = create_test_ath_file(
+        &temp_dir,
+        "prod.ath",
+        "SERVICES SECTION\nSERVICE web\nIMAGE-ID nginx:1.27\nPORT-MAPPING 8443 TO 443\nEND SERVICE",
+    );
+    let output_file = temp_dir.path().join("docker-compose.yml");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build")
+        .arg(&base)
+        .arg(&prod)
+        .arg("-o")
+        .arg(&output_file)
+        .assert()
+        .success();
+
+    let output = fs::read_to_string(&output_file).expect("Output should exist");
+    assert!(output.contains("image: nginx:1.27"));
+    assert!(output.contains("8080:80"));
+    assert!(output.contains("8443:443"));
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("verify").arg(&output_file).assert().success();
+
+    fs::write(&prod, "SERVICES SECTION\nSERVICE web\nIMAGE-ID nginx:1.28\nEND SERVICE").unwrap();
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("verify")
+        .arg(&output_file)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("is stale"));
+}
+
+#[test]
+fn test_cli_limits_from_config_and_flags() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    create_test_ath_file(
+        &temp_dir,
+        "stack.ath",
+        "SERVICES SECTION\nSERVICE web\nIMAGE-ID nginx:alpine\nEND SERVICE\nSERVICE api\nIMAGE-ID node:20\nEND SERVICE",
+    );
+    fs::write(temp_dir.path().join("athena.toml"), "[limits]\nservices = 1\n").unwrap();
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .arg("validate")
+        .arg("stack.ath")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("2 services exceed the limit of 1"));
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .args(["build", "stack.ath", "--limit", "services=2"])
+        .assert()
+        .success();
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .args(["build", "stack.ath", "--no-limits", "--limit", "output_bytes=10"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .args(["build", "stack.ath", "--limit", "services=2,output_bytes=10"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("(output_bytes)"));
+}
+
+#[test]
+fn test_cli_fail_on_warnings_and_ignore_comments() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    create_test_ath_file(
+        &temp_dir,
+        "stack.ath",
+        "SERVICES SECTION\nSERVICE WebApp\nIMAGE-ID nginx:alpine\nEND SERVICE",
+    );
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .args(["validate", "stack.ath"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("[ATH001] Service 'WebApp'"));
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .args(["build", "stack.ath", "--fail-on-warnings"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("1 warning(s) treated as errors"));
+    assert!(!temp_dir.path().join("docker-compose.yml").exists());
+
+    create_test_ath_file(
+        &temp_dir,
+        "stack.ath",
+        "SERVICES SECTION\n// athena:ignore ATH001\nSERVICE WebApp\nIMAGE-ID nginx:alpine\nEND SERVICE",
+    );
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .args(["validate", "stack.ath", "--fail-on-warnings", "--report-suppressed"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Suppressed at 2: [ATH001]"));
+}
+
+#[test]
+fn test_cli_validate_summary_table() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    create_test_ath_file(
+        &temp_dir,
+        "stack.ath",
+        "SERVICES SECTION\nSERVICE WebApp\nIMAGE-ID nginx:alpine\nEND SERVICE\nSERVICE AdminPanel\nIMAGE-ID adminer\nEND SERVICE",
+    );
+
+    // Piped stdout gets plain ASCII
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .args(["validate", "stack.ath"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("| Rule   | Severity | Count |"))
+        .stdout(predicate::str::contains("| ATH001 | warning  | 2     |"))
+        .stdout(predicate::str::contains("\x1b[").not());
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .args(["--color", "always", "validate", "stack.ath", "--fail-on-warnings"])
+        .assert()
+        .code(4)
+        .stdout(predicate::str::contains("│ ATH001 │ \x1b[1;31merror   \x1b[0m │ 2     │"));
+}
+
+#[test]
+fn test_cli_explain_warning_code() {
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.args(["explain", "ath005"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("ATH005: literal secret in ENV-VARIABLE"))
+        .stdout(predicate::str::contains("ENV-VARIABLE {{DB_PASSWORD}}"));
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.args(["explain", "ATH999"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("Unknown warning code 'ATH999'"));
+}
+
+#[test]
+fn test_cli_build_environments() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    create_test_ath_file(
+        &temp_dir,
+        "stack.ath",
+        r#"SERVICES SECTION
+SERVICE api
+IMAGE-ID "shop/api:latest"
+END SERVICE
+
+ENVIRONMENT "staging"
+SERVICE api
+IMAGE-ID "shop/api:rc"
+END SERVICE
+END ENVIRONMENT
+
+ENVIRONMENT "prod"
+SERVICE api
+REPLICAS 3
+END SERVICE
+END ENVIRONMENT"#,
+    );
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .args(["build", "stack.ath", "--env", "staging", "-o", "staging.yml"])
+        .assert()
+        .success();
+    let staging = fs::read_to_string(temp_dir.path().join("staging.yml")).unwrap();
+    assert!(staging.contains("shop/api:rc"));
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .args(["build", "stack.ath", "--all-envs", "-o", "out"])
+        .assert()
+        .success();
+    let prod = fs::read_to_string(temp_dir.path().join("out/prod/docker-compose.yml")).unwrap();
+    assert!(prod.contains("replicas: 3"));
+    assert!(prod.contains("shop/api:latest"));
+    assert!(temp_dir.path().join("out/staging/docker-compose.yml").exists());
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .args(["info", "--environments", "stack.ath"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("staging (overrides: api)"))
+        .stdout(predicate::str::contains("prod (overrides: api)"));
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .args(["build", "stack.ath", "--env", "qa"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("Declared environments: staging, prod"));
+}
+
+/// A stand-in for the docker CLI that knows a single local image
+#[cfg(unix)]
+fn fake_docker(temp_dir: &TempDir, script: &str) -> std::path::PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = temp_dir.path().join("docker");
+    fs::write(&path, format!("#!/bin/sh\n{script}\n")).expect("Failed to write fake docker");
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).expect("Failed to chmod fake docker");
+    path
+}
+
+#[cfg(unix)]
+const DOCKER_WITH_NGINX: &str = r#"case "$1" in
+  version) echo 27.0.0 ;;
+  image) [ "$5" = "nginx:alpine" ] && echo linux/amd64 && exit 0
+         echo "Error: No such image: $5" >&2; exit 1 ;;
+esac"#;
+
+#[cfg(unix)]
+#[test]
+fn test_cli_build_require_images_exist() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let docker = fake_docker(&temp_dir, DOCKER_WITH_NGINX);
+    create_test_ath_file(
+        &temp_dir,
+        "stack.ath",
+        "SERVICES SECTION\nSERVICE web\nIMAGE-ID nginx:alpine\nEND SERVICE\n\
+         SERVICE db\nIMAGE-ID postgres:16\nEND SERVICE\nSERVICE replica\nIMAGE-ID postgres:16\nEND SERVICE\n\
+         SERVICE api\nIMAGE-ID node:20\nBUILD-ARGS NODE_ENV=\"production\"\nEND SERVICE",
+    );
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .env("ATHENA_DOCKER", &docker)
+        .args(["build", "stack.ath", "--require-images-exist"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("1 image(s) missing from the local Docker daemon"))
+        .stderr(predicate::str::contains("postgres:16 (services: db, replica)"))
+        .stderr(predicate::str::contains("node:20").not());
+    assert!(!temp_dir.path().join("docker-compose.yml").exists());
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .env("ATHENA_DOCKER", &docker)
+        .args(["build", "stack.ath", "--require-images-exist", "--platform", "linux/arm64"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("nginx:alpine for linux/arm64 (found linux/amd64) (services: web)"));
+
+    create_test_ath_file(
+        &temp_dir,
+        "web.ath",
+        "SERVICES SECTION\nSERVICE web\nIMAGE-ID nginx:alpine\nEND SERVICE",
+    );
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .env("ATHENA_DOCKER", &docker)
+        .args(["build", "web.ath", "--require-images-exist", "--platform", "linux/amd64"])
+        .assert()
+        .success();
+    assert!(temp_dir.path().join("docker-compose.yml").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_build_require_images_exist_unreachable_daemon() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    create_test_ath_file(
+        &temp_dir,
+        "web.ath",
+        "SERVICES SECTION\nSERVICE web\nIMAGE-ID nginx:alpine\nEND SERVICE",
+    );
+
+    let hanging = fake_docker(&temp_dir, "exec sleep 10");
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .env("ATHENA_DOCKER", &hanging)
+        .env("ATHENA_DOCKER_TIMEOUT", "0.2")
+        .args(["build", "web.ath", "--require-images-exist"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("could not reach the Docker daemon"))
+        .stderr(predicate::str::contains("did not answer within 0.2s"));
+
+    let missing = temp_dir.path().join("no-such-docker");
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(temp_dir.path())
+        .env("ATHENA_DOCKER", &missing)
+        .args(["build", "web.ath", "--require-images-exist"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("could not run"));
+}
+
+#[test]
+fn test_cli_add_service() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let original = "// Production stack\nSERVICES SECTION\n\nSERVICE api\nIMAGE-ID nginx:alpine // pinned by ops\nEND SERVICE\n";
+    let ath_file = create_test_ath_file(&temp_dir, "stack.ath", original);
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.args([
+        "add-service", &ath_file, "--name", "worker", "--image", "myorg/worker:1.2",
+        "--env", "FOO=bar", "--port", "9090:9090", "--depends-on", "api",
+    ])
+    .assert()
+    .success()
+    .stdout(predicate::str::contains("Added service 'worker'"));
+
+    let edited = fs::read_to_string(&ath_file).expect("Failed to read edited file");
+    assert!(edited.starts_with(original.trim_end()));
+    assert!(edited.ends_with(
+        "END SERVICE\n\nSERVICE worker\nIMAGE-ID \"myorg/worker:1.2\"\nPORT-MAPPING 9090 TO 9090\nENV-VARIABLE \"FOO=bar\"\nDEPENDS-ON api\nEND SERVICE\n"
+    ));
+
+    // The file is left alone when the name is taken
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.args(["add-service", &ath_file, "--name", "worker", "--image", "other"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("Service 'worker' already exists"));
+    assert_eq!(fs::read_to_string(&ath_file).expect("Failed to read file"), edited);
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.args(["add-service", "-", "--name", "cache", "--image", "redis:7"])
+        .write_stdin(original)
+        .assert()
+        .success()
+        .stdout(predicate::str::ends_with("END SERVICE\n\nSERVICE cache\nIMAGE-ID \"redis:7\"\nEND SERVICE\n"));
+}
+
+#[test]
+fn test_cli_scripts_generates_volume_backups() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "stack.ath",
+        "DEPLOYMENT-ID SHOP\nENVIRONMENT SECTION\nVOLUME pg_data\nSERVICES SECTION\nSERVICE db\nIMAGE-ID postgres:16\nVOLUME-MAPPING \"pg_data\" TO \"/var/lib/postgresql/data\"\nEND SERVICE",
+    );
+    let scripts_dir = temp_dir.path().join("scripts");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("scripts")
+        .arg(&ath_file)
+        .arg("-o")
+        .arg(&scripts_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("backup.sh"))
+        .stdout(predicate::str::contains("restore.sh"));
+
+    for name in ["backup.sh", "restore.sh"] {
+        let path = scripts_dir.join(name);
+        let script = fs::read_to_string(&path).expect("Failed to read script");
+        assert!(script.contains("#   shop_pg_data: db"));
+
+        // Syntax check with the system sh, which need not be bash
+        let status = std::process::Command::new("sh").arg("-n").arg(&path).status();
+        assert!(status.is_ok_and(|s| s.success()), "{name} is not valid sh");
+    }
+}
+
+#[test]
+fn test_cli_build_directory_continues_past_failures() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let stacks = temp_dir.path().join("stacks");
+    fs::create_dir_all(stacks.join("nested")).expect("Failed to create stacks directory");
+    for (name, image) in [("api", "myorg/api"), ("web", "nginx:alpine"), ("nested/extra", "redis")] {
+        let content = format!("SERVICES SECTION\nSERVICE {}\nIMAGE-ID {image}\nEND SERVICE", name.replace('/', "_"));
+        fs::write(stacks.join(format!("{name}.ath")), content).expect("Failed to write stack");
+    }
+    fs::write(stacks.join("broken.ath"), "SERVICES SECTION\nSERVICE bad\nIMAGE-ID nginx\n")
+        .expect("Failed to write stack");
+    let build_dir = temp_dir.path().join("build");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build").arg(&stacks).arg("-o").arg(&build_dir).arg("--jobs").arg("2");
+    cmd.assert()
+        .failure()
+        .stdout(predicate::str::contains("failed"))
+        .stderr(predicate::str::contains("1 of 3 file(s) failed to build"));
+
+    assert!(build_dir.join("api.yml").exists());
+    assert!(build_dir.join("web.yml").exists());
+    assert!(!build_dir.join("broken.yml").exists());
+    // Subdirectories need --recursive
+    assert!(!build_dir.join("extra.yml").exists());
+}
+
+#[test]
+fn test_cli_build_directory_detects_output_collisions() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let stacks = temp_dir.path().join("stacks");
+    for dir in ["team-a", "team-b"] {
+        fs::create_dir_all(stacks.join(dir)).expect("Failed to create team directory");
+        fs::write(
+            stacks.join(dir).join("api.ath"),
+            "SERVICES SECTION\nSERVICE api\nIMAGE-ID myorg/api\nEND SERVICE",
+        )
+        .expect("Failed to write stack");
+    }
+    let build_dir = temp_dir.path().join("build");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build").arg(&stacks).arg("--recursive").arg("-o").arg(&build_dir);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Several inputs would be written to the same output"))
+        .stderr(predicate::str::contains("api.yml"));
+
+    assert!(!build_dir.exists(), "Nothing should be built after a collision");
+}
+
+#[test]
+fn test_cli_build_only_selected_services() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "diamond.ath",
+        r#"SERVICES SECTION
+SERVICE db
+IMAGE-ID postgres:16
+END SERVICE
+SERVICE auth
+IMAGE-ID myorg/auth
+DEPENDS-ON db
+END SERVICE
+SERVICE cache
+IMAGE-ID redis:7
+DEPENDS-ON db
+END SERVICE
+SERVICE api
+IMAGE-ID myorg/api
+DEPENDS-ON auth
+DEPENDS-ON cache
+END SERVICE
+SERVICE worker
+IMAGE-ID myorg/worker
+END SERVICE"#,
+    );
+    let output_file = temp_dir.path().join("partial.yml");
+
+    let build = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+        cmd.arg("build").arg(&ath_file).arg("-o").arg(&output_file).args(args);
+        let assert = cmd.assert().success();
+        let content = fs::read_to_string(&output_file).expect("Failed to read output file");
+        let compose: serde_yaml::Value = serde_yaml::from_str(&content).expect("Invalid YAML");
+        let services: Vec<String> = compose["services"]
+            .as_mapping()
+            .expect("services should be a mapping")
+            .keys()
+            .map(|key| key.as_str().unwrap().to_string())
+            .collect();
+        (assert, services, compose)
+    };
+
+    let (_, services, _) = build(&["--only", "api"]);
+    assert_eq!(services, ["db", "auth", "cache", "api"]);
+
+    let (assert, services, compose) = build(&["--only", "api,auth", "--no-deps"]);
+    assert.stderr(predicate::str::contains("Service 'api' depends on 'cache', which --no-deps leaves out"));
+    assert_eq!(services, ["auth", "api"]);
+    assert!(compose["services"]["auth"]["depends_on"].is_null());
+    let api_deps = serde_yaml::to_string(&compose["services"]["api"]["depends_on"]).unwrap();
+    assert!(api_deps.contains("auth") && !api_deps.contains("cache"), "{api_deps}");
+}
+
+#[test]
+fn test_cli_chdir_and_paths_relative_to_the_ath_file() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("app/config")).expect("Failed to create directories");
+    fs::write(
+        root.join("app/stack.ath"),
+        r#"SERVICES SECTION
+SERVICE api
+BUILD-ARGS VERSION="1.0"
+VOLUME-MAPPING "./config" TO "/etc/api"
+END SERVICE"#,
+    )
+    .unwrap();
+    fs::write(root.join("app/Dockerfile"), "FROM alpine\nARG VERSION\n").unwrap();
+
+    let athena = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+        cmd.current_dir(root).args(args).assert()
+    };
+
+    // -C makes CLI paths, the default output included, relative to app/
+    athena(&["-C", "app", "build", "stack.ath", "--check-bind-mounts"])
+        .success()
+        .stderr(predicate::str::contains("does not exist").not());
+    assert!(root.join("app/docker-compose.yml").exists());
+    assert!(!root.join("docker-compose.yml").exists());
+
+    // Without it, CLI paths are relative to the current directory, while the
+    // bind mount and the Dockerfile are still found next to the .ath file
+    athena(&["build", "app/stack.ath", "--check-bind-mounts", "-o", "out.yml"])
+        .success()
+        .stderr(predicate::str::contains("does not exist").not());
+    assert!(root.join("out.yml").exists());
+
+    fs::write(root.join("app/Dockerfile"), "FROM alpine\n").unwrap();
+    athena(&["build", "app/stack.ath", "-o", "out.yml"])
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("BUILD-ARG 'VERSION' not found in Dockerfile"));
+
+    athena(&["-C", "missing", "validate"])
+        .failure()
+        .code(5)
+        .stderr(predicate::str::contains("Cannot change to directory missing"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_build_post_process_hooks() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let root = temp_dir.path();
+    create_test_ath_file(
+        &temp_dir,
+        "stack.ath",
+        "SERVICES SECTION\nSERVICE api\nIMAGE-ID myorg/api:1.0\nEND SERVICE",
+    );
+    let mirror = root.join("mirror.sh");
+    fs::write(&mirror, "#!/bin/sh\nsed 's#image: myorg/#image: mirror.local/myorg/#'\n").unwrap();
+    fs::set_permissions(&mirror, fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(root.join("athena.toml"), "[[postprocess]]\ncommand = \"./mirror.sh\"\n").unwrap();
+
+    let athena = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+        cmd.current_dir(root).args(args).assert()
+    };
+    let output = || fs::read_to_string(root.join("docker-compose.yml")).expect("Output should exist");
+
+    athena(&["build", "stack.ath", "--post-process", "sed 's/^services:/x-team: payments\\nservices:/'"]).success();
+    assert!(output().contains("image: mirror.local/myorg/api:1.0"), "{}", output());
+    assert!(output().contains("x-team: payments\nservices:"), "{}", output());
+    athena(&["verify", "docker-compose.yml"]).success();
+
+    athena(&["build", "stack.ath", "--no-post-process"]).success();
+    assert!(output().contains("image: myorg/api:1.0"), "{}", output());
+
+    // Neither a failing hook nor a broken file replaces the last good output
+    athena(&["build", "stack.ath", "--post-process", "echo 'mirror is down' >&2; exit 2"])
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains("mirror is down"));
+    athena(&["build", "stack.ath", "--post-process", "sed 's/^    image: .*/&\\n    depends_on: [ghost]/'"])
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("depends on undefined service 'ghost'"));
+    assert!(output().contains("image: myorg/api:1.0"), "{}", output());
+
+    athena(&["build", "stack.ath", "--target", "terraform-docker"])
+        .failure()
+        .stderr(predicate::str::contains("--no-post-process"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_cli_examples_pass_the_post_process_self_check() {
+    let examples = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    for entry in fs::read_dir(&examples).expect("Failed to read examples directory") {
+        let path = entry.expect("Failed to read directory entry").path();
+        if path.extension().is_none_or(|ext| ext != "ath") {
+            continue;
+        }
+        let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+        cmd.arg("build")
+            .arg(&path)
+            .arg("-o")
+            .arg(temp_dir.path().join("docker-compose.yml"))
+            .args(["--post-process", "cat"])
+            .assert()
+            .success();
+    }
+}
+
+#[test]
+fn test_cli_build_into_another_directory_keeps_relative_paths() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("sub/html")).expect("Failed to create directories");
+    fs::create_dir_all(root.join("out")).expect("Failed to create directories");
+    fs::write(
+        root.join("sub/st.ath"),
+        r#"SERVICES SECTION
+SERVICE web
+BUILD-ARGS VERSION="1.0"
+VOLUME-MAPPING "./html" TO "/usr/share/nginx/html" (ro)
+VOLUME-MAPPING "site_data" TO "/data"
+END SERVICE"#,
+    )
+    .unwrap();
+    fs::write(root.join("sub/Dockerfile"), "FROM nginx\nARG VERSION\n").unwrap();
+
+    let build = |output: &str| -> serde_yaml::Value {
+        let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+        cmd.current_dir(root)
+            .args(["build", "sub/st.ath", "-o", output, "--check-bind-mounts"])
+            .assert()
+            .success()
+            .stderr(predicate::str::contains("does not exist").not());
+        serde_yaml::from_str(&fs::read_to_string(root.join(output)).unwrap()).unwrap()
+    };
+
+    // Compose resolves both against out/, so they have to climb back to sub/
+    let web = &build("out/c.yml")["services"]["web"];
+    assert_eq!(web["volumes"][0], "../sub/html:/usr/share/nginx/html:ro");
+    assert_eq!(web["volumes"][1], "site_data:/data");
+    assert_eq!(web["build"]["context"], "../sub");
+
+    let web = &build("c.yml")["services"]["web"];
+    assert_eq!(web["volumes"][0], "./sub/html:/usr/share/nginx/html:ro");
+    assert_eq!(web["build"]["context"], "./sub");
+
+    let web = &build("sub/c.yml")["services"]["web"];
+    assert_eq!(web["volumes"][0], "./html:/usr/share/nginx/html:ro");
+    assert_eq!(web["build"]["context"], ".");
+}
+
+#[test]
+fn test_cli_report_inventories_the_stack() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "stack.ath",
+        r#"DEPLOYMENT-ID shop
+SERVICES SECTION
+SERVICE web
+IMAGE-ID nginx:1.27
+PORT-MAPPING 8080 TO 80
+VOLUME-MAPPING "./site" TO "/usr/share/nginx/html" (ro)
+END SERVICE
+
+ENVIRONMENT "prod"
+SERVICE web
+PORT-MAPPING 443 TO 443
+END SERVICE
+END ENVIRONMENT"#,
+    );
+    let report_file = temp_dir.path().join("inventory.md");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("report").arg(&ath_file).arg("-o").arg(&report_file).assert().success();
+    let markdown = fs::read_to_string(&report_file).expect("Report should exist");
+    assert!(markdown.contains("| web | nginx:1.27 | 1.27 |  |"), "{markdown}");
+    assert!(markdown.contains("| 8080 | 80 | tcp | web |"), "{markdown}");
+    assert!(markdown.contains("| ./site | /usr/share/nginx/html | ro | web |"), "{markdown}");
+    assert!(markdown.contains("## Privileged access\n\nNone.\n"), "{markdown}");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    let output = cmd
+        .arg("report")
+        .arg(&ath_file)
+        .args(["--env", "prod", "--format", "json"])
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).expect("Report should be JSON");
+    let ports: Vec<&serde_json::Value> = json["ports"].as_array().unwrap().iter().map(|p| &p["host_port"]).collect();
+    assert_eq!(ports, [443, 8080]);
+    assert_eq!(json["images"][0]["service"], "web");
+}
+
+#[test]
+fn test_cli_env_shows_resolved_values_and_layers() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let base = create_test_ath_file(
+        &temp_dir,
+        "base.ath",
+        r#"SERVICES SECTION
+SERVICE api
+IMAGE-ID myorg/api
+ENV-VARIABLE "LOG_LEVEL=info"
+ENV-VARIABLE {{API_TOKEN}}
+END SERVICE
+
+ENVIRONMENT "prod"
+SERVICE api
+ENV-VARIABLE "LOG_LEVEL=error"
+END SERVICE
+END ENVIRONMENT"#,
+    );
+    let overlay = create_test_ath_file(
+        &temp_dir,
+        "local.ath",
+        "SERVICES SECTION\nSERVICE api\nENV-VARIABLE \"LOG_LEVEL=debug\"\nEND SERVICE",
+    );
+
+    let env = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+        cmd.arg("env").arg(&base).arg(&overlay).arg("--service").arg("api").args(args);
+        cmd.assert()
+    };
+
+    env(&[]).success().stdout("LOG_LEVEL=debug\nAPI_TOKEN=${API_TOKEN}\n");
+    env(&["--env", "prod"]).success().stdout(predicate::str::starts_with("LOG_LEVEL=error\n"));
+
+    let explained = env(&["--env", "prod", "--explain", "LOG_LEVEL"]).success();
+    let stdout = String::from_utf8(explained.get_output().stdout.clone()).unwrap();
+    let rows: Vec<&str> = stdout.lines().filter(|line| line.contains("LOG_LEVEL") || line.contains(".ath:")).collect();
+    assert_eq!(rows.len(), 4, "{stdout}");
+    assert!(rows[1].contains("base.ath:4") && rows[1].contains(text(Message::EnvOverridden)), "{stdout}");
+    assert!(rows[2].contains("local.ath:3") && rows[2].contains("debug"), "{stdout}");
+    assert!(rows[3].contains("ENVIRONMENT \"prod\"") && rows[3].contains("base.ath:10"), "{stdout}");
+    assert!(rows[3].contains(text(Message::EnvWins)), "{stdout}");
+
+    env(&["--explain", "LOGLEVEL"])
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("Variables of 'api': LOG_LEVEL, API_TOKEN"));
+}
+
+#[test]
+fn test_cli_test_checks_every_file_and_writes_junit() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("stacks/generated")).expect("Failed to create directories");
+    fs::write(root.join(".gitignore"), "generated/\n").expect("Failed to write .gitignore");
+    let valid = "SERVICES SECTION\nSERVICE api\nIMAGE-ID myorg/api\nEND SERVICE";
+    create_test_ath_file(&temp_dir, "app.ath", valid);
+    create_test_ath_file(&temp_dir, "stacks/broken.ath", "SERVICES SECTION\nSERVICE api\nIMAGE-ID myorg/api\nDEPENDS-ON ghost\nEND SERVICE");
+    create_test_ath_file(&temp_dir, "stacks/noisy.ath", "SERVICES SECTION\nSERVICE Api\nIMAGE-ID myorg/api\nEND SERVICE");
+    create_test_ath_file(&temp_dir, "stacks/generated/ignored.ath", "not an athena file");
+    let report = root.join("report.xml");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(root).arg("test").arg("--report").arg(format!("junit:{}", report.display()));
+    cmd.assert()
+        .failure()
+        .code(4)
+        .stdout(predicate::str::contains("stacks/broken.ath\n  error: "))
+        .stdout(predicate::str::contains("ignored.ath").not())
+        .stderr(predicate::str::contains("1 of 3 file(s) failed the checks"));
+
+    let xml = fs::read_to_string(&report).expect("Failed to read report");
+    assert!(xml.contains(r#"<testsuites name="athena" tests="3" failures="1""#), "{xml}");
+    assert!(xml.contains(r#"name="app.ath""#), "{xml}");
+    assert!(xml.contains(r#"<failure type="error" message="#), "{xml}");
+    assert!(xml.contains("<system-out>[ATH001]"), "{xml}");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.current_dir(root).arg("test").arg("app.ath").arg("stacks/noisy.ath").arg("--strict");
+    cmd.assert()
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("1 of 2 file(s) failed the checks (--strict counts warnings)"));
+}
+
+#[test]
+fn test_cli_build_stack_prefix() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let root = temp_dir.path();
+    create_test_ath_file(
+        &temp_dir,
+        "stack.ath",
+        r#"DEPLOYMENT-ID shop
+ENVIRONMENT SECTION
+NETWORK-NAME backend
+VOLUME pg_data
+
+SERVICES SECTION
+SERVICE db
+IMAGE-ID postgres:16
+CONTAINER-NAME "shop-db"
+VOLUME-MAPPING "pg_data" TO "/var/lib/postgresql/data"
+END SERVICE
+
+SERVICE api
+IMAGE-ID myorg/api:1.0
+ENV-VARIABLE "DATA_VOLUME={{stack_prefix}}-pg_data"
+DEPENDS-ON db
+END SERVICE"#,
+    );
+
+    let athena = |args: &[&str]| {
+        let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+        cmd.current_dir(root).args(args).assert()
+    };
+
+    athena(&["build", "stack.ath", "--stack-prefix", "feature123", "-o", "feature.yml"]).success();
+    let output = fs::read_to_string(root.join("feature.yml")).expect("Output should exist");
+    for expected in [
+        "\nname: feature123-shop",
+        "container_name: feature123-shop-db",
+        "- feature123-pg_data:/var/lib/postgresql/data",
+        "DATA_VOLUME=feature123-pg_data",
+        "\n  feature123-backend:\n",
+        "\n  feature123-pg_data:\n",
+        "\n  api:\n",
+    ] {
+        assert!(output.contains(expected), "missing {expected:?} in:\n{output}");
+    }
+    athena(&["verify", "feature.yml"]).success();
+
+    athena(&["build", "stack.ath", "-o", "plain.yml"])
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains("Service 'api' uses {{stack_prefix}}"))
+        .stderr(predicate::str::contains("--stack-prefix"));
+    assert!(!root.join("plain.yml").exists());
+
+    athena(&["build", "stack.ath", "--stack-prefix", "feature/123"])
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("may only contain letters, digits"));
+}
@@ -0,0 +1,53 @@
+use assert_cmd::Command;
+use std::fs;
+use tempfile::TempDir;
+
+const INVALID_ATH: &str = r#"SERVICES SECTION
+SERVICE web
+IMAGE-ID nginx
+REPLICAS 0
+END SERVICE
+"#;
+
+const EXPECTED_MESSAGE: &str = " Parse error at line 4, column 10: Invalid replicas number
+
+Suggestion: Replicas must be at least 1. To run a service on every node, use DEPLOY-MODE global instead
+Check the syntax of your .ath file. Use 'athena info --examples' for syntax examples.
+";
+
+fn validate_stderr(args: &[&str], no_color: bool) -> String {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = temp_dir.path().join("invalid.ath");
+    fs::write(&ath_file, INVALID_ATH).expect("Failed to create test file");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("validate").arg(&ath_file).args(args);
+    if no_color {
+        cmd.env("NO_COLOR", "1");
+    } else {
+        cmd.env_remove("NO_COLOR");
+    }
+
+    let output = cmd.output().expect("Failed to run athena");
+    assert!(!output.status.success(), "Validation should fail");
+    String::from_utf8(output.stderr).expect("stderr should be UTF-8")
+}
+
+#[test]
+fn test_error_output_without_color() {
+    let expected = format!("Error:{EXPECTED_MESSAGE}");
+
+    assert_eq!(validate_stderr(&["--color", "never"], false), expected);
+    // Piped stderr is not a terminal, so auto mode stays plain
+    assert_eq!(validate_stderr(&[], false), expected);
+    assert_eq!(validate_stderr(&["--color", "auto"], true), expected);
+}
+
+#[test]
+fn test_error_output_with_color() {
+    let expected = format!("\x1b[1;31mError:\x1b[0m{EXPECTED_MESSAGE}");
+
+    assert_eq!(validate_stderr(&["--color", "always"], false), expected);
+    // An explicit --color always wins over NO_COLOR
+    assert_eq!(validate_stderr(&["--color", "always"], true), expected);
+}
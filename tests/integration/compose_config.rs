@@ -0,0 +1,149 @@
+//! Optional end-to-end checks of generated files with the real docker CLI.
+//!
+//! Off by default so CI without Docker still passes:
+//! - `ATHENA_COMPOSE_CHECK=1` runs `docker compose -f <file> config --quiet` on
+//!   every structural test's output
+//! - `ATHENA_STACK_CHECK=1` runs `docker stack config --compose-file <file>` on
+//!   the Swarm fixtures, which applies the stricter parser of `docker stack
+//!   deploy` without needing a swarm. CLIs older than `docker stack config`
+//!   fall back to `docker compose convert` as a weaker proxy
+//!
+//! `ATHENA_DOCKER` overrides the docker binary found on PATH. When a check is
+//! enabled but the docker command it needs can't be found, the tests print why
+//! once and skip the check instead of failing.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+const ENABLE_VAR: &str = "ATHENA_COMPOSE_CHECK";
+const STACK_ENABLE_VAR: &str = "ATHENA_STACK_CHECK";
+const DOCKER_VAR: &str = "ATHENA_DOCKER";
+
+/// Docker arguments before and after the file being checked
+type CheckCommand = (&'static [&'static str], &'static [&'static str]);
+
+const COMPOSE_CONFIG: CheckCommand = (&["compose", "-f"], &["config", "--quiet"]);
+const STACK_CONFIG: CheckCommand = (&["stack", "config", "--compose-file"], &[]);
+const COMPOSE_CONVERT: CheckCommand = (&["compose", "-f"], &["convert", "--quiet"]);
+
+/// Decided once per test binary: the docker binary to use, or None to skip
+fn docker() -> Option<&'static Path> {
+    static DOCKER: OnceLock<Option<PathBuf>> = OnceLock::new();
+
+    DOCKER
+        .get_or_init(|| {
+            let docker = enabled_docker(ENABLE_VAR, "compose config checks")?;
+            if !succeeds(&docker, &["compose", "version"]) {
+                eprintln!(
+                    "{ENABLE_VAR} is set but '{} compose' is unavailable; skipping compose config checks",
+                    docker.display()
+                );
+                return None;
+            }
+            Some(docker)
+        })
+        .as_deref()
+}
+
+/// Decided once per test binary: the docker binary and the command validating
+/// a stack file with it, or None to skip
+fn stack_validator() -> Option<&'static (PathBuf, CheckCommand)> {
+    static VALIDATOR: OnceLock<Option<(PathBuf, CheckCommand)>> = OnceLock::new();
+
+    VALIDATOR
+        .get_or_init(|| {
+            let docker = enabled_docker(STACK_ENABLE_VAR, "stack config checks")?;
+            if succeeds(&docker, &["stack", "config", "--help"]) {
+                return Some((docker, STACK_CONFIG));
+            }
+            if succeeds(&docker, &["compose", "version"]) {
+                eprintln!(
+                    "'{} stack config' is unavailable; checking Swarm output with 'docker compose convert' instead",
+                    docker.display()
+                );
+                return Some((docker, COMPOSE_CONVERT));
+            }
+            eprintln!(
+                "{STACK_ENABLE_VAR} is set but neither '{0} stack config' nor '{0} compose' is available; skipping stack config checks",
+                docker.display()
+            );
+            None
+        })
+        .as_ref()
+}
+
+/// The docker binary when `enable_var` turns a check on and one can be found
+fn enabled_docker(enable_var: &str, checks: &str) -> Option<PathBuf> {
+    if env::var(enable_var).map_or(true, |v| v.is_empty() || v == "0") {
+        return None;
+    }
+
+    let docker = find_docker();
+    if docker.is_none() {
+        eprintln!("{enable_var} is set but no docker binary was found; skipping {checks}");
+    }
+    docker
+}
+
+fn succeeds(docker: &Path, args: &[&str]) -> bool {
+    Command::new(docker)
+        .args(args)
+        .output()
+        .is_ok_and(|output| output.status.success())
+}
+
+fn find_docker() -> Option<PathBuf> {
+    if let Some(docker) = env::var_os(DOCKER_VAR) {
+        return Some(PathBuf::from(docker));
+    }
+
+    let name = if cfg!(windows) { "docker.exe" } else { "docker" };
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Run `docker <before> <file> <after>`, returning its stderr as the error
+/// when docker rejects the file
+fn run_check(docker: &Path, (before, after): CheckCommand, file: &Path) -> Result<(), String> {
+    let output = Command::new(docker)
+        .args(before)
+        .arg(file)
+        .args(after)
+        .output()
+        .map_err(|e| format!("Failed to run {}: {e}", docker.display()))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        // e.g. "compose config" or "stack config", without the flags
+        let subcommand: Vec<&str> = before.iter().chain(after).copied().filter(|arg| !arg.starts_with('-')).collect();
+        Err(format!(
+            "docker {} rejected {}:\n{}",
+            subcommand.join(" "),
+            file.display(),
+            String::from_utf8_lossy(&output.stderr)
+        ))
+    }
+}
+
+/// Run `docker compose config --quiet` on a generated file when the check is enabled.
+/// Returns the command's stderr as the error when Compose rejects the file.
+pub fn check_compose_config(compose_file: &Path) -> Result<(), String> {
+    match docker() {
+        Some(docker) => run_check(docker, COMPOSE_CONFIG, compose_file),
+        None => Ok(()),
+    }
+}
+
+/// Validate a generated Swarm file the way `docker stack deploy` parses it,
+/// when ATHENA_STACK_CHECK is enabled. Returns docker's stderr as the error
+/// when the file is rejected.
+pub fn check_stack_config(compose_file: &Path) -> Result<(), String> {
+    match stack_validator() {
+        Some((docker, command)) => run_check(docker, *command, compose_file),
+        None => Ok(()),
+    }
+}
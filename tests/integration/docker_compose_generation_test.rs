@@ -497,4 +497,70 @@ END SERVICE"#;
     
     assert!(port1_str.contains("8080"), "Service1 should use port 8080");
     assert!(port2_str.contains("8081"), "Service2 should use port 8081");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_network_mode_omits_networks() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_content = r#"DEPLOYMENT-ID NETWORK_MODE_TEST
+
+SERVICES SECTION
+
+SERVICE vpn
+IMAGE-ID qmcgaw/gluetun:latest
+PORT-MAPPING 8080 TO 8080
+END SERVICE
+
+SERVICE downloader
+IMAGE-ID alpine:latest
+NETWORK-MODE service:vpn
+END SERVICE
+
+SERVICE profiler
+IMAGE-ID alpine:latest
+NETWORK-MODE host
+PID host
+IPC shareable
+END SERVICE"#;
+
+    let ath_file = create_test_ath_file(&temp_dir, "network_mode.ath", ath_content);
+    let output_file = temp_dir.path().join("docker-compose.yml").to_string_lossy().to_string();
+
+    let yaml_content = run_athena_build(&ath_file, &output_file)
+        .expect("Build should succeed with network modes");
+    let parsed: Value = parse_yaml_safely(&yaml_content).expect("Generated YAML should be valid");
+
+    let downloader = &parsed["services"]["downloader"];
+    assert_eq!(downloader["network_mode"], "service:vpn");
+    assert!(downloader["networks"].is_null());
+
+    let profiler = &parsed["services"]["profiler"];
+    assert_eq!(profiler["network_mode"], "host");
+    assert_eq!(profiler["pid"], "host");
+    assert_eq!(profiler["ipc"], "shareable");
+    assert!(parsed["services"]["vpn"]["networks"].is_sequence());
+}
+
+#[test]
+fn test_network_mode_service_with_ports_is_rejected() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_content = r#"DEPLOYMENT-ID NETWORK_MODE_PORTS_TEST
+
+SERVICES SECTION
+
+SERVICE vpn
+IMAGE-ID qmcgaw/gluetun:latest
+END SERVICE
+
+SERVICE downloader
+IMAGE-ID alpine:latest
+NETWORK-MODE service:vpn
+PORT-MAPPING 8080 TO 8080
+END SERVICE"#;
+
+    let ath_file = create_test_ath_file(&temp_dir, "network_mode_ports.ath", ath_content);
+    let output_file = temp_dir.path().join("docker-compose.yml").to_string_lossy().to_string();
+
+    let error = run_athena_build(&ath_file, &output_file).unwrap_err().to_string();
+    assert!(error.contains("NETWORK-MODE service:vpn gives it no network of its own"));
+}
@@ -173,7 +173,7 @@ END SERVICE"#;
     let ath_file = create_test_ath_file(&temp_dir, "no_conflicts.ath", no_conflicts_content);
     
     let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
-    cmd.arg("build").arg(&ath_file);
+    cmd.arg("build").arg(&ath_file).arg("-o").arg(temp_dir.path().join("docker-compose.yml"));
 
     // This should succeed without any port conflicts
     cmd.assert()
@@ -205,4 +205,131 @@ END SERVICE"#;
         .stderr(predicate::str::contains("Service 'problematic_service' depends on"))
         .stderr(predicate::str::contains("which doesn't exist"))
         .stderr(predicate::str::contains("Available services:"));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_invalid_dns_ip_fails_with_location() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let content = r#"DEPLOYMENT-ID dns_typo_test
+
+SERVICES SECTION
+
+SERVICE api
+IMAGE-ID "alpine:latest"
+EXTRA-HOST "registry.internal" "10.0.0.300"
+END SERVICE"#;
+
+    let ath_file = create_test_ath_file(&temp_dir, "dns_typo.ath", content);
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("validate").arg(&ath_file);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid IP address '10.0.0.300' in EXTRA-HOST"))
+        .stderr(predicate::str::contains("line 7"));
+}
+
+#[test]
+fn test_invalid_service_name_character_reports_position() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "bad_name.ath",
+        r#"DEPLOYMENT-ID NAME_TEST
+SERVICES SECTION
+
+SERVICE api:v2
+IMAGE-ID nginx:alpine
+END SERVICE"#,
+    );
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("validate").arg(&ath_file);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid character ':' in service name 'api:v2' at position 4"))
+        .stderr(predicate::str::contains("line 4, column 12"));
+}
+
+#[test]
+fn test_conflicting_volume_options_are_rejected() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "volume_conflict.ath",
+        r#"DEPLOYMENT-ID VOLUME_CONFLICT
+SERVICES SECTION
+
+SERVICE db
+IMAGE-ID postgres:16
+VOLUME-MAPPING "pgdata" TO "/var/lib/postgresql/data" (rshared)
+END SERVICE"#,
+    );
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("validate").arg(&ath_file);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Volume option 'rshared' does not apply to named volume 'pgdata'"))
+        .stderr(predicate::str::contains("line 6"));
+}
+
+#[test]
+fn test_circular_dependency_shows_full_cycle_path() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "cycle.ath",
+        include_str!("../fixtures/circular_dependencies.ath"),
+    );
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build").arg(&ath_file);
+
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Circular dependency detected: service_a -> service_b -> service_c -> service_a",
+        ))
+        .stderr(predicate::str::contains("Affected services: service_a, service_b, service_c"));
+}
+
+#[test]
+fn test_show_start_order_groups_parallel_services() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(
+        &temp_dir,
+        "order.ath",
+        r#"DEPLOYMENT-ID ORDER_TEST
+SERVICES SECTION
+
+SERVICE web
+IMAGE-ID nginx:alpine
+DEPENDS-ON api
+END SERVICE
+
+SERVICE api
+IMAGE-ID node:20-alpine
+DEPENDS-ON db
+DEPENDS-ON cache
+END SERVICE
+
+SERVICE db
+IMAGE-ID postgres:16
+END SERVICE
+
+SERVICE cache
+IMAGE-ID redis:7
+END SERVICE"#,
+    );
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("validate").arg(&ath_file).arg("--show-start-order");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("1. cache, db\n  2. api\n  3. web"));
+}
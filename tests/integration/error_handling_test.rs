@@ -16,6 +16,7 @@ fn test_file_not_found_error() {
 
     cmd.assert()
         .failure()
+        .code(5)
         .stderr(predicate::str::contains("Error:"))
         .stderr(predicate::str::contains("Make sure the file path is correct"));
 }
@@ -34,6 +35,7 @@ fn test_invalid_syntax_error() {
 
     cmd.assert()
         .failure()
+        .code(3)
         .stderr(predicate::str::contains("Error:"))
         .stderr(predicate::str::contains("Check the syntax of your .ath file"));
 }
@@ -52,6 +54,7 @@ fn test_circular_dependency_error() {
 
     cmd.assert()
         .failure()
+        .code(4)
         .stderr(predicate::str::contains("Error:"));
         // Note: The exact error message depends on your validation logic
 }
@@ -376,6 +379,7 @@ fn test_auto_detection_with_no_ath_files() {
     // Magic mode - no arguments
     cmd.assert()
         .failure()
+        .code(1)
         .stderr(predicate::str::contains("Error:"));
 }
 
@@ -523,7 +527,7 @@ END SERVICE"#;
     let ath_file = create_test_ath_file(&temp_dir, "no_conflicts.ath", no_conflict_content);
     
     let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
-    cmd.arg("build").arg(&ath_file);
+    cmd.arg("build").arg(&ath_file).arg("-o").arg(temp_dir.path().join("docker-compose.yml"));
 
     // This should succeed without port conflicts
     cmd.assert()
@@ -559,4 +563,86 @@ END SERVICE"#;
         .stderr(predicate::str::contains("80"))
         .stderr(predicate::str::contains("web"))
         .stderr(predicate::str::contains("api"));
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_usage_error_exit_code() {
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build").arg("--no-such-flag");
+
+    cmd.assert().failure().code(2);
+}
+
+#[test]
+fn test_conflicting_options_exit_code() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(&temp_dir, "web.ath", "SERVICES SECTION\nSERVICE web\nIMAGE-ID nginx\nEND SERVICE");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build").arg(&ath_file).arg("--recursive");
+
+    cmd.assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("--recursive and --jobs only apply to a directory input"));
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build").arg(temp_dir.path()).arg("--stats");
+
+    cmd.assert()
+        .failure()
+        .code(2)
+        .stderr(predicate::str::contains("--stats builds a single stack"));
+}
+
+#[test]
+fn test_option_unsupported_by_target_exit_code() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(&temp_dir, "web.ath", "SERVICES SECTION\nSERVICE web\nIMAGE-ID nginx\nEND SERVICE");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build").arg(&ath_file).arg("--minimal").arg("--target").arg("terraform-docker");
+
+    cmd.assert()
+        .failure()
+        .code(6)
+        .stderr(predicate::str::contains("--minimal only applies to Compose output"));
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build").arg(&ath_file).arg("--annotate-sources").arg("--output-format").arg("json");
+
+    cmd.assert().failure().code(6);
+}
+
+#[test]
+fn test_info_exit_codes() {
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("info").arg("--exit-codes");
+
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("3  Parse error in a .ath file"))
+        .stdout(predicate::str::contains("5  A file could not be read or written"))
+        .stdout(predicate::str::contains("6  An option the chosen target"));
+}
+
+#[test]
+fn test_duplicate_declaration_names_its_file() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let base = create_test_ath_file(&temp_dir, "base.ath", "SERVICES SECTION\nSERVICE web\nIMAGE-ID nginx\nEND SERVICE");
+    let prod = create_test_ath_file(
+        &temp_dir,
+        "prod.ath",
+        "ENVIRONMENT SECTION\nVOLUME data\nVOLUME data\nSERVICES SECTION\nSERVICE web\nIMAGE-ID nginx:1.27\nEND SERVICE",
+    );
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build").arg(&base).arg(&prod);
+
+    cmd.assert()
+        .failure()
+        .code(4)
+        .stderr(predicate::str::contains(format!(
+            "{prod}: Volume 'data' is declared on lines 2 and 3; the one on line 3 would win"
+        )));
+}
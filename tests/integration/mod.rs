@@ -1,5 +1,7 @@
 // Integration test modules
 pub mod cli_commands_test;
+pub mod compose_config;
+pub mod color_output_test;
 pub mod docker_compose_generation_test;
 pub mod error_handling_test;
 pub mod enhanced_error_handling_test;
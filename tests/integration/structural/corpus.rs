@@ -0,0 +1,69 @@
+//! Every file under `examples/` builds, and the output keeps each service and
+//! its DEPENDS-ON list. New examples join the corpus automatically.
+
+use super::{create_test_ath_file, run_athena_build_and_parse};
+use athena::athena::parser::parse_athena_file;
+use serde_yaml::Value;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+fn corpus() -> Vec<PathBuf> {
+    let examples = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let mut files: Vec<PathBuf> = fs::read_dir(&examples)
+        .expect("Failed to read examples directory")
+        .map(|entry| entry.expect("Failed to read directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ath"))
+        .collect();
+    files.sort();
+    files
+}
+
+fn dependency_names(service: &Value) -> Vec<String> {
+    let mut names: Vec<String> = match &service["depends_on"] {
+        Value::Sequence(names) => names.iter().filter_map(|n| n.as_str()).map(String::from).collect(),
+        Value::Mapping(long) => long.keys().filter_map(|n| n.as_str()).map(String::from).collect(),
+        _ => Vec::new(),
+    };
+    names.sort();
+    names
+}
+
+#[test]
+fn test_examples_round_trip() {
+    let files = corpus();
+    assert!(!files.is_empty(), "examples/ should hold .ath files");
+
+    for file in files {
+        let source = fs::read_to_string(&file).expect("Failed to read example");
+        let athena_file = parse_athena_file(&source)
+            .unwrap_or_else(|e| panic!("{} should parse: {e}", file.display()));
+        // Built from a copy, since the output lands next to the input
+        let temp_dir = TempDir::new().expect("Failed to create temp directory");
+        let name = file.file_name().unwrap().to_string_lossy();
+        let copy = create_test_ath_file(&temp_dir, &name, &source);
+        let compose = run_athena_build_and_parse(&copy)
+            .unwrap_or_else(|e| panic!("{} should build: {e}", file.display()));
+
+        for service in &athena_file.services.services {
+            let generated = &compose["services"][service.name.as_str()];
+            assert!(
+                generated.is_mapping(),
+                "{}: service '{}' is missing from the output",
+                file.display(),
+                service.name
+            );
+
+            let mut expected: Vec<String> =
+                service.depends_on.iter().map(|d| d.service.clone()).collect();
+            expected.sort();
+            assert_eq!(
+                dependency_names(generated),
+                expected,
+                "{}: depends_on of '{}'",
+                file.display(),
+                service.name
+            );
+        }
+    }
+}
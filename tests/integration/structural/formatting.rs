@@ -99,8 +99,7 @@ END SERVICE"#;
     assert_eq!(service_lines.len(), 3, "Should have exactly 3 service definitions");
     
     // Check that there are blank lines between services (except before the first one)
-    for i in 1..service_lines.len() {
-        let current_service_line = service_lines[i];
+    for &current_service_line in service_lines.iter().skip(1) {
         let previous_line = current_service_line - 1;
         
         // The line before each service (except the first) should be blank
@@ -0,0 +1,226 @@
+//! `athena build --minimal` must describe the same stack as a full build.
+//! Both outputs are reduced to the configuration Compose acts on, with its
+//! defaults filled in, and compared; the minimal file may only be shorter.
+
+use super::create_test_ath_file;
+use crate::integration::compose_config::check_compose_config;
+use assert_cmd::Command;
+use serde_yaml::{Mapping, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+/// Build `ath_file`, returning the generated text
+fn build(ath_file: &Path, minimal: bool) -> String {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let output_file = temp_dir.path().join("docker-compose.yml");
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    cmd.arg("build").arg(ath_file).arg("-o").arg(&output_file).arg("--reproducible");
+    if minimal {
+        cmd.arg("--minimal");
+    }
+    let result = cmd.output().expect("Failed to execute command");
+    assert!(
+        result.status.success(),
+        "{} failed to build: {}",
+        ath_file.display(),
+        String::from_utf8_lossy(&result.stderr)
+    );
+
+    check_compose_config(&output_file).unwrap_or_else(|e| panic!("{e}"));
+    fs::read_to_string(&output_file).expect("Failed to read output")
+}
+
+fn insert_missing(mapping: &mut Mapping, defaults: &[(&str, Value)]) {
+    for (key, value) in defaults {
+        if !mapping.contains_key(*key) {
+            mapping.insert((*key).into(), value.clone());
+        }
+    }
+}
+
+/// A mapping whatever the short or long form: names to settings
+fn as_entries(value: Option<Value>, empty: impl Fn() -> Value) -> Mapping {
+    match value {
+        Some(Value::Sequence(names)) => names.into_iter().map(|name| (name, empty())).collect(),
+        Some(Value::Mapping(entries)) => entries,
+        _ => Mapping::new(),
+    }
+}
+
+/// The stack as Compose sees it. Networks are described by their settings and
+/// the services attached to them rather than by name, since the network a
+/// minimal file leaves to Compose is called `default`.
+fn effective(compose: &Value) -> Value {
+    let mut compose = compose.as_mapping().expect("Compose file should be a mapping").clone();
+    let mut services = compose
+        .remove("services")
+        .and_then(|services| services.as_mapping().cloned())
+        .expect("Compose file should have services");
+    let mut networks = as_entries(compose.remove("networks"), || Value::Mapping(Mapping::new()));
+    let mut volumes = as_entries(compose.remove("volumes"), || Value::Mapping(Mapping::new()));
+
+    let mut members: Vec<(Value, Value, Value)> = Vec::new();
+    for (name, service) in services.iter_mut() {
+        let service = service.as_mapping_mut().expect("Service should be a mapping");
+        insert_missing(service, &[
+            ("restart", "no".into()),
+            ("pull_policy", "missing".into()),
+            ("init", false.into()),
+            ("tty", false.into()),
+            ("stdin_open", false.into()),
+            ("stop_grace_period", "10s".into()),
+        ]);
+        let deploy = service.entry("deploy".into()).or_insert_with(|| Value::Mapping(Mapping::new()));
+        insert_missing(deploy.as_mapping_mut().expect("deploy should be a mapping"), &[
+            ("mode", "replicated".into()),
+            ("replicas", 1.into()),
+        ]);
+        let started = || {
+            let mut condition = Mapping::new();
+            condition.insert("condition".into(), "service_started".into());
+            Value::Mapping(condition)
+        };
+        let depends_on = as_entries(service.remove("depends_on"), started);
+        if !depends_on.is_empty() {
+            service.insert("depends_on".into(), Value::Mapping(depends_on));
+        }
+
+        let mut attachments = as_entries(service.remove("networks"), || Value::Mapping(Mapping::new()));
+        if attachments.is_empty() && !service.contains_key("network_mode") {
+            attachments.insert("default".into(), Value::Mapping(Mapping::new()));
+        }
+        for (network, attachment) in attachments {
+            members.push((network, name.clone(), attachment));
+        }
+    }
+
+    if members.iter().any(|(network, _, _)| network == "default") && !networks.contains_key("default") {
+        networks.insert("default".into(), Value::Mapping(Mapping::new()));
+    }
+    let mut described: Vec<String> = networks
+        .into_iter()
+        .map(|(name, mut settings)| {
+            if let Some(settings) = settings.as_mapping_mut() {
+                insert_missing(settings, &[("driver", "bridge".into())]);
+            }
+            let mut attached: Vec<String> = members
+                .iter()
+                .filter(|(network, _, _)| *network == name)
+                .map(|(_, service, attachment)| serde_yaml::to_string(&(service, attachment)).unwrap())
+                .collect();
+            attached.sort();
+            serde_yaml::to_string(&(settings, attached)).unwrap()
+        })
+        .collect();
+    described.sort();
+
+    for (_, settings) in volumes.iter_mut() {
+        if let Some(settings) = settings.as_mapping_mut() {
+            insert_missing(settings, &[("driver", "local".into())]);
+        }
+    }
+
+    compose.insert("services".into(), Value::Mapping(services));
+    compose.insert("networks".into(), described.into_iter().map(Value::from).collect());
+    compose.insert("volumes".into(), Value::Mapping(volumes));
+    Value::Mapping(compose)
+}
+
+fn assert_equivalent(ath_file: &Path) -> (String, String) {
+    let full = build(ath_file, false);
+    let minimal = build(ath_file, true);
+    let parse = |yaml: &str| -> Value { serde_yaml::from_str(yaml).expect("Output should be YAML") };
+
+    assert_eq!(
+        effective(&parse(&full)),
+        effective(&parse(&minimal)),
+        "{}: --minimal changed the configuration:\n{minimal}",
+        ath_file.display()
+    );
+    assert!(minimal.len() <= full.len(), "{}: --minimal output is longer", ath_file.display());
+    (full, minimal)
+}
+
+#[test]
+fn test_minimal_examples_are_equivalent() {
+    let examples = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("examples");
+    let mut files: Vec<PathBuf> = fs::read_dir(&examples)
+        .expect("Failed to read examples directory")
+        .map(|entry| entry.expect("Failed to read directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ath"))
+        .collect();
+    files.sort();
+
+    for file in files {
+        assert_equivalent(&file);
+    }
+}
+
+#[test]
+fn test_minimal_drops_defaults_and_the_implicit_network() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(&temp_dir, "minimal_test.ath", r#"DEPLOYMENT-ID shop
+SERVICES SECTION
+
+SERVICE db
+IMAGE-ID postgres:16
+VOLUME-MAPPING "pg_data" TO "/var/lib/postgresql/data"
+END SERVICE
+
+SERVICE api
+IMAGE-ID myorg/api:1.0
+PORT-MAPPING 8080 TO 80
+DEPENDS-ON db STARTED
+RESTART-POLICY no
+STOP-SIGNAL SIGTERM
+STOP-GRACE-PERIOD 10s
+INIT FALSE
+END SERVICE
+
+SERVICE vpn_client
+IMAGE-ID myorg/client
+NETWORK-MODE service:api
+END SERVICE"#);
+
+    let (full, minimal) = assert_equivalent(Path::new(&ath_file));
+    assert!(full.contains("restart: no") && full.contains("driver: bridge"));
+
+    let minimal: Value = serde_yaml::from_str(&minimal).unwrap();
+    let api = &minimal["services"]["api"];
+    for key in ["restart", "stop_grace_period", "init", "networks"] {
+        assert!(api.get(key).is_none(), "{key} should be left out:\n{api:#?}");
+    }
+    // Without it the image's STOPSIGNAL would apply
+    assert_eq!(api["stop_signal"], "SIGTERM");
+    assert_eq!(api["depends_on"], Value::Sequence(vec!["db".into()]));
+    assert!(minimal.get("networks").is_none());
+    assert_eq!(minimal["services"]["vpn_client"]["network_mode"], "service:api");
+}
+
+#[test]
+fn test_minimal_keeps_declared_networks() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(&temp_dir, "minimal_networks.ath", r#"ENVIRONMENT SECTION
+NETWORK-NAME app_net
+NETWORK-NAME jobs_net
+VOLUME queue_data
+
+SERVICES SECTION
+SERVICE api
+IMAGE-ID myorg/api:1.0
+END SERVICE
+
+SERVICE worker
+IMAGE-ID myorg/worker:1.0
+VOLUME-MAPPING "queue_data" TO "/data"
+ON NETWORK jobs_net ALIAS "jobs"
+END SERVICE"#);
+
+    let (_, minimal) = assert_equivalent(Path::new(&ath_file));
+    let minimal: Value = serde_yaml::from_str(&minimal).unwrap();
+    assert_eq!(minimal["networks"]["app_net"], Value::Mapping(Mapping::new()));
+    assert_eq!(minimal["services"]["api"]["networks"][0], "app_net");
+    assert_eq!(minimal["volumes"]["queue_data"], Value::Mapping(Mapping::new()));
+}
@@ -1,8 +1,11 @@
 use assert_cmd::Command;
 use serde_yaml::Value;
 use std::fs;
+use std::path::Path;
 use tempfile::TempDir;
 
+use super::compose_config::check_compose_config;
+
 // Common test modules
 pub mod basic_structure;
 pub mod service_configuration;
@@ -11,6 +14,8 @@ pub mod policies;
 pub mod formatting;
 pub mod complex_scenarios;
 pub mod comments;
+pub mod corpus;
+pub mod minimal;
 
 /// Create a test .ath file with given content
 pub fn create_test_ath_file(temp_dir: &TempDir, filename: &str, content: &str) -> String {
@@ -19,10 +24,11 @@ pub fn create_test_ath_file(temp_dir: &TempDir, filename: &str, content: &str) -
     file_path.to_string_lossy().to_string()
 }
 
-/// Run athena build command and parse the resulting YAML
+/// Run athena build command and parse the resulting YAML, checking it with
+/// `docker compose config` when ATHENA_COMPOSE_CHECK is set
 pub fn run_athena_build_and_parse(ath_file: &str) -> Result<Value, Box<dyn std::error::Error>> {
-    let temp_dir = TempDir::new().expect("Failed to create temp directory");
-    let output_file = temp_dir.path().join("docker-compose.yml").to_string_lossy().to_string();
+    // Next to the input, so relative bind mounts are written as they are in the .ath file
+    let output_file = Path::new(ath_file).with_file_name("docker-compose.yml").to_string_lossy().to_string();
     
     let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
     let result = cmd.arg("build")
@@ -37,6 +43,8 @@ pub fn run_athena_build_and_parse(ath_file: &str) -> Result<Value, Box<dyn std::
         return Err(format!("Command failed: {}", stderr).into());
     }
     
+    check_compose_config(output_file.as_ref())?;
+
     let yaml_content = fs::read_to_string(&output_file)?;
     let parsed: Value = serde_yaml::from_str(&yaml_content)?;
     Ok(parsed)
@@ -57,4 +57,87 @@ fn test_service_dependencies() {
     
     assert!(dep_strings.contains(&"database".to_string()), 
         "App service should depend on database service");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_dns_extra_hosts_and_hostname() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_content = r#"DEPLOYMENT-ID DNS_TEST
+
+ENVIRONMENT SECTION
+DNS "10.0.0.2"
+
+SERVICES SECTION
+
+SERVICE api
+IMAGE-ID alpine:latest
+HOSTNAME "api-1"
+EXTRA-HOST "registry.internal" "10.0.0.9"
+EXTRA-HOST "ipv6.internal" "fd00::9"
+END SERVICE
+
+SERVICE resolver
+IMAGE-ID alpine:latest
+DNS "1.1.1.1"
+DNS "2606:4700:4700::1111"
+END SERVICE"#;
+
+    let ath_file = create_test_ath_file(&temp_dir, "dns_test.ath", ath_content);
+    let parsed = run_athena_build_and_parse(&ath_file)
+        .expect("Failed to generate and parse YAML");
+
+    let api = &parsed["services"]["api"];
+    assert_eq!(api["hostname"], "api-1");
+    assert_eq!(api["dns"][0], "10.0.0.2", "File-level DNS should apply to services without their own");
+    assert_eq!(api["extra_hosts"][0], "registry.internal:10.0.0.9");
+    assert_eq!(api["extra_hosts"][1], "ipv6.internal:fd00::9");
+
+    let resolver = &parsed["services"]["resolver"];
+    let dns = resolver["dns"].as_sequence().expect("DNS should be a sequence");
+    assert_eq!(dns.len(), 2, "Service-level DNS should replace the file-level default");
+    assert_eq!(dns[0], "1.1.1.1");
+    assert_eq!(dns[1], "2606:4700:4700::1111");
+    assert!(resolver["hostname"].is_null());
+    assert!(resolver["extra_hosts"].is_null());
+}
+
+#[test]
+fn test_network_aliases_and_static_address() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_content = r#"DEPLOYMENT-ID LEGACY_CLIENTS
+
+ENVIRONMENT SECTION
+NETWORK-NAME frontend
+NETWORK-NAME backend SUBNET "172.28.0.0/16"
+
+SERVICES SECTION
+
+SERVICE db
+IMAGE-ID postgres:16
+ON NETWORK backend ALIAS "db.legacy.local" IPV4 "172.28.0.10"
+END SERVICE
+
+SERVICE api
+IMAGE-ID node:20-alpine
+ON NETWORK backend
+DEPENDS-ON db
+END SERVICE"#;
+
+    let ath_file = create_test_ath_file(&temp_dir, "aliases.ath", ath_content);
+    let parsed = run_athena_build_and_parse(&ath_file).expect("Failed to build and parse YAML");
+
+    let db_networks = &parsed["services"]["db"]["networks"];
+    assert!(db_networks.is_mapping(), "Options switch networks to the mapping form");
+    assert_eq!(db_networks["backend"]["aliases"][0].as_str(), Some("db.legacy.local"));
+    assert_eq!(db_networks["backend"]["ipv4_address"].as_str(), Some("172.28.0.10"));
+
+    let api_networks = parsed["services"]["api"]["networks"]
+        .as_sequence()
+        .expect("Plain attachments keep the list form");
+    assert_eq!(api_networks.len(), 2);
+
+    assert_eq!(
+        parsed["networks"]["backend"]["ipam"]["config"][0]["subnet"].as_str(),
+        Some("172.28.0.0/16")
+    );
+}
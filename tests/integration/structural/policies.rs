@@ -58,4 +58,150 @@ END SERVICE"#;
     assert!(healthcheck["interval"].is_string(), "Healthcheck should have interval");
     assert!(healthcheck["timeout"].is_string(), "Healthcheck should have timeout");
     assert!(healthcheck["retries"].is_number(), "Healthcheck should have retries");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_profiles() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_content = r#"DEPLOYMENT-ID PROFILE_TEST
+SERVICES SECTION
+
+SERVICE api
+IMAGE-ID node:20
+END SERVICE
+
+SERVICE adminer
+IMAGE-ID adminer
+PROFILE dev
+PROFILE debug
+END SERVICE"#;
+
+    let ath_file = create_test_ath_file(&temp_dir, "profile_test.ath", ath_content);
+    let parsed = run_athena_build_and_parse(&ath_file)
+        .expect("Failed to generate and parse YAML");
+
+    let services = parsed["services"].as_mapping().expect("Services should be a mapping");
+    assert!(services["api"]["profiles"].is_null(), "Services without PROFILE always run");
+    assert_eq!(services["adminer"]["profiles"][0], "dev");
+    assert_eq!(services["adminer"]["profiles"][1], "debug");
+}
+
+/// Run `athena validate --fail-on-warnings` and return its stderr
+fn validate_warnings(ath_content: &str) -> String {
+    use assert_cmd::Command;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(&temp_dir, "policy_test.ath", ath_content);
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    let result = cmd.arg("validate")
+        .arg(&ath_file)
+        .arg("--fail-on-warnings")
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(result.status.code(), Some(4), "Warnings should fail the run");
+    String::from_utf8_lossy(&result.stderr).to_string()
+}
+
+#[test]
+fn test_dependency_on_profile_only_service() {
+    let stderr = validate_warnings(r#"SERVICES SECTION
+SERVICE mailhog
+IMAGE-ID mailhog/mailhog
+PROFILE dev
+END SERVICE
+SERVICE api
+IMAGE-ID node:20
+DEPENDS-ON mailhog
+END SERVICE"#);
+
+    assert!(stderr.contains("[ATH006] Service 'api' depends on 'mailhog', which only runs with profile dev"));
+}
+
+#[test]
+fn test_completed_dependency_on_replicated_service() {
+    let stderr = validate_warnings(r#"SERVICES SECTION
+SERVICE worker
+IMAGE-ID worker:latest
+REPLICAS 3
+END SERVICE
+SERVICE api
+IMAGE-ID node:20
+DEPENDS-ON worker COMPLETED
+END SERVICE"#);
+
+    assert!(stderr.contains("[ATH007] Service 'api' waits for 'worker' to complete, but it runs 3 replicas"));
+}
+
+#[test]
+fn test_dependency_on_job_needs_completed() {
+    let stderr = validate_warnings(r#"SERVICES SECTION
+SERVICE migrate
+IMAGE-ID migrate:latest
+KIND job
+END SERVICE
+SERVICE api
+IMAGE-ID node:20
+DEPENDS-ON migrate STARTED
+END SERVICE"#);
+
+    assert!(stderr.contains("[ATH008] Service 'api' starts as soon as job 'migrate' starts"));
+}
+
+/// Run `athena build` on a file expected to fail validation and return its stderr
+fn build_error(ath_content: &str) -> String {
+    use assert_cmd::Command;
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(&temp_dir, "policy_test.ath", ath_content);
+
+    let mut cmd = Command::cargo_bin("athena").expect("Failed to find athena binary");
+    let result = cmd.arg("build")
+        .arg(&ath_file)
+        .arg("-o")
+        .arg(temp_dir.path().join("docker-compose.yml"))
+        .output()
+        .expect("Failed to execute command");
+
+    assert_eq!(result.status.code(), Some(4), "Validation should fail");
+    String::from_utf8_lossy(&result.stderr).to_string()
+}
+
+#[test]
+fn test_docker_socket_mount_needs_opt_in() {
+    // Plain mounts use the short syntax
+    let stderr = build_error(r#"SERVICES SECTION
+SERVICE agent
+IMAGE-ID portainer/agent
+VOLUME-MAPPING "/var/run/docker.sock" TO "/var/run/docker.sock"
+END SERVICE"#);
+    assert!(stderr.contains("Service 'agent' mounts the container runtime socket /var/run/docker.sock"), "{stderr}");
+    assert!(stderr.contains("add ALLOW DOCKER SOCKET to service 'agent'"), "{stderr}");
+
+    // Propagation switches to the long syntax
+    let stderr = build_error(r#"SERVICES SECTION
+SERVICE runner
+IMAGE-ID myorg/runner
+VOLUME-MAPPING "/run/containerd/containerd.sock" TO "/run/containerd/containerd.sock" (rslave)
+END SERVICE"#);
+    assert!(stderr.contains("Service 'runner' mounts the container runtime socket /run/containerd/containerd.sock"), "{stderr}");
+}
+
+#[test]
+fn test_allowed_docker_socket_mount_warns() {
+    let ath_content = r#"SERVICES SECTION
+SERVICE traefik
+IMAGE-ID traefik:v3
+VOLUME-MAPPING "/var/run/docker.sock" TO "/var/run/docker.sock" (ro)
+ALLOW DOCKER SOCKET
+END SERVICE"#;
+
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_file = create_test_ath_file(&temp_dir, "socket_test.ath", ath_content);
+    let parsed = run_athena_build_and_parse(&ath_file)
+        .expect("Failed to generate and parse YAML");
+    assert_eq!(parsed["services"]["traefik"]["volumes"][0], "/var/run/docker.sock:/var/run/docker.sock:ro");
+
+    let stderr = validate_warnings(ath_content);
+    assert!(stderr.contains("[ATH010] Service 'traefik' mounts /var/run/docker.sock (ALLOW DOCKER SOCKET)"), "{stderr}");
+    assert!(stderr.contains("read-only does not limit the API calls"), "{stderr}");
+}
@@ -161,4 +161,150 @@ END SERVICE"#;
         "Should contain data volume mapping");
     assert!(volume_strings.iter().any(|v| v.contains("./config") && v.contains("/etc/postgresql")), 
         "Should contain config volume mapping");
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_volume_long_syntax_for_consistency_and_propagation() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_content = r#"DEPLOYMENT-ID VOLUME_LONG_TEST
+SERVICES SECTION
+
+SERVICE app
+IMAGE-ID node:20-alpine
+VOLUME-MAPPING "./config" TO "/etc/app" (ro)
+VOLUME-MAPPING "./src" TO "/app/src" (cached)
+VOLUME-MAPPING "/mnt/shared" TO "/shared" (ro, rslave)
+END SERVICE"#;
+
+    let ath_file = create_test_ath_file(&temp_dir, "volume_long.ath", ath_content);
+    let parsed = run_athena_build_and_parse(&ath_file)
+        .expect("Failed to generate and parse YAML");
+
+    let volumes = parsed["services"]["app"]["volumes"]
+        .as_sequence()
+        .expect("Volumes should be sequence");
+
+    // Plain options keep the short syntax
+    assert_eq!(volumes[0].as_str(), Some("./config:/etc/app:ro"));
+
+    assert_eq!(volumes[1]["type"].as_str(), Some("bind"));
+    assert_eq!(volumes[1]["consistency"].as_str(), Some("cached"));
+
+    assert_eq!(volumes[2]["read_only"].as_bool(), Some(true));
+    assert_eq!(volumes[2]["bind"]["propagation"].as_str(), Some("rslave"));
+}
+
+#[test]
+fn test_command_entrypoint_workdir_and_user() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_content = r#"DEPLOYMENT-ID COMMAND_TEST
+SERVICES SECTION
+
+SERVICE web
+IMAGE-ID python:3.12-slim
+COMMAND "gunicorn app:app -b 0.0.0.0:8000"
+ENTRYPOINT ["/docker-entrypoint.sh", "--wait"]
+WORKDIR "/app"
+USER "1000:1000"
+END SERVICE
+
+SERVICE worker
+IMAGE-ID python:3.12-slim
+COMMAND ["celery", "-A", "app", "worker"]
+END SERVICE"#;
+
+    let ath_file = create_test_ath_file(&temp_dir, "command_test.ath", ath_content);
+    let parsed = run_athena_build_and_parse(&ath_file)
+        .expect("Failed to generate and parse YAML");
+
+    let web = &parsed["services"]["web"];
+    assert_eq!(web["command"], "gunicorn app:app -b 0.0.0.0:8000", "Shell form should stay a string");
+    let entrypoint = web["entrypoint"].as_sequence().expect("Exec form should stay a list");
+    assert_eq!(entrypoint.len(), 2);
+    assert_eq!(entrypoint[0], "/docker-entrypoint.sh");
+    assert_eq!(entrypoint[1], "--wait");
+    assert_eq!(web["working_dir"], "/app");
+    assert_eq!(web["user"], "1000:1000");
+
+    let worker = &parsed["services"]["worker"];
+    let command = worker["command"].as_sequence().expect("Exec form should stay a list");
+    assert_eq!(command.len(), 4);
+    assert_eq!(command[0], "celery");
+    assert!(worker["entrypoint"].is_null());
+    assert!(worker["user"].is_null());
+}
+
+#[test]
+fn test_init_tty_stdin_and_stop_settings() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let ath_content = r#"DEPLOYMENT-ID LIFECYCLE_TEST
+SERVICES SECTION
+
+SERVICE debug
+IMAGE-ID alpine:latest
+INIT TRUE
+TTY TRUE
+STDIN-OPEN TRUE
+STOP-GRACE-PERIOD 1m30s
+STOP-SIGNAL SIGINT
+END SERVICE
+
+SERVICE plain
+IMAGE-ID alpine:latest
+END SERVICE"#;
+
+    let ath_file = create_test_ath_file(&temp_dir, "lifecycle_test.ath", ath_content);
+    let parsed = run_athena_build_and_parse(&ath_file)
+        .expect("Failed to generate and parse YAML");
+
+    let debug = &parsed["services"]["debug"];
+    assert_eq!(debug["init"], true);
+    assert_eq!(debug["tty"], true);
+    assert_eq!(debug["stdin_open"], true);
+    assert_eq!(debug["stop_grace_period"], "1m30s");
+    assert_eq!(debug["stop_signal"], "SIGINT");
+
+    let plain = &parsed["services"]["plain"];
+    for key in ["init", "tty", "stdin_open", "stop_grace_period", "stop_signal"] {
+        assert!(plain[key].is_null(), "{key} should be omitted when not set");
+    }
+}
+
+#[test]
+fn test_default_labels_and_service_labels() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    std::fs::write(
+        temp_dir.path().join("labels.yml"),
+        "com.acme.owner: platform-team\ncom.acme.cost-center: 4200\norg.opencontainers.image.licenses: MIT\n",
+    )
+    .expect("Failed to write labels file");
+
+    let ath_content = r#"ENVIRONMENT SECTION
+DEFAULT LABELS FROM "labels.yml"
+
+SERVICES SECTION
+
+SERVICE api
+IMAGE-ID myorg/api:1.0
+LABEL com.acme.owner="payments" org.opencontainers.image.source="https://github.com/acme/api"
+END SERVICE
+
+SERVICE worker
+IMAGE-ID myorg/worker:1.0
+END SERVICE"#;
+
+    let ath_file = create_test_ath_file(&temp_dir, "labels_test.ath", ath_content);
+    let parsed = run_athena_build_and_parse(&ath_file)
+        .expect("Failed to generate and parse YAML");
+
+    let api = &parsed["services"]["api"]["labels"];
+    assert_eq!(api["com.acme.owner"], "payments", "LABEL overrides the default");
+    assert_eq!(api["org.opencontainers.image.source"], "https://github.com/acme/api");
+    assert_eq!(api["com.acme.cost-center"], "4200");
+    assert_eq!(api["athena.service"], "api");
+
+    let worker = &parsed["services"]["worker"]["labels"];
+    assert_eq!(worker["com.acme.owner"], "platform-team");
+    assert_eq!(worker["org.opencontainers.image.licenses"], "MIT");
+    assert!(worker["org.opencontainers.image.source"].is_null());
+}
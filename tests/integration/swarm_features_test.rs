@@ -1,5 +1,9 @@
 use athena::athena::parser::parser::parse_athena_file;
 use athena::athena::generator::compose::generate_docker_compose;
+use athena::athena::parser::ast::Duration;
+use tempfile::TempDir;
+
+use super::compose_config::check_stack_config;
 
 #[test]
 fn test_swarm_replicas_parsing() {
@@ -54,7 +58,7 @@ fn test_swarm_update_config_parsing() {
     
     let update_config = swarm_config.update_config.as_ref().unwrap();
     assert_eq!(update_config.parallelism, Some(1));
-    assert_eq!(update_config.delay, Some("30s".to_string()));
+    assert_eq!(update_config.delay, Some(Duration::from_secs(30)));
     assert!(update_config.failure_action.is_some());
 }
 
@@ -259,14 +263,13 @@ fn test_invalid_replica_zero() {
         END SERVICE
     "#;
 
-    // Zero replicas should parse but might be logically invalid for deployment
+    // Zero replicas is rejected: only positive replica counts are meaningful
     let result = parse_athena_file(input);
-    assert!(result.is_ok());
-    
-    let athena_file = result.unwrap();
-    let service = &athena_file.services.services[0];
-    let swarm_config = service.swarm_config.as_ref().unwrap();
-    assert_eq!(swarm_config.replicas, Some(0));
+    assert!(result.is_err());
+
+    let error_msg = format!("{}", result.unwrap_err());
+    assert!(error_msg.contains("Invalid replicas number"));
+    assert!(error_msg.contains("DEPLOY-MODE global"));
 }
 
 #[test]
@@ -527,4 +530,209 @@ fn test_conflicting_swarm_and_compose_features() {
     assert!(!service.ports.is_empty()); // Compose feature
     assert!(service.swarm_config.is_some()); // Swarm feature
     assert!(service.restart.is_some()); // Compose feature
-}
\ No newline at end of file
+}
+
+#[test]
+fn test_global_deploy_mode_generation() {
+    let input = r#"
+        DEPLOYMENT-ID GLOBAL_MODE_TEST
+
+        SERVICES SECTION
+
+        SERVICE node_exporter
+        IMAGE-ID prom/node-exporter:latest
+        DEPLOY-MODE global
+        END SERVICE
+    "#;
+
+    let athena_file = parse_athena_file(input).expect("Global mode should parse");
+    let yaml = generate_docker_compose(&athena_file).expect("Global mode should generate");
+
+    let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+    let deploy = &parsed["services"]["node_exporter"]["deploy"];
+    assert_eq!(deploy["mode"], "global");
+    assert!(deploy["replicas"].is_null());
+}
+
+#[test]
+fn test_global_deploy_mode_with_replicas_is_rejected() {
+    let input = r#"
+        DEPLOYMENT-ID GLOBAL_REPLICAS_TEST
+
+        SERVICES SECTION
+
+        SERVICE node_exporter
+        IMAGE-ID prom/node-exporter:latest
+        DEPLOY-MODE global
+        REPLICAS 3
+        END SERVICE
+    "#;
+
+    let athena_file = parse_athena_file(input).expect("Should parse");
+    let result = generate_docker_compose(&athena_file);
+    assert!(result.is_err());
+
+    let error_msg = format!("{}", result.unwrap_err());
+    assert!(error_msg.contains(
+        "Service 'node_exporter' uses DEPLOY-MODE global and REPLICAS 3, which are mutually exclusive"
+    ));
+    assert!(error_msg.contains("Remove REPLICAS"));
+}
+
+#[test]
+fn test_restart_policy_drives_swarm_condition() {
+    let input = r#"
+        DEPLOYMENT-ID RESTART_SWARM_TEST
+
+        SERVICES SECTION
+
+        SERVICE worker
+        IMAGE-ID python:3.11
+        RESTART-POLICY always
+        REPLICAS 2
+        END SERVICE
+    "#;
+
+    let athena_file = parse_athena_file(input).expect("Should parse");
+    let yaml = generate_docker_compose(&athena_file).expect("Should generate");
+
+    let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+    let worker = &parsed["services"]["worker"];
+    assert_eq!(worker["restart"], "always");
+    assert_eq!(worker["deploy"]["restart_policy"]["condition"], "any");
+}
+
+#[test]
+fn test_container_name_with_replicas_is_rejected() {
+    let input = r#"
+        DEPLOYMENT-ID CONTAINER_NAME_TEST
+
+        SERVICES SECTION
+
+        SERVICE api
+        IMAGE-ID node:20
+        CONTAINER-NAME "api"
+        REPLICAS 3
+        END SERVICE
+    "#;
+
+    let athena_file = parse_athena_file(input).expect("Should parse");
+    let error_msg = generate_docker_compose(&athena_file).unwrap_err().to_string();
+    assert!(error_msg.contains("sets CONTAINER-NAME 'api' and REPLICAS 3"));
+}
+
+#[test]
+fn test_replicas_with_a_host_port_range() {
+    let input = r#"
+        DEPLOYMENT-ID LOCAL_SCALE_TEST
+
+        SERVICES SECTION
+
+        SERVICE worker
+        IMAGE-ID python:3.11
+        PORT-MAPPING 8000-8002 TO 8000
+        REPLICAS 3
+        END SERVICE
+    "#;
+
+    let athena_file = parse_athena_file(input).expect("Should parse");
+    let yaml = generate_docker_compose(&athena_file).expect("Should generate");
+
+    let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+    let worker = &parsed["services"]["worker"];
+    assert_eq!(worker["deploy"]["replicas"], 3);
+    assert_eq!(worker["ports"][0], "8000-8002:8000");
+}
+
+#[test]
+fn test_resource_reservations_generation() {
+    let input = r#"
+        DEPLOYMENT-ID RESERVATIONS_TEST
+
+        SERVICES SECTION
+
+        SERVICE api
+        IMAGE-ID node:20
+        RESOURCE-LIMITS CPU "1.0" MEMORY "1G"
+        RESERVE MEMORY 256M CPUS 0.25
+        DEPLOY-MODE replicated
+        REPLICAS 2
+        END SERVICE
+
+        SERVICE worker
+        IMAGE-ID python:3.11
+        RESERVE MEMORY 128M
+        END SERVICE
+    "#;
+
+    let athena_file = parse_athena_file(input).expect("Reservations should parse");
+    let yaml = generate_docker_compose(&athena_file).expect("Reservations should generate");
+
+    let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+    let api = &parsed["services"]["api"]["deploy"]["resources"];
+    assert_eq!(api["limits"]["cpus"], "1.0");
+    assert_eq!(api["limits"]["memory"], "1G");
+    assert_eq!(api["reservations"]["cpus"], "0.25");
+    assert_eq!(api["reservations"]["memory"], "256M");
+
+    let worker = &parsed["services"]["worker"]["deploy"]["resources"];
+    assert!(worker["limits"].is_null());
+    assert_eq!(worker["reservations"]["memory"], "128M");
+    assert!(worker["reservations"]["cpus"].is_null());
+}
+
+#[test]
+fn test_reservation_above_limit_is_rejected() {
+    let input = |reserve: &str| {
+        format!(
+            r#"
+        DEPLOYMENT-ID RESERVATIONS_TEST
+
+        SERVICES SECTION
+
+        SERVICE api
+        IMAGE-ID node:20
+        RESOURCE-LIMITS CPU "0.5" MEMORY "512M"
+        {reserve}
+        END SERVICE
+    "#
+        )
+    };
+    let error_for = |reserve: &str| {
+        let athena_file = parse_athena_file(&input(reserve)).expect("Should parse");
+        generate_docker_compose(&athena_file).unwrap_err().to_string()
+    };
+
+    let memory = error_for("RESERVE MEMORY 1G");
+    assert!(memory.contains("Service 'api' reserves MEMORY 1G above the 512M limit"), "{memory}");
+    let cpus = error_for("RESERVE CPUS 0.75 MEMORY 512M");
+    assert!(cpus.contains("Service 'api' reserves CPUS 0.75 above the 0.5 limit"), "{cpus}");
+    let both = error_for("RESERVE MEMORY 2G CPUS 2");
+    assert!(both.contains("MEMORY 2G above the 512M limit and CPUS 2 above the 0.5 limit"), "{both}");
+
+    let athena_file = parse_athena_file(&input("RESERVE MEMORY 512M CPUS 0.5")).unwrap();
+    assert!(generate_docker_compose(&athena_file).is_ok(), "A reservation equal to the limit fits");
+}
+
+/// With ATHENA_STACK_CHECK=1, the Swarm fixtures must also pass the parser of
+/// `docker stack deploy`, which rejects some constructs Compose accepts
+#[test]
+fn test_swarm_fixtures_pass_docker_stack_config() {
+    let temp_dir = TempDir::new().expect("Failed to create temp directory");
+    let fixtures = [
+        ("swarm_basic", include_str!("../fixtures/swarm_basic.ath")),
+        ("swarm_advanced", include_str!("../fixtures/swarm_advanced.ath")),
+        ("mixed_features", include_str!("../fixtures/mixed_features.ath")),
+    ];
+
+    for (name, input) in fixtures {
+        let athena_file = parse_athena_file(input).unwrap_or_else(|e| panic!("{name} should parse: {e}"));
+        let yaml = generate_docker_compose(&athena_file).unwrap_or_else(|e| panic!("{name} should generate: {e}"));
+        let output_file = temp_dir.path().join(format!("{name}.yml"));
+        std::fs::write(&output_file, yaml).expect("Failed to write generated file");
+
+        if let Err(rejection) = check_stack_config(&output_file) {
+            panic!("{rejection}");
+        }
+    }
+}